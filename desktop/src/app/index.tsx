@@ -2,6 +2,7 @@ import { Theme } from "@radix-ui/themes";
 import { useEffect } from "react";
 import { RouterProvider } from "react-router-dom";
 import { MessageProvider, ModalProvider, useAppearanceValue } from "shared";
+import { useTransferQueueSync } from "@/atoms/transferQueue.atom";
 import { useAutoCheckUpdate } from "@/atoms/update.atom";
 import UpdateDialog from "@/components/UpdateDialog";
 import router from "@/routes";
@@ -11,6 +12,7 @@ export default function App() {
   const appearance = useAppearanceValue();
 
   useAutoCheckUpdate();
+  useTransferQueueSync();
 
   useEffect(() => {
     const handler = (e: MouseEvent) => e.preventDefault();
@@ -2,6 +2,7 @@ import { useAtom } from "jotai";
 import { atomWithStorage } from "jotai/utils";
 import { useCallback } from "react";
 import {
+  DEFAULT_TERMINAL_ENCODING,
   DEFAULT_TERMINAL_FONT_FAMILY,
   DEFAULT_TERMINAL_FONT_SIZE,
   DEFAULT_TERMINAL_THEME,
@@ -12,6 +13,7 @@ export type LocalTerminalSettings = {
   fontSize: number;
   theme: string;
   shell: string;
+  encoding: string;
 };
 
 const DEFAULT_SETTINGS: LocalTerminalSettings = {
@@ -19,6 +21,7 @@ const DEFAULT_SETTINGS: LocalTerminalSettings = {
   fontSize: DEFAULT_TERMINAL_FONT_SIZE,
   theme: DEFAULT_TERMINAL_THEME.name,
   shell: "",
+  encoding: DEFAULT_TERMINAL_ENCODING,
 };
 
 export const localTerminalSettingsAtom = atomWithStorage<LocalTerminalSettings>(
@@ -0,0 +1,55 @@
+import { useAtom } from "jotai";
+import { atomWithStorage } from "jotai/utils";
+import { useCallback, useMemo } from "react";
+
+/**
+ * 未完成的文件传输，开始时记录，完成或手动放弃时移除，
+ * 取消、断线、失败或应用退出后留下的记录可以续传
+ */
+export type PendingTransfer = {
+  taskId: string;
+  hostId: string;
+  type: "upload" | "download";
+  fileName: string;
+  localFilename: string;
+  remoteFilename: string;
+};
+
+export const pendingTransfersAtom = atomWithStorage<PendingTransfer[]>(
+  "pendingTransfers",
+  [],
+  undefined,
+  { getOnInit: true },
+);
+
+export function usePendingTransfers(hostId?: string) {
+  const [transfers, setTransfers] = useAtom(pendingTransfersAtom);
+
+  const hostTransfers = useMemo(
+    () => transfers.filter((transfer) => transfer.hostId === hostId),
+    [transfers, hostId],
+  );
+
+  const addPendingTransfer = useCallback(
+    (transfer: PendingTransfer) => {
+      setTransfers((prev) => [
+        ...prev.filter((item) => item.taskId !== transfer.taskId),
+        transfer,
+      ]);
+    },
+    [setTransfers],
+  );
+
+  const removePendingTransfer = useCallback(
+    (taskId: string) => {
+      setTransfers((prev) => prev.filter((item) => item.taskId !== taskId));
+    },
+    [setTransfers],
+  );
+
+  return {
+    pendingTransfers: hostTransfers,
+    addPendingTransfer,
+    removePendingTransfer,
+  };
+}
@@ -0,0 +1,114 @@
+import { atom, useAtomValue, useStore } from "jotai";
+import { useCallback, useEffect, useMemo } from "react";
+import {
+  type SSHTransferQueueEnqueueOpts,
+  type SSHTransferQueueEvent,
+  type SSHTransferQueueItem,
+  transferQueueCancel,
+  transferQueueClear,
+  transferQueueEnqueue,
+  transferQueueMove,
+  transferQueuePause,
+  transferQueueResume,
+  transferQueueRemove,
+  transferQueueRetry,
+  transferQueueSubscribe,
+} from "tauri-plugin-ssh";
+
+/**
+ * 后端传输队列的镜像，按队列顺序排列
+ */
+export const transferQueueAtom = atom<SSHTransferQueueItem[]>([]);
+
+function applyEvent(
+  items: SSHTransferQueueItem[],
+  event: SSHTransferQueueEvent,
+): SSHTransferQueueItem[] {
+  if (event.type === "Update") {
+    const index = items.findIndex((item) => item.id === event.item.id);
+    if (index < 0) {
+      return [...items, event.item];
+    }
+    return items.map((item, i) => (i === index ? event.item : item));
+  }
+
+  if (event.type === "Remove") {
+    return items.filter((item) => !event.ids.includes(item.id));
+  }
+
+  const itemMap = new Map(items.map((item) => [item.id, item]));
+  return event.ids.flatMap((id) => {
+    const item = itemMap.get(id);
+    return item ? [item] : [];
+  });
+}
+
+/**
+ * 订阅后端队列，在应用根组件调用一次，传输历史由后端在传输结束时写入
+ */
+export function useTransferQueueSync() {
+  const store = useStore();
+
+  useEffect(() => {
+    let disposed = false;
+
+    const handleEvent = (event: SSHTransferQueueEvent) => {
+      store.set(transferQueueAtom, (prev) => applyEvent(prev, event));
+    };
+
+    // 订阅返回当前队列之前收到的事件，在初始化后按顺序应用
+    let ready = false;
+    const pendingEvents: SSHTransferQueueEvent[] = [];
+
+    transferQueueSubscribe((event) => {
+      if (disposed) return;
+      if (ready) {
+        handleEvent(event);
+      } else {
+        pendingEvents.push(event);
+      }
+    })
+      .then((items) => {
+        if (disposed) return;
+        store.set(transferQueueAtom, items);
+        ready = true;
+        for (const event of pendingEvents) {
+          handleEvent(event);
+        }
+      })
+      .catch((err) => {
+        console.error("transfer queue subscribe error", err);
+      });
+
+    return () => {
+      disposed = true;
+    };
+  }, [store]);
+}
+
+export function useTransferQueue(hostId?: string) {
+  const items = useAtomValue(transferQueueAtom);
+
+  const hostItems = useMemo(
+    () => items.filter((item) => (item.hostId ?? undefined) === hostId),
+    [items, hostId],
+  );
+
+  const enqueue = useCallback(
+    (opts: SSHTransferQueueEnqueueOpts) =>
+      transferQueueEnqueue({ ...opts, hostId }),
+    [hostId],
+  );
+
+  return {
+    items: hostItems,
+    enqueue,
+    cancel: transferQueueCancel,
+    pause: transferQueuePause,
+    resume: transferQueueResume,
+    retry: transferQueueRetry,
+    move: transferQueueMove,
+    remove: transferQueueRemove,
+    clear: transferQueueClear,
+  };
+}
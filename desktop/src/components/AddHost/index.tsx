@@ -2,6 +2,7 @@ import { Button, DropdownMenu } from "@radix-ui/themes";
 import { useCallback, useEffect, useMemo, useState } from "react";
 import { type FieldErrors, useForm } from "react-hook-form";
 import {
+  DEFAULT_TERMINAL_ENCODING,
   DEFAULT_TERMINAL_FONT_FAMILY,
   DEFAULT_TERMINAL_FONT_SIZE,
   DEFAULT_TERMINAL_THEME,
@@ -10,10 +11,13 @@ import {
   type EditHostFormFields,
   MoreIcon,
   parseEnvs,
+  saveHostTriggers,
   stringifyEnvs,
+  toTriggerFormFields,
   useHosts,
   useKeys,
   useTerminalsAtomWithApi,
+  useTriggers,
 } from "shared";
 import {
   AuthenticationMethod,
@@ -37,6 +41,7 @@ type AddHostProps = {
 export default function AddHost({ open, data, onOk, onCancel }: AddHostProps) {
   const activateTerminal = useActivateTerminal();
   const { refresh: refreshHosts } = useHosts();
+  const { data: triggers, refresh: refreshTriggers } = useTriggers();
   const { refresh: refreshKeys } = useKeys();
   const [addKeyOpen, setAddKeyOpen] = useState(false);
   const msg = useMessage();
@@ -53,10 +58,14 @@ export default function AddHost({ open, data, onOk, onCancel }: AddHostProps) {
       password: "",
       keyId: "",
       startupCommand: "",
+      shellCommand: "",
       terminalType: DEFAULT_TERMINAL_TYPE,
+      terminalEncoding: DEFAULT_TERMINAL_ENCODING,
+      urlForwarding: undefined,
       envs: "",
       jumpHostEnabled: false,
       jumpHostIds: [],
+      triggers: [],
       terminalSettings: {
         fontFamily: DEFAULT_TERMINAL_FONT_FAMILY,
         fontSize: DEFAULT_TERMINAL_FONT_SIZE,
@@ -75,10 +84,14 @@ export default function AddHost({ open, data, onOk, onCancel }: AddHostProps) {
       password: data?.password ?? "",
       keyId: data?.keyId ?? "",
       startupCommand: data?.startupCommand ?? "",
+      shellCommand: data?.shellCommand ?? "",
       terminalType: data?.terminalType ?? DEFAULT_TERMINAL_TYPE,
+      terminalEncoding: data?.terminalEncoding ?? DEFAULT_TERMINAL_ENCODING,
+      urlForwarding: data?.urlForwarding,
       envs: stringifyEnvs(data?.envs),
       jumpHostEnabled: !!data?.jumpHostIds?.length,
       jumpHostIds: data?.jumpHostIds ?? [],
+      triggers: toTriggerFormFields(triggers, data?.id),
       terminalSettings: {
         fontFamily:
           data?.terminalSettings?.fontFamily ?? DEFAULT_TERMINAL_FONT_FAMILY,
@@ -114,7 +127,13 @@ export default function AddHost({ open, data, onOk, onCancel }: AddHostProps) {
             ? values.keyId || ""
             : undefined,
         startupCommand: values.startupCommand || undefined,
+        shellCommand: values.shellCommand || undefined,
         terminalType: values.terminalType || DEFAULT_TERMINAL_TYPE,
+        terminalEncoding:
+          values.terminalEncoding === DEFAULT_TERMINAL_ENCODING
+            ? undefined
+            : values.terminalEncoding,
+        urlForwarding: values.urlForwarding || undefined,
         envs: parseEnvs(values.envs),
         jumpHostIds: values.jumpHostEnabled ? values.jumpHostIds : undefined,
         terminalSettings: values.terminalSettings
@@ -126,16 +145,20 @@ export default function AddHost({ open, data, onOk, onCancel }: AddHostProps) {
           : undefined,
       };
 
-      if (data) {
-        return updateHost({
-          ...hostData,
-          id: data.id,
-        });
-      }
+      const savedHost = data
+        ? await updateHost({
+            ...hostData,
+            id: data.id,
+          })
+        : await addHost(hostData);
+
+      // 触发器需要主机 id，新主机保存后才能添加
+      await saveHostTriggers(savedHost.id, values.triggers ?? []);
+      await refreshTriggers();
 
-      return addHost(hostData);
+      return savedHost;
     },
-    [data],
+    [data, refreshTriggers],
   );
 
   const onSaveAndConnect = useCallback(
@@ -0,0 +1,96 @@
+import { Button, Dialog, Flex } from "@radix-ui/themes";
+import { useEffect, useRef, useState } from "react";
+import {
+  type SSHContainer,
+  SSHContainerLogs,
+  type SSHContainerRuntime,
+  type SSHSession,
+} from "tauri-plugin-ssh";
+import useMessage from "@/hooks/useMessage";
+import styles from "./index.module.less";
+
+/**
+ * 只保留最近的日志，避免长时间跟随时占用过多内存
+ */
+const MAX_LOGS_LENGTH = 512 * 1024;
+const TAIL_LINES = 500;
+
+type ContainerLogsProps = {
+  session?: SSHSession;
+  runtime?: SSHContainerRuntime;
+  container?: SSHContainer;
+  onClose: () => void;
+};
+
+export default function ContainerLogs({
+  session,
+  runtime,
+  container,
+  onClose,
+}: ContainerLogsProps) {
+  const [logs, setLogs] = useState("");
+  const [following, setFollowing] = useState(false);
+  const contentRef = useRef<HTMLPreElement>(null);
+  const message = useMessage();
+
+  useEffect(() => {
+    if (!session || !runtime || !container) {
+      return;
+    }
+
+    const decoder = new TextDecoder();
+    const containerLogs = new SSHContainerLogs({
+      session,
+      runtime,
+      containerId: container.id,
+      onData: (data) => {
+        const text = decoder.decode(data, { stream: true });
+        setLogs((prev) => (prev + text).slice(-MAX_LOGS_LENGTH));
+      },
+      onClose: () => setFollowing(false),
+    });
+
+    setLogs("");
+    setFollowing(true);
+    containerLogs
+      .open({ tail: TAIL_LINES, follow: true, timestamps: true })
+      .catch((err) => {
+        setFollowing(false);
+        message.error({ message: `Failed to read logs: ${String(err)}` });
+      });
+
+    return () => {
+      containerLogs.close().catch(() => {
+        // 日志命令已经退出
+      });
+    };
+  }, [session, runtime, container, message]);
+
+  useEffect(() => {
+    const content = contentRef.current;
+    if (following && content) {
+      content.scrollTop = content.scrollHeight;
+    }
+  }, [logs, following]);
+
+  return (
+    <Dialog.Root open={!!container} onOpenChange={(open) => !open && onClose()}>
+      <Dialog.Content maxWidth="960px">
+        <Dialog.Title>Logs: {container?.name}</Dialog.Title>
+        <Dialog.Description size="2" color="gray">
+          {following ? "Following new output..." : "Logs command exited"}
+        </Dialog.Description>
+        <pre ref={contentRef} className={styles.logs}>
+          {logs}
+        </pre>
+        <Flex justify="end" mt="3">
+          <Dialog.Close>
+            <Button variant="soft" color="gray">
+              Close
+            </Button>
+          </Dialog.Close>
+        </Flex>
+      </Dialog.Content>
+    </Dialog.Root>
+  );
+}
@@ -0,0 +1,207 @@
+import {
+  Badge,
+  Button,
+  DropdownMenu,
+  IconButton,
+  Table,
+  Text,
+} from "@radix-ui/themes";
+import { useCallback, useState } from "react";
+import {
+  MoreIcon,
+  SSHLoading,
+  type TerminalAtom,
+  TerminalIcon,
+  useContainers,
+  useTerminalsAtomWithApi,
+} from "shared";
+import type { SSHContainer, SSHContainerAction } from "tauri-plugin-ssh";
+import { useActivateTerminal } from "@/hooks/useActivateTerminal";
+import useMessage from "@/hooks/useMessage";
+import ContainerLogs from "./ContainerLogs";
+import styles from "./index.module.less";
+
+type ContainerBrowserProps = {
+  item: TerminalAtom;
+  onClose: () => unknown;
+  onOpenAddKey: () => unknown;
+};
+
+export default function ContainerBrowser({
+  item,
+  onClose,
+  onOpenAddKey,
+}: ContainerBrowserProps) {
+  const [logsContainer, setLogsContainer] = useState<SSHContainer>();
+  const terminalsAtomWithApi = useTerminalsAtomWithApi();
+  const activateTerminal = useActivateTerminal();
+  const message = useMessage();
+
+  const {
+    loading,
+    error,
+    session,
+    currentJumpHostChainItem,
+    onReConnect,
+    onReAuth,
+    onRetry,
+    runtime,
+    containers,
+    refreshing,
+    refresh,
+    runAction,
+  } = useContainers({ item });
+
+  const onOpenShell = useCallback(
+    (container: SSHContainer) => {
+      if (!runtime) {
+        return;
+      }
+      const [terminal] = terminalsAtomWithApi.addContainer(
+        item.host,
+        runtime,
+        container,
+      );
+      activateTerminal(terminal.uuid);
+    },
+    [activateTerminal, item.host, runtime, terminalsAtomWithApi],
+  );
+
+  const onAction = useCallback(
+    async (container: SSHContainer, action: SSHContainerAction) => {
+      try {
+        await runAction(container, action);
+      } catch (err) {
+        message.error({
+          message: `Failed to ${action.toLowerCase()} ${container.name}: ${String(err)}`,
+        });
+      }
+    },
+    [message, runAction],
+  );
+
+  const onRefresh = useCallback(() => {
+    refresh().catch((err) => {
+      message.error({ message: `Failed to list containers: ${String(err)}` });
+    });
+  }, [message, refresh]);
+
+  if (loading || error) {
+    return (
+      <SSHLoading
+        host={currentJumpHostChainItem?.host || item.host}
+        loading={currentJumpHostChainItem?.loading}
+        error={error}
+        sx={{ width: "100%", height: "100%" }}
+        onReConnect={onReConnect}
+        onReAuth={onReAuth}
+        onRetry={onRetry}
+        onClose={onClose}
+        onOpenAddKey={onOpenAddKey}
+      />
+    );
+  }
+
+  return (
+    <div className={styles.root}>
+      <div className={styles.toolbar}>
+        <Text size="2" weight="medium">
+          {runtime} containers
+        </Text>
+        <Button
+          size="1"
+          variant="soft"
+          loading={refreshing}
+          onClick={onRefresh}
+        >
+          Refresh
+        </Button>
+      </div>
+      <div className={styles.table}>
+        <Table.Root size="1">
+          <Table.Header>
+            <Table.Row>
+              <Table.ColumnHeaderCell>Name</Table.ColumnHeaderCell>
+              <Table.ColumnHeaderCell>Image</Table.ColumnHeaderCell>
+              <Table.ColumnHeaderCell>Status</Table.ColumnHeaderCell>
+              <Table.ColumnHeaderCell>Ports</Table.ColumnHeaderCell>
+              <Table.ColumnHeaderCell />
+            </Table.Row>
+          </Table.Header>
+          <Table.Body>
+            {containers.map((container) => (
+              <Table.Row key={container.id} align="center">
+                <Table.RowHeaderCell>{container.name}</Table.RowHeaderCell>
+                <Table.Cell>{container.image}</Table.Cell>
+                <Table.Cell>
+                  <Badge color={container.running ? "green" : "gray"}>
+                    {container.status || container.state}
+                  </Badge>
+                </Table.Cell>
+                <Table.Cell className={styles.ports}>
+                  {container.ports}
+                </Table.Cell>
+                <Table.Cell justify="end">
+                  <div className={styles.actions}>
+                    <IconButton
+                      size="1"
+                      variant="ghost"
+                      title="Open shell"
+                      disabled={!container.running}
+                      onClick={() => onOpenShell(container)}
+                    >
+                      <TerminalIcon />
+                    </IconButton>
+                    <DropdownMenu.Root>
+                      <DropdownMenu.Trigger>
+                        <IconButton size="1" variant="ghost">
+                          <MoreIcon />
+                        </IconButton>
+                      </DropdownMenu.Trigger>
+                      <DropdownMenu.Content align="end">
+                        <DropdownMenu.Item
+                          disabled={container.running}
+                          onSelect={() => onAction(container, "Start")}
+                        >
+                          Start
+                        </DropdownMenu.Item>
+                        <DropdownMenu.Item
+                          disabled={!container.running}
+                          onSelect={() => onAction(container, "Stop")}
+                        >
+                          Stop
+                        </DropdownMenu.Item>
+                        <DropdownMenu.Item
+                          onSelect={() => onAction(container, "Restart")}
+                        >
+                          Restart
+                        </DropdownMenu.Item>
+                        <DropdownMenu.Separator />
+                        <DropdownMenu.Item
+                          onSelect={() => setLogsContainer(container)}
+                        >
+                          Logs
+                        </DropdownMenu.Item>
+                      </DropdownMenu.Content>
+                    </DropdownMenu.Root>
+                  </div>
+                </Table.Cell>
+              </Table.Row>
+            ))}
+          </Table.Body>
+        </Table.Root>
+        {containers.length === 0 && (
+          <Text as="p" size="2" color="gray" className={styles.empty}>
+            No containers
+          </Text>
+        )}
+      </div>
+      <ContainerLogs
+        session={session}
+        runtime={runtime}
+        container={logsContainer}
+        onClose={() => setLogsContainer(undefined)}
+      />
+    </div>
+  );
+}
@@ -0,0 +1,28 @@
+import type { IDockviewPanelProps } from "dockview-react";
+import { useTerminalsAtomValue, useTerminalsAtomWithApi } from "shared";
+import ContainerBrowser from "@/components/ContainerBrowser";
+
+export default function ContainersPanel({
+  params,
+  api,
+}: IDockviewPanelProps<{ terminalId: string; onOpenAddKey: () => void }>) {
+  const { terminalId, onOpenAddKey } = params;
+  const terminalsState = useTerminalsAtomValue();
+  const terminalsApi = useTerminalsAtomWithApi();
+  const term = terminalsState.get(terminalId);
+
+  if (!term) return null;
+
+  return (
+    <div style={{ width: "100%", height: "100%", overflow: "hidden" }}>
+      <ContainerBrowser
+        item={term}
+        onClose={() => {
+          api.close();
+          terminalsApi.delete(terminalId);
+        }}
+        onOpenAddKey={onOpenAddKey}
+      />
+    </div>
+  );
+}
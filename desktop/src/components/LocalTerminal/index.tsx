@@ -1,6 +1,8 @@
+import { useMemoizedFn } from "ahooks";
 import { useCallback, useEffect } from "react";
 import type { TerminalAtom } from "shared";
-import { TERMINAL_THEMES_MAP, XTerminal } from "shared";
+import { TERMINAL_THEMES_MAP, useTriggerActions, XTerminal } from "shared";
+import type { PtyShellTriggerEvent } from "tauri-plugin-pty";
 import { useLocalTerminalSettings } from "@/atoms/localTerminalSettings.atom";
 import { useTerminalActiveId } from "@/atoms/terminalView.atom";
 import TerminalContextMenu from "@/components/TerminalContextMenu";
@@ -23,6 +25,11 @@ export default function LocalTerminal({
 }: LocalTerminalProps) {
   const [localSettings] = useLocalTerminalSettings();
 
+  const runTriggerAction = useTriggerActions();
+  const onTrigger = useMemoizedFn((event: PtyShellTriggerEvent) => {
+    runTriggerAction(event, terminal);
+  });
+
   const {
     loading,
     error,
@@ -35,7 +42,9 @@ export default function LocalTerminal({
   } = useLocalShell({
     onClose,
     onCopy: copy,
+    onTrigger,
     shell: localSettings.shell,
+    encoding: localSettings.encoding,
   });
 
   const [activeTerminalId] = useTerminalActiveId();
@@ -8,7 +8,12 @@ import {
 } from "@radix-ui/themes";
 import { useEffect } from "react";
 import { Controller, useForm } from "react-hook-form";
-import { onInputChange, TERMINAL_THEMES } from "shared";
+import {
+  DEFAULT_TERMINAL_ENCODING,
+  onInputChange,
+  TERMINAL_ENCODINGS,
+  TERMINAL_THEMES,
+} from "shared";
 import { useLocalTerminalSettings } from "@/atoms/localTerminalSettings.atom";
 
 type FormValues = {
@@ -16,6 +21,7 @@ type FormValues = {
   fontSize: string;
   theme: string;
   shell: string;
+  encoding: string;
 };
 
 type LocalTerminalSettingsDialogProps = {
@@ -35,6 +41,7 @@ export default function LocalTerminalSettingsDialog({
       fontSize: String(settings.fontSize),
       theme: settings.theme,
       shell: settings.shell,
+      encoding: settings.encoding ?? DEFAULT_TERMINAL_ENCODING,
     },
   });
 
@@ -45,6 +52,7 @@ export default function LocalTerminalSettingsDialog({
         fontSize: String(settings.fontSize),
         theme: settings.theme,
         shell: settings.shell,
+      encoding: settings.encoding ?? DEFAULT_TERMINAL_ENCODING,
       });
     }
   }, [open, settings, formApi]);
@@ -55,6 +63,7 @@ export default function LocalTerminalSettingsDialog({
       fontSize: Number(data.fontSize),
       theme: data.theme,
       shell: data.shell,
+      encoding: data.encoding,
     });
     onCancel();
   });
@@ -69,7 +78,8 @@ export default function LocalTerminalSettingsDialog({
       <Dialog.Content style={{ maxWidth: 420 }}>
         <Dialog.Title>Local Terminal Settings</Dialog.Title>
         <Dialog.Description size="2" color="gray">
-          Configure the font, theme, and shell for the built-in local terminal.
+          Configure the font, theme, shell, and encoding for the built-in local
+          terminal.
         </Dialog.Description>
         <form noValidate autoComplete="off">
           <Flex direction="column" gap="4" mt="4">
@@ -182,6 +192,34 @@ export default function LocalTerminalSettingsDialog({
                 </Flex>
               )}
             />
+
+            <Controller
+              name="encoding"
+              control={formApi.control}
+              render={({ field }) => (
+                <Flex direction="column" gap="1">
+                  <Text as="label" size="2" weight="medium">
+                    Encoding
+                  </Text>
+                  <Select.Root
+                    value={field.value}
+                    onValueChange={field.onChange}
+                  >
+                    <Select.Trigger
+                      style={{ width: "100%" }}
+                      placeholder="Select encoding"
+                    />
+                    <Select.Content>
+                      {TERMINAL_ENCODINGS.map((item) => (
+                        <Select.Item key={item.value} value={item.value}>
+                          {item.label}
+                        </Select.Item>
+                      ))}
+                    </Select.Content>
+                  </Select.Root>
+                </Flex>
+              )}
+            />
           </Flex>
         </form>
         <Flex gap="3" justify="end" mt="4">
@@ -11,6 +11,7 @@ import {
 import { useNavigate } from "react-router-dom";
 import {
   FolderIcon,
+  GridIcon,
   getHostDesc,
   getHostName,
   HostIcon,
@@ -117,8 +118,20 @@ export default function QuickSearch({ open, onClose }: QuickSearchProps) {
         key: `session:${terminal.uuid}`,
         section: "Active Sessions",
         label: terminal.name,
-        description: terminal.type === "sftp" ? "SFTP" : "Terminal",
-        icon: terminal.type === "sftp" ? <FolderIcon /> : <TerminalIcon />,
+        description:
+          terminal.type === "sftp"
+            ? "SFTP"
+            : terminal.type === "containers"
+              ? "Containers"
+              : "Terminal",
+        icon:
+          terminal.type === "sftp" ? (
+            <FolderIcon />
+          ) : terminal.type === "containers" ? (
+            <GridIcon />
+          ) : (
+            <TerminalIcon />
+          ),
         onSelect: () => {
           activateTerminal(terminal.uuid);
           handleClose();
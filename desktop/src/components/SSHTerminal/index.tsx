@@ -1,11 +1,21 @@
+import { Button, Flex, Text } from "@radix-ui/themes";
+import { useMemoizedFn } from "ahooks";
 import { useCallback, useEffect } from "react";
 import {
+  message,
   SSHLoading,
   TERMINAL_THEMES_MAP,
+  TMUX_TABS_HEIGHT,
   type TerminalAtom,
+  TmuxTabs,
   useTerminal,
+  useTriggerActions,
   XTerminal,
 } from "shared";
+import type {
+  SSHShellTriggerEvent,
+  SSHShellUrlForwardingEvent,
+} from "tauri-plugin-ssh";
 import { useTerminalActiveId } from "@/atoms/terminalView.atom";
 import TerminalContextMenu from "@/components/TerminalContextMenu";
 import { copy } from "@/utils/clipboard";
@@ -25,6 +35,64 @@ export default function SSHTerminal({
   onClose,
   onOpenAddKey,
 }: SSHTerminalProps) {
+  const onUrlForwarding = useMemoizedFn(
+    (event: SSHShellUrlForwardingEvent) => {
+      if (event.type === "Forwarded") {
+        message.success(
+          <Flex align="center" gap="2">
+            <Text>
+              {event.url} is forwarded to {event.localUrl}
+            </Text>
+            <Button size="1" onClick={() => openUrl(event.localUrl)}>
+              Open
+            </Button>
+          </Flex>,
+          10000,
+        );
+      } else if (event.type === "Failed") {
+        message.error(`Failed to forward ${event.url}: ${event.message}`);
+      } else if (event.type === "Detected") {
+        const { type: _, ...localUrl } = event;
+        const close = message.open({
+          type: "info",
+          key: localUrl.url,
+          duration: 0,
+          content: (
+            <Flex align="center" gap="2">
+              <Text>{localUrl.url} is running on the remote host</Text>
+              <Button
+                size="1"
+                onClick={async () => {
+                  close();
+                  try {
+                    const forwardedUrl = await forwardUrl(localUrl);
+                    onUrlForwarding({ type: "Forwarded", ...forwardedUrl });
+                  } catch (error) {
+                    onUrlForwarding({
+                      type: "Failed",
+                      url: localUrl.url,
+                      message: String(error),
+                    });
+                  }
+                }}
+              >
+                Forward
+              </Button>
+              <Button size="1" variant="soft" color="gray" onClick={close}>
+                Ignore
+              </Button>
+            </Flex>
+          ),
+        });
+      }
+    },
+  );
+
+  const runTriggerAction = useTriggerActions();
+  const onTrigger = useMemoizedFn((event: SSHShellTriggerEvent) => {
+    runTriggerAction(event, terminal);
+  });
+
   const {
     loading,
     error,
@@ -37,7 +105,17 @@ export default function SSHTerminal({
     onTerminalData,
     onTerminalBinaryData,
     onTerminalResize,
-  } = useTerminal({ item, onClose, onCopy: copy });
+    forwardUrl,
+    tmux,
+    selectTmuxPane,
+    detachTmux,
+  } = useTerminal({
+    item,
+    onClose,
+    onCopy: copy,
+    onTrigger,
+    onUrlForwarding,
+  });
 
   const [activeTerminalId] = useTerminalActiveId();
 
@@ -59,8 +137,17 @@ export default function SSHTerminal({
       style={style}
       onMouseDown={handleRootMouseDown}
     >
+      {tmux && !showLoading && (
+        <TmuxTabs
+          tmux={tmux}
+          style={{ position: "absolute", top: 0, left: 0, right: 0 }}
+          onSelect={selectTmuxPane}
+          onDetach={detachTmux}
+        />
+      )}
       <div
         className={`${styles.terminalLayer} ${showLoading ? styles.terminalLayerHidden : ""}`}
+        style={{ top: tmux ? TMUX_TABS_HEIGHT : 0 }}
       >
         <TerminalContextMenu terminal={terminal}>
           <XTerminal
@@ -0,0 +1,144 @@
+import { Badge, Button, Dialog, Flex, Table, Text } from "@radix-ui/themes";
+import { useRequest } from "ahooks";
+import { formatBytes, formatEta } from "shared";
+import {
+  clearTransferHistories,
+  getTransferHistories,
+  type TransferHistory as TransferHistoryItem,
+} from "tauri-plugin-data";
+import useMessage from "@/hooks/useMessage";
+import {
+  getErrorMessage,
+  getLocalBasename,
+  getSftpBasename,
+} from "../messages";
+import styles from "./index.module.less";
+
+const statusColors = {
+  Completed: "green",
+  Failed: "red",
+  Cancelled: "gray",
+} as const;
+
+type TransferHistoryProps = {
+  hostId?: string;
+  open: boolean;
+  onClose: () => void;
+};
+
+function getFileName(history: TransferHistoryItem) {
+  return history.kind === "Upload"
+    ? getLocalBasename(history.localFilename)
+    : getSftpBasename(history.remoteFilename);
+}
+
+/**
+ * 主机的传输历史，由后端传输队列在传输结束时写入
+ */
+export default function TransferHistory({
+  hostId,
+  open,
+  onClose,
+}: TransferHistoryProps) {
+  const message = useMessage();
+
+  const { data: histories = [], loading, mutate } = useRequest(
+    async () => (hostId ? getTransferHistories(hostId) : []),
+    {
+      ready: open,
+      refreshDeps: [open, hostId],
+      onError: (err) =>
+        message.error({
+          message: `Failed to load transfer history: ${getErrorMessage(err)}`,
+        }),
+    },
+  );
+
+  const { loading: clearLoading, run: clear } = useRequest(
+    async () => {
+      if (hostId) {
+        await clearTransferHistories(hostId);
+      }
+    },
+    {
+      manual: true,
+      onSuccess: () => mutate([]),
+      onError: (err) =>
+        message.error({
+          message: `Failed to clear transfer history: ${getErrorMessage(err)}`,
+        }),
+    },
+  );
+
+  return (
+    <Dialog.Root open={open} onOpenChange={(value) => !value && onClose()}>
+      <Dialog.Content maxWidth="960px">
+        <Dialog.Title>Transfer History</Dialog.Title>
+        <Dialog.Description size="2" color="gray">
+          Files uploaded and downloaded on this host
+        </Dialog.Description>
+        <div className={styles.table}>
+          <Table.Root size="1">
+            <Table.Header>
+              <Table.Row>
+                <Table.ColumnHeaderCell>File</Table.ColumnHeaderCell>
+                <Table.ColumnHeaderCell>Type</Table.ColumnHeaderCell>
+                <Table.ColumnHeaderCell>Size</Table.ColumnHeaderCell>
+                <Table.ColumnHeaderCell>Duration</Table.ColumnHeaderCell>
+                <Table.ColumnHeaderCell>Status</Table.ColumnHeaderCell>
+                <Table.ColumnHeaderCell>Time</Table.ColumnHeaderCell>
+              </Table.Row>
+            </Table.Header>
+            <Table.Body>
+              {histories.map((history) => (
+                <Table.Row key={history.id} align="center">
+                  <Table.RowHeaderCell
+                    className={styles.file}
+                    title={history.remoteFilename}
+                  >
+                    {getFileName(history)}
+                  </Table.RowHeaderCell>
+                  <Table.Cell>{history.kind}</Table.Cell>
+                  <Table.Cell>{formatBytes(history.size)}</Table.Cell>
+                  <Table.Cell>{formatEta(history.duration / 1000)}</Table.Cell>
+                  <Table.Cell>
+                    <Badge
+                      color={statusColors[history.status]}
+                      title={history.error}
+                    >
+                      {history.status}
+                    </Badge>
+                  </Table.Cell>
+                  <Table.Cell>
+                    {new Date(history.createdAt).toLocaleString()}
+                  </Table.Cell>
+                </Table.Row>
+              ))}
+            </Table.Body>
+          </Table.Root>
+          {histories.length === 0 && (
+            <Text as="p" size="2" color="gray" className={styles.empty}>
+              {loading ? "Loading..." : "No transfers"}
+            </Text>
+          )}
+        </div>
+        <Flex justify="end" gap="3" mt="3">
+          <Button
+            variant="soft"
+            color="red"
+            loading={clearLoading}
+            disabled={histories.length === 0}
+            onClick={clear}
+          >
+            Clear
+          </Button>
+          <Dialog.Close>
+            <Button variant="soft" color="gray">
+              Close
+            </Button>
+          </Dialog.Close>
+        </Flex>
+      </Dialog.Content>
+    </Dialog.Root>
+  );
+}
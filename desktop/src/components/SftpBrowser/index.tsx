@@ -28,6 +28,7 @@ import { SftpTableOrder } from "./types";
 import useCells from "./useCells";
 import useCreate, { CreateType } from "./useCreate";
 import useRename from "./useRename";
+import TransferHistory from "./TransferHistory";
 import useSftpActions from "./useSftpActions";
 
 type SftpProps = {
@@ -45,6 +46,7 @@ export default function Sftp({ item, onClose, onOpenAddKey }: SftpProps) {
   const message = useMessage();
   const [keyword, setKeyword] = useState("");
   const [isShowHiddenFiles, setIsShowHiddenFiles] = useState(false);
+  const [isHistoryOpen, setIsHistoryOpen] = useState(false);
 
   const {
     sftpRef,
@@ -131,11 +133,17 @@ export default function Sftp({ item, onClose, onOpenAddKey }: SftpProps) {
     removeFileItem,
     uploadFile,
     downloadFile,
+    uploadDir,
+    downloadDir,
+    resumableTransfers,
+    resumeTransfers,
+    discardTransfers,
     removeDir,
     removeDirLoading,
     removeFile,
     removeFileLoading,
   } = useSftpActions({
+    hostId: item.host.id,
     dirname,
     message,
     modal,
@@ -144,7 +152,10 @@ export default function Sftp({ item, onClose, onOpenAddKey }: SftpProps) {
   });
 
   const onSelectDir = useCallback((item: SSHSftpFile) => {
-    if (item.fileType === SSHSftpFileType.Dir) {
+    if (
+      item.fileType === SSHSftpFileType.Dir ||
+      item.targetType === SSHSftpFileType.Dir
+    ) {
       setDirname(item.path);
     }
   }, []);
@@ -196,6 +207,7 @@ export default function Sftp({ item, onClose, onOpenAddKey }: SftpProps) {
     onRenameCancel,
     onRenameOk,
     downloadFile,
+    downloadDir,
     removeFile,
     removeDir,
     modal,
@@ -306,6 +318,30 @@ export default function Sftp({ item, onClose, onOpenAddKey }: SftpProps) {
         value: "New Folder",
         onClick: () => onCreate(CreateType.Dir, "New Folder"),
       },
+      {
+        label: "Upload Folder",
+        value: "Upload Folder",
+        onClick: uploadDir,
+      },
+      ...(resumableTransfers.length > 0
+        ? [
+            {
+              label: `Resume Transfers (${resumableTransfers.length})`,
+              value: "Resume Transfers",
+              onClick: resumeTransfers,
+            },
+            {
+              label: "Discard Transfers",
+              value: "Discard Transfers",
+              onClick: discardTransfers,
+            },
+          ]
+        : []),
+      {
+        label: "Transfer History",
+        value: "Transfer History",
+        onClick: () => setIsHistoryOpen(true),
+      },
       {
         label: "Refresh",
         value: "Refresh",
@@ -317,7 +353,15 @@ export default function Sftp({ item, onClose, onOpenAddKey }: SftpProps) {
         onClick: () => setIsShowHiddenFiles(!isShowHiddenFiles),
       },
     ];
-  }, [isShowHiddenFiles, onCreate, safeRefreshDir]);
+  }, [
+    isShowHiddenFiles,
+    onCreate,
+    safeRefreshDir,
+    uploadDir,
+    resumableTransfers,
+    resumeTransfers,
+    discardTransfers,
+  ]);
 
   const isLoading =
     readDirLoading ||
@@ -488,6 +532,11 @@ export default function Sftp({ item, onClose, onOpenAddKey }: SftpProps) {
         onSave={saveFileContent}
         onLoadContent={loadFileContent}
       />
+      <TransferHistory
+        hostId={item.host.id}
+        open={isHistoryOpen}
+        onClose={() => setIsHistoryOpen(false)}
+      />
     </div>
   );
 }
@@ -17,3 +17,7 @@ export function getErrorMessage(err: unknown, fallback = "Unknown error") {
 export function formatTransferCount(count: number, singular: string) {
   return `${count} ${singular}${count === 1 ? "" : "s"}`;
 }
+
+export function getLocalBasename(path: string) {
+  return path.split(/[/\\]/).pop() || path;
+}
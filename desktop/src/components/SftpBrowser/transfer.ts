@@ -1,4 +1,6 @@
-import type { TransferQueueItem } from "shared";
+import type { QueueItemStatus, TransferQueueItem } from "shared";
+import type { SSHTransferQueueItem, SSHTransferStatus } from "tauri-plugin-ssh";
+import { getLocalBasename, getSftpBasename } from "./messages";
 
 export type TransferStatus =
   | "transferring"
@@ -80,3 +82,35 @@ export function getCurrentTransferIndex(
     : -1;
   return Math.max(fallbackIndex, 0);
 }
+
+const queueStatuses: Record<SSHTransferStatus, QueueItemStatus> = {
+  Queued: "waiting",
+  Running: "transferring",
+  Paused: "paused",
+  Completed: "completed",
+  Failed: "failed",
+  Cancelled: "cancelled",
+};
+
+/**
+ * 后端队列中的传输转换为面板中的一项，id 即 taskId
+ */
+export function toTransferQueueItem(
+  item: SSHTransferQueueItem,
+): TransferQueueItem {
+  const isUpload = item.kind === "Upload";
+  return {
+    id: item.id,
+    type: isUpload ? "upload" : "download",
+    fileName: isUpload
+      ? getLocalBasename(item.localFilename)
+      : getSftpBasename(item.remoteFilename),
+    status: queueStatuses[item.status],
+    progress: item.progress,
+    total: item.total,
+    speed: item.speed,
+    eta: item.eta ?? -1,
+    taskId: item.id,
+    error: item.error ?? undefined,
+  };
+}
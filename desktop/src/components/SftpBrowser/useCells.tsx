@@ -23,6 +23,7 @@ type UseCellsOpts = {
   onRenameOk: () => unknown;
   onRename: (item: SSHSftpFile) => unknown;
   downloadFile: (item: SSHSftpFile) => unknown;
+  downloadDir: (item: SSHSftpFile) => unknown;
   removeDir: (item: SSHSftpFile) => unknown;
   removeFile: (item: SSHSftpFile) => unknown;
   onSelectDir: (item: SSHSftpFile) => unknown;
@@ -43,6 +44,7 @@ export default function useCells({
   onRenameOk,
   onRename,
   downloadFile,
+  downloadDir,
   removeFile,
   removeDir,
   onSelectDir,
@@ -54,7 +56,11 @@ export default function useCells({
       if (selectedFile?.path === row.path) {
         return;
       }
-      if (row.fileType === SSHSftpFileType.Dir) {
+      // 指向目录的符号链接可以直接进入
+      if (
+        row.fileType === SSHSftpFileType.Dir ||
+        row.targetType === SSHSftpFileType.Dir
+      ) {
         onSelectDir(row);
       } else if (row.fileType === SSHSftpFileType.File) {
         onEditFile(row);
@@ -106,7 +112,11 @@ export default function useCells({
           return (
             <div
               className={styles.nameRow}
-              title={item.name}
+              title={
+                item.linkTarget
+                  ? `${item.name} -> ${item.linkTarget}`
+                  : item.name
+              }
               onDoubleClick={() => onDoubleClickName(item)}
             >
               <IconComponent className={styles.fileIcon} />
@@ -197,8 +207,15 @@ export default function useCells({
             <button
               type="button"
               className={styles.optButton}
-              disabled={item.fileType !== SSHSftpFileType.File}
-              onClick={() => downloadFile(item)}
+              disabled={
+                item.fileType !== SSHSftpFileType.File &&
+                item.fileType !== SSHSftpFileType.Dir
+              }
+              onClick={() =>
+                item.fileType === SSHSftpFileType.Dir
+                  ? downloadDir(item)
+                  : downloadFile(item)
+              }
             >
               <FileDownloadIcon />
             </button>
@@ -228,6 +245,7 @@ export default function useCells({
       onRenameOk,
       onRename,
       downloadFile,
+      downloadDir,
       onDoubleClickName,
       onDelete,
     ],
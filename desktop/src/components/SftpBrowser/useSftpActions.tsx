@@ -1,15 +1,33 @@
+import { join } from "@tauri-apps/api/path";
 import { open, save } from "@tauri-apps/plugin-dialog";
 import { useRequest } from "ahooks";
 import { throttle } from "lodash-es";
-import { type MutableRefObject, useCallback, useRef, useState } from "react";
+import {
+  type MutableRefObject,
+  useCallback,
+  useEffect,
+  useMemo,
+  useRef,
+  useState,
+} from "react";
 import { joinSftpPath, type TransferQueueItem } from "shared";
-import type { SSHSftp, SSHSftpFile } from "tauri-plugin-ssh";
+import {
+  isTransferFinished,
+  type SSHSftp,
+  type SSHSftpDirTransferOpts,
+  type SSHSftpFile,
+  type SSHSftpOnDirProgressOpts,
+  type SSHTransferQueueItem,
+} from "tauri-plugin-ssh";
+import { usePendingTransfers } from "@/atoms/pendingTransfers.atom";
 import { useFileTransfersActions } from "@/atoms/terminalView.atom";
+import { useTransferQueue } from "@/atoms/transferQueue.atom";
 import type useMessage from "@/hooks/useMessage";
 import type useModal from "@/hooks/useModal";
 import {
   formatTransferCount,
   getErrorMessage,
+  getLocalBasename,
   getSftpBasename,
 } from "./messages";
 import {
@@ -17,14 +35,51 @@ import {
   deriveTransferStatus,
   getCurrentTransferIndex,
   type TransferInfo,
-  type TransferStatus,
+  toTransferQueueItem,
 } from "./transfer";
 
-const UPLOAD_CONCURRENCY = 6;
+/**
+ * 文件夹传输默认跳过的内容
+ */
+const DIR_TRANSFER_OPTS: SSHSftpDirTransferOpts = {
+  excludes: [".git/", "node_modules/"],
+  gitignore: true,
+};
+
+type TransferTarget = {
+  localFilename: string;
+  remoteFilename: string;
+  /**
+   * 续传时沿用记录中的 taskId
+   */
+  taskId?: string;
+};
+
+/**
+ * 一次加入后端队列的文件，全部结束后汇总提示
+ */
+type TransferBatch = {
+  type: "upload" | "download";
+  dirname?: string;
+  taskIds: string[];
+  /**
+   * 队列事件可能晚于入队命令返回，全部出现在队列中后才判断是否结束
+   */
+  seen: boolean;
+};
 
-type UploadBatch = { aborted: boolean };
+/**
+ * 文件夹传输不经过后端队列，作为面板中的一项单独维护
+ */
+type DirTransferItem = TransferQueueItem & {
+  /**
+   * 文件夹中正在传输的文件
+   */
+  current?: string;
+};
 
 type UseSftpActionsOpts = {
+  hostId?: string;
   dirname?: string;
   message: ReturnType<typeof useMessage>;
   modal: ReturnType<typeof useModal>;
@@ -32,227 +87,371 @@ type UseSftpActionsOpts = {
   refreshDir: () => unknown;
 };
 
+function isActive(item: TransferQueueItem) {
+  return (
+    item.status === "transferring" ||
+    item.status === "paused" ||
+    item.status === "waiting"
+  );
+}
+
 export default function useSftpActions({
+  hostId,
   dirname,
   message,
   modal,
   sftpRef,
   refreshDir,
 }: UseSftpActionsOpts) {
-  const [transferInfo, setTransferInfo] = useState<TransferInfo | null>(null);
-  const [transferStatus, setTransferStatus] = useState<TransferStatus | null>(
-    null,
-  );
+  const [dirItems, setDirItems] = useState<DirTransferItem[]>([]);
   const [panelOpen, setPanelOpen] = useState(false);
-  const activeUploadBatchesRef = useRef(new Set<UploadBatch>());
   const cancelledItemIdsRef = useRef(new Set<string>());
-  const transferInfoRef = useRef<TransferInfo | null>(null);
+  const batchesRef = useRef<TransferBatch[]>([]);
+  const dirItemsRef = useRef(dirItems);
   const dirnameRef = useRef(dirname);
   dirnameRef.current = dirname;
   const { startTransfer: incTransfer, finishTransfer: decTransfer } =
     useFileTransfersActions();
-
-  const setTransferInfoWithRef = useCallback(
-    (
-      updater:
-        | TransferInfo
-        | ((prev: TransferInfo | null) => TransferInfo | null),
-    ) => {
-      setTransferInfo((prev) => {
-        const next = typeof updater === "function" ? updater(prev) : updater;
-        transferInfoRef.current = next;
+  const { pendingTransfers, addPendingTransfer, removePendingTransfer } =
+    usePendingTransfers(hostId);
+  const {
+    items: queueItems,
+    enqueue,
+    cancel,
+    pause,
+    resume,
+    retry,
+    remove,
+  } = useTransferQueue(hostId);
+  const queueItemsRef = useRef(queueItems);
+  queueItemsRef.current = queueItems;
+
+  const setDirItemsWithRef = useCallback(
+    (updater: (prev: DirTransferItem[]) => DirTransferItem[]) => {
+      setDirItems((prev) => {
+        const next = updater(prev);
+        dirItemsRef.current = next;
         return next;
       });
     },
     [],
   );
 
-  const togglePanel = useCallback(() => {
-    setPanelOpen((prev) => !prev);
-  }, []);
+  const updateDirItem = useCallback(
+    (itemId: string, patch: Partial<DirTransferItem>) => {
+      setDirItemsWithRef((prev) =>
+        prev.map((item) => (item.id === itemId ? { ...item, ...patch } : item)),
+      );
+    },
+    [setDirItemsWithRef],
+  );
 
-  const cancelTransfer = useCallback(() => {
-    for (const batch of activeUploadBatchesRef.current) {
-      batch.aborted = true;
+  const transferInfo = useMemo<TransferInfo | null>(() => {
+    const queue: DirTransferItem[] = [
+      ...queueItems.map(toTransferQueueItem),
+      ...dirItems,
+    ];
+    if (queue.length === 0) {
+      return null;
     }
-    const info = transferInfoRef.current;
-    if (!info) return;
-    for (const item of info.queue) {
-      if (
-        item.taskId &&
-        (item.status === "transferring" ||
-          item.status === "paused" ||
-          item.status === "waiting")
-      ) {
-        sftpRef.current?.sftpCancelTask(item.taskId);
-      }
-    }
-    setTransferStatus("cancelled");
-  }, [sftpRef]);
 
-  const pauseTransfer = useCallback(() => {
-    const info = transferInfoRef.current;
-    if (!info) return;
-    for (const item of info.queue) {
-      if (item.taskId && item.status === "transferring") {
-        sftpRef.current?.sftpPauseTask(item.taskId);
-      }
-    }
-    setTransferInfoWithRef((prev) => {
-      if (!prev) return null;
-      const q = prev.queue.map((item) =>
-        item.status === "transferring"
-          ? { ...item, status: "paused" as const }
-          : item,
+    const currentIndex = getCurrentTransferIndex(queue);
+    const current = queue[currentIndex];
+    return {
+      type: current.type ?? "upload",
+      dirname,
+      fileName: current.current
+        ? `${current.fileName}/${current.current}`
+        : current.fileName,
+      ...computeOverall(queue),
+      queue,
+      currentIndex,
+    };
+  }, [queueItems, dirItems, dirname]);
+
+  const transferStatus = transferInfo
+    ? deriveTransferStatus(transferInfo.queue)
+    : null;
+
+  const runQueueAction = useCallback(
+    (action: Promise<unknown>) => {
+      action.catch((err) =>
+        message.error({
+          message: `Failed to update transfer: ${getErrorMessage(err)}`,
+        }),
       );
-      return { ...prev, queue: q, ...computeOverall(q) };
-    });
-    setTransferStatus("paused");
-  }, [sftpRef, setTransferInfoWithRef]);
+    },
+    [message],
+  );
 
-  const resumeTransfer = useCallback(() => {
-    const info = transferInfoRef.current;
-    if (!info) return;
-    for (const item of info.queue) {
-      if (item.taskId && item.status === "paused") {
-        sftpRef.current?.sftpResumeTask(item.taskId);
-      }
-    }
-    setTransferInfoWithRef((prev) => {
-      if (!prev) return null;
-      const q = prev.queue.map((item) =>
-        item.status === "paused"
-          ? { ...item, status: "transferring" as const }
-          : item,
-      );
-      return { ...prev, queue: q, ...computeOverall(q) };
-    });
-    setTransferStatus("transferring");
-  }, [sftpRef, setTransferInfoWithRef]);
+  const isDirItem = useCallback(
+    (itemId: string) => dirItemsRef.current.some((item) => item.id === itemId),
+    [],
+  );
+
+  const togglePanel = useCallback(() => {
+    setPanelOpen((prev) => !prev);
+  }, []);
 
   const cancelFileItem = useCallback(
     (itemId: string) => {
-      const info = transferInfoRef.current;
-      if (!info) return;
-      const item = info.queue.find((i) => i.id === itemId);
-      if (!item) return;
+      if (!isDirItem(itemId)) {
+        runQueueAction(cancel(itemId));
+        return;
+      }
 
-      if (
-        item.taskId &&
-        (item.status === "transferring" || item.status === "paused")
-      ) {
+      const item = dirItemsRef.current.find((i) => i.id === itemId);
+      if (item?.taskId && isActive(item)) {
         cancelledItemIdsRef.current.add(itemId);
         sftpRef.current?.sftpCancelTask(item.taskId);
       }
-      setTransferInfoWithRef((prev) => {
-        if (!prev) return null;
-        const q = prev.queue.map((i) =>
-          i.id === itemId ? { ...i, status: "cancelled" as const } : i,
-        );
-        return { ...prev, queue: q, ...computeOverall(q) };
-      });
     },
-    [sftpRef, setTransferInfoWithRef],
+    [sftpRef, isDirItem, runQueueAction, cancel],
   );
 
   const pauseFileItem = useCallback(
     (itemId: string) => {
-      const info = transferInfoRef.current;
-      if (!info) return;
-      const item = info.queue.find((i) => i.id === itemId);
-      if (item?.status !== "transferring") return;
+      if (!isDirItem(itemId)) {
+        runQueueAction(pause(itemId));
+        return;
+      }
 
+      const item = dirItemsRef.current.find((i) => i.id === itemId);
+      if (item?.status !== "transferring") return;
       if (item.taskId) {
         sftpRef.current?.sftpPauseTask(item.taskId);
       }
-      setTransferInfoWithRef((prev) => {
-        if (!prev) return null;
-        const q = prev.queue.map((i) =>
-          i.id === itemId ? { ...i, status: "paused" as const } : i,
-        );
-        return { ...prev, queue: q, ...computeOverall(q) };
-      });
+      updateDirItem(itemId, { status: "paused" });
     },
-    [sftpRef, setTransferInfoWithRef],
+    [sftpRef, isDirItem, runQueueAction, pause, updateDirItem],
   );
 
   const resumeFileItem = useCallback(
     (itemId: string) => {
-      const info = transferInfoRef.current;
-      if (!info) return;
-      const item = info.queue.find((i) => i.id === itemId);
-      if (item?.status !== "paused") return;
+      if (!isDirItem(itemId)) {
+        runQueueAction(resume(itemId));
+        return;
+      }
 
+      const item = dirItemsRef.current.find((i) => i.id === itemId);
+      if (item?.status !== "paused") return;
       if (item.taskId) {
         sftpRef.current?.sftpResumeTask(item.taskId);
       }
-      setTransferInfoWithRef((prev) => {
-        if (!prev) return null;
-        const q = prev.queue.map((i) =>
-          i.id === itemId ? { ...i, status: "transferring" as const } : i,
-        );
-        return { ...prev, queue: q, ...computeOverall(q) };
-      });
+      updateDirItem(itemId, { status: "transferring" });
     },
-    [sftpRef, setTransferInfoWithRef],
+    [sftpRef, isDirItem, runQueueAction, resume, updateDirItem],
   );
 
   const removeFileItem = useCallback(
     (itemId: string) => {
-      const info = transferInfoRef.current;
-      const item = info?.queue.find((i) => i.id === itemId);
-      if (
-        item?.taskId &&
-        (item.status === "transferring" ||
-          item.status === "paused" ||
-          item.status === "waiting")
-      ) {
+      if (!isDirItem(itemId)) {
+        runQueueAction(remove(itemId));
+        return;
+      }
+
+      const item = dirItemsRef.current.find((i) => i.id === itemId);
+      if (item?.taskId && isActive(item)) {
         sftpRef.current?.sftpCancelTask(item.taskId);
       }
+      setDirItemsWithRef((prev) => prev.filter((i) => i.id !== itemId));
+    },
+    [sftpRef, isDirItem, runQueueAction, remove, setDirItemsWithRef],
+  );
+
+  const cancelTransfer = useCallback(() => {
+    for (const item of queueItemsRef.current) {
+      if (!isTransferFinished(item.status)) {
+        runQueueAction(cancel(item.id));
+      }
+    }
+    for (const item of dirItemsRef.current) {
+      cancelFileItem(item.id);
+    }
+  }, [runQueueAction, cancel, cancelFileItem]);
+
+  const pauseTransfer = useCallback(() => {
+    for (const item of queueItemsRef.current) {
+      if (item.status === "Queued" || item.status === "Running") {
+        runQueueAction(pause(item.id));
+      }
+    }
+    for (const item of dirItemsRef.current) {
+      pauseFileItem(item.id);
+    }
+  }, [runQueueAction, pause, pauseFileItem]);
+
+  const resumeTransfer = useCallback(() => {
+    for (const item of queueItemsRef.current) {
+      if (item.status === "Paused") {
+        runQueueAction(resume(item.id));
+      }
+    }
+    for (const item of dirItemsRef.current) {
+      resumeFileItem(item.id);
+    }
+  }, [runQueueAction, resume, resumeFileItem]);
+
+  /**
+   * 文件加入后端队列，续传时队列中仍有同一传输则直接重试
+   */
+  const enqueueTransfers = async (
+    type: "upload" | "download",
+    targets: TransferTarget[],
+    resumeTarget = false,
+  ) => {
+    const sftp = sftpRef.current;
+    if (!sftp) {
+      return;
+    }
+
+    const taskIds: string[] = [];
+    for (const target of targets) {
+      const { localFilename, remoteFilename } = target;
+      const taskId = target.taskId ?? crypto.randomUUID();
+      if (hostId) {
+        addPendingTransfer({
+          taskId,
+          hostId,
+          type,
+          fileName:
+            type === "upload"
+              ? getLocalBasename(localFilename)
+              : getSftpBasename(remoteFilename),
+          localFilename,
+          remoteFilename,
+        });
+      }
+
+      if (queueItemsRef.current.some((item) => item.id === taskId)) {
+        await retry(taskId);
+      } else {
+        await enqueue({
+          sftp,
+          kind: type === "upload" ? "Upload" : "Download",
+          localFilename,
+          remoteFilename,
+          taskId,
+          resume: resumeTarget,
+        });
+      }
+      taskIds.push(taskId);
+    }
 
-      setTransferInfoWithRef((prev) => {
-        if (!prev) return null;
-        const q = prev.queue.filter((i) => i.id !== itemId);
-        if (q.length === 0) {
-          setTransferStatus(null);
-          return null;
+    batchesRef.current.push({
+      type,
+      dirname: dirnameRef.current,
+      taskIds,
+      seen: false,
+    });
+    setPanelOpen(true);
+  };
+
+  const notifyBatch = useCallback(
+    (batch: TransferBatch, items: SSHTransferQueueItem[]) => {
+      const verb = batch.type === "upload" ? "Upload" : "Download";
+      if (items.length === 1) {
+        const [item] = items;
+        const name =
+          batch.type === "upload"
+            ? getLocalBasename(item.localFilename)
+            : getSftpBasename(item.remoteFilename);
+        if (item.status === "Completed") {
+          message.success({ message: `${verb}ed "${name}"` });
+        } else if (item.status === "Failed") {
+          message.error({
+            message: `Failed to ${verb.toLowerCase()} "${name}": ${item.error ?? "Unknown error"}`,
+          });
+        } else {
+          message.info({ message: `${verb} cancelled` });
         }
-        return {
-          ...prev,
-          currentIndex: Math.min(prev.currentIndex, q.length - 1),
-          queue: q,
-          ...computeOverall(q),
-        };
-      });
+        return;
+      }
+
+      const countOf = (status: SSHTransferQueueItem["status"]) =>
+        items.filter((item) => item.status === status).length;
+      const succeededCount = countOf("Completed");
+      const failedCount = countOf("Failed");
+      const cancelledCount = countOf("Cancelled");
+      const target = batch.type === "upload" ? ` to ${batch.dirname}` : "";
+
+      if (succeededCount > 0 && failedCount === 0 && cancelledCount === 0) {
+        message.success({
+          message: `${verb}ed ${formatTransferCount(succeededCount, "file")}${target}`,
+        });
+      } else if (succeededCount > 0) {
+        message.warning({
+          message: `${verb} finished: ${succeededCount} ${verb.toLowerCase()}ed, ${failedCount} failed, ${cancelledCount} cancelled`,
+        });
+      } else if (failedCount > 0) {
+        message.error({
+          message: `${verb} failed: ${formatTransferCount(failedCount, "file")} could not be ${verb.toLowerCase()}ed`,
+        });
+      } else if (cancelledCount > 0) {
+        message.info({ message: `${verb} cancelled` });
+      }
     },
-    [sftpRef, setTransferInfoWithRef],
+    [message],
   );
 
+  // 完成的传输移除续传记录，整批结束后汇总提示并刷新目录
+  useEffect(() => {
+    for (const item of queueItems) {
+      if (
+        item.status === "Completed" &&
+        pendingTransfers.some((transfer) => transfer.taskId === item.id)
+      ) {
+        removePendingTransfer(item.id);
+      }
+    }
+
+    const itemMap = new Map(queueItems.map((item) => [item.id, item]));
+    let finished = false;
+    batchesRef.current = batchesRef.current.filter((batch) => {
+      const items = batch.taskIds.flatMap((taskId) => {
+        const item = itemMap.get(taskId);
+        return item ? [item] : [];
+      });
+      if (!batch.seen) {
+        if (items.length < batch.taskIds.length) {
+          return true;
+        }
+        batch.seen = true;
+      }
+      if (items.some((item) => !isTransferFinished(item.status))) {
+        return true;
+      }
+
+      notifyBatch(batch, items);
+      finished = true;
+      return false;
+    });
+    if (finished) {
+      refreshDir();
+    }
+  }, [
+    queueItems,
+    pendingTransfers,
+    removePendingTransfer,
+    notifyBatch,
+    refreshDir,
+  ]);
+
   const { loading: uploadFileLoading, run: uploadFile } = useRequest(
     async () => {
       const filePaths = await open({ multiple: true, directory: false });
       if (!filePaths || filePaths.length === 0) return;
 
-      const items: TransferQueueItem[] = filePaths.map((p) => ({
-        id: crypto.randomUUID(),
-        type: "upload",
-        fileName: p.split(/(\/)|(\\)/).pop() || p,
-        status: "waiting" as const,
-        progress: 0,
-        total: 0,
-        speed: 0,
-        eta: -1,
-        taskId: crypto.randomUUID(),
-      }));
-
       const uploadDir = dirnameRef.current;
+      const targets: TransferTarget[] = filePaths.map((p) => ({
+        localFilename: p,
+        remoteFilename: joinSftpPath(uploadDir, getLocalBasename(p)),
+      }));
       const conflicts = (
         await Promise.all(
-          items.map(async (item) => {
-            const exists = await sftpRef.current?.sftpExists(
-              joinSftpPath(uploadDir, item.fileName),
-            );
-            return exists ? item.fileName : null;
+          targets.map(async ({ remoteFilename }) => {
+            const exists = await sftpRef.current?.sftpExists(remoteFilename);
+            return exists ? getSftpBasename(remoteFilename) : null;
           }),
         )
       ).filter((name): name is string => name !== null);
@@ -272,412 +471,241 @@ export default function useSftpActions({
         }
       }
 
-      const batch: UploadBatch = { aborted: false };
-      const batchProgress = new Map<
-        string,
-        { time: number; progress: number }
-      >();
-      activeUploadBatchesRef.current.add(batch);
-      const queuedInfo: TransferInfo = (() => {
-        const q = [...(transferInfoRef.current?.queue ?? []), ...items];
-        return {
-          type: "upload",
-          dirname: dirnameRef.current,
-          fileName: items[0].fileName,
-          ...computeOverall(q),
-          queue: q,
-          currentIndex: q.findIndex((item) => item.id === items[0].id),
-        };
-      })();
-      transferInfoRef.current = queuedInfo;
-      setTransferInfoWithRef(queuedInfo);
-      setTransferStatus("transferring");
-      setPanelOpen(true);
-      incTransfer();
-
-      const uploadedIds = new Set(items.map((item) => item.id));
-
-      const uploadOne = async (index: number) => {
-        const item = items[index];
-        if (batch.aborted) {
-          return;
-        }
-        const currentStatus = transferInfoRef.current?.queue.find(
-          (queueItem) => queueItem.id === item.id,
-        )?.status;
-        if (currentStatus !== "waiting") {
-          return;
-        }
-        const remoteName = joinSftpPath(dirnameRef.current, item.fileName);
-        const localFilePath = filePaths[index] ?? item.fileName;
-        batchProgress.set(item.id, {
-          time: performance.now(),
-          progress: 0,
-        });
-        const latestProgress = { progress: 0, total: 0, speed: 0, eta: -1 };
-        const throttledRender = throttle(
-          () => {
-            setTransferInfoWithRef((prev) => {
-              if (!prev) return null;
-              const q = prev.queue.map((queueItem) =>
-                queueItem.id === item.id
-                  ? {
-                      ...queueItem,
-                      progress: latestProgress.progress,
-                      total: latestProgress.total,
-                      speed: latestProgress.speed,
-                      eta: latestProgress.eta,
-                    }
-                  : queueItem,
-              );
-              return {
-                ...prev,
-                fileName: item.fileName,
-                queue: q,
-                currentIndex: getCurrentTransferIndex(q, item.id),
-                ...computeOverall(q),
-              };
-            });
-          },
-          1000,
-          { leading: true, trailing: true },
-        );
-        setTransferInfoWithRef((prev) => {
-          if (!prev) return null;
-          const q = prev.queue.map((queueItem) =>
-            queueItem.id === item.id
-              ? { ...queueItem, status: "transferring" as const }
-              : queueItem,
-          );
-          return {
-            ...prev,
-            fileName: item.fileName,
-            currentIndex: getCurrentTransferIndex(q, item.id),
-            queue: q,
-            ...computeOverall(q),
-          };
-        });
+      await enqueueTransfers("upload", targets);
+    },
+    {
+      manual: true,
+      onError: (err) =>
+        message.error({
+          message: `Failed to upload files: ${getErrorMessage(err)}`,
+        }),
+    },
+  );
 
-        try {
-          await sftpRef.current?.sftpUploadFile({
-            localFilename: localFilePath,
-            remoteFilename: remoteName,
-            taskId: item.taskId,
-            onProgress: ({ progress, total }) => {
-              const now = performance.now();
-              const lastUpdate = batchProgress.get(item.id) ?? {
-                time: now,
-                progress: 0,
-              };
-              const dt = Math.max((now - lastUpdate.time) / 1000, 0.001);
-              const db = progress - lastUpdate.progress;
-              const speed = db / dt;
-              const remaining = total - progress;
-              const eta = speed > 0 ? remaining / speed : -1;
-              batchProgress.set(item.id, { time: now, progress });
-
-              latestProgress.progress = progress;
-              latestProgress.total = total;
-              latestProgress.speed = speed;
-              latestProgress.eta = eta;
-              throttledRender();
-            },
-          });
+  const { loading: downloadFileLoading, run: downloadFile } = useRequest(
+    async ({ name, path }: SSHSftpFile) => {
+      const file = await save({ defaultPath: name });
+      if (!file) return;
 
-          setTransferInfoWithRef((prev) => {
-            if (!prev) return null;
-            const q = prev.queue.map((queueItem) =>
-              queueItem.id === item.id
-                ? {
-                    ...queueItem,
-                    status: "completed" as const,
-                    speed: 0,
-                    eta: -1,
-                  }
-                : queueItem,
-            );
-            return {
-              ...prev,
-              queue: q,
-              currentIndex: getCurrentTransferIndex(q, item.id),
-              ...computeOverall(q),
-            };
-          });
-        } catch (err) {
-          const isCancelled =
-            batch.aborted || cancelledItemIdsRef.current.has(item.id);
-          setTransferInfoWithRef((prev) => {
-            if (!prev) return null;
-            const q = prev.queue.map((queueItem) =>
-              queueItem.id === item.id
-                ? {
-                    ...queueItem,
-                    status: isCancelled
-                      ? ("cancelled" as const)
-                      : ("failed" as const),
-                    speed: 0,
-                    eta: -1,
-                    error: isCancelled
-                      ? undefined
-                      : getErrorMessage(err, "Upload failed"),
-                  }
-                : queueItem,
-            );
-            return {
-              ...prev,
-              queue: q,
-              currentIndex: getCurrentTransferIndex(q, item.id),
-              ...computeOverall(q),
-            };
-          });
-        } finally {
-          throttledRender.flush();
-          throttledRender.cancel();
-          batchProgress.delete(item.id);
-        }
-      };
-
-      let nextIndex = 0;
-      const runWorker = async () => {
-        while (true) {
-          if (batch.aborted) return;
-          const index = nextIndex++;
-          if (index >= items.length) return;
-          await uploadOne(index);
-        }
-      };
-      const workerCount = Math.min(UPLOAD_CONCURRENCY, items.length);
-      await Promise.all(Array.from({ length: workerCount }, () => runWorker()));
-
-      if (batch.aborted) {
-        setTransferInfoWithRef((prev) => {
-          if (!prev) return null;
-          const q = prev.queue.map((item) =>
-            uploadedIds.has(item.id) &&
-            (item.status === "waiting" ||
-              item.status === "transferring" ||
-              item.status === "paused")
-              ? { ...item, status: "cancelled" as const, speed: 0, eta: -1 }
-              : item,
-          );
-          return {
-            ...prev,
-            queue: q,
-            currentIndex: getCurrentTransferIndex(q),
-            ...computeOverall(q),
-          };
-        });
-      }
+      await enqueueTransfers("download", [
+        { localFilename: file, remoteFilename: path },
+      ]);
+    },
+    {
+      manual: true,
+      onError: (err, [{ name }]) =>
+        message.error({
+          message: `Failed to download "${name}": ${getErrorMessage(err)}`,
+        }),
+    },
+  );
+
+  /**
+   * 整个文件夹作为队列中的一项，进度为所有文件的合计
+   */
+  const transferDir = async (
+    type: "upload" | "download",
+    name: string,
+    transfer: (
+      taskId: string,
+      onProgress: (opts: SSHSftpOnDirProgressOpts) => unknown,
+    ) => Promise<unknown>,
+  ) => {
+    const taskId = crypto.randomUUID();
+    const item: DirTransferItem = {
+      id: crypto.randomUUID(),
+      type,
+      fileName: name,
+      status: "transferring",
+      progress: 0,
+      total: 0,
+      speed: 0,
+      eta: -1,
+      taskId,
+    };
+
+    let lastUpdate = { time: performance.now(), progress: 0 };
+    const latestProgress = {
+      progress: 0,
+      total: 0,
+      speed: 0,
+      eta: -1,
+      current: "",
+    };
+    const throttledRender = throttle(
+      () => updateDirItem(item.id, { ...latestProgress }),
+      1000,
+      { leading: true, trailing: true },
+    );
+    incTransfer();
+    setDirItemsWithRef((prev) => [...prev, item]);
+    setPanelOpen(true);
+
+    const finish = (patch: Partial<DirTransferItem>) => {
+      updateDirItem(item.id, { ...patch, speed: 0, eta: -1, current: "" });
+    };
+
+    try {
+      await transfer(taskId, ({ progress, total, current }) => {
+        const now = performance.now();
+        const dt = Math.max((now - lastUpdate.time) / 1000, 0.001);
+        const speed = (progress - lastUpdate.progress) / dt;
+        lastUpdate = { time: now, progress };
+
+        latestProgress.progress = progress;
+        latestProgress.total = total;
+        latestProgress.speed = speed;
+        latestProgress.eta = speed > 0 ? (total - progress) / speed : -1;
+        latestProgress.current = current;
+        throttledRender();
+      });
 
-      activeUploadBatchesRef.current.delete(batch);
+      throttledRender.flush();
+      throttledRender.cancel();
+      finish({ status: "completed" });
+    } catch (err) {
+      throttledRender.cancel();
+      if (cancelledItemIdsRef.current.has(item.id)) {
+        finish({ status: "cancelled" });
+        return false;
+      }
+      finish({
+        status: "failed",
+        error: getErrorMessage(
+          err,
+          type === "upload" ? "Upload failed" : "Download failed",
+        ),
+      });
+      throw err;
+    } finally {
       decTransfer();
-      const uploadedItems =
-        transferInfoRef.current?.queue.filter((item) =>
-          uploadedIds.has(item.id),
-        ) ?? [];
-      const succeededCount = uploadedItems.filter(
-        (item) => item.status === "completed",
-      ).length;
-      const failedCount = uploadedItems.filter(
-        (item) => item.status === "failed",
-      ).length;
-      const cancelledCount = uploadedItems.filter(
-        (item) => item.status === "cancelled",
-      ).length;
+    }
+    return true;
+  };
 
-      if (succeededCount > 0 && failedCount === 0 && cancelledCount === 0) {
-        message.success({
-          message: `Uploaded ${formatTransferCount(succeededCount, "file")} to ${dirnameRef.current}`,
-        });
-      } else if (succeededCount > 0) {
-        message.warning({
-          message: `Upload finished: ${succeededCount} uploaded, ${failedCount} failed, ${cancelledCount} cancelled`,
-        });
-      } else if (failedCount > 0) {
-        message.error({
-          message: `Upload failed: ${formatTransferCount(failedCount, "file")} could not be uploaded`,
-        });
-      } else if (cancelledCount > 0 || batch.aborted) {
-        message.info({
-          message: "Upload cancelled",
+  const { loading: uploadDirLoading, run: uploadDir } = useRequest(
+    async () => {
+      const localDirname = await open({ multiple: false, directory: true });
+      if (!localDirname) return;
+
+      const name = getLocalBasename(localDirname);
+      const remoteDirname = joinSftpPath(dirnameRef.current, name);
+      if (await sftpRef.current?.sftpExists(remoteDirname)) {
+        const confirmed = await modal.confirm({
+          title: "Merge into existing folder?",
+          content: `"${name}" already exists in this folder, files with the same name will be overwritten.`,
+          okText: "Merge",
+          danger: true,
         });
+        if (!confirmed) {
+          return;
+        }
       }
-      if (failedCount > 0 && succeededCount === 0) {
-        setTransferStatus("failed");
-      } else if (cancelledCount > 0 && succeededCount === 0) {
-        setTransferStatus("cancelled");
-      } else {
-        setTransferStatus("completed");
+
+      const completed = await transferDir(
+        "upload",
+        name,
+        async (taskId, onProgress) =>
+          sftpRef.current?.sftpUploadDir({
+            localDirname,
+            remoteDirname,
+            ...DIR_TRANSFER_OPTS,
+            taskId,
+            onProgress,
+          }),
+      );
+      if (completed) {
+        message.success({ message: `Uploaded folder "${name}"` });
       }
     },
     {
       manual: true,
-      onFinally: () => {
-        refreshDir();
-      },
+      onFinally: () => refreshDir(),
       onError: (err) =>
         message.error({
-          message: `Failed to upload files: ${getErrorMessage(err)}`,
+          message: `Failed to upload folder: ${getErrorMessage(err)}`,
         }),
     },
   );
 
-  const { loading: downloadFileLoading, run: downloadFile } = useRequest(
+  const { loading: downloadDirLoading, run: downloadDir } = useRequest(
     async ({ name, path }: SSHSftpFile) => {
-      const file = await save({ defaultPath: name });
-      if (!file) return false;
-
-      const taskId = crypto.randomUUID();
-      const items: TransferQueueItem[] = [
-        {
-          id: crypto.randomUUID(),
-          type: "download",
-          fileName: name,
-          status: "waiting" as const,
-          progress: 0,
-          total: 0,
-          speed: 0,
-          eta: -1,
-          taskId,
-        },
-      ];
-
-      let lastUpdate = { time: performance.now(), progress: 0 };
-      const latestProgress = { progress: 0, total: 0, speed: 0, eta: -1 };
-      const throttledRender = throttle(
-        () => {
-          setTransferInfoWithRef((prev) => {
-            if (!prev) return null;
-            const q = prev.queue.map((item) =>
-              item.id === items[0].id
-                ? {
-                    ...item,
-                    progress: latestProgress.progress,
-                    total: latestProgress.total,
-                    speed: latestProgress.speed,
-                    eta: latestProgress.eta,
-                  }
-                : item,
-            );
-            return {
-              ...prev,
-              type: "download",
-              fileName: name,
-              ...computeOverall(q),
-              queue: q,
-              currentIndex: getCurrentTransferIndex(q, items[0].id),
-            };
-          });
-        },
-        1000,
-        { leading: true, trailing: true },
+      const parent = await open({ multiple: false, directory: true });
+      if (!parent) return;
+
+      const completed = await transferDir(
+        "download",
+        name,
+        async (taskId, onProgress) =>
+          sftpRef.current?.sftpDownloadDir({
+            localDirname: await join(parent, name),
+            remoteDirname: path,
+            ...DIR_TRANSFER_OPTS,
+            taskId,
+            onProgress,
+          }),
       );
-      incTransfer();
-      setTransferInfoWithRef((prev) => {
-        const transferItem = { ...items[0], status: "transferring" as const };
-        const q = [...(prev?.queue ?? []), transferItem];
-        return {
-          type: "download",
-          dirname: dirnameRef.current,
-          fileName: name,
-          ...computeOverall(q),
-          queue: q,
-          currentIndex: q.findIndex((item) => item.id === transferItem.id),
-        };
-      });
-      setTransferStatus("transferring");
-      setPanelOpen(true);
+      if (completed) {
+        message.success({ message: `Downloaded folder "${name}"` });
+      }
+    },
+    {
+      manual: true,
+      onError: (err, [{ name }]) =>
+        message.error({
+          message: `Failed to download folder "${name}": ${getErrorMessage(err)}`,
+        }),
+    },
+  );
 
-      try {
-        await sftpRef.current?.sftpDownloadFile({
-          localFilename: file,
-          remoteFilename: path,
-          taskId,
-          onProgress: ({ progress, total }) => {
-            const now = performance.now();
-            const dt = Math.max((now - lastUpdate.time) / 1000, 0.001);
-            const db = progress - lastUpdate.progress;
-            const speed = db / dt;
-            const remaining = total - progress;
-            const eta = speed > 0 ? remaining / speed : -1;
-            lastUpdate = { time: now, progress };
-
-            latestProgress.progress = progress;
-            latestProgress.total = total;
-            latestProgress.speed = speed;
-            latestProgress.eta = eta;
-            throttledRender();
-          },
-        });
+  /**
+   * 未在队列中进行的记录，即中断后可以续传的传输
+   */
+  const resumableTransfers = useMemo(() => {
+    const activeTaskIds = new Set(
+      queueItems
+        .filter((item) => !isTransferFinished(item.status))
+        .map((item) => item.id),
+    );
+    return pendingTransfers.filter(
+      (transfer) => !activeTaskIds.has(transfer.taskId),
+    );
+  }, [pendingTransfers, queueItems]);
+
+  const { loading: resumeTransfersLoading, run: resumeTransfers } = useRequest(
+    async () => {
+      const uploads = resumableTransfers.filter(
+        (transfer) => transfer.type === "upload",
+      );
+      const downloads = resumableTransfers.filter(
+        (transfer) => transfer.type === "download",
+      );
 
-        throttledRender.flush();
-        throttledRender.cancel();
-
-        {
-          const base = transferInfoRef.current;
-          if (base) {
-            const q = base.queue.map((item) =>
-              item.id === items[0].id
-                ? { ...item, status: "completed" as const, speed: 0, eta: -1 }
-                : item,
-            );
-            const next: TransferInfo = {
-              ...base,
-              queue: q,
-              currentIndex: getCurrentTransferIndex(q),
-              ...computeOverall(q),
-            };
-            transferInfoRef.current = next;
-            setTransferInfoWithRef(next);
-            setTransferStatus(deriveTransferStatus(q));
-          }
-        }
-      } catch (err) {
-        const base = transferInfoRef.current;
-        if (base) {
-          const q = base.queue.map((item) =>
-            item.id === items[0].id
-              ? {
-                  ...item,
-                  status: "failed" as const,
-                  speed: 0,
-                  eta: -1,
-                  error: getErrorMessage(err, "Download failed"),
-                }
-              : item,
-          );
-          const next: TransferInfo = {
-            ...base,
-            queue: q,
-            currentIndex: getCurrentTransferIndex(q),
-            ...computeOverall(q),
-          };
-          transferInfoRef.current = next;
-          setTransferInfoWithRef(next);
-          setTransferStatus(deriveTransferStatus(q));
-        }
-        throw err;
-      } finally {
-        decTransfer();
+      if (uploads.length > 0) {
+        await enqueueTransfers("upload", uploads, true);
+      }
+      if (downloads.length > 0) {
+        await enqueueTransfers("download", downloads, true);
       }
-      return true;
     },
     {
       manual: true,
-      onFinally: () => refreshDir(),
-      onSuccess: (completed, [{ name }]) => {
-        if (!completed) {
-          return;
-        }
-        message.success({ message: `Downloaded "${name}"` });
-      },
-      onError: (err, [{ name }]) =>
+      onError: (err) =>
         message.error({
-          message: `Failed to download "${name}": ${getErrorMessage(err)}`,
+          message: `Failed to resume transfers: ${getErrorMessage(err)}`,
         }),
     },
   );
 
+  /**
+   * 放弃中断的传输，不再提示续传
+   */
+  const discardTransfers = useCallback(() => {
+    for (const transfer of resumableTransfers) {
+      removePendingTransfer(transfer.taskId);
+    }
+  }, [resumableTransfers, removePendingTransfer]);
+
   const { loading: removeFileLoading, run: removeFile } = useRequest(
     async ({ path }: SSHSftpFile) => {
       await sftpRef.current?.sftpRemoveFile(path);
@@ -698,7 +726,14 @@ export default function useSftpActions({
 
   const { loading: removeDirLoading, run: removeDir } = useRequest(
     async ({ path }: SSHSftpFile) => {
-      await sftpRef.current?.sftpRemoveDir(path);
+      // 非空目录也一并删除，部分失败时报告第一个失败的条目
+      const failures = await sftpRef.current?.sftpRemoveAll({ path });
+      if (failures?.length) {
+        const [{ path: failedPath, error }] = failures;
+        throw new Error(
+          `${failures.length} entries not removed, ${failedPath}: ${error}`,
+        );
+      }
     },
     {
       manual: true,
@@ -730,6 +765,14 @@ export default function useSftpActions({
     uploadFileLoading,
     downloadFile,
     downloadFileLoading,
+    uploadDir,
+    uploadDirLoading,
+    downloadDir,
+    downloadDirLoading,
+    resumableTransfers,
+    resumeTransfers,
+    resumeTransfersLoading,
+    discardTransfers,
     removeDir,
     removeDirLoading,
     removeFile,
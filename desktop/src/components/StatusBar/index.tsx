@@ -11,7 +11,9 @@ export default function StatusBar() {
   const terminalsState = useTerminalsAtomValue();
   const portForwardingsState = usePortForwardingsAtomValue();
   const terminals = [...terminalsState.values()];
-  const sshCount = terminals.filter((item) => item.type !== "sftp").length;
+  const sshCount = terminals.filter(
+    (item) => item.type !== "sftp" && item.type !== "containers",
+  ).length;
   const sftpCount = terminals.filter((item) => item.type === "sftp").length;
   const portForwardingCount = portForwardingsState.size;
   return (
@@ -2,15 +2,21 @@ import {
   DockviewDefaultTab,
   type IDockviewPanelHeaderProps,
 } from "dockview-react";
-import { FolderIcon, TerminalIcon } from "shared";
+import { FolderIcon, GridIcon, TerminalIcon } from "shared";
 import styles from "./index.module.less";
 
 export default function Tab(props: IDockviewPanelHeaderProps) {
-  const isSftp = props.params?.type === "sftp";
+  const type = props.params?.type;
   return (
     <div className={styles.tab}>
       <span className={styles.icon}>
-        {isSftp ? <FolderIcon /> : <TerminalIcon />}
+        {type === "sftp" ? (
+          <FolderIcon />
+        ) : type === "containers" ? (
+          <GridIcon />
+        ) : (
+          <TerminalIcon />
+        )}
       </span>
       <DockviewDefaultTab {...props} />
     </div>
@@ -13,6 +13,7 @@ import {
   useTerminalViewVisible,
 } from "@/atoms/terminalView.atom";
 import AddKey from "@/components/AddKey";
+import ContainersPanel from "@/components/ContainersPanel";
 import SftpContent from "@/components/SftpPanel";
 import TerminalPanel from "@/components/TerminalPanel";
 import styles from "./index.module.less";
@@ -38,7 +39,10 @@ function getAddPanelOptions(
     id,
     title,
     inactive,
-    component: params.type === "sftp" ? "sftp" : "terminal",
+    component:
+      params.type === "sftp" || params.type === "containers"
+        ? params.type
+        : "terminal",
     params,
     position: {
       referenceGroup: api.activeGroup as NonNullable<typeof api.activeGroup>,
@@ -52,6 +56,7 @@ function getAddPanelOptions(
 const components = {
   terminal: TerminalPanel,
   sftp: SftpContent,
+  containers: ContainersPanel,
 };
 
 export default function Workspace() {
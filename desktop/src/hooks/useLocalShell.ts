@@ -2,25 +2,43 @@ import { useMemoizedFn, useRequest, useUnmount } from "ahooks";
 import { Buffer } from "buffer";
 import { useRef, useState } from "react";
 import type { Terminal, TerminalSize } from "shared";
-import { oscParse, xtermBinaryToBytes } from "shared";
-import { PtyShell } from "tauri-plugin-pty";
+import {
+  getShellTriggers,
+  oscParse,
+  saveCommandHistory,
+  xtermBinaryToBytes,
+} from "shared";
+import { getTriggers } from "tauri-plugin-data";
+import {
+  PtyShell,
+  type PtyShellIntegrationEvent,
+  type PtyShellTriggerEvent,
+} from "tauri-plugin-pty";
 
 export interface UseLocalShellOpts {
   onClose?: () => void;
   onCopy?: (content: string) => void;
+  onTrigger?: (event: PtyShellTriggerEvent) => void;
+  onShellIntegration?: (event: PtyShellIntegrationEvent) => void;
   shell?: string;
+  encoding?: string;
 }
 
 export function useLocalShell({
   onClose,
   onCopy,
+  onTrigger,
+  onShellIntegration,
   shell: shellPath,
+  encoding,
 }: UseLocalShellOpts) {
   const [terminal, setTerminal] = useState<Terminal>();
 
   const shellRef = useRef<PtyShell | null>(null);
   const shellPathRef = useRef(shellPath);
   shellPathRef.current = shellPath;
+  const encodingRef = useRef(encoding);
+  encodingRef.current = encoding;
 
   const { loading, error, run, refresh } = useRequest(
     async () => {
@@ -37,12 +55,21 @@ export function useLocalShell({
       const shell = new PtyShell({
         onData: (data: Uint8Array) => {
           oscParse(Buffer.from(data), { onCopy });
-          terminal.write(data);
+          return new Promise<void>((resolve) => {
+            terminal.write(data, resolve);
+          });
         },
         onExit: () => onClose?.(),
+        onTrigger,
+        onShellIntegration: (event) => {
+          saveCommandHistory(event);
+          onShellIntegration?.(event);
+        },
       });
       shellRef.current = shell;
 
+      const triggers = await getTriggers().catch(() => []);
+
       await shell.open({
         size: {
           col: terminal.cols,
@@ -51,6 +78,8 @@ export function useLocalShell({
           height: terminal.element?.clientHeight ?? 0,
         },
         shell: shellPathRef.current || undefined,
+        triggers: getShellTriggers(triggers),
+        encoding: encodingRef.current || undefined,
       });
     },
     {
@@ -1,6 +1,12 @@
 import { DropdownMenu } from "@radix-ui/themes";
 import type { ReactNode } from "react";
-import { ContentCopyIcon, DeleteIcon, EditIcon, MoreIcon } from "shared";
+import {
+  ContentCopyIcon,
+  DeleteIcon,
+  EditIcon,
+  GridIcon,
+  MoreIcon,
+} from "shared";
 import type { Host } from "tauri-plugin-data";
 
 type HostActionsMenuProps = {
@@ -9,6 +15,7 @@ type HostActionsMenuProps = {
   onCopy: (host: Host) => void;
   onDelete: (host: Host) => void;
   onEdit: (host: Host) => void;
+  onOpenContainers: (host: Host) => void;
 };
 
 export default function HostActionsMenu({
@@ -17,6 +24,7 @@ export default function HostActionsMenu({
   onCopy,
   onDelete,
   onEdit,
+  onOpenContainers,
 }: HostActionsMenuProps) {
   return (
     <DropdownMenu.Root>
@@ -28,6 +36,11 @@ export default function HostActionsMenu({
         )}
       </DropdownMenu.Trigger>
       <DropdownMenu.Content side="bottom" align="end" sideOffset={4}>
+        <DropdownMenu.Item onSelect={() => onOpenContainers(host)}>
+          <GridIcon style={{ marginRight: 8 }} />
+          Containers
+        </DropdownMenu.Item>
+        <DropdownMenu.Separator />
         <DropdownMenu.Item onSelect={() => onEdit(host)}>
           <EditIcon style={{ marginRight: 8 }} />
           Edit
@@ -40,6 +53,7 @@ export default function HostActionsMenu({
           <DeleteIcon style={{ marginRight: 8 }} />
           Delete
         </DropdownMenu.Item>
+
       </DropdownMenu.Content>
     </DropdownMenu.Root>
   );
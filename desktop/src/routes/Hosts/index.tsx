@@ -77,6 +77,14 @@ export default function Hosts() {
     [activateTerminal, terminalsAtomWithApi],
   );
 
+  const onOpenContainers = useCallback(
+    (host: Host) => {
+      const [item] = terminalsAtomWithApi.addContainers(host);
+      activateTerminal(item.uuid);
+    },
+    [activateTerminal, terminalsAtomWithApi],
+  );
+
   const onAddHostClose = useCallback(() => {
     setIsOpenAddHost(false);
     setEditHost(undefined);
@@ -247,6 +255,7 @@ export default function Hosts() {
                           onEdit={onEditHost}
                           onCopy={onCopyHost}
                           onDelete={onDeleteHost}
+                          onOpenContainers={onOpenContainers}
                           trigger={
                             <button type="button" className={styles.moreBtn}>
                               <MoreIcon width="12" height="12" />
@@ -320,6 +329,7 @@ export default function Hosts() {
                                   onEdit={onEditHost}
                                   onCopy={onCopyHost}
                                   onDelete={onDeleteHost}
+                                  onOpenContainers={onOpenContainers}
                                   trigger={
                                     <IconButton
                                       type="button"
@@ -1,15 +1,19 @@
-import { useSize } from "ahooks";
+import { useMemoizedFn, useSize } from "ahooks";
 import type { CSSProperties } from "react";
 import { useCallback, useEffect, useRef, useState } from "react";
 import {
   KeyboardIcon,
   SSHLoading,
   TERMINAL_THEMES_MAP,
+  TMUX_TABS_HEIGHT,
   type TerminalAtom,
+  TmuxTabs,
   useTerminal,
+  useTriggerActions,
   VirtualKeyboard,
   XTerminal,
 } from "shared";
+import type { SSHShellTriggerEvent } from "tauri-plugin-ssh";
 
 import openUrl from "@/utils/openUrl";
 
@@ -28,6 +32,11 @@ export default function SSHTerminal({
   onClose,
   onOpenAddKey,
 }: SSHTerminalProps) {
+  const runTriggerAction = useTriggerActions();
+  const onTrigger = useMemoizedFn((event: SSHShellTriggerEvent) => {
+    runTriggerAction(event, terminal);
+  });
+
   const {
     loading,
     error,
@@ -41,7 +50,10 @@ export default function SSHTerminal({
     onTerminalData,
     onTerminalBinaryData,
     onTerminalResize,
-  } = useTerminal({ item, onClose });
+    tmux,
+    selectTmuxPane,
+    detachTmux,
+  } = useTerminal({ item, onClose, onTrigger });
 
   const footerRef = useRef<HTMLDivElement>(null);
   const [showVirtualKeyboard, setShowVirtualKeyboard] = useState(false);
@@ -76,10 +88,18 @@ export default function SSHTerminal({
 
   return (
     <div style={{ position: "relative", overflow: "hidden", ...style }}>
+      {tmux && !hasBlockingState && (
+        <TmuxTabs
+          tmux={tmux}
+          style={{ position: "absolute", top: 0, left: 0, right: 0 }}
+          onSelect={selectTmuxPane}
+          onDetach={detachTmux}
+        />
+      )}
       <div
         style={{
           position: "absolute",
-          top: 0,
+          top: tmux ? TMUX_TABS_HEIGHT : 0,
           right: 0,
           bottom: size?.height || 0,
           left: 0,
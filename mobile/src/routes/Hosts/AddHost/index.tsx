@@ -3,6 +3,7 @@ import { useCallback, useEffect, useMemo, useState } from "react";
 import { useForm } from "react-hook-form";
 import { useNavigate } from "react-router-dom";
 import {
+  DEFAULT_TERMINAL_ENCODING,
   DEFAULT_TERMINAL_FONT_FAMILY,
   DEFAULT_TERMINAL_FONT_SIZE,
   DEFAULT_TERMINAL_THEME,
@@ -11,9 +12,12 @@ import {
   type EditHostFormFields,
   MoreIcon,
   parseEnvs,
+  saveHostTriggers,
   stringifyEnvs,
+  toTriggerFormFields,
   useHosts,
   useTerminalsAtomWithApi,
+  useTriggers,
 } from "shared";
 import {
   AuthenticationMethod,
@@ -34,6 +38,7 @@ type AddHostProps = {
 export default function AddHost({ open, data, onOk, onCancel }: AddHostProps) {
   const navigate = useNavigate();
   const { refresh: refreshHosts } = useHosts();
+  const { data: triggers, refresh: refreshTriggers } = useTriggers();
   const [addKeyOpen, setAddKeyOpen] = useState(false);
 
   const formApi = useForm<EditHostFormFields>({
@@ -48,10 +53,14 @@ export default function AddHost({ open, data, onOk, onCancel }: AddHostProps) {
       password: "",
       keyId: "",
       startupCommand: "",
+      shellCommand: "",
       terminalType: DEFAULT_TERMINAL_TYPE,
+      terminalEncoding: DEFAULT_TERMINAL_ENCODING,
+      urlForwarding: undefined,
       envs: "",
       jumpHostEnabled: false,
       jumpHostIds: [],
+      triggers: [],
       terminalSettings: {
         fontFamily: DEFAULT_TERMINAL_FONT_FAMILY,
         fontSize: DEFAULT_TERMINAL_FONT_SIZE,
@@ -70,10 +79,14 @@ export default function AddHost({ open, data, onOk, onCancel }: AddHostProps) {
       password: data?.password ?? "",
       keyId: data?.keyId ?? "",
       startupCommand: data?.startupCommand ?? "",
+      shellCommand: data?.shellCommand ?? "",
       terminalType: data?.terminalType ?? DEFAULT_TERMINAL_TYPE,
+      terminalEncoding: data?.terminalEncoding ?? DEFAULT_TERMINAL_ENCODING,
+      urlForwarding: data?.urlForwarding,
       envs: stringifyEnvs(data?.envs),
       jumpHostEnabled: !!data?.jumpHostIds?.length,
       jumpHostIds: data?.jumpHostIds ?? [],
+      triggers: toTriggerFormFields(triggers, data?.id),
       terminalSettings: {
         fontFamily:
           data?.terminalSettings?.fontFamily ?? DEFAULT_TERMINAL_FONT_FAMILY,
@@ -107,7 +120,13 @@ export default function AddHost({ open, data, onOk, onCancel }: AddHostProps) {
             ? values.keyId
             : undefined,
         startupCommand: values.startupCommand || undefined,
+        shellCommand: values.shellCommand || undefined,
         terminalType: values.terminalType || DEFAULT_TERMINAL_TYPE,
+        terminalEncoding:
+          values.terminalEncoding === DEFAULT_TERMINAL_ENCODING
+            ? undefined
+            : values.terminalEncoding,
+        urlForwarding: values.urlForwarding || undefined,
         envs: parseEnvs(values.envs),
         jumpHostIds: values.jumpHostEnabled ? values.jumpHostIds : undefined,
         terminalSettings: values.terminalSettings
@@ -119,16 +138,20 @@ export default function AddHost({ open, data, onOk, onCancel }: AddHostProps) {
           : undefined,
       };
 
-      if (data) {
-        return updateHost({
-          ...hostData,
-          id: data.id,
-        });
-      }
+      const savedHost = data
+        ? await updateHost({
+            ...hostData,
+            id: data.id,
+          })
+        : await addHost(hostData);
 
-      return addHost(hostData);
+      // 触发器需要主机 id，新主机保存后才能添加
+      await saveHostTriggers(savedHost.id, values.triggers ?? []);
+      await refreshTriggers();
+
+      return savedHost;
     },
-    [data],
+    [data, refreshTriggers],
   );
 
   const onSaveAndConnect = useCallback(
@@ -2,6 +2,7 @@ import { useLatest, useMemoizedFn } from "ahooks";
 import { atom, useAtom, useAtomValue } from "jotai";
 import { useMemo } from "react";
 import { AuthenticationMethod, type Host } from "tauri-plugin-data";
+import type { SSHContainer, SSHContainerRuntime } from "tauri-plugin-ssh";
 import { v4 as uuidV4 } from "uuid";
 
 import { useHosts } from "@/hooks/useHosts";
@@ -15,6 +16,17 @@ import {
   tearDownJumpHostChainConnections,
 } from "../utils/ssh";
 
+/**
+ * 容器内的 shell，通过 docker exec 打开
+ */
+export type TerminalContainer = {
+  runtime: SSHContainerRuntime;
+  containerId: string;
+  name: string;
+};
+
+export type TerminalType = "terminal" | "sftp" | "containers";
+
 export type TerminalAtom = {
   uuid: string;
   host: Host;
@@ -22,8 +34,9 @@ export type TerminalAtom = {
   jumpHostChain: JumpHostChainItem[];
   status: "pending" | "success" | "failed";
   error?: unknown;
-  type?: "terminal" | "sftp";
+  type?: TerminalType;
   connectionType?: "ssh" | "local";
+  container?: TerminalContainer;
 };
 
 const terminalsAtom = atom<Map<string, TerminalAtom>>(new Map());
@@ -106,20 +119,24 @@ export function useTerminalsAtomWithApi() {
   const addTerminalOfType = useMemoizedFn(
     (
       host: Host,
-      type: "terminal" | "sftp",
+      type: TerminalType,
+      container?: TerminalContainer,
     ): [TerminalAtom, Map<string, TerminalAtom>] => {
       const uuid = uuidV4();
       const map = new Map(stateRef.current);
 
       const count = [...map.values()].reduce((prev, item) => {
-        const sameType = type === "sftp" ? item.type === "sftp" : true;
-        if (item.host.id === host.id && sameType) {
+        const sameType = type === "terminal" ? true : item.type === type;
+        const sameContainer =
+          item.container?.containerId === container?.containerId;
+        if (item.host.id === host.id && sameType && sameContainer) {
           return prev + 1;
         }
         return prev;
       }, 0);
 
-      const name = host.name || `${host.hostname}:${host.port}`;
+      const hostName = host.name || `${host.hostname}:${host.port}`;
+      const name = container ? `${container.name} @ ${hostName}` : hostName;
 
       const jumpHostChain = resolveJumpHostChain(host, {
         hostsMap,
@@ -133,6 +150,7 @@ export function useTerminalsAtomWithApi() {
         jumpHostChain,
         status: "pending",
         type,
+        container,
       };
 
       map.set(uuid, item);
@@ -151,6 +169,19 @@ export function useTerminalsAtomWithApi() {
     addTerminalOfType(host, "sftp"),
   );
 
+  const addContainersTerminal = useMemoizedFn((host: Host) =>
+    addTerminalOfType(host, "containers"),
+  );
+
+  const addContainerTerminal = useMemoizedFn(
+    (host: Host, runtime: SSHContainerRuntime, container: SSHContainer) =>
+      addTerminalOfType(host, "terminal", {
+        runtime,
+        containerId: container.id,
+        name: container.name || container.id,
+      }),
+  );
+
   const addLocalTerminal = useMemoizedFn(
     (): [TerminalAtom, Map<string, TerminalAtom>] => {
       const uuid = uuidV4();
@@ -193,6 +224,8 @@ export function useTerminalsAtomWithApi() {
     getState,
     add: addTerminal,
     addSftp: addSftpTerminal,
+    addContainers: addContainersTerminal,
+    addContainer: addContainerTerminal,
     addLocal: addLocalTerminal,
     update: updateTerminal,
     delete: deleteTerminal,
@@ -1,7 +1,7 @@
 import { IconButton, Select, Text, TextField } from "@radix-ui/themes";
 import { type KeyboardEvent, useMemo, useState } from "react";
 import { Controller } from "react-hook-form";
-import { AuthenticationMethod } from "tauri-plugin-data";
+import { AuthenticationMethod, UrlForwardingMode } from "tauri-plugin-data";
 
 import { useHosts } from "@/hooks/useHosts";
 import { useKeys } from "@/hooks/useKeys";
@@ -18,9 +18,15 @@ import {
 } from "../Icon";
 import { TextFieldPassword } from "../TextFieldPassword";
 import styles from "./BasicForm.module.less";
+import { TERMINAL_ENCODINGS } from "./terminalEncodings";
 import { TERMINAL_TYPES } from "./terminalTypes";
 import type { EditHostFormApi } from "./types";
 
+/**
+ * Select 不支持空值，关闭时保存为 undefined
+ */
+const URL_FORWARDING_OFF = "Off";
+
 type BasicFormProps = {
   formApi: EditHostFormApi;
   sx?: unknown;
@@ -520,6 +526,43 @@ export default function BasicForm({
         )}
       />
 
+      <Controller
+        name="shellCommand"
+        control={formApi.control}
+        rules={{
+          maxLength: {
+            value: 500,
+            message: "Please enter no more than 500 characters",
+          },
+        }}
+        render={({ field, fieldState }) => (
+          <div className={styles.formField}>
+            <Text
+              as="label"
+              size="2"
+              weight="medium"
+              className={styles.fieldLabel}
+            >
+              Shell Command
+            </Text>
+            <TextField.Root
+              value={field.value || ""}
+              placeholder="Command to run instead of the login shell (optional)"
+              onChange={onInputChange(field.onChange)}
+            >
+              <TextField.Slot>
+                <CodeIcon aria-hidden="true" />
+              </TextField.Slot>
+            </TextField.Root>
+            {fieldState.invalid && (
+              <Text size="1" color="red" as="p" mt="1">
+                {fieldState.error?.message}
+              </Text>
+            )}
+          </div>
+        )}
+      />
+
       <Controller
         name="terminalType"
         control={formApi.control}
@@ -564,6 +607,78 @@ export default function BasicForm({
         )}
       />
 
+      <Controller
+        name="terminalEncoding"
+        control={formApi.control}
+        render={({ field }) => (
+          <div className={styles.formField}>
+            <Text
+              as="label"
+              size="2"
+              weight="medium"
+              className={styles.fieldLabel}
+            >
+              Terminal encoding
+            </Text>
+            <Select.Root
+              value={field.value || ""}
+              onValueChange={field.onChange}
+            >
+              <Select.Trigger
+                style={{ width: "100%" }}
+                placeholder="Terminal encoding"
+              />
+              <Select.Content>
+                {TERMINAL_ENCODINGS.map((item) => (
+                  <Select.Item key={item.value} value={item.value}>
+                    {item.label}
+                  </Select.Item>
+                ))}
+              </Select.Content>
+            </Select.Root>
+          </div>
+        )}
+      />
+
+      <Controller
+        name="urlForwarding"
+        control={formApi.control}
+        render={({ field }) => (
+          <div className={styles.formField}>
+            <Text
+              as="label"
+              size="2"
+              weight="medium"
+              className={styles.fieldLabel}
+            >
+              Forward localhost URLs
+            </Text>
+            <Select.Root
+              value={field.value || URL_FORWARDING_OFF}
+              onValueChange={(value) =>
+                field.onChange(
+                  value === URL_FORWARDING_OFF ? undefined : value,
+                )
+              }
+            >
+              <Select.Trigger
+                style={{ width: "100%" }}
+                placeholder="Forward localhost URLs"
+              />
+              <Select.Content>
+                <Select.Item value={URL_FORWARDING_OFF}>Off</Select.Item>
+                <Select.Item value={UrlForwardingMode.Prompt}>
+                  Ask before forwarding
+                </Select.Item>
+                <Select.Item value={UrlForwardingMode.Auto}>
+                  Forward automatically
+                </Select.Item>
+              </Select.Content>
+            </Select.Root>
+          </div>
+        )}
+      />
+
       <Controller
         name="envs"
         control={formApi.control}
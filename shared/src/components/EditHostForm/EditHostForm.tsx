@@ -3,6 +3,7 @@ import BasicForm from "./BasicForm";
 import styles from "./EditHostForm.module.less";
 import JumpHostsForm from "./JumpHostsForm";
 import TerminalSettingsForm from "./TerminalSettingsForm";
+import TriggersForm from "./TriggersForm";
 import type { EditHostFormApi } from "./types";
 
 export type EditHostFormProps = {
@@ -29,6 +30,7 @@ export function EditHostForm({
       <BasicForm formApi={formApi} sx={{ mb: 3 }} onOpenAddKey={onOpenAddKey} />
       <JumpHostsForm formApi={formApi} sx={{ mb: 3 }} />
       <TerminalSettingsForm formApi={formApi} sx={{ mb: 3 }} />
+      <TriggersForm formApi={formApi} sx={{ mb: 3 }} />
     </form>
   );
 }
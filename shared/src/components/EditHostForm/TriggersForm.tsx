@@ -0,0 +1,260 @@
+import {
+  Button,
+  IconButton,
+  Select,
+  Switch,
+  Text,
+  TextArea,
+  TextField,
+} from "@radix-ui/themes";
+import { Controller, useFieldArray } from "react-hook-form";
+
+import { usePortForwardings } from "@/hooks/usePortForwardings";
+import { onInputChange, onTextareaChange } from "@/utils/form";
+import { resolveSpacing } from "@/utils/style";
+import type { TriggerFormField } from "@/utils/trigger";
+import styles from "./TriggersForm.module.less";
+import type { EditHostFormApi } from "./types";
+
+const ACTION_TYPES: { value: TriggerFormField["actionType"]; label: string }[] =
+  [
+    { value: "Notify", label: "Show notification" },
+    { value: "Highlight", label: "Highlight line" },
+    { value: "SendText", label: "Send text" },
+    { value: "PortForwarding", label: "Open port forwarding" },
+  ];
+
+const ACTION_VALUE_PLACEHOLDERS: Record<string, string> = {
+  Notify: "Message (optional, the matched text by default)",
+  Highlight: "Color, e.g. #ffcc00",
+  SendText: "Text to send, end with a new line to press Enter",
+};
+
+const NEW_TRIGGER: TriggerFormField = {
+  name: "",
+  pattern: "",
+  actionType: "Notify",
+  actionValue: "",
+  enabled: true,
+};
+
+type TriggersFormProps = {
+  formApi: EditHostFormApi;
+  sx?: unknown;
+};
+
+function validatePattern(value: string) {
+  if (!value) {
+    return "Please enter pattern";
+  }
+  try {
+    new RegExp(value);
+    return true;
+  } catch {
+    return "Please enter a valid regular expression";
+  }
+}
+
+export default function TriggersForm({ formApi, sx }: TriggersFormProps) {
+  const { data: portForwardings } = usePortForwardings();
+  const { fields, append, remove } = useFieldArray({
+    control: formApi.control,
+    name: "triggers",
+  });
+  const wrapperStyle = resolveSpacing(sx);
+
+  return (
+    <section className={styles.section} style={wrapperStyle}>
+      <div className={styles.sectionTitleWrap}>
+        <Text size="3" weight="medium">
+          Triggers
+        </Text>
+        <Button
+          type="button"
+          size="1"
+          variant="soft"
+          onClick={() => append({ ...NEW_TRIGGER })}
+        >
+          Add trigger
+        </Button>
+      </div>
+
+      {fields.length === 0 && (
+        <Text size="2" color="gray">
+          Run an action when a line of the terminal output matches a pattern
+        </Text>
+      )}
+
+      {fields.map((item, index) => {
+        const actionType = formApi.watch(`triggers.${index}.actionType`);
+
+        return (
+          <div key={item.id} className={styles.trigger}>
+            <div className={styles.triggerHeader}>
+              <Controller
+                name={`triggers.${index}.name`}
+                control={formApi.control}
+                rules={{
+                  required: {
+                    value: true,
+                    message: "Please enter name",
+                  },
+                  maxLength: {
+                    value: 60,
+                    message: "Please enter no more than 60 characters",
+                  },
+                }}
+                render={({ field, fieldState }) => (
+                  <div className={styles.triggerName}>
+                    <TextField.Root
+                      value={field.value || ""}
+                      placeholder="Name"
+                      onChange={onInputChange(field.onChange)}
+                    />
+                    {fieldState.invalid && (
+                      <Text size="1" className={styles.errorHint}>
+                        {fieldState.error?.message}
+                      </Text>
+                    )}
+                  </div>
+                )}
+              />
+              <Controller
+                name={`triggers.${index}.enabled`}
+                control={formApi.control}
+                render={({ field }) => (
+                  <Switch
+                    checked={field.value}
+                    onCheckedChange={field.onChange}
+                    aria-label="Enabled"
+                  />
+                )}
+              />
+              <IconButton
+                type="button"
+                variant="ghost"
+                color="gray"
+                onClick={() => remove(index)}
+                aria-label="Remove trigger"
+              >
+                ×
+              </IconButton>
+            </div>
+
+            <Controller
+              name={`triggers.${index}.pattern`}
+              control={formApi.control}
+              rules={{ validate: validatePattern }}
+              render={({ field, fieldState }) => (
+                <div>
+                  <TextField.Root
+                    value={field.value || ""}
+                    placeholder="Regular expression, e.g. [Pp]assword:"
+                    onChange={onInputChange(field.onChange)}
+                  />
+                  {fieldState.invalid && (
+                    <Text size="1" className={styles.errorHint}>
+                      {fieldState.error?.message}
+                    </Text>
+                  )}
+                </div>
+              )}
+            />
+
+            <div className={styles.triggerAction}>
+              <Controller
+                name={`triggers.${index}.actionType`}
+                control={formApi.control}
+                render={({ field }) => (
+                  <Select.Root
+                    value={field.value}
+                    onValueChange={(value) => {
+                      field.onChange(value);
+                      formApi.setValue(`triggers.${index}.actionValue`, "");
+                    }}
+                  >
+                    <Select.Trigger className={styles.triggerActionType} />
+                    <Select.Content>
+                      {ACTION_TYPES.map((actionType) => (
+                        <Select.Item
+                          key={actionType.value}
+                          value={actionType.value}
+                        >
+                          {actionType.label}
+                        </Select.Item>
+                      ))}
+                    </Select.Content>
+                  </Select.Root>
+                )}
+              />
+              <Controller
+                name={`triggers.${index}.actionValue`}
+                control={formApi.control}
+                rules={{
+                  validate: (value) => {
+                    if (actionType === "Highlight") {
+                      return (
+                        /^#[0-9a-fA-F]{6}$/.test(value) ||
+                        "Please enter a color like #ffcc00"
+                      );
+                    }
+                    if (actionType === "SendText" && !value) {
+                      return "Please enter text";
+                    }
+                    if (actionType === "PortForwarding" && !value) {
+                      return "Please select port forwarding";
+                    }
+                    return true;
+                  },
+                }}
+                render={({ field, fieldState }) => (
+                  <div className={styles.triggerActionValue}>
+                    {actionType === "PortForwarding" ? (
+                      <Select.Root
+                        value={field.value || ""}
+                        onValueChange={field.onChange}
+                      >
+                        <Select.Trigger
+                          style={{ width: "100%" }}
+                          placeholder="Port forwarding"
+                        />
+                        <Select.Content>
+                          {portForwardings.map((portForwarding) => (
+                            <Select.Item
+                              key={portForwarding.id}
+                              value={portForwarding.id}
+                            >
+                              {portForwarding.name}
+                            </Select.Item>
+                          ))}
+                        </Select.Content>
+                      </Select.Root>
+                    ) : actionType === "SendText" ? (
+                      <TextArea
+                        value={field.value || ""}
+                        rows={2}
+                        placeholder={ACTION_VALUE_PLACEHOLDERS[actionType]}
+                        onChange={onTextareaChange(field.onChange)}
+                      />
+                    ) : (
+                      <TextField.Root
+                        value={field.value || ""}
+                        placeholder={ACTION_VALUE_PLACEHOLDERS[actionType]}
+                        onChange={onInputChange(field.onChange)}
+                      />
+                    )}
+                    {fieldState.invalid && (
+                      <Text size="1" className={styles.errorHint}>
+                        {fieldState.error?.message}
+                      </Text>
+                    )}
+                  </div>
+                )}
+              />
+            </div>
+          </div>
+        );
+      })}
+    </section>
+  );
+}
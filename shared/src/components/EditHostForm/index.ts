@@ -1,3 +1,7 @@
 export * from "./EditHostForm";
+export {
+  DEFAULT_TERMINAL_ENCODING,
+  TERMINAL_ENCODINGS,
+} from "./terminalEncodings";
 export { DEFAULT_TERMINAL_TYPE } from "./terminalTypes";
 export type { EditHostFormApi, EditHostFormFields } from "./types";
@@ -0,0 +1,21 @@
+/**
+ * 值为 encoding_rs 支持的编码标签，Latin-1 按 windows-1252 处理
+ */
+export const TERMINAL_ENCODINGS = [
+  { label: "UTF-8", value: "UTF-8" },
+  { label: "GBK", value: "GBK" },
+  { label: "GB18030", value: "GB18030" },
+  { label: "Big5", value: "Big5" },
+  { label: "Shift_JIS", value: "Shift_JIS" },
+  { label: "EUC-JP", value: "EUC-JP" },
+  { label: "EUC-KR", value: "EUC-KR" },
+  { label: "Latin-1 (ISO-8859-1)", value: "ISO-8859-1" },
+  { label: "ISO-8859-2", value: "ISO-8859-2" },
+  { label: "ISO-8859-5", value: "ISO-8859-5" },
+  { label: "ISO-8859-15", value: "ISO-8859-15" },
+  { label: "KOI8-R", value: "KOI8-R" },
+  { label: "Windows-1251", value: "windows-1251" },
+  { label: "Windows-1252", value: "windows-1252" },
+];
+
+export const DEFAULT_TERMINAL_ENCODING = TERMINAL_ENCODINGS[0].value;
@@ -1,6 +1,8 @@
 import type { UseFormReturn } from "react-hook-form";
 import type { Host } from "tauri-plugin-data";
 
+import type { TriggerFormField } from "@/utils/trigger";
+
 export type JumpHostsFormFields = {
   jumpHostEnabled?: boolean;
   jumpHostIds?: string[];
@@ -9,6 +11,7 @@ export type JumpHostsFormFields = {
 export type EditHostFormFields = Omit<Partial<Host>, "envs" | "jumpHostIds"> &
   JumpHostsFormFields & {
     envs?: string;
+    triggers?: TriggerFormField[];
   };
 
 export type EditHostFormApi = UseFormReturn<EditHostFormFields>;
@@ -0,0 +1,51 @@
+import { Button, Tabs } from "@radix-ui/themes";
+import type { CSSProperties } from "react";
+import type { SSHTmuxPaneInfo } from "tauri-plugin-ssh";
+
+import type { ShellTmuxState } from "@/hooks/useShell";
+import styles from "./index.module.less";
+
+export const TMUX_TABS_HEIGHT = 32;
+
+export type TmuxTabsProps = {
+  tmux: ShellTmuxState;
+  style?: CSSProperties;
+  onSelect: (paneId: string) => unknown;
+  onDetach: () => unknown;
+};
+
+function getPaneLabel(pane: SSHTmuxPaneInfo, panes: SSHTmuxPaneInfo[]) {
+  const windowPanes = panes.filter((item) => item.windowId === pane.windowId);
+  // 窗口只有一个面板时只显示窗口名
+  if (windowPanes.length === 1) {
+    return pane.windowName || pane.windowId;
+  }
+
+  return `${pane.windowName || pane.windowId} ${windowPanes.indexOf(pane) + 1}`;
+}
+
+/**
+ * tmux 控制模式下的面板标签，切换终端显示的面板
+ */
+export function TmuxTabs({ tmux, style, onSelect, onDetach }: TmuxTabsProps) {
+  return (
+    <div className={styles.root} style={{ height: TMUX_TABS_HEIGHT, ...style }}>
+      <Tabs.Root
+        className={styles.tabs}
+        value={tmux.activePaneId}
+        onValueChange={onSelect}
+      >
+        <Tabs.List size="1">
+          {tmux.panes.map((pane) => (
+            <Tabs.Trigger key={pane.paneId} value={pane.paneId}>
+              {getPaneLabel(pane, tmux.panes)}
+            </Tabs.Trigger>
+          ))}
+        </Tabs.List>
+      </Tabs.Root>
+      <Button size="1" variant="soft" color="gray" onClick={onDetach}>
+        Detach
+      </Button>
+    </div>
+  );
+}
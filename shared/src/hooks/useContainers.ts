@@ -0,0 +1,108 @@
+import { useMemoizedFn, useRequest } from "ahooks";
+import { useMemo, useRef } from "react";
+import {
+  containerAction,
+  containerList,
+  type SSHContainer,
+  type SSHContainerAction,
+  type SSHContainerRuntime,
+} from "tauri-plugin-ssh";
+
+import {
+  type TerminalAtom,
+  useTerminalsAtomWithApi,
+} from "@/atoms/session.atom";
+import { getActiveSession } from "@/utils/ssh";
+
+import { useConnection } from "./useConnection";
+
+export interface UseContainersOpts {
+  item: TerminalAtom;
+}
+
+export function useContainers({ item }: UseContainersOpts) {
+  const terminalsAtomWithApi = useTerminalsAtomWithApi();
+  const runtimeRef = useRef<SSHContainerRuntime>(undefined);
+
+  const session = useMemo(() => {
+    return getActiveSession(item.jumpHostChain);
+  }, [item.jumpHostChain]);
+
+  const list = useMemoizedFn(async () => {
+    if (!session) {
+      throw new Error("session is undefined");
+    }
+
+    // 首次列出时检测 docker 或 podman，之后沿用
+    const result = await containerList(session, runtimeRef.current);
+    runtimeRef.current = result.runtime;
+    return result;
+  });
+
+  const {
+    data,
+    mutate,
+    loading: listLoading,
+    error: listError,
+    runAsync: listRunAsync,
+  } = useRequest(list, {
+    ready: !!session,
+    onBefore: () => {
+      terminalsAtomWithApi.update({
+        ...item,
+        status: "pending",
+        error: undefined,
+      });
+    },
+    onSuccess: () => {
+      terminalsAtomWithApi.update({
+        ...item,
+        status: "success",
+      });
+    },
+    onError: (error) => {
+      terminalsAtomWithApi.update({
+        ...item,
+        status: "failed",
+        error,
+      });
+    },
+  });
+
+  const connection = useConnection({
+    item,
+    underlyingLoading: listLoading,
+    underlyingError: listError,
+    onRunAsync: listRunAsync,
+  });
+
+  /**
+   * 刷新列表，不影响连接状态
+   */
+  const { loading: refreshing, runAsync: refresh } = useRequest(
+    async () => {
+      mutate(await list());
+    },
+    { manual: true },
+  );
+
+  const runAction = useMemoizedFn(
+    async (container: SSHContainer, action: SSHContainerAction) => {
+      if (!session || !runtimeRef.current) {
+        throw new Error("session is undefined");
+      }
+
+      await containerAction(session, runtimeRef.current, container.id, action);
+      await refresh();
+    },
+  );
+
+  return {
+    ...connection,
+    runtime: data?.runtime,
+    containers: data?.containers ?? [],
+    refreshing,
+    refresh,
+    runAction,
+  };
+}
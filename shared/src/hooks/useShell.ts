@@ -1,36 +1,214 @@
 import { useMemoizedFn, useRequest, useUnmount } from "ahooks";
 import { Buffer } from "buffer";
 import { useRef, useState } from "react";
-import type { Host } from "tauri-plugin-data";
-import { type SSHSession, SSHShell } from "tauri-plugin-ssh";
+import { getTriggers, type Host } from "tauri-plugin-data";
+import {
+  type SSHSession,
+  SSHShell,
+  type SSHShellIntegrationEvent,
+  type SSHShellLocalUrl,
+  type SSHShellTriggerEvent,
+  type SSHShellUrlForwardingEvent,
+  type SSHTmuxEvent,
+  SSHTmuxPane,
+  type SSHTmuxPaneInfo,
+  tmuxDetach,
+  tmuxListPanes,
+  tmuxWindowResize,
+} from "tauri-plugin-ssh";
+import type { TerminalContainer } from "@/atoms/session.atom";
+import { message } from "@/components/Message";
 import type { Terminal, TerminalSize } from "@/components/XTerminal";
+import { saveCommandHistory } from "@/utils/commandHistory";
 import { oscParse } from "@/utils/osc";
-import { sleep } from "@/utils/sleep";
 import { xtermBinaryToBytes } from "@/utils/terminal";
+import { getShellTriggers } from "@/utils/trigger";
+import { runZmodem } from "@/utils/zmodem";
 
 export interface UseShellOpts {
   session?: SSHSession;
   host?: Host;
+  /**
+   * 设置后在容器内打开 shell，代替主机的登录 shell
+   */
+  container?: TerminalContainer;
   onClose?: () => void;
   onBefore?: () => void;
   onSuccess?: () => void;
   onError?: (error: unknown) => void;
   onCopy?: (content: string) => void;
+  onTrigger?: (event: SSHShellTriggerEvent) => void;
+  /**
+   * 命令结束事件带有退出码和耗时，可用于长时间命令完成的通知
+   */
+  onShellIntegration?: (event: SSHShellIntegrationEvent) => void;
+  /**
+   * 主机开启了地址转发时，上报检测到的 localhost 地址及转发结果
+   */
+  onUrlForwarding?: (event: SSHShellUrlForwardingEvent) => void;
 }
 
+/**
+ * 远程 tmux -CC 运行时的窗口和面板，终端显示其中一个面板
+ */
+export type ShellTmuxState = {
+  panes: SSHTmuxPaneInfo[];
+  activePaneId?: string;
+};
+
 export function useShell({
   session,
   host,
+  container,
   onClose,
   onBefore,
   onSuccess,
   onError,
   onCopy,
+  onTrigger,
+  onShellIntegration,
+  onUrlForwarding,
 }: UseShellOpts) {
   const [terminal, setTerminal] = useState<Terminal>();
 
   const shellRef = useRef<SSHShell>(null);
 
+  const [tmux, setTmux] = useState<ShellTmuxState>();
+  const tmuxRef = useRef<ShellTmuxState>(undefined);
+  const tmuxPaneRef = useRef<SSHTmuxPane>(null);
+
+  const updateTmux = useMemoizedFn((state?: ShellTmuxState) => {
+    tmuxRef.current = state;
+    setTmux(state);
+  });
+
+  const getTerminalSize = useMemoizedFn((terminal: Terminal) => ({
+    col: terminal.cols,
+    row: terminal.rows,
+    width: terminal.element?.clientWidth ?? 0,
+    height: terminal.element?.clientHeight ?? 0,
+  }));
+
+  // tmux 窗口跟随终端大小，面板按窗口布局排列
+  const resizeTmuxWindow = useMemoizedFn(async () => {
+    const shell = shellRef.current;
+    const paneId = tmuxRef.current?.activePaneId;
+    const pane = tmuxRef.current?.panes.find((item) => item.paneId === paneId);
+    if (!shell || !terminal || !pane) {
+      return;
+    }
+
+    await tmuxWindowResize(shell, pane.windowId, getTerminalSize(terminal));
+  });
+
+  const openTmuxPane = useMemoizedFn(async (paneId: string) => {
+    const shell = shellRef.current;
+    if (!shell || !terminal || !tmuxRef.current) {
+      return;
+    }
+
+    const prevPane = tmuxPaneRef.current;
+    tmuxPaneRef.current = null;
+    await prevPane?.close().catch(() => undefined);
+
+    // 打开面板时会回放历史和屏幕，先清空终端
+    terminal.reset();
+    const pane = new SSHTmuxPane({
+      shell,
+      paneId,
+      onData: (data) => {
+        if (tmuxPaneRef.current === pane) {
+          terminal.write(data);
+        }
+      },
+      onClose: () => {
+        if (tmuxPaneRef.current === pane) {
+          tmuxPaneRef.current = null;
+          refreshTmuxPanes();
+        }
+      },
+    });
+    tmuxPaneRef.current = pane;
+    updateTmux({ ...tmuxRef.current, activePaneId: paneId });
+
+    try {
+      await pane.open();
+      await resizeTmuxWindow();
+    } catch (error) {
+      message.error(`Failed to open tmux pane ${paneId}: ${String(error)}`);
+    }
+  });
+
+  const refreshTmuxPanes = useMemoizedFn(async () => {
+    const shell = shellRef.current;
+    if (!shell || !tmuxRef.current) {
+      return;
+    }
+
+    try {
+      const panes = await tmuxListPanes(shell);
+      if (!tmuxRef.current) {
+        return;
+      }
+
+      const activePaneId = tmuxRef.current.activePaneId;
+      updateTmux({ panes, activePaneId });
+      // 当前面板已关闭时切换到 tmux 中活动的面板
+      if (
+        !activePaneId ||
+        !panes.some((pane) => pane.paneId === activePaneId)
+      ) {
+        const nextPane =
+          panes.find((pane) => pane.windowActive && pane.paneActive) ??
+          panes[0];
+        if (nextPane) {
+          await openTmuxPane(nextPane.paneId);
+        }
+      }
+    } catch (error) {
+      message.error(`Failed to list tmux panes: ${String(error)}`);
+    }
+  });
+
+  const onTmux = useMemoizedFn((event: SSHTmuxEvent) => {
+    switch (event.type) {
+      case "Start":
+        updateTmux({ panes: [] });
+        refreshTmuxPanes();
+        break;
+      case "Exit":
+        // 面板已由后端关闭，之后的输出回到终端
+        tmuxPaneRef.current = null;
+        updateTmux(undefined);
+        terminal?.reset();
+        if (event.reason) {
+          message.info(`tmux exited: ${event.reason}`);
+        }
+        break;
+      default:
+        refreshTmuxPanes();
+        break;
+    }
+  });
+
+  const selectTmuxPane = useMemoizedFn((paneId: string) => {
+    if (paneId !== tmuxRef.current?.activePaneId) {
+      openTmuxPane(paneId);
+    }
+  });
+
+  const detachTmux = useMemoizedFn(async () => {
+    if (!shellRef.current) {
+      return;
+    }
+
+    try {
+      await tmuxDetach(shellRef.current);
+    } catch (error) {
+      message.error(`Failed to detach tmux: ${String(error)}`);
+    }
+  });
+
   const { loading, error, run, runAsync, refresh, refreshAsync } = useRequest(
     async () => {
       if (!terminal) {
@@ -46,16 +224,32 @@ export function useShell({
       } catch {
         // Ignore stale shell close errors before opening a replacement shell.
       }
+      tmuxPaneRef.current = null;
+      updateTmux(undefined);
       const shell = new SSHShell({
         session,
         onData: (data: Uint8Array) => {
           oscParse(Buffer.from(data), { onCopy });
-          terminal.write(data);
+          return new Promise<void>((resolve) => {
+            terminal.write(data, resolve);
+          });
         },
         onClose,
+        onTrigger,
+        onZmodem: (direction) => {
+          runZmodem(shell, terminal, direction);
+        },
+        onShellIntegration: (event) => {
+          saveCommandHistory(event, host?.id);
+          onShellIntegration?.(event);
+        },
+        onUrlForwarding,
+        onTmux,
       });
       shellRef.current = shell;
 
+      const triggers = await getTriggers().catch(() => []);
+
       await shell.open({
         term: host?.terminalType,
         envs: host?.envs?.reduce<Record<string, string>>((prev, cur) => {
@@ -67,18 +261,25 @@ export function useShell({
           prev[key] = value;
           return prev;
         }, {}),
-        size: {
-          col: terminal.cols,
-          row: terminal.rows,
-          width: terminal.element?.clientWidth ?? 0,
-          height: terminal.element?.clientHeight ?? 0,
-        },
+        size: getTerminalSize(terminal),
+        // 主机的启动命令不在容器内运行
+        startupScript:
+          host?.startupCommand && !container
+            ? { script: host.startupCommand }
+            : undefined,
+        triggers: getShellTriggers(triggers, host?.id),
+        request: container
+          ? {
+              type: "Container",
+              runtime: container.runtime,
+              containerId: container.containerId,
+            }
+          : host?.shellCommand
+            ? { type: "Exec", command: host.shellCommand }
+            : undefined,
+        encoding: host?.terminalEncoding,
+        urlForwarding: host?.urlForwarding,
       });
-
-      if (host?.startupCommand) {
-        await sleep(200);
-        await shell.send(`${host.startupCommand}\n`);
-      }
     },
     {
       ready: !!terminal && !!session,
@@ -92,10 +293,19 @@ export function useShell({
     setTerminal(terminal);
   });
 
+  // tmux 运行时输入发送到当前面板
   const onTerminalData = useMemoizedFn((data: string) => {
+    if (tmuxPaneRef.current) {
+      tmuxPaneRef.current.send(data);
+      return;
+    }
     shellRef.current?.send(data);
   });
   const onTerminalBinaryData = useMemoizedFn((data: string) => {
+    if (tmuxPaneRef.current) {
+      tmuxPaneRef.current.send(xtermBinaryToBytes(data));
+      return;
+    }
     shellRef.current?.send(xtermBinaryToBytes(data));
   });
   const onTerminalResize = useMemoizedFn((size: TerminalSize) => {
@@ -103,9 +313,23 @@ export function useShell({
       return;
     }
 
+    if (tmuxRef.current) {
+      resizeTmuxWindow().catch((err) => {
+        console.error("tmux window resize failed", err);
+      });
+      return;
+    }
     shellRef.current?.resize(size);
   });
 
+  const forwardUrl = useMemoizedFn(async (localUrl: SSHShellLocalUrl) => {
+    if (!shellRef.current) {
+      throw new Error("shell is undefined");
+    }
+
+    return shellRef.current.forwardUrl(localUrl);
+  });
+
   useUnmount(() => {
     shellRef.current?.close();
   });
@@ -115,6 +339,10 @@ export function useShell({
     onTerminalData,
     onTerminalBinaryData,
     onTerminalResize,
+    forwardUrl,
+    tmux,
+    selectTmuxPane,
+    detachTmux,
     terminal,
     loading,
     error,
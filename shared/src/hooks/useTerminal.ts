@@ -4,6 +4,11 @@ import {
   type TerminalAtom,
   useTerminalsAtomWithApi,
 } from "@/atoms/session.atom";
+import type {
+  SSHShellTriggerEvent,
+  SSHShellUrlForwardingEvent,
+} from "tauri-plugin-ssh";
+
 import { getActiveSession } from "@/utils/ssh";
 
 import { useConnection } from "./useConnection";
@@ -13,9 +18,17 @@ export interface UseTerminalOpts {
   item: TerminalAtom;
   onClose?: () => unknown;
   onCopy?: (content: string) => unknown;
+  onTrigger?: (event: SSHShellTriggerEvent) => unknown;
+  onUrlForwarding?: (event: SSHShellUrlForwardingEvent) => unknown;
 }
 
-export function useTerminal({ item, onClose, onCopy }: UseTerminalOpts) {
+export function useTerminal({
+  item,
+  onClose,
+  onCopy,
+  onTrigger,
+  onUrlForwarding,
+}: UseTerminalOpts) {
   const terminalsAtomWithApi = useTerminalsAtomWithApi();
 
   const session = useMemo(() => {
@@ -27,6 +40,10 @@ export function useTerminal({ item, onClose, onCopy }: UseTerminalOpts) {
     onTerminalData,
     onTerminalBinaryData,
     onTerminalResize,
+    forwardUrl,
+    tmux,
+    selectTmuxPane,
+    detachTmux,
     terminal,
     loading: shellLoading,
     error: shellError,
@@ -34,8 +51,11 @@ export function useTerminal({ item, onClose, onCopy }: UseTerminalOpts) {
   } = useShell({
     session,
     host: item.host,
+    container: item.container,
     onClose,
     onCopy,
+    onTrigger,
+    onUrlForwarding,
     onBefore: () => {
       terminalsAtomWithApi.update({
         ...item,
@@ -72,5 +92,9 @@ export function useTerminal({ item, onClose, onCopy }: UseTerminalOpts) {
     onTerminalData,
     onTerminalBinaryData,
     onTerminalResize,
+    forwardUrl,
+    tmux,
+    selectTmuxPane,
+    detachTmux,
   };
 }
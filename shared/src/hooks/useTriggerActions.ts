@@ -0,0 +1,111 @@
+import { useMemoizedFn } from "ahooks";
+import { useMemo } from "react";
+import type { SSHShellTriggerEvent } from "tauri-plugin-ssh";
+
+import { usePortForwardingsAtomWithApi } from "@/atoms/portForwardings.atom";
+import { message } from "@/components/Message";
+import type { Terminal } from "@/components/XTerminal";
+import { establishPortForwarding } from "@/utils/ssh";
+
+import { useKeys } from "./useKeys";
+import { usePortForwardings } from "./usePortForwardings";
+
+/**
+ * 在终端中高亮匹配的行，从光标所在行向上查找最近一屏内的匹配
+ */
+function highlightLine(terminal: Terminal, matched: string, color: string) {
+  const buffer = terminal.buffer.active;
+  const cursorLine = buffer.baseY + buffer.cursorY;
+  const firstLine = Math.max(0, cursorLine - terminal.rows);
+
+  for (let line = cursorLine; line >= firstLine; line--) {
+    const text = buffer.getLine(line)?.translateToString(true);
+    if (!text?.includes(matched)) {
+      continue;
+    }
+
+    const marker = terminal.registerMarker(line - cursorLine);
+    if (marker) {
+      terminal.registerDecoration({
+        marker,
+        width: terminal.cols,
+        backgroundColor: color,
+      });
+    }
+    return;
+  }
+}
+
+/**
+ * 处理触发器中交给界面的动作：通知、高亮匹配行和打开端口转发，
+ * 发送文本的动作已由后端处理
+ */
+export function useTriggerActions() {
+  const { data: portForwardings } = usePortForwardings();
+  const { data: keys } = useKeys();
+  const portForwardingsAtomWithApi = usePortForwardingsAtomWithApi();
+
+  const keysMap = useMemo(
+    () => new Map(keys.map((key) => [key.id, key])),
+    [keys],
+  );
+
+  const openPortForwarding = useMemoizedFn(
+    async (portForwardingId: string) => {
+      // 已经打开的转发不重复打开
+      if (portForwardingsAtomWithApi.getState().has(portForwardingId)) {
+        return;
+      }
+
+      const portForwarding = portForwardings.find(
+        (item) => item.id === portForwardingId,
+      );
+      if (!portForwarding) {
+        message.error("The port forwarding of the trigger no longer exists");
+        return;
+      }
+
+      const [added] = portForwardingsAtomWithApi.add(portForwarding);
+      try {
+        await establishPortForwarding(added, keysMap, (updated) => {
+          portForwardingsAtomWithApi.update(updated);
+        });
+        message.success(`Port forwarding ${portForwarding.name} is opened`);
+      } catch (error) {
+        const failedItem = portForwardingsAtomWithApi
+          .getState()
+          .get(portForwardingId);
+        if (failedItem) {
+          portForwardingsAtomWithApi.update({
+            ...failedItem,
+            status: "failed",
+            error,
+          });
+        }
+        message.error(
+          `Failed to open port forwarding ${portForwarding.name}: ${String(error)}`,
+        );
+      }
+    },
+  );
+
+  return useMemoizedFn((event: SSHShellTriggerEvent, terminal?: Terminal) => {
+    const { action } = event;
+    switch (action.type) {
+      case "Notify":
+        message.info(action.message || event.matched);
+        break;
+      case "Highlight":
+        if (terminal) {
+          // 等已收到的输出写入终端后再查找匹配的行
+          terminal.write("", () => {
+            highlightLine(terminal, event.matched, action.color);
+          });
+        }
+        break;
+      case "PortForwarding":
+        openPortForwarding(action.portForwardingId);
+        break;
+    }
+  });
+}
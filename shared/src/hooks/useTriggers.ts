@@ -0,0 +1,14 @@
+import { getTriggers } from "tauri-plugin-data";
+
+import { useSWR } from "./useSWR";
+
+export function useTriggers() {
+  const { data, loading, error, refresh } = useSWR("getTriggers", getTriggers);
+
+  return {
+    data: data ?? [],
+    loading,
+    error,
+    refresh,
+  };
+}
@@ -16,10 +16,12 @@ export * from "./components/PortForwardingForm";
 export * from "./components/PortForwardingLoading";
 export * from "./components/SSHLoading";
 export * from "./components/TextFieldPassword";
+export * from "./components/TmuxTabs";
 export * from "./components/TransferProgress";
 export * from "./components/VirtualKeyboard";
 export * from "./components/XTerminal";
 // hooks
+export * from "./hooks/useContainers";
 export * from "./hooks/useHosts";
 export * from "./hooks/useImportAppData";
 export * from "./hooks/useKeys";
@@ -31,7 +33,11 @@ export * from "./hooks/useSftpFileEditor";
 export * from "./hooks/useShell";
 export * from "./hooks/useSWR";
 export * from "./hooks/useTerminal";
+export * from "./hooks/useTriggerActions";
+export * from "./hooks/useTriggers";
 // utils
+export * from "./utils/broadcast";
+export * from "./utils/commandHistory";
 export * from "./utils/display";
 export * from "./utils/env";
 export * from "./utils/form";
@@ -44,4 +50,6 @@ export * from "./utils/sleep";
 export * from "./utils/ssh";
 export * from "./utils/style";
 export * from "./utils/terminal";
+export * from "./utils/trigger";
 export * from "./utils/umami";
+export * from "./utils/zmodem";
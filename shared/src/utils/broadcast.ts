@@ -0,0 +1,36 @@
+import { invoke } from "@tauri-apps/api/core";
+
+export type BroadcastMember =
+  | { type: "Ssh"; id: string }
+  | { type: "Pty"; id: string };
+
+export type BroadcastSendResult = {
+  member: BroadcastMember;
+  error: string | null;
+};
+
+export function broadcastJoin(groupId: string, member: BroadcastMember) {
+  return invoke<BroadcastMember[]>("broadcast_join", { groupId, member });
+}
+
+export function broadcastLeave(groupId: string, member: BroadcastMember) {
+  return invoke<BroadcastMember[]>("broadcast_leave", { groupId, member });
+}
+
+export function broadcastMembers(groupId: string) {
+  return invoke<BroadcastMember[]>("broadcast_members", { groupId });
+}
+
+export function broadcastRemove(groupId: string) {
+  return invoke<void>("broadcast_remove", { groupId });
+}
+
+export function broadcastSend(groupId: string, data: string | Uint8Array) {
+  const bytes =
+    typeof data === "string" ? new TextEncoder().encode(data) : data;
+
+  return invoke<BroadcastSendResult[]>("broadcast_send", {
+    groupId,
+    data: Array.from(bytes),
+  });
+}
@@ -0,0 +1,26 @@
+import { addCommandHistory } from "tauri-plugin-data";
+import type { SSHShellIntegrationEvent } from "tauri-plugin-ssh";
+
+/**
+ * 命令结束时写入命令历史，hostId 为空表示本地终端
+ */
+export async function saveCommandHistory(
+  event: SSHShellIntegrationEvent,
+  hostId?: string,
+) {
+  if (event.type !== "CommandFinished" || !event.command) {
+    return;
+  }
+
+  try {
+    await addCommandHistory({
+      hostId,
+      command: event.command,
+      cwd: event.cwd ?? undefined,
+      exitCode: event.exitCode ?? undefined,
+      duration: event.duration,
+    });
+  } catch (error) {
+    console.error("save command history failed", error);
+  }
+}
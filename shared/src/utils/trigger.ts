@@ -0,0 +1,116 @@
+import {
+  addTrigger,
+  deleteTrigger,
+  getTriggers,
+  type Trigger,
+  type TriggerAction,
+  updateTrigger,
+} from "tauri-plugin-data";
+
+/**
+ * 主机表单中编辑的触发器，动作参数拍平为一个字段
+ */
+export type TriggerFormField = {
+  id?: string;
+  name: string;
+  pattern: string;
+  actionType: TriggerAction["type"];
+  /**
+   * SendText 的文本、Notify 的消息、Highlight 的颜色或 PortForwarding 的转发 id
+   */
+  actionValue: string;
+  enabled: boolean;
+};
+
+/**
+ * 筛选出对指定主机生效的触发器，hostId 为空时只保留全局触发器（用于本地终端）
+ */
+export function getShellTriggers(triggers: Trigger[], hostId?: string) {
+  return triggers
+    .filter((item) => item.enabled && (!item.hostId || item.hostId === hostId))
+    .map(({ id, pattern, action }) => ({ id, pattern, action }));
+}
+
+function getActionValue(action: TriggerAction) {
+  switch (action.type) {
+    case "SendText":
+      return action.text;
+    case "Notify":
+      return action.message ?? "";
+    case "Highlight":
+      return action.color;
+    case "PortForwarding":
+      return action.portForwardingId;
+  }
+}
+
+function toTriggerAction({
+  actionType,
+  actionValue,
+}: TriggerFormField): TriggerAction {
+  switch (actionType) {
+    case "SendText":
+      return { type: "SendText", text: actionValue };
+    case "Notify":
+      return { type: "Notify", message: actionValue || undefined };
+    case "Highlight":
+      return { type: "Highlight", color: actionValue };
+    case "PortForwarding":
+      return { type: "PortForwarding", portForwardingId: actionValue };
+  }
+}
+
+/**
+ * 主机自己的触发器转为表单字段，全局触发器不在主机表单中编辑
+ */
+export function toTriggerFormFields(
+  triggers: Trigger[],
+  hostId?: string,
+): TriggerFormField[] {
+  if (!hostId) {
+    return [];
+  }
+
+  return triggers
+    .filter((item) => item.hostId === hostId)
+    .map(({ id, name, pattern, action, enabled }) => ({
+      id,
+      name,
+      pattern,
+      actionType: action.type,
+      actionValue: getActionValue(action),
+      enabled,
+    }));
+}
+
+/**
+ * 保存主机后同步它的触发器：删除表单中移除的，更新已有的，添加新增的
+ */
+export async function saveHostTriggers(
+  hostId: string,
+  fields: TriggerFormField[],
+) {
+  const triggers = await getTriggers();
+  const ids = new Set(fields.map((item) => item.id).filter(Boolean));
+
+  await Promise.all(
+    triggers
+      .filter((item) => item.hostId === hostId && !ids.has(item.id))
+      .map((item) => deleteTrigger(item)),
+  );
+
+  await Promise.all(
+    fields.map((field) => {
+      const trigger = {
+        name: field.name,
+        hostId,
+        pattern: field.pattern,
+        action: toTriggerAction(field),
+        enabled: field.enabled,
+      };
+      return field.id
+        ? updateTrigger({ ...trigger, id: field.id })
+        : addTrigger(trigger);
+    }),
+  );
+}
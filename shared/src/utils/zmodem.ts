@@ -0,0 +1,54 @@
+import { open } from "@tauri-apps/plugin-dialog";
+import { get, throttle } from "lodash-es";
+import type { SSHShell, SSHShellZmodemDirection } from "tauri-plugin-ssh";
+import type { Terminal } from "@/components/XTerminal";
+import { formatBytes } from "./display";
+
+/**
+ * 检测到 rz/sz 后选择本地文件或目录并执行传输，进度输出到终端的当前行
+ */
+export async function runZmodem(
+  shell: SSHShell,
+  terminal: Terminal,
+  direction: SSHShellZmodemDirection,
+) {
+  const selected =
+    direction === "Upload"
+      ? await open({ multiple: true, directory: false })
+      : await open({ multiple: false, directory: true });
+
+  if (!selected || (Array.isArray(selected) && !selected.length)) {
+    await shell.zmodemCancel();
+    terminal.write("\r\n\x1b[33mZMODEM cancelled\x1b[0m\r\n");
+    return;
+  }
+
+  const onProgress = throttle(
+    ({ progress, total }: { progress: number; total: number }) => {
+      const percent = total ? Math.floor((progress / total) * 100) : 100;
+      terminal.write(
+        `\r\x1b[KZMODEM ${percent}% ${formatBytes(progress)} / ${formatBytes(total)}`,
+      );
+    },
+    100,
+  );
+
+  try {
+    const filenames =
+      direction === "Upload"
+        ? await shell.zmodemUpload(
+            Array.isArray(selected) ? selected : [selected],
+            { onProgress },
+          )
+        : await shell.zmodemDownload(selected as string, { onProgress });
+    onProgress.flush();
+    terminal.write(
+      `\r\x1b[K\x1b[32mZMODEM ${filenames.length} file(s) transferred\x1b[0m\r\n`,
+    );
+  } catch (error) {
+    onProgress.cancel();
+    terminal.write(
+      `\r\x1b[K\x1b[31mZMODEM ${get(error, "message", String(error))}\x1b[0m\r\n`,
+    );
+  }
+}
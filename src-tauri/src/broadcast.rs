@@ -0,0 +1,262 @@
+use std::{
+  collections::{HashMap, HashSet},
+  sync::{Arc, Mutex},
+};
+
+use serde::{Deserialize, Serialize};
+use tauri::{async_runtime, AppHandle, Listener, Manager, Runtime, State};
+use tauri_plugin_ssh::SSHShellId;
+use tokio::sync::{mpsc, oneshot};
+
+use crate::error::Shell360Result;
+
+pub type BroadcastGroupId = String;
+
+/// A terminal that receives broadcast input, either a ssh shell or a local pty.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(tag = "type", content = "id")]
+pub enum BroadcastMember {
+  Ssh(SSHShellId),
+  Pty(String),
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BroadcastSendResult {
+  member: BroadcastMember,
+  error: Option<String>,
+}
+
+/// Input queued for one member, the result of writing it is sent back on `reply`.
+struct BroadcastInput {
+  data: Arc<[u8]>,
+  reply: oneshot::Sender<Result<(), String>>,
+}
+
+#[derive(Default)]
+pub struct BroadcastManager {
+  groups: Mutex<HashMap<BroadcastGroupId, HashSet<BroadcastMember>>>,
+  /// Input of each member, written in order by a task of its own
+  queues: Mutex<HashMap<BroadcastMember, mpsc::UnboundedSender<BroadcastInput>>>,
+}
+
+impl BroadcastManager {
+  fn members(&self, group_id: &str) -> Shell360Result<Vec<BroadcastMember>> {
+    let groups = self.groups.lock()?;
+    Ok(
+      groups
+        .get(group_id)
+        .map(|members| members.iter().cloned().collect())
+        .unwrap_or_default(),
+    )
+  }
+
+  /// Removes a closed terminal from every group, groups left empty are removed too.
+  pub fn remove_member(&self, member: &BroadcastMember) -> Shell360Result<()> {
+    let mut groups = self.groups.lock()?;
+    groups.retain(|_group_id, members| {
+      members.remove(member);
+      !members.is_empty()
+    });
+    self.prune_queues(&groups)
+  }
+
+  /// Drops the queues of terminals left in no group, their tasks end once the queued input is
+  /// written.
+  fn prune_queues(
+    &self,
+    groups: &HashMap<BroadcastGroupId, HashSet<BroadcastMember>>,
+  ) -> Shell360Result<()> {
+    let mut queues = self.queues.lock()?;
+    queues.retain(|member, _| groups.values().any(|members| members.contains(member)));
+
+    Ok(())
+  }
+
+  /// Queues input for a member, starting its writer task on first use.
+  fn queue<R: Runtime>(
+    &self,
+    app_handle: &AppHandle<R>,
+    member: &BroadcastMember,
+    data: Arc<[u8]>,
+  ) -> Shell360Result<oneshot::Receiver<Result<(), String>>> {
+    let (reply, receiver) = oneshot::channel();
+    let mut input = BroadcastInput { data, reply };
+
+    let mut queues = self.queues.lock()?;
+    if let Some(sender) = queues.get(member) {
+      // 写入任务已结束时重新创建队列
+      match sender.send(input) {
+        Ok(()) => return Ok(receiver),
+        Err(mpsc::error::SendError(unsent)) => input = unsent,
+      }
+    }
+
+    let (sender, inputs) = mpsc::unbounded_channel();
+    async_runtime::spawn(write_member_input(
+      app_handle.clone(),
+      member.clone(),
+      inputs,
+    ));
+    // 接收端刚刚创建，发送不会失败
+    let _ = sender.send(input);
+    queues.insert(member.clone(), sender);
+
+    Ok(receiver)
+  }
+}
+
+/// Writes the input queued for a member one send after another, so it arrives in the order it
+/// was broadcast.
+async fn write_member_input<R: Runtime>(
+  app_handle: AppHandle<R>,
+  member: BroadcastMember,
+  mut inputs: mpsc::UnboundedReceiver<BroadcastInput>,
+) {
+  while let Some(input) = inputs.recv().await {
+    let result = send_to_member(&app_handle, &member, &input.data).await;
+    // 调用方不再等待结果时忽略即可
+    let _ = input.reply.send(result);
+  }
+}
+
+/// Prunes terminals from the groups once their shell closes.
+pub fn watch_closed_members<R: Runtime>(app_handle: &AppHandle<R>) {
+  let ssh_app_handle = app_handle.clone();
+  app_handle.listen_any(
+    tauri_plugin_ssh::SHELL_CLOSE_EVENT,
+    move |event| match serde_json::from_str::<SSHShellId>(event.payload()) {
+      Ok(ssh_shell_id) => remove_closed_member(&ssh_app_handle, BroadcastMember::Ssh(ssh_shell_id)),
+      Err(err) => log::error!("invalid ssh shell close event: {}", err),
+    },
+  );
+
+  #[cfg(desktop)]
+  {
+    let pty_app_handle = app_handle.clone();
+    app_handle.listen_any(tauri_plugin_pty::SHELL_CLOSE_EVENT, move |event| {
+      match serde_json::from_str::<String>(event.payload()) {
+        Ok(shell_id) => remove_closed_member(&pty_app_handle, BroadcastMember::Pty(shell_id)),
+        Err(err) => log::error!("invalid pty shell close event: {}", err),
+      }
+    });
+  }
+}
+
+fn remove_closed_member<R: Runtime>(app_handle: &AppHandle<R>, member: BroadcastMember) {
+  let broadcast_manager = app_handle.state::<BroadcastManager>();
+  if let Err(err) = broadcast_manager.remove_member(&member) {
+    log::error!("broadcast remove {:?} failed: {}", member, err);
+  }
+}
+
+async fn send_to_member<R: Runtime>(
+  app_handle: &AppHandle<R>,
+  member: &BroadcastMember,
+  data: &[u8],
+) -> Result<(), String> {
+  match member {
+    BroadcastMember::Ssh(ssh_shell_id) => {
+      tauri_plugin_ssh::write_shell(app_handle, *ssh_shell_id, data)
+        .await
+        .map_err(|err| err.to_string())
+    }
+    #[cfg(desktop)]
+    BroadcastMember::Pty(shell_id) => {
+      tauri_plugin_pty::write_shell(app_handle, shell_id, data).map_err(|err| err.to_string())
+    }
+    #[cfg(mobile)]
+    BroadcastMember::Pty(_) => Err("Local terminal is not supported".to_string()),
+  }
+}
+
+#[tauri::command]
+pub async fn broadcast_join(
+  broadcast_manager: State<'_, BroadcastManager>,
+  group_id: BroadcastGroupId,
+  member: BroadcastMember,
+) -> Shell360Result<Vec<BroadcastMember>> {
+  {
+    let mut groups = broadcast_manager.groups.lock()?;
+    groups.entry(group_id.clone()).or_default().insert(member);
+  }
+
+  broadcast_manager.members(&group_id)
+}
+
+#[tauri::command]
+pub async fn broadcast_leave(
+  broadcast_manager: State<'_, BroadcastManager>,
+  group_id: BroadcastGroupId,
+  member: BroadcastMember,
+) -> Shell360Result<Vec<BroadcastMember>> {
+  {
+    let mut groups = broadcast_manager.groups.lock()?;
+    if let Some(members) = groups.get_mut(&group_id) {
+      members.remove(&member);
+      // 组内没有成员时直接移除该组
+      if members.is_empty() {
+        groups.remove(&group_id);
+      }
+    }
+    broadcast_manager.prune_queues(&groups)?;
+  }
+
+  broadcast_manager.members(&group_id)
+}
+
+#[tauri::command]
+pub async fn broadcast_members(
+  broadcast_manager: State<'_, BroadcastManager>,
+  group_id: BroadcastGroupId,
+) -> Shell360Result<Vec<BroadcastMember>> {
+  broadcast_manager.members(&group_id)
+}
+
+#[tauri::command]
+pub async fn broadcast_remove(
+  broadcast_manager: State<'_, BroadcastManager>,
+  group_id: BroadcastGroupId,
+) -> Shell360Result<()> {
+  let mut groups = broadcast_manager.groups.lock()?;
+  groups.remove(&group_id);
+  broadcast_manager.prune_queues(&groups)
+}
+
+/// Sends data to every member of the group concurrently.
+///
+/// The data is queued for each member before anything is awaited, every member receives the
+/// sends in the order they were made. A failing member doesn't abort the send, its error is
+/// returned in the result.
+#[tauri::command]
+pub async fn broadcast_send<R: Runtime>(
+  app_handle: AppHandle<R>,
+  broadcast_manager: State<'_, BroadcastManager>,
+  group_id: BroadcastGroupId,
+  data: Vec<u8>,
+) -> Shell360Result<Vec<BroadcastSendResult>> {
+  let members = broadcast_manager.members(&group_id)?;
+  let data: Arc<[u8]> = Arc::from(data);
+
+  let replies = members
+    .into_iter()
+    .map(|member| {
+      let reply = broadcast_manager.queue(&app_handle, &member, data.clone())?;
+      Ok((member, reply))
+    })
+    .collect::<Shell360Result<Vec<_>>>()?;
+
+  let mut results = Vec::with_capacity(replies.len());
+  for (member, reply) in replies {
+    let error = match reply.await {
+      Ok(result) => result.err(),
+      Err(_) => Some("Broadcast input was dropped".to_string()),
+    };
+    if let Some(error) = &error {
+      log::warn!("broadcast send to {:?} failed: {}", member, error);
+    }
+    results.push(BroadcastSendResult { member, error });
+  }
+
+  Ok(results)
+}
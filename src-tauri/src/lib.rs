@@ -1,11 +1,18 @@
+mod broadcast;
 mod command;
 mod error;
+mod transfer_history;
 
 use log::LevelFilter;
 #[cfg(debug_assertions)]
 use tauri::Manager;
 
+use broadcast::{
+  broadcast_join, broadcast_leave, broadcast_members, broadcast_remove, broadcast_send,
+  watch_closed_members, BroadcastManager,
+};
 use command::{generate_key, open_url};
+use transfer_history::watch_finished_transfers;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
@@ -23,8 +30,20 @@ pub fn run() {
     )
     .plugin(tauri_plugin_data::init())
     .plugin(tauri_plugin_ssh::init())
-    .invoke_handler(tauri::generate_handler![generate_key, open_url])
+    .manage(BroadcastManager::default())
+    .invoke_handler(tauri::generate_handler![
+      generate_key,
+      open_url,
+      broadcast_join,
+      broadcast_leave,
+      broadcast_members,
+      broadcast_remove,
+      broadcast_send
+    ])
     .setup(|app| {
+      watch_closed_members(app.handle());
+      watch_finished_transfers(app.handle());
+
       #[cfg(debug_assertions)]
       {
         if let Some(window) = app.get_webview_window("main") {
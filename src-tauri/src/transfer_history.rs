@@ -0,0 +1,54 @@
+use tauri::{async_runtime, AppHandle, Listener, Runtime};
+use tauri_plugin_data::TransferHistoryBase;
+use tauri_plugin_ssh::{TransferKind, TransferQueueItem, TransferStatus};
+
+/// Records the transfers of the queue in the history of their host once they finish, so the
+/// history doesn't depend on a window listening to the queue.
+pub fn watch_finished_transfers<R: Runtime>(app_handle: &AppHandle<R>) {
+  let listener_app_handle = app_handle.clone();
+  app_handle.listen_any(tauri_plugin_ssh::TRANSFER_FINISH_EVENT, move |event| {
+    match serde_json::from_str::<TransferQueueItem>(event.payload()) {
+      Ok(item) => record_transfer(&listener_app_handle, item),
+      Err(err) => log::error!("invalid transfer finish event: {}", err),
+    }
+  });
+}
+
+fn record_transfer<R: Runtime>(app_handle: &AppHandle<R>, item: TransferQueueItem) {
+  // 取消的传输可以重试，只记录完成和失败的
+  let status = match item.status {
+    TransferStatus::Completed => "Completed",
+    TransferStatus::Failed => "Failed",
+    _ => return,
+  };
+  let Some(host_id) = item.host_id.and_then(|host_id| host_id.parse().ok()) else {
+    return;
+  };
+  let kind = match item.kind {
+    TransferKind::Upload => "Upload",
+    TransferKind::Download => "Download",
+  };
+
+  let finished_at = item.finished_at.unwrap_or(item.created_at);
+  let started_at = item.started_at.unwrap_or(item.created_at);
+  let transfer_history = TransferHistoryBase {
+    host_id,
+    kind: kind.to_string(),
+    status: status.to_string(),
+    local_filename: item.local_filename,
+    remote_filename: item.remote_filename,
+    size: item.total as i64,
+    error: item.error,
+    duration: finished_at.saturating_sub(started_at) as i64,
+    created_at: 0,
+  };
+
+  let app_handle = app_handle.clone();
+  async_runtime::spawn(async move {
+    if let Err(err) =
+      tauri_plugin_data::insert_transfer_history(&app_handle, transfer_history).await
+    {
+      log::error!("add transfer history error {:?}", err);
+    }
+  });
+}
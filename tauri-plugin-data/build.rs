@@ -23,6 +23,17 @@
   "add_port_forwarding",
   "update_port_forwarding",
   "delete_port_forwarding",
+  "get_triggers",
+  "add_trigger",
+  "update_trigger",
+  "delete_trigger",
+  "get_command_histories",
+  "add_command_history",
+  "delete_command_history",
+  "clear_command_histories",
+  "get_transfer_histories",
+  "add_transfer_history",
+  "clear_transfer_histories",
 ];
 
 fn main() {
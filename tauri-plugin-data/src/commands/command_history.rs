@@ -0,0 +1,272 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use futures::future::try_join_all;
+use sea_orm::{
+  ActiveModelTrait, ActiveValue, ColumnTrait, EntityTrait, QueryFilter, QueryOrder, QuerySelect,
+  sea_query::SimpleExpr,
+};
+use serde::{Deserialize, Serialize};
+use serde_with::{DisplayFromStr, serde_as};
+use tauri::{AppHandle, Runtime, State};
+
+use crate::{
+  commands::ModelConvert, crypto_manager::CryptoManager, data_manager::DataManager, entities,
+  error::DataResult,
+};
+
+/// Commands kept per host, older ones are removed when new ones are added
+const HISTORY_LIMIT: u64 = 1000;
+/// Commands returned when no limit is given
+const DEFAULT_LIMIT: usize = 100;
+
+#[serde_as]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandHistoryBase {
+  #[serde_as(as = "Option<DisplayFromStr>")]
+  #[serde(default)]
+  host_id: Option<i64>,
+  command: String,
+  cwd: Option<String>,
+  exit_code: Option<i32>,
+  duration: i64,
+  /// Set by the backend when the command is added
+  #[serde(default)]
+  created_at: i64,
+}
+
+impl ModelConvert for CommandHistoryBase {
+  type Model = entities::command_histories::Model;
+  type ActiveModel = entities::command_histories::ActiveModel;
+
+  async fn from_model<R: Runtime>(
+    crypto_manager: &State<'_, CryptoManager<R>>,
+    model: Self::Model,
+  ) -> DataResult<Self> {
+    let command = crypto_manager.decrypt(&model.command).await?;
+    let cwd = if let Some(cwd) = model.cwd {
+      let decrypted = crypto_manager.decrypt(&cwd).await?;
+      Some(String::from_utf8(decrypted)?)
+    } else {
+      None
+    };
+
+    Ok(CommandHistoryBase {
+      host_id: model.host_id,
+      command: String::from_utf8(command)?,
+      cwd,
+      exit_code: model.exit_code,
+      duration: model.duration,
+      created_at: model.created_at,
+    })
+  }
+
+  async fn into_active_model<R: Runtime>(
+    &self,
+    crypto_manager: &State<'_, CryptoManager<R>>,
+  ) -> DataResult<Self::ActiveModel> {
+    let command = crypto_manager.encrypt(self.command.as_bytes()).await?;
+    let cwd = if let Some(cwd) = &self.cwd {
+      Some(crypto_manager.encrypt(cwd.as_bytes()).await?)
+    } else {
+      None
+    };
+
+    let active_model = Self::ActiveModel {
+      host_id: ActiveValue::Set(self.host_id),
+      command: ActiveValue::Set(command),
+      cwd: ActiveValue::Set(cwd),
+      exit_code: ActiveValue::Set(self.exit_code),
+      duration: ActiveValue::Set(self.duration),
+      created_at: ActiveValue::Set(self.created_at),
+      ..Default::default()
+    };
+
+    Ok(active_model)
+  }
+}
+
+#[serde_as]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandHistory {
+  #[serde_as(as = "DisplayFromStr")]
+  pub id: i64,
+  #[serde(flatten)]
+  pub base: CommandHistoryBase,
+}
+
+impl ModelConvert for CommandHistory {
+  type Model = entities::command_histories::Model;
+  type ActiveModel = entities::command_histories::ActiveModel;
+
+  async fn from_model<R: Runtime>(
+    crypto_manager: &State<'_, CryptoManager<R>>,
+    model: Self::Model,
+  ) -> DataResult<CommandHistory> {
+    Ok(CommandHistory {
+      id: model.id,
+      base: CommandHistoryBase::from_model(crypto_manager, model).await?,
+    })
+  }
+
+  async fn into_active_model<R: Runtime>(
+    &self,
+    crypto_manager: &State<'_, CryptoManager<R>>,
+  ) -> DataResult<Self::ActiveModel> {
+    let mut active_model = self.base.into_active_model(crypto_manager).await?;
+    active_model.id = ActiveValue::unchanged(self.id);
+
+    Ok(active_model)
+  }
+}
+
+/// Selects the history of a host, or of the local terminals when the host is empty.
+#[serde_as]
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandHistoryFilter {
+  #[serde_as(as = "Option<DisplayFromStr>")]
+  #[serde(default)]
+  host_id: Option<i64>,
+}
+
+impl CommandHistoryFilter {
+  fn condition(&self) -> SimpleExpr {
+    match self.host_id {
+      Some(host_id) => entities::command_histories::Column::HostId.eq(host_id),
+      None => entities::command_histories::Column::HostId.is_null(),
+    }
+  }
+}
+
+/// Loads the history of every host, used when the data is encrypted again.
+pub(crate) async fn get_all_command_histories<R: Runtime>(
+  crypto_manager: &State<'_, CryptoManager<R>>,
+  data_manager: &State<'_, DataManager>,
+) -> DataResult<Vec<CommandHistory>> {
+  let models = entities::command_histories::Entity::find()
+    .all(&data_manager.database_connection)
+    .await?;
+
+  try_join_all(
+    models
+      .into_iter()
+      .map(|model| CommandHistory::from_model(crypto_manager, model)),
+  )
+  .await
+}
+
+/// Returns the newest commands first, the keyword is matched case insensitively.
+///
+/// Commands are encrypted, so the keyword is matched after decrypting them.
+#[tauri::command]
+pub async fn get_command_histories<R: Runtime>(
+  _app_handle: AppHandle<R>,
+  crypto_manager: State<'_, CryptoManager<R>>,
+  data_manager: State<'_, DataManager>,
+  filter: CommandHistoryFilter,
+  keyword: Option<String>,
+  limit: Option<usize>,
+) -> DataResult<Vec<CommandHistory>> {
+  let models = entities::command_histories::Entity::find()
+    .filter(filter.condition())
+    .order_by_desc(entities::command_histories::Column::Id)
+    .all(&data_manager.database_connection)
+    .await?;
+
+  let command_histories = try_join_all(
+    models
+      .into_iter()
+      .map(|model| CommandHistory::from_model(&crypto_manager, model)),
+  )
+  .await?;
+
+  let keyword = keyword
+    .map(|keyword| keyword.trim().to_lowercase())
+    .filter(|keyword| !keyword.is_empty());
+
+  Ok(
+    command_histories
+      .into_iter()
+      .filter(|command_history| {
+        keyword.as_ref().is_none_or(|keyword| {
+          command_history
+            .base
+            .command
+            .to_lowercase()
+            .contains(keyword)
+        })
+      })
+      .take(limit.unwrap_or(DEFAULT_LIMIT))
+      .collect(),
+  )
+}
+
+#[tauri::command]
+pub async fn add_command_history<R: Runtime>(
+  _app_handle: AppHandle<R>,
+  crypto_manager: State<'_, CryptoManager<R>>,
+  data_manager: State<'_, DataManager>,
+  mut command_history: CommandHistoryBase,
+) -> DataResult<CommandHistory> {
+  command_history.created_at = SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .map(|duration| duration.as_millis() as i64)
+    .unwrap_or_default();
+
+  let model = command_history
+    .into_active_model(&crypto_manager)
+    .await?
+    .insert(&data_manager.database_connection)
+    .await?;
+
+  let filter = CommandHistoryFilter {
+    host_id: command_history.host_id,
+  };
+  let expired = entities::command_histories::Entity::find()
+    .filter(filter.condition())
+    .order_by_desc(entities::command_histories::Column::Id)
+    .offset(HISTORY_LIMIT)
+    .one(&data_manager.database_connection)
+    .await?;
+  if let Some(expired) = expired {
+    entities::command_histories::Entity::delete_many()
+      .filter(filter.condition())
+      .filter(entities::command_histories::Column::Id.lte(expired.id))
+      .exec(&data_manager.database_connection)
+      .await?;
+  }
+
+  CommandHistory::from_model(&crypto_manager, model).await
+}
+
+#[tauri::command]
+pub async fn delete_command_history(
+  data_manager: State<'_, DataManager>,
+  command_history: CommandHistory,
+) -> DataResult<()> {
+  let active_model = entities::command_histories::ActiveModel {
+    id: ActiveValue::Unchanged(command_history.id),
+    ..Default::default()
+  };
+
+  active_model
+    .delete(&data_manager.database_connection)
+    .await?;
+
+  Ok(())
+}
+
+#[tauri::command]
+pub async fn clear_command_histories(
+  data_manager: State<'_, DataManager>,
+  filter: CommandHistoryFilter,
+) -> DataResult<()> {
+  entities::command_histories::Entity::delete_many()
+    .filter(filter.condition())
+    .exec(&data_manager.database_connection)
+    .await?;
+
+  Ok(())
+}
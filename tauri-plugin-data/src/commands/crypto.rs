@@ -7,9 +7,12 @@
 use crate::{
   commands::{
     ModelConvert,
+    command_history::{self, CommandHistory},
     host::{self, Host},
     key::{self, Key},
     port_forwarding::{self, PortForwarding},
+    transfer_history::{self, TransferHistory},
+    trigger::{self, Trigger},
   },
   crypto_manager::CryptoManager,
   data_manager::DataManager,
@@ -106,12 +109,16 @@ pub async fn load_crypto_by_biometric<R: Runtime>(
   unimplemented!();
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn update_database<R: Runtime>(
   crypto_manager: &State<'_, CryptoManager<R>>,
   data_manager: &State<'_, DataManager>,
   hosts: &Vec<Host>,
   keys: &Vec<Key>,
   port_forwardings: &Vec<PortForwarding>,
+  triggers: &Vec<Trigger>,
+  command_histories: &Vec<CommandHistory>,
+  transfer_histories: &Vec<TransferHistory>,
 ) -> DataResult<()> {
   let tx = data_manager.database_connection.begin().await?;
 
@@ -119,7 +126,7 @@ async fn update_database<R: Runtime>(
     host
       .into_active_model(crypto_manager)
       .await?
-      .update(&data_manager.database_connection)
+      .update(&tx)
       .await?;
   }
 
@@ -127,7 +134,7 @@ async fn update_database<R: Runtime>(
     key
       .into_active_model(crypto_manager)
       .await?
-      .update(&data_manager.database_connection)
+      .update(&tx)
       .await?;
   }
 
@@ -135,7 +142,31 @@ async fn update_database<R: Runtime>(
     port_forwarding
       .into_active_model(crypto_manager)
       .await?
-      .update(&data_manager.database_connection)
+      .update(&tx)
+      .await?;
+  }
+
+  for trigger in triggers {
+    trigger
+      .into_active_model(crypto_manager)
+      .await?
+      .update(&tx)
+      .await?;
+  }
+
+  for command_history in command_histories {
+    command_history
+      .into_active_model(crypto_manager)
+      .await?
+      .update(&tx)
+      .await?;
+  }
+
+  for transfer_history in transfer_histories {
+    transfer_history
+      .into_active_model(crypto_manager)
+      .await?
+      .update(&tx)
       .await?;
   }
 
@@ -175,6 +206,16 @@ pub async fn change_crypto_enable<R: Runtime>(
     data_manager.clone(),
   )
   .await?;
+  let triggers = trigger::get_triggers(
+    app_handle.clone(),
+    crypto_manager.clone(),
+    data_manager.clone(),
+  )
+  .await?;
+  let command_histories =
+    command_history::get_all_command_histories(&crypto_manager, &data_manager).await?;
+  let transfer_histories =
+    transfer_history::get_all_transfer_histories(&crypto_manager, &data_manager).await?;
 
   let old_crypto_enable = crypto_manager.is_enable_crypto();
   crypto_manager.set_enable_crypto(crypto_enable);
@@ -192,6 +233,9 @@ pub async fn change_crypto_enable<R: Runtime>(
     &hosts,
     &keys,
     &port_forwardings,
+    &triggers,
+    &command_histories,
+    &transfer_histories,
   )
   .await
   {
@@ -1,5 +1,7 @@
 use futures::future::try_join_all;
-use sea_orm::{ActiveModelTrait, ActiveValue, ColumnTrait, EntityTrait, QueryFilter};
+use sea_orm::{
+  ActiveModelTrait, ActiveValue, ColumnTrait, EntityTrait, QueryFilter, TransactionTrait,
+};
 use serde::{Deserialize, Serialize};
 use serde_with::{DisplayFromStr, serde_as};
 use tauri::{AppHandle, Runtime, State};
@@ -26,7 +28,10 @@ pub struct HostBase {
   #[serde_as(as = "Option<DisplayFromStr>")]
   key_id: Option<i64>,
   startup_command: Option<String>,
+  shell_command: Option<String>,
   terminal_type: Option<String>,
+  terminal_encoding: Option<String>,
+  url_forwarding: Option<entities::hosts::UrlForwardingMode>,
   envs: Option<Vec<entities::hosts::Env>>,
   #[serde_as(as = "Option<Vec<DisplayFromStr>>")]
   jump_host_ids: Option<Vec<i64>>,
@@ -60,7 +65,10 @@ async fn from_model<R: Runtime>(
       password,
       key_id: model.key_id,
       startup_command: model.startup_command,
+      shell_command: model.shell_command,
       terminal_type: model.terminal_type,
+      terminal_encoding: model.terminal_encoding,
+      url_forwarding: model.url_forwarding,
       envs: model.envs.map(|v| v.into()),
       jump_host_ids: model.jump_host_ids.map(|v| v.into()),
       terminal_settings: model.terminal_settings,
@@ -89,7 +97,10 @@ async fn into_active_model<R: Runtime>(
       password: ActiveValue::Set(password),
       key_id: ActiveValue::Set(self.key_id),
       startup_command: ActiveValue::Set(self.startup_command.clone()),
+      shell_command: ActiveValue::Set(self.shell_command.clone()),
       terminal_type: ActiveValue::Set(self.terminal_type.clone()),
+      terminal_encoding: ActiveValue::Set(self.terminal_encoding.clone()),
+      url_forwarding: ActiveValue::Set(self.url_forwarding.clone()),
       envs: ActiveValue::Set(self.envs.clone().map(|v| v.into())),
       jump_host_ids: ActiveValue::Set(self.jump_host_ids.clone().map(|v| v.into())),
       terminal_settings: ActiveValue::Set(self.terminal_settings.clone()),
@@ -216,14 +227,33 @@ pub async fn delete_host(data_manager: State<'_, DataManager>, host: Host) -> Da
     ));
   }
 
+  // 任一步失败时全部回滚，不会留下没有主机的记录
+  let tx = data_manager.database_connection.begin().await?;
+
+  // 触发器属于主机配置的一部分，随主机一起删除
+  entities::triggers::Entity::delete_many()
+    .filter(entities::triggers::Column::HostId.eq(host.id))
+    .exec(&tx)
+    .await?;
+
+  entities::command_histories::Entity::delete_many()
+    .filter(entities::command_histories::Column::HostId.eq(host.id))
+    .exec(&tx)
+    .await?;
+
+  entities::transfer_histories::Entity::delete_many()
+    .filter(entities::transfer_histories::Column::HostId.eq(host.id))
+    .exec(&tx)
+    .await?;
+
   let active_model = entities::hosts::ActiveModel {
     id: ActiveValue::Unchanged(host.id),
     ..Default::default()
   };
 
-  active_model
-    .delete(&data_manager.database_connection)
-    .await?;
+  active_model.delete(&tx).await?;
+
+  tx.commit().await?;
 
   Ok(())
 }
@@ -1,7 +1,10 @@
+pub mod command_history;
 pub mod crypto;
 pub mod host;
 pub mod key;
 pub mod port_forwarding;
+pub mod transfer_history;
+pub mod trigger;
 
 use tauri::{Runtime, State};
 
@@ -0,0 +1,240 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use futures::future::try_join_all;
+use sea_orm::{
+  ActiveModelTrait, ActiveValue, ColumnTrait, EntityTrait, QueryFilter, QueryOrder, QuerySelect,
+  sea_query::SimpleExpr,
+};
+use serde::{Deserialize, Serialize};
+use serde_with::{DisplayFromStr, serde_as};
+use tauri::{AppHandle, Manager, Runtime, State};
+
+use crate::{
+  commands::ModelConvert, crypto_manager::CryptoManager, data_manager::DataManager, entities,
+  error::DataResult,
+};
+
+/// Transfers kept per host, older ones are removed when new ones are added
+const HISTORY_LIMIT: u64 = 500;
+/// Transfers returned when no limit is given
+const DEFAULT_LIMIT: u64 = 100;
+
+#[serde_as]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferHistoryBase {
+  #[serde_as(as = "DisplayFromStr")]
+  pub host_id: i64,
+  pub kind: String,
+  pub status: String,
+  pub local_filename: String,
+  pub remote_filename: String,
+  pub size: i64,
+  pub error: Option<String>,
+  pub duration: i64,
+  /// Set by the backend when the transfer is added
+  #[serde(default)]
+  pub created_at: i64,
+}
+
+impl ModelConvert for TransferHistoryBase {
+  type Model = entities::transfer_histories::Model;
+  type ActiveModel = entities::transfer_histories::ActiveModel;
+
+  async fn from_model<R: Runtime>(
+    crypto_manager: &State<'_, CryptoManager<R>>,
+    model: Self::Model,
+  ) -> DataResult<Self> {
+    let local_filename = crypto_manager.decrypt(&model.local_filename).await?;
+    let remote_filename = crypto_manager.decrypt(&model.remote_filename).await?;
+
+    Ok(TransferHistoryBase {
+      host_id: model.host_id,
+      kind: model.kind,
+      status: model.status,
+      local_filename: String::from_utf8(local_filename)?,
+      remote_filename: String::from_utf8(remote_filename)?,
+      size: model.size,
+      error: model.error,
+      duration: model.duration,
+      created_at: model.created_at,
+    })
+  }
+
+  async fn into_active_model<R: Runtime>(
+    &self,
+    crypto_manager: &State<'_, CryptoManager<R>>,
+  ) -> DataResult<Self::ActiveModel> {
+    let local_filename = crypto_manager
+      .encrypt(self.local_filename.as_bytes())
+      .await?;
+    let remote_filename = crypto_manager
+      .encrypt(self.remote_filename.as_bytes())
+      .await?;
+
+    let active_model = Self::ActiveModel {
+      host_id: ActiveValue::Set(self.host_id),
+      kind: ActiveValue::Set(self.kind.clone()),
+      status: ActiveValue::Set(self.status.clone()),
+      local_filename: ActiveValue::Set(local_filename),
+      remote_filename: ActiveValue::Set(remote_filename),
+      size: ActiveValue::Set(self.size),
+      error: ActiveValue::Set(self.error.clone()),
+      duration: ActiveValue::Set(self.duration),
+      created_at: ActiveValue::Set(self.created_at),
+      ..Default::default()
+    };
+
+    Ok(active_model)
+  }
+}
+
+#[serde_as]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferHistory {
+  #[serde_as(as = "DisplayFromStr")]
+  pub id: i64,
+  #[serde(flatten)]
+  pub base: TransferHistoryBase,
+}
+
+impl ModelConvert for TransferHistory {
+  type Model = entities::transfer_histories::Model;
+  type ActiveModel = entities::transfer_histories::ActiveModel;
+
+  async fn from_model<R: Runtime>(
+    crypto_manager: &State<'_, CryptoManager<R>>,
+    model: Self::Model,
+  ) -> DataResult<TransferHistory> {
+    Ok(TransferHistory {
+      id: model.id,
+      base: TransferHistoryBase::from_model(crypto_manager, model).await?,
+    })
+  }
+
+  async fn into_active_model<R: Runtime>(
+    &self,
+    crypto_manager: &State<'_, CryptoManager<R>>,
+  ) -> DataResult<Self::ActiveModel> {
+    let mut active_model = self.base.into_active_model(crypto_manager).await?;
+    active_model.id = ActiveValue::unchanged(self.id);
+
+    Ok(active_model)
+  }
+}
+
+#[serde_as]
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferHistoryFilter {
+  #[serde_as(as = "DisplayFromStr")]
+  host_id: i64,
+}
+
+impl TransferHistoryFilter {
+  fn condition(&self) -> SimpleExpr {
+    entities::transfer_histories::Column::HostId.eq(self.host_id)
+  }
+}
+
+/// Loads the history of every host, used when the data is encrypted again.
+pub(crate) async fn get_all_transfer_histories<R: Runtime>(
+  crypto_manager: &State<'_, CryptoManager<R>>,
+  data_manager: &State<'_, DataManager>,
+) -> DataResult<Vec<TransferHistory>> {
+  let models = entities::transfer_histories::Entity::find()
+    .all(&data_manager.database_connection)
+    .await?;
+
+  try_join_all(
+    models
+      .into_iter()
+      .map(|model| TransferHistory::from_model(crypto_manager, model)),
+  )
+  .await
+}
+
+/// Returns the newest transfers of a host first.
+#[tauri::command]
+pub async fn get_transfer_histories<R: Runtime>(
+  _app_handle: AppHandle<R>,
+  crypto_manager: State<'_, CryptoManager<R>>,
+  data_manager: State<'_, DataManager>,
+  filter: TransferHistoryFilter,
+  limit: Option<u64>,
+) -> DataResult<Vec<TransferHistory>> {
+  let models = entities::transfer_histories::Entity::find()
+    .filter(filter.condition())
+    .order_by_desc(entities::transfer_histories::Column::Id)
+    .limit(limit.unwrap_or(DEFAULT_LIMIT))
+    .all(&data_manager.database_connection)
+    .await?;
+
+  try_join_all(
+    models
+      .into_iter()
+      .map(|model| TransferHistory::from_model(&crypto_manager, model)),
+  )
+  .await
+}
+
+#[tauri::command]
+pub async fn add_transfer_history<R: Runtime>(
+  app_handle: AppHandle<R>,
+  transfer_history: TransferHistoryBase,
+) -> DataResult<TransferHistory> {
+  insert_transfer_history(&app_handle, transfer_history).await
+}
+
+/// Adds a finished transfer to the history of its host, the transfer queue of the ssh plugin
+/// records its transfers through this.
+pub async fn insert_transfer_history<R: Runtime>(
+  app_handle: &AppHandle<R>,
+  mut transfer_history: TransferHistoryBase,
+) -> DataResult<TransferHistory> {
+  let crypto_manager = app_handle.state::<CryptoManager<R>>();
+  let data_manager = app_handle.state::<DataManager>();
+  transfer_history.created_at = SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .map(|duration| duration.as_millis() as i64)
+    .unwrap_or_default();
+
+  let model = transfer_history
+    .into_active_model(&crypto_manager)
+    .await?
+    .insert(&data_manager.database_connection)
+    .await?;
+
+  let filter = TransferHistoryFilter {
+    host_id: transfer_history.host_id,
+  };
+  let expired = entities::transfer_histories::Entity::find()
+    .filter(filter.condition())
+    .order_by_desc(entities::transfer_histories::Column::Id)
+    .offset(HISTORY_LIMIT)
+    .one(&data_manager.database_connection)
+    .await?;
+  if let Some(expired) = expired {
+    entities::transfer_histories::Entity::delete_many()
+      .filter(filter.condition())
+      .filter(entities::transfer_histories::Column::Id.lte(expired.id))
+      .exec(&data_manager.database_connection)
+      .await?;
+  }
+
+  TransferHistory::from_model(&crypto_manager, model).await
+}
+
+#[tauri::command]
+pub async fn clear_transfer_histories(
+  data_manager: State<'_, DataManager>,
+  filter: TransferHistoryFilter,
+) -> DataResult<()> {
+  entities::transfer_histories::Entity::delete_many()
+    .filter(filter.condition())
+    .exec(&data_manager.database_connection)
+    .await?;
+
+  Ok(())
+}
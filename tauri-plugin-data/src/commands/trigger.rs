@@ -0,0 +1,183 @@
+use futures::future::try_join_all;
+use sea_orm::{ActiveModelTrait, ActiveValue, EntityTrait};
+use serde::{Deserialize, Serialize};
+use serde_with::{DisplayFromStr, serde_as};
+use tauri::{AppHandle, Runtime, State};
+
+use crate::{
+  commands::ModelConvert, crypto_manager::CryptoManager, data_manager::DataManager, entities,
+  error::DataResult,
+};
+
+#[serde_as]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all_fields = "camelCase")]
+pub enum TriggerAction {
+  SendText {
+    text: String,
+  },
+  Notify {
+    message: Option<String>,
+  },
+  Highlight {
+    color: String,
+  },
+  PortForwarding {
+    #[serde_as(as = "DisplayFromStr")]
+    port_forwarding_id: i64,
+  },
+}
+
+#[serde_as]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TriggerBase {
+  name: String,
+  #[serde_as(as = "Option<DisplayFromStr>")]
+  host_id: Option<i64>,
+  pattern: String,
+  action: TriggerAction,
+  enabled: bool,
+}
+
+impl ModelConvert for TriggerBase {
+  type Model = entities::triggers::Model;
+  type ActiveModel = entities::triggers::ActiveModel;
+
+  async fn from_model<R: Runtime>(
+    crypto_manager: &State<'_, CryptoManager<R>>,
+    model: Self::Model,
+  ) -> DataResult<Self> {
+    let action = crypto_manager.decrypt(&model.action).await?;
+
+    Ok(TriggerBase {
+      name: model.name,
+      host_id: model.host_id,
+      pattern: model.pattern,
+      action: serde_json::from_slice(&action)?,
+      enabled: model.enabled,
+    })
+  }
+
+  async fn into_active_model<R: Runtime>(
+    &self,
+    crypto_manager: &State<'_, CryptoManager<R>>,
+  ) -> DataResult<Self::ActiveModel> {
+    let action = crypto_manager
+      .encrypt(&serde_json::to_vec(&self.action)?)
+      .await?;
+
+    let active_model = Self::ActiveModel {
+      name: ActiveValue::Set(self.name.clone()),
+      host_id: ActiveValue::Set(self.host_id),
+      pattern: ActiveValue::Set(self.pattern.clone()),
+      action: ActiveValue::Set(action),
+      enabled: ActiveValue::Set(self.enabled),
+      ..Default::default()
+    };
+
+    Ok(active_model)
+  }
+}
+
+#[serde_as]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Trigger {
+  #[serde_as(as = "DisplayFromStr")]
+  pub id: i64,
+  #[serde(flatten)]
+  pub base: TriggerBase,
+}
+
+impl ModelConvert for Trigger {
+  type Model = entities::triggers::Model;
+  type ActiveModel = entities::triggers::ActiveModel;
+
+  async fn from_model<R: Runtime>(
+    crypto_manager: &State<'_, CryptoManager<R>>,
+    model: Self::Model,
+  ) -> DataResult<Trigger> {
+    Ok(Trigger {
+      id: model.id,
+      base: TriggerBase::from_model(crypto_manager, model).await?,
+    })
+  }
+
+  async fn into_active_model<R: Runtime>(
+    &self,
+    crypto_manager: &State<'_, CryptoManager<R>>,
+  ) -> DataResult<Self::ActiveModel> {
+    let mut active_model = self.base.into_active_model(crypto_manager).await?;
+    active_model.id = ActiveValue::unchanged(self.id);
+
+    Ok(active_model)
+  }
+}
+
+#[tauri::command]
+pub async fn get_triggers<R: Runtime>(
+  _app_handle: AppHandle<R>,
+  crypto_manager: State<'_, CryptoManager<R>>,
+  data_manager: State<'_, DataManager>,
+) -> DataResult<Vec<Trigger>> {
+  let models = entities::triggers::Entity::find()
+    .all(&data_manager.database_connection)
+    .await?;
+
+  try_join_all(
+    models
+      .into_iter()
+      .map(|model| Trigger::from_model(&crypto_manager, model)),
+  )
+  .await
+}
+
+#[tauri::command]
+pub async fn add_trigger<R: Runtime>(
+  _app_handle: AppHandle<R>,
+  crypto_manager: State<'_, CryptoManager<R>>,
+  data_manager: State<'_, DataManager>,
+  trigger: TriggerBase,
+) -> DataResult<Trigger> {
+  let model = trigger
+    .into_active_model(&crypto_manager)
+    .await?
+    .insert(&data_manager.database_connection)
+    .await?;
+
+  Trigger::from_model(&crypto_manager, model).await
+}
+
+#[tauri::command]
+pub async fn update_trigger<R: Runtime>(
+  _app_handle: AppHandle<R>,
+  crypto_manager: State<'_, CryptoManager<R>>,
+  data_manager: State<'_, DataManager>,
+  trigger: Trigger,
+) -> DataResult<Trigger> {
+  let model = trigger
+    .into_active_model(&crypto_manager)
+    .await?
+    .update(&data_manager.database_connection)
+    .await?;
+
+  Trigger::from_model(&crypto_manager, model).await
+}
+
+#[tauri::command]
+pub async fn delete_trigger(
+  data_manager: State<'_, DataManager>,
+  trigger: Trigger,
+) -> DataResult<()> {
+  let active_model = entities::triggers::ActiveModel {
+    id: ActiveValue::Unchanged(trigger.id),
+    ..Default::default()
+  };
+
+  active_model
+    .delete(&data_manager.database_connection)
+    .await?;
+
+  Ok(())
+}
@@ -0,0 +1,38 @@
+use sea_orm::entity::prelude::*;
+use sea_orm_migration::async_trait::async_trait;
+
+use super::hosts;
+
+#[derive(Clone, Debug, DeriveEntityModel, PartialEq, Eq)]
+#[sea_orm(table_name = "command_histories")]
+pub struct Model {
+  #[sea_orm(primary_key)]
+  pub id: i64,
+  /// None for commands run in local terminals
+  pub host_id: Option<i64>,
+  /// Encrypted, commands may contain secrets
+  #[sea_orm(column_type = "Blob")]
+  pub command: Vec<u8>,
+  #[sea_orm(column_type = "Blob", nullable)]
+  pub cwd: Option<Vec<u8>>,
+  pub exit_code: Option<i32>,
+  /// Milliseconds the command ran
+  pub duration: i64,
+  /// Unix timestamp in milliseconds
+  pub created_at: i64,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+  #[sea_orm(has_one = "hosts::Entity")]
+  Host,
+}
+
+impl Related<hosts::Entity> for Entity {
+  fn to() -> RelationDef {
+    Relation::Host.def()
+  }
+}
+
+#[async_trait]
+impl ActiveModelBehavior for ActiveModel {}
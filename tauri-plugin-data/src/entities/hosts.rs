@@ -4,7 +4,7 @@
 use sea_orm_migration::async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 
-use super::{keys, port_forwardings};
+use super::{command_histories, keys, port_forwardings, transfer_histories, triggers};
 
 #[derive(Clone, Debug, FromJsonQueryResult, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -40,6 +40,16 @@ pub enum AuthenticationMethod {
   Certificate,
 }
 
+/// Forwarding of the localhost urls printed in the shell, disabled when empty
+#[derive(Clone, Debug, EnumIter, DeriveActiveEnum, PartialEq, Eq, Serialize, Deserialize)]
+#[sea_orm(rs_type = "i32", db_type = "Integer")]
+pub enum UrlForwardingMode {
+  #[sea_orm(num_value = 0)]
+  Prompt,
+  #[sea_orm(num_value = 1)]
+  Auto,
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Env {
@@ -118,7 +128,12 @@ pub struct Model {
   pub password: Option<Vec<u8>>,
   pub key_id: Option<i64>,
   pub startup_command: Option<String>,
+  /// Runs instead of the login shell, e.g. `tmux new -A -s main`
+  pub shell_command: Option<String>,
   pub terminal_type: Option<String>,
+  /// Label of a legacy encoding such as GBK, the terminal uses UTF-8 when empty
+  pub terminal_encoding: Option<String>,
+  pub url_forwarding: Option<UrlForwardingMode>,
   pub envs: Option<Envs>,
   pub jump_host_ids: Option<JumpHostIds>,
   pub terminal_settings: Option<TerminalSettings>,
@@ -134,6 +149,24 @@ pub enum Relation {
     to = "port_forwardings::Column::HostId"
   )]
   PortForwardings,
+  #[sea_orm(
+    belongs_to = "triggers::Entity",
+    from = "Column::Id",
+    to = "triggers::Column::HostId"
+  )]
+  Triggers,
+  #[sea_orm(
+    belongs_to = "command_histories::Entity",
+    from = "Column::Id",
+    to = "command_histories::Column::HostId"
+  )]
+  CommandHistories,
+  #[sea_orm(
+    belongs_to = "transfer_histories::Entity",
+    from = "Column::Id",
+    to = "transfer_histories::Column::HostId"
+  )]
+  TransferHistories,
 }
 
 impl Related<keys::Entity> for Entity {
@@ -148,5 +181,23 @@ fn to() -> RelationDef {
   }
 }
 
+impl Related<triggers::Entity> for Entity {
+  fn to() -> RelationDef {
+    Relation::Triggers.def()
+  }
+}
+
+impl Related<command_histories::Entity> for Entity {
+  fn to() -> RelationDef {
+    Relation::CommandHistories.def()
+  }
+}
+
+impl Related<transfer_histories::Entity> for Entity {
+  fn to() -> RelationDef {
+    Relation::TransferHistories.def()
+  }
+}
+
 #[async_trait]
 impl ActiveModelBehavior for ActiveModel {}
@@ -1,3 +1,6 @@
+pub mod command_histories;
 pub mod hosts;
 pub mod keys;
 pub mod port_forwardings;
+pub mod transfer_histories;
+pub mod triggers;
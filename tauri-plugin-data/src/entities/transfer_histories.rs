@@ -0,0 +1,43 @@
+use sea_orm::entity::prelude::*;
+use sea_orm_migration::async_trait::async_trait;
+
+use super::hosts;
+
+#[derive(Clone, Debug, DeriveEntityModel, PartialEq, Eq)]
+#[sea_orm(table_name = "transfer_histories")]
+pub struct Model {
+  #[sea_orm(primary_key)]
+  pub id: i64,
+  pub host_id: i64,
+  /// `Upload` or `Download`
+  pub kind: String,
+  /// `Completed`, `Failed` or `Cancelled`
+  pub status: String,
+  /// Encrypted like the working directories of the command history
+  #[sea_orm(column_type = "Blob")]
+  pub local_filename: Vec<u8>,
+  #[sea_orm(column_type = "Blob")]
+  pub remote_filename: Vec<u8>,
+  /// Bytes of the file
+  pub size: i64,
+  pub error: Option<String>,
+  /// Milliseconds the transfer ran
+  pub duration: i64,
+  /// Unix timestamp in milliseconds
+  pub created_at: i64,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+  #[sea_orm(has_one = "hosts::Entity")]
+  Host,
+}
+
+impl Related<hosts::Entity> for Entity {
+  fn to() -> RelationDef {
+    Relation::Host.def()
+  }
+}
+
+#[async_trait]
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,34 @@
+use sea_orm::entity::prelude::*;
+use sea_orm_migration::async_trait::async_trait;
+
+use super::hosts;
+
+#[derive(Clone, Debug, DeriveEntityModel, PartialEq, Eq)]
+#[sea_orm(table_name = "triggers")]
+pub struct Model {
+  #[sea_orm(primary_key)]
+  pub id: i64,
+  pub name: String,
+  /// None applies the trigger to every terminal, including local ones
+  pub host_id: Option<i64>,
+  pub pattern: String,
+  /// Encrypted json of the action, the text to send may contain secrets
+  #[sea_orm(column_type = "Blob")]
+  pub action: Vec<u8>,
+  pub enabled: bool,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+  #[sea_orm(has_one = "hosts::Entity")]
+  Host,
+}
+
+impl Related<hosts::Entity> for Entity {
+  fn to() -> RelationDef {
+    Relation::Host.def()
+  }
+}
+
+#[async_trait]
+impl ActiveModelBehavior for ActiveModel {}
@@ -12,12 +12,16 @@
 };
 
 use crate::{
-  commands::{crypto, host, key, port_forwarding},
+  commands::{command_history, crypto, host, key, port_forwarding, transfer_history, trigger},
   crypto_manager::CryptoManager,
   data_manager::DataManager,
-  error::DataError,
 };
 
+pub use commands::transfer_history::{
+  TransferHistory, TransferHistoryBase, insert_transfer_history,
+};
+pub use error::{DataError, DataResult};
+
 /// Initializes the plugin.
 pub fn init<R: Runtime>() -> TauriPlugin<R> {
   Builder::new("data")
@@ -46,6 +50,17 @@ pub fn init<R: Runtime>() -> TauriPlugin<R> {
       port_forwarding::add_port_forwarding,
       port_forwarding::update_port_forwarding,
       port_forwarding::delete_port_forwarding,
+      trigger::get_triggers,
+      trigger::add_trigger,
+      trigger::update_trigger,
+      trigger::delete_trigger,
+      command_history::get_command_histories,
+      command_history::add_command_history,
+      command_history::delete_command_history,
+      command_history::clear_command_histories,
+      transfer_history::get_transfer_histories,
+      transfer_history::add_transfer_history,
+      transfer_history::clear_transfer_histories,
     ])
     .setup(|app, _api| {
       async_runtime::block_on(async {
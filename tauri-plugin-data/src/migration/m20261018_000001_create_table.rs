@@ -0,0 +1,52 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .create_table(
+        Table::create()
+          .table(Triggers::Table)
+          .if_not_exists()
+          .col(pk_auto(Triggers::Id))
+          .col(string(Triggers::Name))
+          .col(integer_null(Triggers::HostId))
+          .foreign_key(
+            ForeignKey::create()
+              .from(Triggers::Table, Triggers::HostId)
+              .to(Hosts::Table, Hosts::Id),
+          )
+          .col(string(Triggers::Pattern))
+          .col(blob(Triggers::Action))
+          .col(boolean(Triggers::Enabled).default(true))
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .drop_table(Table::drop().table(Triggers::Table).to_owned())
+      .await
+  }
+}
+
+#[derive(DeriveIden)]
+enum Hosts {
+  Table,
+  Id,
+}
+
+#[derive(DeriveIden)]
+enum Triggers {
+  Table,
+  Id,
+  Name,
+  HostId,
+  Pattern,
+  Action,
+  Enabled,
+}
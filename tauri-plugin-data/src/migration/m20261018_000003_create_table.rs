@@ -0,0 +1,64 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .create_table(
+        Table::create()
+          .table(CommandHistories::Table)
+          .if_not_exists()
+          .col(pk_auto(CommandHistories::Id))
+          .col(integer_null(CommandHistories::HostId))
+          .foreign_key(
+            ForeignKey::create()
+              .from(CommandHistories::Table, CommandHistories::HostId)
+              .to(Hosts::Table, Hosts::Id),
+          )
+          .col(blob(CommandHistories::Command))
+          .col(blob_null(CommandHistories::Cwd))
+          .col(integer_null(CommandHistories::ExitCode))
+          .col(big_integer(CommandHistories::Duration))
+          .col(big_integer(CommandHistories::CreatedAt))
+          .to_owned(),
+      )
+      .await?;
+
+    manager
+      .create_index(
+        Index::create()
+          .name("idx_command_histories_host_id")
+          .table(CommandHistories::Table)
+          .col(CommandHistories::HostId)
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .drop_table(Table::drop().table(CommandHistories::Table).to_owned())
+      .await
+  }
+}
+
+#[derive(DeriveIden)]
+enum Hosts {
+  Table,
+  Id,
+}
+
+#[derive(DeriveIden)]
+enum CommandHistories {
+  Table,
+  Id,
+  HostId,
+  Command,
+  Cwd,
+  ExitCode,
+  Duration,
+  CreatedAt,
+}
@@ -0,0 +1,70 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .create_table(
+        Table::create()
+          .table(TransferHistories::Table)
+          .if_not_exists()
+          .col(pk_auto(TransferHistories::Id))
+          .col(integer(TransferHistories::HostId))
+          .foreign_key(
+            ForeignKey::create()
+              .from(TransferHistories::Table, TransferHistories::HostId)
+              .to(Hosts::Table, Hosts::Id),
+          )
+          .col(string(TransferHistories::Kind))
+          .col(string(TransferHistories::Status))
+          .col(blob(TransferHistories::LocalFilename))
+          .col(blob(TransferHistories::RemoteFilename))
+          .col(big_integer(TransferHistories::Size))
+          .col(string_null(TransferHistories::Error))
+          .col(big_integer(TransferHistories::Duration))
+          .col(big_integer(TransferHistories::CreatedAt))
+          .to_owned(),
+      )
+      .await?;
+
+    manager
+      .create_index(
+        Index::create()
+          .name("idx_transfer_histories_host_id")
+          .table(TransferHistories::Table)
+          .col(TransferHistories::HostId)
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .drop_table(Table::drop().table(TransferHistories::Table).to_owned())
+      .await
+  }
+}
+
+#[derive(DeriveIden)]
+enum Hosts {
+  Table,
+  Id,
+}
+
+#[derive(DeriveIden)]
+enum TransferHistories {
+  Table,
+  Id,
+  HostId,
+  Kind,
+  Status,
+  LocalFilename,
+  RemoteFilename,
+  Size,
+  Error,
+  Duration,
+  CreatedAt,
+}
@@ -2,6 +2,12 @@
 mod m20251021_000001_alter_table;
 mod m20251024_000001_alter_table;
 mod m20251027_000001_alter_table;
+mod m20261018_000001_create_table;
+mod m20261018_000002_alter_table;
+mod m20261018_000003_create_table;
+mod m20261018_000004_alter_table;
+mod m20261018_000005_alter_table;
+mod m20261018_000006_create_table;
 
 pub use sea_orm_migration::prelude::*;
 
@@ -15,6 +21,12 @@ fn migrations() -> Vec<Box<dyn MigrationTrait>> {
       Box::new(m20251021_000001_alter_table::Migration),
       Box::new(m20251024_000001_alter_table::Migration),
       Box::new(m20251027_000001_alter_table::Migration),
+      Box::new(m20261018_000001_create_table::Migration),
+      Box::new(m20261018_000002_alter_table::Migration),
+      Box::new(m20261018_000003_create_table::Migration),
+      Box::new(m20261018_000004_alter_table::Migration),
+      Box::new(m20261018_000005_alter_table::Migration),
+      Box::new(m20261018_000006_create_table::Migration),
     ]
   }
 }
@@ -0,0 +1,66 @@
+import { invoke } from "@tauri-apps/api/core";
+
+export interface CommandHistory {
+  id: string;
+  /**
+   * 为空表示本地终端执行的命令
+   */
+  hostId?: string;
+  command: string;
+  cwd?: string;
+  exitCode?: number;
+  /**
+   * 命令执行的毫秒数
+   */
+  duration: number;
+  /**
+   * 毫秒时间戳，由后端在添加时设置
+   */
+  createdAt: number;
+}
+
+export type CommandHistoryQuery = {
+  hostId?: string;
+  /**
+   * 按命令内容搜索，不区分大小写
+   */
+  keyword?: string;
+  limit?: number;
+};
+
+/**
+ * 按时间倒序返回指定主机的命令历史，hostId 为空时返回本地终端的历史
+ */
+export function getCommandHistories({
+  hostId,
+  keyword,
+  limit,
+}: CommandHistoryQuery = {}): Promise<CommandHistory[]> {
+  return invoke<CommandHistory[]>("plugin:data|get_command_histories", {
+    filter: { hostId },
+    keyword,
+    limit,
+  });
+}
+
+export function addCommandHistory(
+  commandHistory: Omit<CommandHistory, "id" | "createdAt">,
+): Promise<CommandHistory> {
+  return invoke<CommandHistory>("plugin:data|add_command_history", {
+    commandHistory,
+  });
+}
+
+export function deleteCommandHistory(
+  commandHistory: CommandHistory,
+): Promise<null> {
+  return invoke<null>("plugin:data|delete_command_history", {
+    commandHistory,
+  });
+}
+
+export function clearCommandHistories(hostId?: string): Promise<null> {
+  return invoke<null>("plugin:data|clear_command_histories", {
+    filter: { hostId },
+  });
+}
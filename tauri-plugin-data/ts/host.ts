@@ -12,6 +12,14 @@ export enum AuthenticationMethod {
   Certificate = "Certificate",
 }
 
+/**
+ * 终端输出 localhost 地址时的处理方式，Prompt 询问后转发，Auto 直接转发
+ */
+export enum UrlForwardingMode {
+  Prompt = "Prompt",
+  Auto = "Auto",
+}
+
 export interface Env {
   key: string;
   value: string;
@@ -28,7 +36,19 @@ export interface Host {
   password?: string;
   keyId?: string;
   startupCommand?: string;
+  /**
+   * 代替登录 shell 运行的命令，命令退出后终端关闭
+   */
+  shellCommand?: string;
   terminalType?: string;
+  /**
+   * 终端使用的旧编码，如 GBK、Big5、Shift_JIS，为空时使用 UTF-8
+   */
+  terminalEncoding?: string;
+  /**
+   * 为空时不检测终端输出中的地址
+   */
+  urlForwarding?: UrlForwardingMode;
   envs?: Env[];
   /**
    * 完整链路（强顺序链），jumpHostIds 中的主机必须按顺序连接
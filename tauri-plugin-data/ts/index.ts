@@ -1,4 +1,7 @@
+export * from "./commandHistory";
 export * from "./crypto";
 export * from "./host";
 export * from "./key";
 export * from "./portForwarding";
+export * from "./transferHistory";
+export * from "./trigger";
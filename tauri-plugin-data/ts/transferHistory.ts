@@ -0,0 +1,50 @@
+import { invoke } from "@tauri-apps/api/core";
+
+export interface TransferHistory {
+  id: string;
+  hostId: string;
+  kind: "Upload" | "Download";
+  status: "Completed" | "Failed" | "Cancelled";
+  localFilename: string;
+  remoteFilename: string;
+  /**
+   * 文件字节数
+   */
+  size: number;
+  error?: string;
+  /**
+   * 传输耗费的毫秒数
+   */
+  duration: number;
+  /**
+   * 毫秒时间戳，由后端在添加时设置
+   */
+  createdAt: number;
+}
+
+/**
+ * 按时间倒序返回指定主机的传输历史
+ */
+export function getTransferHistories(
+  hostId: string,
+  limit?: number,
+): Promise<TransferHistory[]> {
+  return invoke<TransferHistory[]>("plugin:data|get_transfer_histories", {
+    filter: { hostId },
+    limit,
+  });
+}
+
+export function addTransferHistory(
+  transferHistory: Omit<TransferHistory, "id" | "createdAt">,
+): Promise<TransferHistory> {
+  return invoke<TransferHistory>("plugin:data|add_transfer_history", {
+    transferHistory,
+  });
+}
+
+export function clearTransferHistories(hostId: string): Promise<null> {
+  return invoke<null>("plugin:data|clear_transfer_histories", {
+    filter: { hostId },
+  });
+}
@@ -0,0 +1,41 @@
+import { invoke } from "@tauri-apps/api/core";
+
+export type TriggerAction =
+  | { type: "SendText"; text: string }
+  | { type: "Notify"; message?: string }
+  | { type: "Highlight"; color: string }
+  | { type: "PortForwarding"; portForwardingId: string };
+
+export interface Trigger {
+  id: string;
+  name: string;
+  /**
+   * 为空时对所有终端生效，包括本地终端
+   */
+  hostId?: string;
+  pattern: string;
+  action: TriggerAction;
+  enabled: boolean;
+}
+
+export async function getTriggers(): Promise<Trigger[]> {
+  return invoke<Trigger[]>("plugin:data|get_triggers");
+}
+
+export function addTrigger(trigger: Omit<Trigger, "id">): Promise<Trigger> {
+  return invoke<Trigger>("plugin:data|add_trigger", {
+    trigger,
+  });
+}
+
+export function updateTrigger(trigger: Trigger): Promise<Trigger> {
+  return invoke<Trigger>("plugin:data|update_trigger", {
+    trigger,
+  });
+}
+
+export function deleteTrigger(trigger: Trigger): Promise<null> {
+  return invoke<null>("plugin:data|delete_trigger", {
+    trigger,
+  });
+}
@@ -1,4 +1,16 @@
-const COMMANDS: &[&str] = &["shell_open", "shell_close", "shell_resize", "shell_send"];
+const COMMANDS: &[&str] = &[
+  "shell_open",
+  "shell_close",
+  "shell_resize",
+  "shell_send",
+  "shell_ack",
+  "shell_attach",
+  "shell_detach",
+  "shell_set_triggers",
+  "shell_screen",
+  "shell_screen_search",
+  "shell_screen_export",
+];
 
 fn main() {
   tauri_plugin::Builder::new(COMMANDS).build();
@@ -1,22 +1,37 @@
 use portable_pty::{NativePtySystem, PtySize, PtySystem};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use std::borrow::Cow;
 use std::io::{Read, Write};
-use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
 use tauri::async_runtime;
 use tauri::{
-  AppHandle, Manager, Runtime, State,
+  AppHandle, Emitter, Manager, Runtime, State,
   ipc::{Channel, InvokeResponseBody, IpcResponse},
 };
 
+use terminal_core::{
+  encoding::{TerminalEncoding, decode_output, encode_input},
+  screen::{
+    ScreenExportFormat, ScreenSearchMatch, ScreenSearchOpts, ScreenSnapshot, TerminalScreen,
+  },
+  shell_integration::{ShellIntegration, ShellIntegrationEvent},
+  trigger::{TriggerAction, TriggerEngine, TriggerEvent, TriggerRule},
+};
+
 use crate::{
   error::{PtyError, PtyResult},
+  pty_manager::ShellWriter,
   pty_manager::{PtyManager, ShellInstance},
+  shell_output::ShellOutput,
   utils::{build_shell_command, detect_shell},
 };
 
-type ShellId = String;
+pub type ShellId = String;
+
+/// Emitted with the shell id once a shell has exited or was closed
+pub const SHELL_CLOSE_EVENT: &str = "pty://shell_close";
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ShellSize {
@@ -30,6 +45,8 @@ pub struct ShellSize {
 pub enum PtyIpcEvent {
   Data(Vec<u8>),
   Exit { code: Option<u32> },
+  Trigger(TriggerEvent),
+  ShellIntegration(ShellIntegrationEvent),
 }
 
 impl IpcResponse for PtyIpcEvent {
@@ -39,6 +56,12 @@ fn body(self) -> tauri::Result<InvokeResponseBody> {
       PtyIpcEvent::Exit { code } => Ok(InvokeResponseBody::Json(
         json!({"type": "Exit", "code": code}).to_string(),
       )),
+      PtyIpcEvent::Trigger(event) => Ok(InvokeResponseBody::Json(
+        json!({"type": "Trigger", "event": event}).to_string(),
+      )),
+      PtyIpcEvent::ShellIntegration(event) => Ok(InvokeResponseBody::Json(
+        json!({"type": "ShellIntegration", "event": event}).to_string(),
+      )),
     }
   }
 }
@@ -48,14 +71,27 @@ fn u32_to_u16(val: u32) -> u16 {
 }
 
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn shell_open<R: Runtime>(
   shell_id: ShellId,
   ipc_channel: Channel<PtyIpcEvent>,
   size: ShellSize,
   shell: Option<String>,
+  triggers: Option<Vec<TriggerRule>>,
+  encoding: Option<String>,
   app: AppHandle<R>,
   pty_manager: State<'_, PtyManager>,
 ) -> PtyResult<ShellId> {
+  let encoding = encoding
+    .as_deref()
+    .map(TerminalEncoding::new)
+    .transpose()?
+    .flatten()
+    .map(Arc::new);
+  let trigger_engine = Arc::new(Mutex::new(TriggerEngine::new(
+    triggers.unwrap_or_default(),
+  )?));
+  let screen = Arc::new(Mutex::new(TerminalScreen::new(size.row, size.col)));
   let pty_system = NativePtySystem::default();
 
   let pty_size = PtySize {
@@ -70,7 +106,12 @@ pub async fn shell_open<R: Runtime>(
     .map_err(|e| PtyError::new(e.to_string()))?;
 
   let shell_cmd = shell.unwrap_or_else(detect_shell);
-  let cmd = build_shell_command(&shell_cmd);
+  let mut cmd = build_shell_command(&shell_cmd);
+  // 本地的区域设置是 UTF-8，使用旧编码时改为对应的区域设置
+  if let Some(locale) = encoding.as_ref().and_then(|encoding| encoding.locale()) {
+    cmd.env("LANG", locale);
+    cmd.env("LC_ALL", locale);
+  }
   let mut child = pair
     .slave
     .spawn_command(cmd)
@@ -95,12 +136,17 @@ pub async fn shell_open<R: Runtime>(
   let cleanup_started = Arc::new(AtomicBool::new(false));
   let writer = Arc::new(std::sync::Mutex::new(writer));
   let killer = Arc::new(std::sync::Mutex::new(child.clone_killer()));
+  let output = Arc::new(ShellOutput::new(shell_id.clone(), ipc_channel));
 
   let instance = ShellInstance {
     master: pair.master,
     writer: Arc::clone(&writer),
     killer: Arc::clone(&killer),
     cleanup_started: Arc::clone(&cleanup_started),
+    output: Arc::clone(&output),
+    trigger_engine: Arc::clone(&trigger_engine),
+    screen: Arc::clone(&screen),
+    encoding: encoding.clone(),
   };
 
   let existing = {
@@ -112,36 +158,66 @@ pub async fn shell_open<R: Runtime>(
   };
   if let Some(existing) = existing {
     existing.kill()?;
+    existing.output.close()?;
   }
 
-  let reader_channel = ipc_channel.clone();
+  let reader_output = Arc::clone(&output);
   let reader_shell_id = shell_id.clone();
   let reader_app = app.clone();
   let reader_cleanup = Arc::clone(&cleanup_started);
   let reader_killer = Arc::clone(&killer);
+  let reader_writer = Arc::clone(&writer);
 
   async_runtime::spawn_blocking(move || {
     let mut buf = [0u8; 65536];
     let mut reader: Box<dyn Read + Send> = reader;
+    let mut shell_integration = ShellIntegration::default();
 
     loop {
       if reader_cleanup.load(Ordering::Relaxed) {
         break;
       }
 
+      // webview 处理不过来时暂停读取，由 pty 缓冲区阻塞子进程
+      if let Err(e) = reader_output.wait_writable() {
+        log::error!("pty shell {} wait writable error: {}", reader_shell_id, e);
+      }
+
       match reader.read(&mut buf) {
         Ok(0) => {
           log::info!("pty shell {} EOF", reader_shell_id);
           break;
         }
         Ok(n) => {
-          let data = buf[..n].to_vec();
-          if reader_channel.send(PtyIpcEvent::Data(data)).is_err() {
+          let data = decode_output(encoding.as_deref(), &buf[..n]).unwrap_or_else(|e| {
+            log::error!("pty shell {} decode error: {}", reader_shell_id, e);
+            Cow::Borrowed(&buf[..n])
+          });
+          if reader_output.write(&data).is_err() {
             log::info!("pty shell {} ipc channel closed", reader_shell_id);
             kill_shell(&reader_killer);
             cleanup_shell(&reader_app, &reader_shell_id, &reader_cleanup);
             break;
           }
+          if let Err(e) = run_triggers(
+            &trigger_engine,
+            &screen,
+            &reader_writer,
+            &reader_output,
+            encoding.as_deref(),
+            &data,
+          ) {
+            log::error!("pty shell {} trigger error: {}", reader_shell_id, e);
+          }
+          for event in shell_integration.feed(&data) {
+            if let Err(e) = reader_output.send(PtyIpcEvent::ShellIntegration(event)) {
+              log::error!(
+                "pty shell {} shell integration error: {}",
+                reader_shell_id,
+                e
+              );
+            }
+          }
         }
         Err(e) => {
           log::error!("pty shell {} read error: {}", reader_shell_id, e);
@@ -153,7 +229,7 @@ pub async fn shell_open<R: Runtime>(
     }
   });
 
-  let wait_channel = ipc_channel.clone();
+  let wait_output = Arc::clone(&output);
   let wait_shell_id = shell_id.clone();
   let wait_app = app.clone();
   let wait_cleanup = Arc::clone(&cleanup_started);
@@ -170,8 +246,9 @@ pub async fn shell_open<R: Runtime>(
       }
     };
 
+    let _ = wait_output.close();
     if cleanup_shell(&wait_app, &wait_shell_id, &wait_cleanup) {
-      let _ = wait_channel.send(PtyIpcEvent::Exit { code });
+      let _ = wait_output.send(PtyIpcEvent::Exit { code });
     }
   });
 
@@ -185,18 +262,30 @@ pub async fn shell_send<R: Runtime>(
   _app: AppHandle<R>,
   pty_manager: State<'_, PtyManager>,
 ) -> PtyResult<()> {
-  let writer = {
+  write_pty(&pty_manager, &shell_id, &data)
+}
+
+/// Writes data to an opened shell, used by the app to send input outside of the webview.
+pub fn write_shell<R: Runtime>(app: &AppHandle<R>, shell_id: &str, data: &[u8]) -> PtyResult<()> {
+  write_pty(&app.state::<PtyManager>(), shell_id, data)
+}
+
+fn write_pty(pty_manager: &PtyManager, shell_id: &str, data: &[u8]) -> PtyResult<()> {
+  let shell = {
     let shells = pty_manager
       .shells
       .lock()
       .map_err(|e| PtyError::new(e.to_string()))?;
-    shells.get(&shell_id).map(|shell| Arc::clone(&shell.writer))
+    shells
+      .get(shell_id)
+      .map(|shell| (Arc::clone(&shell.writer), shell.encoding.clone()))
   };
 
-  let Some(writer) = writer else {
+  let Some((writer, encoding)) = shell else {
     return Err(PtyError::new("Shell already closed"));
   };
 
+  let data = encode_input(encoding.as_deref(), data)?;
   let mut writer = writer.lock().map_err(|e| PtyError::new(e.to_string()))?;
   writer.write_all(&data)?;
   writer.flush()?;
@@ -204,6 +293,66 @@ pub async fn shell_send<R: Runtime>(
   Ok(())
 }
 
+#[tauri::command]
+pub async fn shell_ack<R: Runtime>(
+  shell_id: ShellId,
+  size: usize,
+  _app: AppHandle<R>,
+  pty_manager: State<'_, PtyManager>,
+) -> PtyResult<()> {
+  let output = {
+    let shells = pty_manager
+      .shells
+      .lock()
+      .map_err(|e| PtyError::new(e.to_string()))?;
+    shells.get(&shell_id).map(|shell| Arc::clone(&shell.output))
+  };
+
+  if let Some(output) = output {
+    output.ack(size)?;
+  }
+
+  Ok(())
+}
+
+fn get_shell_output(pty_manager: &PtyManager, shell_id: &str) -> PtyResult<Arc<ShellOutput>> {
+  let shells = pty_manager
+    .shells
+    .lock()
+    .map_err(|e| PtyError::new(e.to_string()))?;
+  shells
+    .get(shell_id)
+    .map(|shell| Arc::clone(&shell.output))
+    .ok_or_else(|| PtyError::new("Shell already closed"))
+}
+
+/// Attaches a new channel to a running shell, the recent output is replayed first.
+#[tauri::command]
+pub async fn shell_attach<R: Runtime>(
+  shell_id: ShellId,
+  ipc_channel: Channel<PtyIpcEvent>,
+  _app: AppHandle<R>,
+  pty_manager: State<'_, PtyManager>,
+) -> PtyResult<ShellId> {
+  log::info!("pty shell {} attach", shell_id);
+  get_shell_output(&pty_manager, &shell_id)?.attach(ipc_channel)?;
+
+  Ok(shell_id)
+}
+
+/// Keeps the shell running without a channel, e.g. while the webview reloads.
+#[tauri::command]
+pub async fn shell_detach<R: Runtime>(
+  shell_id: ShellId,
+  _app: AppHandle<R>,
+  pty_manager: State<'_, PtyManager>,
+) -> PtyResult<ShellId> {
+  log::info!("pty shell {} detach", shell_id);
+  get_shell_output(&pty_manager, &shell_id)?.detach()?;
+
+  Ok(shell_id)
+}
+
 #[tauri::command]
 pub async fn shell_resize<R: Runtime>(
   shell_id: ShellId,
@@ -226,6 +375,7 @@ pub async fn shell_resize<R: Runtime>(
       .master
       .resize(pty_size)
       .map_err(|e| PtyError::new(e.to_string()))?;
+    shell.screen.lock()?.resize(size.row, size.col);
   }
   Ok(())
 }
@@ -233,7 +383,7 @@ pub async fn shell_resize<R: Runtime>(
 #[tauri::command]
 pub async fn shell_close<R: Runtime>(
   shell_id: ShellId,
-  _app: AppHandle<R>,
+  app: AppHandle<R>,
   pty_manager: State<'_, PtyManager>,
 ) -> PtyResult<()> {
   let shell = {
@@ -245,18 +395,57 @@ pub async fn shell_close<R: Runtime>(
       (
         Arc::clone(&shell.killer),
         Arc::clone(&shell.cleanup_started),
+        Arc::clone(&shell.output),
       )
     })
   };
 
-  if let Some((killer, cleanup_started)) = shell {
+  if let Some((killer, cleanup_started, output)) = shell {
     kill_shell_result(&killer)?;
     cleanup_started.store(true, Ordering::SeqCst);
+    output.close()?;
     pty_manager
       .shells
       .lock()
       .map_err(|e| PtyError::new(e.to_string()))?
       .remove(&shell_id);
+    app.emit(SHELL_CLOSE_EVENT, &shell_id)?;
+  }
+
+  Ok(())
+}
+
+/// Feeds the output to the screen and the trigger engine, triggers match the rendered lines.
+/// Text is written to the pty right away and other actions are sent to the webview.
+fn run_triggers(
+  trigger_engine: &Mutex<TriggerEngine>,
+  screen: &Mutex<TerminalScreen>,
+  writer: &ShellWriter,
+  output: &ShellOutput,
+  encoding: Option<&TerminalEncoding>,
+  data: &[u8],
+) -> PtyResult<()> {
+  let events = {
+    let mut trigger_engine = trigger_engine.lock()?;
+    let mut screen = screen.lock()?;
+    // 没有触发器时不必渲染输出行
+    if trigger_engine.is_empty() {
+      screen.process(data);
+      return Ok(());
+    }
+    trigger_engine.feed(screen.process_lines(data))
+  };
+
+  for event in events {
+    match &event.action {
+      TriggerAction::SendText { text } => {
+        let text = encode_input(encoding, text.as_bytes())?;
+        let mut writer = writer.lock()?;
+        writer.write_all(&text)?;
+        writer.flush()?;
+      }
+      _ => output.send(PtyIpcEvent::Trigger(event))?,
+    }
   }
 
   Ok(())
@@ -289,7 +478,87 @@ fn cleanup_shell<R: Runtime>(
 fn remove_shell<R: Runtime>(app: &AppHandle<R>, shell_id: &str) {
   if let Some(manager) = app.try_state::<PtyManager>()
     && let Ok(mut shells) = manager.shells.lock()
+    && shells.remove(shell_id).is_some()
   {
-    shells.remove(shell_id);
+    let _ = app.emit(SHELL_CLOSE_EVENT, shell_id);
   }
 }
+
+/// Replaces the trigger rules of a running shell.
+#[tauri::command]
+pub async fn shell_set_triggers<R: Runtime>(
+  shell_id: ShellId,
+  triggers: Vec<TriggerRule>,
+  _app: AppHandle<R>,
+  pty_manager: State<'_, PtyManager>,
+) -> PtyResult<ShellId> {
+  let trigger_engine = {
+    let shells = pty_manager
+      .shells
+      .lock()
+      .map_err(|e| PtyError::new(e.to_string()))?;
+    shells
+      .get(&shell_id)
+      .map(|shell| Arc::clone(&shell.trigger_engine))
+      .ok_or_else(|| PtyError::new("Shell already closed"))?
+  };
+
+  trigger_engine.lock()?.set_rules(triggers)?;
+
+  Ok(shell_id)
+}
+
+fn get_shell_screen(
+  pty_manager: &PtyManager,
+  shell_id: &str,
+) -> PtyResult<Arc<Mutex<TerminalScreen>>> {
+  let shells = pty_manager
+    .shells
+    .lock()
+    .map_err(|e| PtyError::new(e.to_string()))?;
+  shells
+    .get(shell_id)
+    .map(|shell| Arc::clone(&shell.screen))
+    .ok_or_else(|| PtyError::new("Shell already closed"))
+}
+
+/// Returns the visible screen as the backend renders it, also while no webview is attached.
+#[tauri::command]
+pub async fn shell_screen<R: Runtime>(
+  shell_id: ShellId,
+  _app: AppHandle<R>,
+  pty_manager: State<'_, PtyManager>,
+) -> PtyResult<ScreenSnapshot> {
+  let screen = get_shell_screen(&pty_manager, &shell_id)?;
+  let snapshot = screen.lock()?.snapshot();
+
+  Ok(snapshot)
+}
+
+/// Searches the scrollback and the screen, wrapped rows are matched as one line.
+#[tauri::command]
+pub async fn shell_screen_search<R: Runtime>(
+  shell_id: ShellId,
+  opts: ScreenSearchOpts,
+  _app: AppHandle<R>,
+  pty_manager: State<'_, PtyManager>,
+) -> PtyResult<Vec<ScreenSearchMatch>> {
+  let screen = get_shell_screen(&pty_manager, &shell_id)?;
+  let matches = screen.lock()?.search(&opts)?;
+
+  Ok(matches)
+}
+
+/// Exports the scrollback and the screen as plain text or as HTML keeping the colors.
+#[tauri::command]
+pub async fn shell_screen_export<R: Runtime>(
+  shell_id: ShellId,
+  format: ScreenExportFormat,
+  _app: AppHandle<R>,
+  pty_manager: State<'_, PtyManager>,
+) -> PtyResult<String> {
+  let screen = get_shell_screen(&pty_manager, &shell_id)?;
+  let transcript = screen.lock()?.export(format);
+
+  Ok(transcript)
+}
@@ -16,6 +16,9 @@ pub enum PtyError {
   #[serde(serialize_with = "serialize_to_string")]
   #[error(transparent)]
   TauriError(#[from] tauri::Error),
+  #[serde(serialize_with = "serialize_to_string")]
+  #[error(transparent)]
+  TerminalError(#[from] terminal_core::TerminalError),
   #[error("{0}")]
   StdSyncPoisonError(String),
   #[error("{0}")]
@@ -1,6 +1,7 @@
 pub(crate) mod commands;
 pub(crate) mod error;
 pub(crate) mod pty_manager;
+pub(crate) mod shell_output;
 pub(crate) mod utils;
 
 use pty_manager::PtyManager;
@@ -9,6 +10,7 @@
   plugin::{Builder, TauriPlugin},
 };
 
+pub use commands::shell::{SHELL_CLOSE_EVENT, ShellId, write_shell};
 pub use error::{PtyError, PtyResult};
 
 pub fn init<R: Runtime>() -> TauriPlugin<R> {
@@ -18,6 +20,13 @@ pub fn init<R: Runtime>() -> TauriPlugin<R> {
       commands::shell::shell_close,
       commands::shell::shell_resize,
       commands::shell::shell_send,
+      commands::shell::shell_ack,
+      commands::shell::shell_attach,
+      commands::shell::shell_detach,
+      commands::shell::shell_set_triggers,
+      commands::shell::shell_screen,
+      commands::shell::shell_screen_search,
+      commands::shell::shell_screen_export,
     ])
     .setup(|app, _api| {
       app.manage(PtyManager::new());
@@ -4,6 +4,9 @@
 use std::sync::{Arc, Mutex};
 
 use portable_pty::{ChildKiller, MasterPty};
+use terminal_core::{encoding::TerminalEncoding, screen::TerminalScreen, trigger::TriggerEngine};
+
+use crate::shell_output::ShellOutput;
 
 type ShellId = String;
 pub type ShellWriter = Arc<Mutex<Box<dyn Write + Send>>>;
@@ -14,6 +17,11 @@ pub struct ShellInstance {
   pub writer: ShellWriter,
   pub killer: ShellKiller,
   pub cleanup_started: Arc<AtomicBool>,
+  pub output: Arc<ShellOutput>,
+  pub trigger_engine: Arc<Mutex<TriggerEngine>>,
+  pub screen: Arc<Mutex<TerminalScreen>>,
+  /// Set when the terminal uses a legacy encoding
+  pub encoding: Option<Arc<TerminalEncoding>>,
 }
 
 impl ShellInstance {
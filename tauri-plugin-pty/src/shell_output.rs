@@ -0,0 +1,148 @@
+use std::sync::{Arc, Condvar, Mutex};
+
+use tauri::{async_runtime, ipc::Channel};
+use terminal_core::output_flow::{FRAME_INTERVAL, OutputFlow};
+
+use crate::{commands::shell::PtyIpcEvent, error::PtyResult};
+
+struct ShellOutputState {
+  /// None while the shell runs detached from any webview
+  ipc_channel: Option<Channel<PtyIpcEvent>>,
+  flow: OutputFlow,
+  closed: bool,
+}
+
+/// Coalesces pty output into frames and applies acknowledgement based flow control.
+pub struct ShellOutput {
+  shell_id: String,
+  state: Mutex<ShellOutputState>,
+  writable: Condvar,
+}
+
+impl ShellOutput {
+  pub fn new(shell_id: String, ipc_channel: Channel<PtyIpcEvent>) -> Self {
+    Self {
+      shell_id,
+      state: Mutex::new(ShellOutputState {
+        ipc_channel: Some(ipc_channel),
+        flow: OutputFlow::default(),
+        closed: false,
+      }),
+      writable: Condvar::new(),
+    }
+  }
+
+  /// Queues output for the webview, the frame is sent by size or by timer.
+  pub fn write(self: &Arc<Self>, data: &[u8]) -> PtyResult<()> {
+    let flush_now = {
+      let mut state = self.state.lock()?;
+      let flush_now = state.flow.push(data);
+      if !flush_now && state.flow.schedule_flush() {
+        let shell_output = Arc::clone(self);
+        async_runtime::spawn(async move {
+          tokio::time::sleep(FRAME_INTERVAL).await;
+          if let Err(e) = shell_output.flush() {
+            log::error!(
+              "pty shell {} flush output error: {}",
+              shell_output.shell_id,
+              e
+            );
+          }
+        });
+      }
+      flush_now
+    };
+
+    if flush_now {
+      self.flush()?;
+    }
+
+    Ok(())
+  }
+
+  /// Sends the pending output as one frame.
+  pub fn flush(&self) -> PtyResult<()> {
+    let mut state = self.state.lock()?;
+    let frame = state.flow.take_scheduled_frame();
+    state.send_frame(frame)
+  }
+
+  /// Flushes the pending output before sending a control event, keeping the order.
+  pub fn send(&self, event: PtyIpcEvent) -> PtyResult<()> {
+    let mut state = self.state.lock()?;
+    state.flush()?;
+    if let Some(ipc_channel) = &state.ipc_channel {
+      ipc_channel.send(event)?;
+    }
+
+    Ok(())
+  }
+
+  /// Replaces the channel, replaying the scrollback before any new output.
+  pub fn attach(&self, ipc_channel: Channel<PtyIpcEvent>) -> PtyResult<()> {
+    let mut state = self.state.lock()?;
+    let replay = state.flow.attach();
+    let ipc_channel = state.ipc_channel.insert(ipc_channel);
+    if !replay.is_empty() {
+      ipc_channel.send(PtyIpcEvent::Data(replay))?;
+    }
+    self.writable.notify_all();
+
+    Ok(())
+  }
+
+  /// Drops the channel, the shell keeps running and its output goes to the scrollback only.
+  pub fn detach(&self) -> PtyResult<()> {
+    let mut state = self.state.lock()?;
+    state.ipc_channel = None;
+    state.flow.detach();
+    self.writable.notify_all();
+
+    Ok(())
+  }
+
+  /// Called by the webview after it has processed `size` bytes of output.
+  pub fn ack(&self, size: usize) -> PtyResult<()> {
+    if self.state.lock()?.flow.ack(size) {
+      self.writable.notify_all();
+    }
+
+    Ok(())
+  }
+
+  /// Releases the reader waiting for acknowledgements, the shell is gone.
+  pub fn close(&self) -> PtyResult<()> {
+    self.state.lock()?.closed = true;
+    self.writable.notify_all();
+
+    Ok(())
+  }
+
+  /// Blocks the reader thread while the webview is behind, so the child process
+  /// blocks on a full pty buffer instead of flooding the webview.
+  pub fn wait_writable(&self) -> PtyResult<()> {
+    let state = self.state.lock()?;
+    let _state = self
+      .writable
+      .wait_while(state, |state| !state.closed && !state.flow.is_writable())?;
+
+    Ok(())
+  }
+}
+
+impl ShellOutputState {
+  fn flush(&mut self) -> PtyResult<()> {
+    let frame = self.flow.take_frame();
+    self.send_frame(frame)
+  }
+
+  fn send_frame(&self, frame: Option<Vec<u8>>) -> PtyResult<()> {
+    if let Some(frame) = frame
+      && let Some(ipc_channel) = &self.ipc_channel
+    {
+      ipc_channel.send(PtyIpcEvent::Data(frame))?;
+    }
+
+    Ok(())
+  }
+}
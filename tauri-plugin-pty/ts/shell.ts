@@ -9,20 +9,116 @@ export type PtyShellSize = {
   height: number;
 };
 
+export type PtyShellTriggerAction =
+  | { type: "SendText"; text: string }
+  | { type: "Notify"; message?: string }
+  | { type: "Highlight"; color: string }
+  | { type: "PortForwarding"; portForwardingId: string };
+
+export type PtyShellTrigger = {
+  id: string;
+  /**
+   * 匹配终端渲染后的输出行的正则，自动换行的行会拼接为一行
+   */
+  pattern: string;
+  action: PtyShellTriggerAction;
+};
+
+export type PtyShellScreenSnapshot = {
+  rows: number;
+  cols: number;
+  cursorRow: number;
+  cursorCol: number;
+  alternateScreen: boolean;
+  lines: string[];
+};
+
+export type PtyShellScreenSearchOpts = {
+  query: string;
+  regex?: boolean;
+  caseSensitive?: boolean;
+};
+
+export type PtyShellScreenSearchMatch = {
+  /**
+   * 行首所在的行号，从最早的回滚行开始计数
+   */
+  row: number;
+  line: string;
+  /**
+   * 以字符计的匹配范围
+   */
+  start: number;
+  end: number;
+};
+
+export type PtyShellScreenExportFormat = "Text" | "Html";
+
+export type PtyShellTriggerEvent = {
+  ruleId: string;
+  action: PtyShellTriggerAction;
+  line: string;
+  matched: string;
+};
+
+export type PtyShellIntegrationEvent =
+  | { type: "CommandStarted"; command: string | null; cwd: string | null }
+  | {
+      type: "CommandFinished";
+      command: string | null;
+      cwd: string | null;
+      exitCode: number | null;
+      /**
+       * 命令执行的毫秒数
+       */
+      duration: number;
+    }
+  | { type: "CwdChanged"; cwd: string; host: string | null };
+
 export type PtyShellOpenOpts = {
   size: PtyShellSize;
   shell?: string;
+  triggers?: PtyShellTrigger[];
+  /**
+   * 终端使用的旧编码，如 GBK、Big5、Shift_JIS，由后端与 UTF-8 互相转换
+   */
+  encoding?: string;
 };
 
 export type PtyShellOpts = {
+  /**
+   * 重新连接已存在的 shell 时传入其 id，随后调用 attach
+   */
+  shellId?: string;
+  /**
+   * 返回 Promise 时，在 Promise 完成后才确认数据已处理，用于后端流控
+   */
   onData?: (data: Uint8Array) => unknown;
   onExit?: (code: number | null) => unknown;
+  /**
+   * 发送文本的触发器由后端直接处理，其余动作通过该回调交给界面处理
+   */
+  onTrigger?: (event: PtyShellTriggerEvent) => unknown;
+  /**
+   * shell 通过 OSC 133/633 标记命令边界、通过 OSC 7 报告工作目录时调用，
+   * 需要在 shell 中开启 shell integration
+   */
+  onShellIntegration?: (event: PtyShellIntegrationEvent) => unknown;
 };
 
-export type PtyShellIpcChannelEventJson = {
-  type: "Exit";
-  code: number | null;
-};
+export type PtyShellIpcChannelEventJson =
+  | {
+      type: "Exit";
+      code: number | null;
+    }
+  | {
+      type: "Trigger";
+      event: PtyShellTriggerEvent;
+    }
+  | {
+      type: "ShellIntegration";
+      event: PtyShellIntegrationEvent;
+    };
 
 export type PtyShellIpcChannelEvent = ArrayBuffer | PtyShellIpcChannelEventJson;
 
@@ -32,7 +128,7 @@ export class PtyShell {
   private opts: PtyShellOpts;
 
   constructor(opts: PtyShellOpts) {
-    this.shellId = uuidV4();
+    this.shellId = opts.shellId ?? uuidV4();
     this.opts = opts;
   }
 
@@ -41,21 +137,35 @@ export class PtyShell {
       shellId: this.shellId,
       size: opts.size,
       shell: opts.shell ?? null,
-      ipcChannel: new Channel<PtyShellIpcChannelEvent>((data) => {
-        if (data instanceof ArrayBuffer) {
-          this.opts.onData?.(new Uint8Array(data));
-          return;
-        }
-        if (data.type === "Exit") {
-          this.#opened = false;
-          this.opts.onExit?.(data.code);
-        }
-      }),
+      triggers: opts.triggers ?? null,
+      encoding: opts.encoding ?? null,
+      ipcChannel: this.#createIpcChannel(),
+    });
+    this.#opened = true;
+    return result;
+  }
+
+  /**
+   * 重新连接到后台运行的 shell，会先回放最近的输出
+   */
+  async attach(): Promise<string> {
+    const result = await invoke<string>("plugin:pty|shell_attach", {
+      shellId: this.shellId,
+      ipcChannel: this.#createIpcChannel(),
     });
     this.#opened = true;
     return result;
   }
 
+  /**
+   * 断开与 shell 的连接，shell 在后台继续运行
+   */
+  detach(): Promise<string> {
+    return invoke<string>("plugin:pty|shell_detach", {
+      shellId: this.shellId,
+    });
+  }
+
   send(data: string | Uint8Array): Promise<void> {
     if (!this.#opened) {
       return Promise.resolve();
@@ -68,6 +178,60 @@ export class PtyShell {
     });
   }
 
+  ack(size: number): Promise<void> {
+    if (!this.#opened) {
+      return Promise.resolve();
+    }
+    return invoke("plugin:pty|shell_ack", {
+      shellId: this.shellId,
+      size,
+    }).catch(() => undefined);
+  }
+
+  setTriggers(triggers: PtyShellTrigger[]): Promise<void> {
+    if (!this.#opened) {
+      return Promise.resolve();
+    }
+    return invoke("plugin:pty|shell_set_triggers", {
+      shellId: this.shellId,
+      triggers,
+    });
+  }
+
+  /**
+   * 后端渲染的当前屏幕
+   */
+  screen(): Promise<PtyShellScreenSnapshot> {
+    return invoke<PtyShellScreenSnapshot>("plugin:pty|shell_screen", {
+      shellId: this.shellId,
+    });
+  }
+
+  /**
+   * 在回滚区和屏幕中搜索，最多返回 1000 个匹配
+   */
+  searchScreen(
+    opts: PtyShellScreenSearchOpts,
+  ): Promise<PtyShellScreenSearchMatch[]> {
+    return invoke<PtyShellScreenSearchMatch[]>(
+      "plugin:pty|shell_screen_search",
+      {
+        shellId: this.shellId,
+        opts,
+      },
+    );
+  }
+
+  /**
+   * 导出回滚区和屏幕的内容，Html 保留颜色
+   */
+  exportScreen(format: PtyShellScreenExportFormat): Promise<string> {
+    return invoke<string>("plugin:pty|shell_screen_export", {
+      shellId: this.shellId,
+      format,
+    });
+  }
+
   resize(size: PtyShellSize): Promise<void> {
     if (!this.#opened) {
       return Promise.resolve();
@@ -84,4 +248,24 @@ export class PtyShell {
       shellId: this.shellId,
     });
   }
+
+  #createIpcChannel() {
+    return new Channel<PtyShellIpcChannelEvent>((data) => {
+      if (data instanceof ArrayBuffer) {
+        const bytes = new Uint8Array(data);
+        Promise.resolve(this.opts.onData?.(bytes)).finally(() => {
+          this.ack(bytes.byteLength);
+        });
+        return;
+      }
+      if (data.type === "Exit") {
+        this.#opened = false;
+        this.opts.onExit?.(data.code);
+      } else if (data.type === "Trigger") {
+        this.opts.onTrigger?.(data.event);
+      } else if (data.type === "ShellIntegration") {
+        this.opts.onShellIntegration?.(data.event);
+      }
+    });
+  }
 }
@@ -27,6 +27,57 @@
   "sftp_cancel_task",
   "sftp_pause_task",
   "sftp_resume_task",
+  "shell_ack",
+  "shell_attach",
+  "shell_detach",
+  "shell_set_triggers",
+  "shell_signal",
+  "shell_break",
+  "shell_zmodem_upload",
+  "shell_zmodem_download",
+  "shell_zmodem_cancel",
+  "tmux_command",
+  "tmux_list_panes",
+  "tmux_pane_open",
+  "tmux_pane_close",
+  "tmux_pane_send",
+  "tmux_pane_resize",
+  "tmux_window_resize",
+  "tmux_detach",
+  "shell_screen",
+  "shell_screen_search",
+  "shell_screen_export",
+  "port_forwarding_url_open",
+  "container_list",
+  "container_action",
+  "container_logs_open",
+  "container_logs_close",
+  "sftp_upload_dir",
+  "sftp_download_dir",
+  "transfer_queue_subscribe",
+  "transfer_queue_list",
+  "transfer_queue_enqueue",
+  "transfer_queue_cancel",
+  "transfer_queue_pause",
+  "transfer_queue_resume",
+  "transfer_queue_retry",
+  "transfer_queue_move",
+  "transfer_queue_remove",
+  "transfer_queue_clear",
+  "transfer_queue_set_concurrency",
+  "sftp_chmod",
+  "sftp_chown",
+  "sftp_set_times",
+  "sftp_read_link",
+  "sftp_symlink",
+  "sftp_hardlink",
+  "sftp_stat",
+  "sftp_lstat",
+  "sftp_remove_all",
+  "sftp_copy",
+  "sftp_move",
+  "sftp_remote_transfer",
+  "sftp_statvfs",
 ];
 
 fn main() {
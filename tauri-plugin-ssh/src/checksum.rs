@@ -0,0 +1,260 @@
+use std::{
+  pin::Pin,
+  task::{Context, Poll},
+  time::Duration,
+};
+
+use russh_sftp::protocol::Packet;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use tauri::Runtime;
+use tokio::{
+  io::{self, AsyncRead, AsyncReadExt, AsyncWrite, ReadBuf},
+  time::timeout,
+};
+
+use crate::{
+  commands::{
+    session::SSHSessionId,
+    sftp::{SSHSftpId, get_raw_sftp},
+  },
+  error::{SSHError, SSHResult},
+  exec::{exec, quote},
+  sftp_raw::{put_string, take_string},
+  ssh_manager::SSHManager,
+};
+
+/// Hashing a large file remotely reads all of it, allow for slow disks.
+const REMOTE_HASH_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// Extension of draft-ietf-secsh-filexfer-extensions hashing a file on the server
+const CHECK_FILE_EXTENSION: &str = "check-file";
+
+const CHECK_FILE_NAME_REQUEST: &str = "check-file-name";
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SftpVerifyOpts {
+  /// Transfers the file again from the start when the checksums differ
+  #[serde(default)]
+  pub retries: u32,
+}
+
+/// Compares the SHA-256 of transferred files with the remote side.
+pub struct Verifier<'a, R: Runtime> {
+  pub ssh_manager: &'a SSHManager<R>,
+  pub ssh_session_id: SSHSessionId,
+  pub ssh_sftp_id: SSHSftpId,
+  pub retries: u32,
+}
+
+impl<R: Runtime> Verifier<'_, R> {
+  pub async fn verify(&self, remote_filename: &str, local: &str) -> SSHResult<()> {
+    let remote = remote_sha256(
+      self.ssh_manager,
+      self.ssh_session_id,
+      self.ssh_sftp_id,
+      remote_filename,
+    )
+    .await?;
+    log::info!(
+      "sftp verify {} local {} remote {}",
+      remote_filename,
+      local,
+      remote
+    );
+
+    if remote != local {
+      return Err(SSHError::ChecksumMismatch {
+        filename: remote_filename.to_string(),
+        local: local.to_string(),
+        remote,
+      });
+    }
+
+    Ok(())
+  }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+  bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+pub fn finalize(hasher: Sha256) -> String {
+  to_hex(&hasher.finalize())
+}
+
+/// Feeds the first `len` bytes of `reader` to `hasher`, used for the part a resumed transfer
+/// doesn't stream again.
+pub async fn hash_prefix<T>(reader: &mut T, len: u64, hasher: &mut Sha256) -> SSHResult<()>
+where
+  T: AsyncRead + Unpin,
+{
+  let mut buf = vec![0; 64 * 1024];
+  let mut rest = len;
+  while rest > 0 {
+    let size = rest.min(buf.len() as u64) as usize;
+    reader.read_exact(&mut buf[..size]).await?;
+    hasher.update(&buf[..size]);
+    rest -= size as u64;
+  }
+  Ok(())
+}
+
+/// Hashes the bytes read through it when a hasher is set.
+pub struct HashReader<T> {
+  inner: T,
+  hasher: Option<Sha256>,
+}
+
+impl<T> HashReader<T> {
+  pub fn new(inner: T, hasher: Option<Sha256>) -> Self {
+    Self { inner, hasher }
+  }
+
+  pub fn finalize(self) -> Option<String> {
+    self.hasher.map(finalize)
+  }
+}
+
+impl<T: AsyncRead + Unpin> AsyncRead for HashReader<T> {
+  fn poll_read(
+    mut self: Pin<&mut Self>,
+    cx: &mut Context<'_>,
+    buf: &mut ReadBuf<'_>,
+  ) -> Poll<io::Result<()>> {
+    let filled = buf.filled().len();
+    let poll = Pin::new(&mut self.inner).poll_read(cx, buf);
+    if let (Poll::Ready(Ok(())), Some(hasher)) = (&poll, &mut self.hasher) {
+      hasher.update(&buf.filled()[filled..]);
+    }
+    poll
+  }
+}
+
+/// Hashes the bytes written through it when a hasher is set.
+pub struct HashWriter<T> {
+  inner: T,
+  hasher: Option<Sha256>,
+}
+
+impl<T> HashWriter<T> {
+  pub fn new(inner: T, hasher: Option<Sha256>) -> Self {
+    Self { inner, hasher }
+  }
+
+  pub fn finalize(self) -> Option<String> {
+    self.hasher.map(finalize)
+  }
+}
+
+impl<T: AsyncWrite + Unpin> AsyncWrite for HashWriter<T> {
+  fn poll_write(
+    mut self: Pin<&mut Self>,
+    cx: &mut Context<'_>,
+    buf: &[u8],
+  ) -> Poll<io::Result<usize>> {
+    let poll = Pin::new(&mut self.inner).poll_write(cx, buf);
+    if let (Poll::Ready(Ok(size)), Some(hasher)) = (&poll, &mut self.hasher) {
+      hasher.update(&buf[..*size]);
+    }
+    poll
+  }
+
+  fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+    Pin::new(&mut self.inner).poll_flush(cx)
+  }
+
+  fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+    Pin::new(&mut self.inner).poll_shutdown(cx)
+  }
+}
+
+/// Returns the lowercase hex SHA-256 of a remote file, computed by the server.
+pub async fn remote_sha256<R: Runtime>(
+  ssh_manager: &SSHManager<R>,
+  ssh_session_id: SSHSessionId,
+  ssh_sftp_id: SSHSftpId,
+  filename: &str,
+) -> SSHResult<String> {
+  match timeout(
+    REMOTE_HASH_TIMEOUT,
+    check_file_sha256(ssh_manager, ssh_sftp_id, filename),
+  )
+  .await?
+  {
+    Ok(Some(hash)) => return Ok(hash),
+    Ok(None) => {}
+    Err(err) => log::warn!("sftp check-file {} error {:?}", filename, err),
+  }
+
+  timeout(
+    REMOTE_HASH_TIMEOUT,
+    exec_sha256(ssh_manager, ssh_session_id, filename),
+  )
+  .await?
+}
+
+/// Asks the server to hash the file with the `check-file` extension, `None` when the server
+/// doesn't support it. OpenSSH doesn't, some commercial servers do.
+async fn check_file_sha256<R: Runtime>(
+  ssh_manager: &SSHManager<R>,
+  ssh_sftp_id: SSHSftpId,
+  filename: &str,
+) -> SSHResult<Option<String>> {
+  let Some(sftp) = get_raw_sftp(ssh_manager, ssh_sftp_id, CHECK_FILE_EXTENSION).await? else {
+    return Ok(None);
+  };
+
+  let mut data = Vec::new();
+  put_string(&mut data, filename.as_bytes());
+  put_string(&mut data, b"sha256");
+  // 起始偏移、长度与块大小为 0 表示整个文件一个摘要
+  data.extend_from_slice(&0u64.to_be_bytes());
+  data.extend_from_slice(&0u64.to_be_bytes());
+  data.extend_from_slice(&0u32.to_be_bytes());
+
+  let Packet::ExtendedReply(reply) = sftp.extended(CHECK_FILE_NAME_REQUEST, data).await? else {
+    return Ok(None);
+  };
+
+  let mut reply_data = &reply.data[..];
+  let mut algorithm = take_string(&mut reply_data);
+  if algorithm == Some(CHECK_FILE_EXTENSION.as_bytes()) {
+    algorithm = take_string(&mut reply_data);
+  }
+  if algorithm != Some(b"sha256".as_slice()) || reply_data.len() != 32 {
+    return Ok(None);
+  }
+
+  Ok(Some(to_hex(reply_data)))
+}
+
+/// Hashes the file with `sha256sum`, or `shasum` on BSD and macOS hosts.
+async fn exec_sha256<R: Runtime>(
+  ssh_manager: &SSHManager<R>,
+  ssh_session_id: SSHSessionId,
+  filename: &str,
+) -> SSHResult<String> {
+  let filename = quote(filename);
+  let command = format!(
+    "if command -v sha256sum >/dev/null 2>&1; then sha256sum -- {}; else shasum -a 256 -- {}; fi",
+    filename, filename
+  );
+  let output = exec(ssh_manager, ssh_session_id, &command).await?;
+
+  let stdout = String::from_utf8_lossy(&output.stdout);
+  let hash = stdout.split_whitespace().next().unwrap_or_default();
+  if output.exit_status != Some(0)
+    || hash.len() != 64
+    || !hash.chars().all(|char| char.is_ascii_hexdigit())
+  {
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    return Err(SSHError::new(format!(
+      "Failed to compute remote checksum: {}",
+      stderr.trim()
+    )));
+  }
+
+  Ok(hash.to_lowercase())
+}
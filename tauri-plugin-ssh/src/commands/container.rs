@@ -0,0 +1,244 @@
+use std::{sync::Arc, time::Duration};
+
+use russh::ChannelMsg;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tauri::{
+  AppHandle, Manager, Runtime, State, async_runtime,
+  ipc::{Channel, InvokeResponseBody, IpcResponse},
+};
+use tokio::{select, sync::Notify, time::timeout};
+use uuid::Uuid;
+
+use crate::{
+  commands::session::SSHSessionId,
+  container::{
+    self, Container, ContainerAction, ContainerLogsOpts, ContainerRuntime, DETECT_RUNTIME_COMMAND,
+  },
+  error::{SSHError, SSHResult},
+  exec::{exec, open_exec_channel},
+  ssh_manager::SSHManager,
+};
+
+const COMMAND_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone)]
+pub enum ContainerLogsIpcChannelData {
+  Data(Vec<u8>),
+  /// The logs command exited, e.g. the container was removed
+  Close {
+    exit_status: Option<u32>,
+  },
+}
+
+impl IpcResponse for ContainerLogsIpcChannelData {
+  fn body(self) -> tauri::Result<InvokeResponseBody> {
+    match self {
+      ContainerLogsIpcChannelData::Data(data) => Ok(InvokeResponseBody::Raw(data)),
+      ContainerLogsIpcChannelData::Close { exit_status } => {
+        let body = json!({
+          "type": "Close",
+          "exitStatus": exit_status,
+        });
+        Ok(InvokeResponseBody::Json(body.to_string()))
+      }
+    }
+  }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub struct SSHContainerLogsId(Uuid);
+
+pub struct SSHContainerLogs {
+  pub ssh_session_id: SSHSessionId,
+  pub notify: Arc<Notify>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContainerList {
+  pub runtime: ContainerRuntime,
+  pub containers: Vec<Container>,
+}
+
+/// Runs a container cli command, a non-zero exit fails with its stderr.
+async fn exec_checked<R: Runtime>(
+  ssh_manager: &SSHManager<R>,
+  ssh_session_id: SSHSessionId,
+  command: &str,
+) -> SSHResult<String> {
+  let output = timeout(COMMAND_TIMEOUT, exec(ssh_manager, ssh_session_id, command)).await??;
+
+  if output.exit_status != Some(0) {
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let message = match stderr.trim() {
+      "" => format!("exit status {:?}", output.exit_status),
+      stderr => stderr.to_string(),
+    };
+    return Err(SSHError::ContainerError(message));
+  }
+
+  Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+async fn detect_runtime<R: Runtime>(
+  ssh_manager: &SSHManager<R>,
+  ssh_session_id: SSHSessionId,
+) -> SSHResult<ContainerRuntime> {
+  let output = timeout(
+    COMMAND_TIMEOUT,
+    exec(ssh_manager, ssh_session_id, DETECT_RUNTIME_COMMAND),
+  )
+  .await??;
+
+  ContainerRuntime::detect(&String::from_utf8_lossy(&output.stdout))
+    .ok_or(SSHError::NotFoundContainerRuntime)
+}
+
+/// Lists all containers, the runtime is detected when not given and returned for later calls.
+#[tauri::command]
+pub async fn container_list<R: Runtime>(
+  _app_handle: AppHandle<R>,
+  ssh_manager: State<'_, SSHManager<R>>,
+  ssh_session_id: SSHSessionId,
+  runtime: Option<ContainerRuntime>,
+) -> SSHResult<ContainerList> {
+  let runtime = match runtime {
+    Some(runtime) => runtime,
+    None => detect_runtime(&ssh_manager, ssh_session_id).await?,
+  };
+
+  let output = exec_checked(
+    &ssh_manager,
+    ssh_session_id,
+    &container::list_command(runtime),
+  )
+  .await?;
+
+  Ok(ContainerList {
+    runtime,
+    containers: container::parse_list(&output),
+  })
+}
+
+#[tauri::command]
+pub async fn container_action<R: Runtime>(
+  _app_handle: AppHandle<R>,
+  ssh_manager: State<'_, SSHManager<R>>,
+  ssh_session_id: SSHSessionId,
+  runtime: ContainerRuntime,
+  container_id: String,
+  action: ContainerAction,
+) -> SSHResult<()> {
+  let command = container::action_command(runtime, &container_id, action)?;
+  exec_checked(&ssh_manager, ssh_session_id, &command).await?;
+
+  Ok(())
+}
+
+/// Streams `docker logs` to the webview, stdout and stderr of the container are merged.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn container_logs_open<R: Runtime>(
+  app_handle: AppHandle<R>,
+  ssh_manager: State<'_, SSHManager<R>>,
+  ssh_session_id: SSHSessionId,
+  ssh_container_logs_id: SSHContainerLogsId,
+  runtime: ContainerRuntime,
+  container_id: String,
+  opts: Option<ContainerLogsOpts>,
+  ipc_channel: Channel<ContainerLogsIpcChannelData>,
+) -> SSHResult<SSHContainerLogsId> {
+  let command = container::logs_command(runtime, &container_id, &opts.unwrap_or_default())?;
+  log::info!(
+    "container logs open {:?} {:?} {}",
+    ssh_session_id,
+    ssh_container_logs_id,
+    command
+  );
+  let mut channel = timeout(
+    Duration::from_secs(5),
+    open_exec_channel(&ssh_manager, ssh_session_id, &command),
+  )
+  .await??;
+
+  let notify = Arc::new(Notify::new());
+  {
+    let mut container_logs = ssh_manager.container_logs.lock().await;
+    container_logs.insert(
+      ssh_container_logs_id,
+      SSHContainerLogs {
+        ssh_session_id,
+        notify: notify.clone(),
+      },
+    );
+  }
+
+  async_runtime::spawn(async move {
+    let mut exit_status = None;
+    loop {
+      select! {
+        _ = notify.notified() => {
+          if let Err(err) = channel.close().await {
+            log::error!("container logs {:?} close failed: {}", ssh_container_logs_id, err);
+          }
+          break;
+        }
+        msg = channel.wait() => {
+          let data = match msg {
+            Some(ChannelMsg::Data { data }) | Some(ChannelMsg::ExtendedData { data, .. }) => data,
+            Some(ChannelMsg::ExitStatus { exit_status: status }) => {
+              exit_status = Some(status);
+              continue;
+            }
+            Some(_) => continue,
+            None => break,
+          };
+          if let Err(err) = ipc_channel.send(ContainerLogsIpcChannelData::Data(data.to_vec())) {
+            log::error!("container logs {:?} send failed: {}", ssh_container_logs_id, err);
+            break;
+          }
+        }
+      }
+    }
+
+    let ssh_manager = app_handle.state::<SSHManager<R>>();
+    ssh_manager
+      .container_logs
+      .lock()
+      .await
+      .remove(&ssh_container_logs_id);
+    if let Err(err) = ipc_channel.send(ContainerLogsIpcChannelData::Close { exit_status }) {
+      log::error!(
+        "container logs {:?} send close failed: {}",
+        ssh_container_logs_id,
+        err
+      );
+    }
+  });
+
+  Ok(ssh_container_logs_id)
+}
+
+#[tauri::command]
+pub async fn container_logs_close<R: Runtime>(
+  _app_handle: AppHandle<R>,
+  ssh_manager: State<'_, SSHManager<R>>,
+  ssh_container_logs_id: SSHContainerLogsId,
+) -> SSHResult<SSHContainerLogsId> {
+  let container_logs = ssh_manager
+    .container_logs
+    .lock()
+    .await
+    .remove(&ssh_container_logs_id)
+    .ok_or(SSHError::NotFoundContainerLogs)?;
+
+  log::info!(
+    "container logs close {:?} {:?}",
+    container_logs.ssh_session_id,
+    ssh_container_logs_id
+  );
+  container_logs.notify.notify_one();
+
+  Ok(ssh_container_logs_id)
+}
@@ -1,4 +1,7 @@
+pub(crate) mod container;
 pub(crate) mod port_forwarding;
 pub(crate) mod session;
 pub(crate) mod sftp;
 pub(crate) mod shell;
+pub(crate) mod tmux;
+pub(crate) mod transfer_queue;
@@ -18,7 +18,12 @@
 };
 use uuid::Uuid;
 
-use crate::{SSHError, SSHResult, commands::session::SSHSessionId, ssh_manager::SSHManager};
+use crate::{
+  SSHError, SSHResult,
+  commands::session::SSHSessionId,
+  ssh_manager::SSHManager,
+  url_forwarding::{ForwardedUrl, LocalUrl},
+};
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Serialize, Deserialize)]
 pub struct SSHPortForwardingId(Uuid);
@@ -156,7 +161,6 @@ async fn connect(
 #[allow(clippy::too_many_arguments)]
 pub async fn port_forwarding_local_open<R: Runtime>(
   app_handle: AppHandle<R>,
-  ssh_manager: State<'_, SSHManager<R>>,
   ssh_session_id: SSHSessionId,
   ssh_port_forwarding_id: SSHPortForwardingId,
   local_address: String,
@@ -164,11 +168,37 @@ pub async fn port_forwarding_local_open<R: Runtime>(
   remote_address: String,
   remote_port: u16,
 ) -> SSHResult<SSHSessionId> {
+  open_local_forwarding(
+    &app_handle,
+    ssh_session_id,
+    ssh_port_forwarding_id,
+    local_address,
+    local_port,
+    remote_address,
+    remote_port,
+  )
+  .await?;
+
+  Ok(ssh_session_id)
+}
+
+/// Opens a local forwarding and returns the bound port, port 0 binds a free port.
+async fn open_local_forwarding<R: Runtime>(
+  app_handle: &AppHandle<R>,
+  ssh_session_id: SSHSessionId,
+  ssh_port_forwarding_id: SSHPortForwardingId,
+  local_address: String,
+  local_port: u16,
+  remote_address: String,
+  remote_port: u16,
+) -> SSHResult<u16> {
   let notify = Arc::new(Notify::new());
 
   let listener = TcpListener::bind((local_address.clone(), local_port)).await?;
+  let local_port = listener.local_addr()?.port();
 
   {
+    let ssh_manager = app_handle.state::<SSHManager<R>>();
     let mut port_forwardings = ssh_manager.port_forwardings.lock().await;
     port_forwardings.insert(
       ssh_port_forwarding_id,
@@ -184,6 +214,7 @@ pub async fn port_forwarding_local_open<R: Runtime>(
     );
   }
 
+  let app_handle = app_handle.clone();
   async_runtime::spawn(async move {
     loop {
       select! {
@@ -232,7 +263,97 @@ pub async fn port_forwarding_local_open<R: Runtime>(
     Ok::<(), SSHError>(())
   });
 
-  Ok(ssh_session_id)
+  Ok(local_port)
+}
+
+/// Forwards a url printed by a remote shell, on the same local port when it is free.
+pub(crate) async fn forward_local_url<R: Runtime>(
+  app_handle: &AppHandle<R>,
+  ssh_session_id: SSHSessionId,
+  local_url: &LocalUrl,
+) -> SSHResult<ForwardedUrl> {
+  let remote_address = local_url.remote_address();
+
+  // 已有转发到同一端口时直接复用
+  let existing = {
+    let ssh_manager = app_handle.state::<SSHManager<R>>();
+    let port_forwardings = ssh_manager.port_forwardings.lock().await;
+    port_forwardings
+      .values()
+      .find_map(|ssh_port_forwarding| match ssh_port_forwarding {
+        SSHPortForwarding::Local {
+          ssh_port_forwarding_id,
+          ssh_session_id: id,
+          local_port,
+          remote_address: address,
+          remote_port,
+          ..
+        } if *id == ssh_session_id
+          && *remote_port == local_url.port
+          && *address == remote_address =>
+        {
+          Some((*ssh_port_forwarding_id, *local_port))
+        }
+        _ => None,
+      })
+  };
+
+  let (ssh_port_forwarding_id, local_port) = match existing {
+    Some(existing) => existing,
+    None => {
+      let ssh_port_forwarding_id = SSHPortForwardingId(Uuid::new_v4());
+      let local_port = match open_local_forwarding(
+        app_handle,
+        ssh_session_id,
+        ssh_port_forwarding_id,
+        "127.0.0.1".to_string(),
+        local_url.port,
+        remote_address.clone(),
+        local_url.port,
+      )
+      .await
+      {
+        Ok(local_port) => local_port,
+        Err(err) => {
+          log::info!(
+            "local port {} unavailable, forward {} on a free port: {}",
+            local_url.port,
+            local_url.url,
+            err
+          );
+          open_local_forwarding(
+            app_handle,
+            ssh_session_id,
+            ssh_port_forwarding_id,
+            "127.0.0.1".to_string(),
+            0,
+            remote_address,
+            local_url.port,
+          )
+          .await?
+        }
+      };
+      (ssh_port_forwarding_id, local_port)
+    }
+  };
+
+  Ok(ForwardedUrl {
+    ssh_port_forwarding_id,
+    url: local_url.url.clone(),
+    local_url: local_url.local_url(local_port),
+    local_port,
+    remote_port: local_url.port,
+  })
+}
+
+/// Forwards a url detected in a shell after the user accepted it.
+#[tauri::command]
+pub async fn port_forwarding_url_open<R: Runtime>(
+  app_handle: AppHandle<R>,
+  ssh_session_id: SSHSessionId,
+  local_url: LocalUrl,
+) -> SSHResult<ForwardedUrl> {
+  forward_local_url(&app_handle, ssh_session_id, &local_url).await
 }
 
 #[tauri::command]
@@ -1,29 +1,41 @@
-use std::{ops::Deref, sync::Arc, time::Duration};
+use std::{
+  ops::Deref,
+  path::{Path, PathBuf},
+  sync::Arc,
+  time::Duration,
+};
 
 use russh::ChannelId;
 use russh_sftp::{
   client::{self, SftpSession},
-  protocol::FileType as RusshSftpFileType,
+  protocol::{FileAttributes, FileType as RusshSftpFileType, OpenFlags},
 };
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use sha2::{Digest, Sha256};
 use strum::AsRefStr;
 use tauri::{
-  AppHandle, Runtime, State,
+  AppHandle, Runtime, State, async_runtime,
   ipc::{Channel, InvokeResponseBody, IpcResponse},
 };
 use tauri_plugin_fs::{FsExt, OpenOptions, SafeFilePath};
 use tokio::{
   fs,
-  io::{AsyncReadExt, AsyncWriteExt, BufWriter},
+  io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt, BufWriter, SeekFrom},
   time::timeout,
 };
 use uuid::Uuid;
 
 use crate::{
+  checksum::{self, HashReader, HashWriter, SftpVerifyOpts, Verifier},
   commands::session::SSHSessionId,
   error::{SSHError, SSHResult},
+  exec,
+  file_mode::{FileMode, MODE_MASK},
+  sftp_pipeline::{self, check_transfer_control},
+  sftp_raw::{RawSftp, expect_status_ok, open_raw_sftp, put_string},
   ssh_manager::{SSHManager, TransferControl},
+  transfer_filter::{GITIGNORE, TransferFilter},
 };
 
 #[derive(Debug, Clone, AsRefStr)]
@@ -44,6 +56,12 @@ fn body(self) -> tauri::Result<InvokeResponseBody> {
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Serialize, Deserialize)]
 pub struct SSHSftpId(Uuid);
 
+impl From<Uuid> for SSHSftpId {
+  fn from(value: Uuid) -> Self {
+    Self(value)
+  }
+}
+
 pub type SSHTransferId = String;
 
 pub struct SSHSftp {
@@ -52,6 +70,8 @@ pub struct SSHSftp {
   pub ssh_sftp_id: SSHSftpId,
   pub sftp_channel_id: ChannelId,
   pub sftp_session: Arc<SftpSession>,
+  /// `None` when the raw session couldn't be opened, the extensions are unused then
+  pub raw_sftp: Option<Arc<RawSftp>>,
   pub ipc_channel: Channel<SSHSftpIpcChannelData>,
 }
 
@@ -61,6 +81,7 @@ pub fn new(
     ssh_sftp_id: SSHSftpId,
     sftp_channel_id: ChannelId,
     sftp_session: SftpSession,
+    raw_sftp: Option<RawSftp>,
     ipc_channel: Channel<SSHSftpIpcChannelData>,
   ) -> Self {
     Self {
@@ -68,6 +89,7 @@ pub fn new(
       ssh_sftp_id,
       sftp_channel_id,
       sftp_session: Arc::new(sftp_session),
+      raw_sftp: raw_sftp.map(Arc::new),
       ipc_channel,
     }
   }
@@ -81,7 +103,7 @@ fn deref(&self) -> &Self::Target {
   }
 }
 
-async fn get_sftp_session<R: Runtime>(
+pub(crate) async fn get_sftp_session<R: Runtime>(
   ssh_manager: &SSHManager<R>,
   ssh_sftp_id: SSHSftpId,
 ) -> SSHResult<Arc<SftpSession>> {
@@ -90,6 +112,83 @@ async fn get_sftp_session<R: Runtime>(
   Ok(sftp.sftp_session.clone())
 }
 
+/// Returns the raw sftp session of the sftp channel, `None` when the server doesn't support
+/// `extension`.
+pub(crate) async fn get_raw_sftp<R: Runtime>(
+  ssh_manager: &SSHManager<R>,
+  ssh_sftp_id: SSHSftpId,
+  extension: &str,
+) -> SSHResult<Option<Arc<RawSftp>>> {
+  let sftps = ssh_manager.sftps.lock().await;
+  let sftp = sftps.get(&ssh_sftp_id).ok_or(SSHError::NotFoundSftp)?;
+  Ok(
+    sftp
+      .raw_sftp
+      .clone()
+      .filter(|raw_sftp| raw_sftp.supports(extension)),
+  )
+}
+
+/// Whether the server reads the two paths of `SSH_FXP_SYMLINK` in reverse order like OpenSSH's
+/// sftp-server, detected by its `@openssh.com` extensions.
+async fn is_symlink_reversed<R: Runtime>(
+  ssh_manager: &SSHManager<R>,
+  ssh_sftp_id: SSHSftpId,
+) -> SSHResult<bool> {
+  let sftps = ssh_manager.sftps.lock().await;
+  let sftp = sftps.get(&ssh_sftp_id).ok_or(SSHError::NotFoundSftp)?;
+  Ok(
+    sftp
+      .raw_sftp
+      .as_ref()
+      .is_some_and(|raw_sftp| raw_sftp.is_openssh()),
+  )
+}
+
+/// Creates a symlink at `link_path` pointing to `target`, the paths are sent in the order the
+/// server reads them.
+async fn create_symlink(
+  sftp: &SftpSession,
+  reversed: bool,
+  link_path: &str,
+  target: &str,
+) -> SSHResult<()> {
+  // OpenSSH 把目标路径放在前面，与草案的顺序相反
+  if reversed {
+    sftp.symlink(target, link_path).await?;
+  } else {
+    sftp.symlink(link_path, target).await?;
+  }
+  Ok(())
+}
+
+async fn get_ssh_session_id<R: Runtime>(
+  ssh_manager: &SSHManager<R>,
+  ssh_sftp_id: SSHSftpId,
+) -> SSHResult<SSHSessionId> {
+  let sftps = ssh_manager.sftps.lock().await;
+  let sftp = sftps.get(&ssh_sftp_id).ok_or(SSHError::NotFoundSftp)?;
+  Ok(sftp.ssh_session_id)
+}
+
+/// Returns the verifier of a transfer on the sftp channel, `None` when it isn't verified.
+pub(crate) async fn get_verifier<R: Runtime>(
+  ssh_manager: &SSHManager<R>,
+  ssh_sftp_id: SSHSftpId,
+  verify: Option<SftpVerifyOpts>,
+) -> SSHResult<Option<Verifier<'_, R>>> {
+  let Some(verify) = verify else {
+    return Ok(None);
+  };
+
+  Ok(Some(Verifier {
+    ssh_manager,
+    ssh_session_id: get_ssh_session_id(ssh_manager, ssh_sftp_id).await?,
+    ssh_sftp_id,
+    retries: verify.retries,
+  }))
+}
+
 #[tauri::command]
 pub async fn sftp_open<R: Runtime>(
   _app_handle: AppHandle<R>,
@@ -139,11 +238,21 @@ pub async fn sftp_open<R: Runtime>(
       sftp_channel_id
     );
 
+    // 扩展请求需要原始会话，打开时探测一次扩展，之后的请求复用
+    let raw_sftp = match open_raw_sftp(&ssh_manager, ssh_session_id).await {
+      Ok(raw_sftp) => Some(raw_sftp),
+      Err(err) => {
+        log::warn!("sftp open raw session error {:?}", err);
+        None
+      }
+    };
+
     let sftp = SSHSftp::new(
       ssh_session_id,
       ssh_sftp_id,
       sftp_channel_id,
       sftp_session,
+      raw_sftp,
       ipc_channel,
     );
 
@@ -205,12 +314,39 @@ pub struct SSHSftpFile {
   file_type: SSHSftpFileType,
   size: u64,
   permissions: String,
+  /// Permission, set-id and sticky bits
+  mode: u32,
   atime: u32,
   mtime: u32,
   uid: Option<u32>,
   user: Option<String>,
   gid: Option<u32>,
   group: Option<String>,
+  /// Target of a symlink as stored in the link
+  link_target: Option<String>,
+  /// Type of the file a symlink points to, `None` for broken links
+  target_type: Option<SSHSftpFileType>,
+}
+
+impl SSHSftpFile {
+  fn new(path: String, name: String, metadata: &FileAttributes) -> Self {
+    SSHSftpFile {
+      path,
+      name,
+      file_type: metadata.file_type().into(),
+      size: metadata.size.unwrap_or(0),
+      uid: metadata.uid,
+      user: metadata.user.clone(),
+      gid: metadata.gid,
+      group: metadata.group.clone(),
+      permissions: metadata.permissions().to_string(),
+      mode: metadata.permissions.unwrap_or(0) & MODE_MASK,
+      atime: metadata.atime.unwrap_or(0),
+      mtime: metadata.mtime.unwrap_or(0),
+      link_target: None,
+      target_type: None,
+    }
+  }
 }
 
 #[tauri::command]
@@ -222,84 +358,152 @@ pub async fn sftp_read_dir<R: Runtime>(
 ) -> SSHResult<Vec<SSHSftpFile>> {
   let sftp = get_sftp_session(&ssh_manager, ssh_sftp_id).await?;
   let read_dir = sftp.read_dir(&dirname).await?;
-  let files: Vec<SSHSftpFile> = read_dir
+  let mut files: Vec<SSHSftpFile> = read_dir
     .map(|file| {
-      let metadata = file.metadata();
       let name = file.file_name();
       let path = format!("{}/{}", dirname, name).replace("//", "/");
+      SSHSftpFile::new(path, name, &file.metadata())
+    })
+    .collect();
 
-      SSHSftpFile {
-        path,
-        name,
-        file_type: file.file_type().into(),
-        size: metadata.size.unwrap_or(0),
-        uid: metadata.uid,
-        user: metadata.user.clone(),
-        gid: metadata.gid,
-        group: metadata.group.clone(),
-        permissions: metadata.permissions().to_string(),
-        atime: metadata.atime.unwrap_or(0),
-        mtime: metadata.mtime.unwrap_or(0),
-      }
+  // 并发解析符号链接，目录中的链接较多时避免逐个往返
+  let links: Vec<_> = files
+    .iter()
+    .enumerate()
+    .filter(|(_, file)| file.file_type == SSHSftpFileType::Symlink)
+    .map(|(index, file)| {
+      let sftp = sftp.clone();
+      let path = file.path.clone();
+      (index, async_runtime::spawn(resolve_link(sftp, path)))
     })
     .collect();
+  for (index, link) in links {
+    let (link_target, target_type) = link.await?;
+    files[index].link_target = link_target;
+    files[index].target_type = target_type;
+  }
 
   Ok(files)
 }
 
+/// Returns the target of a symlink and the type of the file it points to, either is `None` when
+/// it can't be read, e.g. for a broken link.
+async fn resolve_link(
+  sftp: Arc<SftpSession>,
+  path: String,
+) -> (Option<String>, Option<SSHSftpFileType>) {
+  let link_target = sftp.read_link(path.as_str()).await.ok();
+  let target_type = sftp
+    .metadata(path.as_str())
+    .await
+    .ok()
+    .map(|metadata| metadata.file_type().into());
+  (link_target, target_type)
+}
+
 #[derive(Clone, Serialize, Deserialize)]
 pub struct SFTPProgressPayload {
-  progress: u64,
-  total: u64,
+  pub(crate) progress: u64,
+  pub(crate) total: u64,
 }
 
-async fn write_file<R, W>(
-  source_file: &mut R,
-  target_file: &mut W,
-  total: usize,
-  on_progress: Channel<SFTPProgressPayload>,
-  control: &TransferControl,
-) -> SSHResult<()>
+/// Bytes before the resume offset compared on both sides, a mismatch restarts the transfer.
+const RESUME_VERIFY_SIZE: u64 = 64 * 1024;
+
+/// Returns the offset a transfer can continue from, that is the size of the partial target when
+/// its tail matches the source, otherwise 0.
+async fn resume_offset<S, T>(
+  source_file: &mut S,
+  source_size: u64,
+  target_file: &mut T,
+  target_size: u64,
+) -> SSHResult<u64>
 where
-  R: AsyncReadExt + Unpin,
-  W: AsyncWriteExt + Unpin,
+  S: AsyncReadExt + AsyncSeekExt + Unpin,
+  T: AsyncReadExt + AsyncSeekExt + Unpin,
 {
-  let mut progress = 0;
-
-  let mut buffer = vec![0; 1024 * 1024];
+  if target_size == 0 || target_size > source_size {
+    return Ok(0);
+  }
 
-  loop {
-    if control.cancel.load(std::sync::atomic::Ordering::Relaxed) {
-      return Err(SSHError::TransferCancelled);
-    }
+  let verify_size = target_size.min(RESUME_VERIFY_SIZE);
+  let start = target_size - verify_size;
+  let mut source_tail = vec![0; verify_size as usize];
+  let mut target_tail = vec![0; verify_size as usize];
 
-    while control.pause.load(std::sync::atomic::Ordering::Relaxed) {
-      tokio::time::sleep(Duration::from_millis(200)).await;
-    }
+  source_file.seek(SeekFrom::Start(start)).await?;
+  source_file.read_exact(&mut source_tail).await?;
+  target_file.seek(SeekFrom::Start(start)).await?;
+  target_file.read_exact(&mut target_tail).await?;
 
-    if control.cancel.load(std::sync::atomic::Ordering::Relaxed) {
-      return Err(SSHError::TransferCancelled);
-    }
+  if source_tail != target_tail {
+    return Ok(0);
+  }
 
-    let size = source_file.read(&mut buffer).await?;
+  Ok(target_size)
+}
 
-    if size == 0 {
-      break;
+/// Truncates the remote file of an upload unless its content can be continued.
+async fn prepare_upload_target<S>(
+  sftp: &SftpSession,
+  remote_filename: &str,
+  local_file: &mut S,
+  total: u64,
+  resume: bool,
+) -> SSHResult<u64>
+where
+  S: AsyncReadExt + AsyncSeekExt + Unpin,
+{
+  if resume {
+    let mut remote_file = sftp
+      .open_with_flags(
+        remote_filename,
+        OpenFlags::READ | OpenFlags::WRITE | OpenFlags::CREATE,
+      )
+      .await?;
+    let remote_size = remote_file.metadata().await?.len();
+    // 并行写入的分块可能未按顺序完成，只信任末尾窗口之前的内容
+    let candidate = if remote_size > total {
+      0
     } else {
-      target_file.write_all(&buffer[..size]).await?;
-
-      progress += size as u64;
-      on_progress.send(SFTPProgressPayload {
-        progress,
-        total: total as u64,
-      })?;
+      remote_size.saturating_sub(sftp_pipeline::UPLOAD_RESUME_MARGIN)
+    };
+    let offset = resume_offset(local_file, total, &mut remote_file, candidate).await?;
+    if offset > 0 {
+      return Ok(offset);
     }
   }
 
-  Ok(())
+  sftp.create(remote_filename).await?;
+  Ok(0)
+}
+
+/// Truncates the local file of a download unless its content can be continued.
+async fn prepare_download_target<S>(
+  local_file: &mut fs::File,
+  remote_file: &mut S,
+  total: u64,
+  resume: bool,
+) -> SSHResult<u64>
+where
+  S: AsyncReadExt + AsyncSeekExt + Unpin,
+{
+  let offset = if resume {
+    let local_size = local_file.metadata().await?.len();
+    resume_offset(remote_file, total, local_file, local_size).await?
+  } else {
+    0
+  };
+
+  if offset == 0 {
+    local_file.set_len(0).await?;
+  }
+
+  Ok(offset)
 }
 
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn sftp_upload_file<R: Runtime>(
   app_handle: AppHandle<R>,
   ssh_manager: State<'_, SSHManager<R>>,
@@ -308,6 +512,10 @@ pub async fn sftp_upload_file<R: Runtime>(
   remote_filename: String,
   on_progress: Channel<SFTPProgressPayload>,
   task_id: Option<String>,
+  resume: Option<bool>,
+  concurrency: Option<usize>,
+  verify: Option<SftpVerifyOpts>,
+  check_space: Option<bool>,
 ) -> SSHResult<SSHTransferId> {
   let task_id = task_id.unwrap_or_else(|| Uuid::new_v4().to_string());
   let control = TransferControl::new();
@@ -325,6 +533,10 @@ pub async fn sftp_upload_file<R: Runtime>(
     remote_filename,
     on_progress,
     control_clone,
+    resume.unwrap_or(false),
+    sftp_pipeline::concurrency(concurrency),
+    verify,
+    check_space.unwrap_or(false),
   )
   .await;
 
@@ -336,6 +548,7 @@ pub async fn sftp_upload_file<R: Runtime>(
   result.map(|_| task_id)
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn upload_file_inner<R: Runtime>(
   app_handle: AppHandle<R>,
   ssh_manager: &SSHManager<R>,
@@ -344,29 +557,117 @@ async fn upload_file_inner<R: Runtime>(
   remote_filename: String,
   on_progress: Channel<SFTPProgressPayload>,
   control: TransferControl,
+  resume: bool,
+  concurrency: usize,
+  verify: Option<SftpVerifyOpts>,
+  check_space: bool,
 ) -> SSHResult<()> {
   let sftp = get_sftp_session(ssh_manager, ssh_sftp_id).await?;
-  let remote_file = sftp.create(remote_filename).await?;
+  let verifier = get_verifier(ssh_manager, ssh_sftp_id, verify).await?;
 
-  let mut local_file = fs::File::from_std(
+  let local_file = fs::File::from_std(
     app_handle
       .fs()
       .open(local_filename, OpenOptions::new().read(true).to_owned())?,
   );
 
-  let metadata = local_file.metadata().await?;
-  let total = metadata.len() as usize;
+  if check_space {
+    let mut required = local_file.metadata().await?.len();
+    // 续传只需要写入剩余部分
+    if resume && let Ok(metadata) = sftp.metadata(remote_filename.as_str()).await {
+      required = required.saturating_sub(metadata.size.unwrap_or(0));
+    }
+    ensure_space(ssh_manager, ssh_sftp_id, &remote_filename, required).await?;
+  }
 
-  let mut writer = BufWriter::new(remote_file);
+  upload_local_file(
+    sftp,
+    local_file,
+    &remote_filename,
+    resume,
+    concurrency,
+    &control,
+    verifier.as_ref(),
+    |progress, total| {
+      on_progress.send(SFTPProgressPayload { progress, total })?;
+      Ok(())
+    },
+  )
+  .await
+}
 
-  write_file(&mut local_file, &mut writer, total, on_progress, &control).await?;
+/// Uploads an opened local file, `on_progress` receives the bytes transferred and the file size.
+/// With a verifier the SHA-256 is computed while streaming and compared with the remote file.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn upload_local_file<R, F>(
+  sftp: Arc<SftpSession>,
+  mut local_file: fs::File,
+  remote_filename: &str,
+  mut resume: bool,
+  concurrency: usize,
+  control: &TransferControl,
+  verifier: Option<&Verifier<'_, R>>,
+  mut on_progress: F,
+) -> SSHResult<()>
+where
+  R: Runtime,
+  F: FnMut(u64, u64) -> SSHResult<()>,
+{
+  let metadata = local_file.metadata().await?;
+  let total = metadata.len();
+  let mut attempt = 0;
 
-  writer.flush().await?;
+  loop {
+    let offset =
+      prepare_upload_target(&sftp, remote_filename, &mut local_file, total, resume).await?;
+    log::info!(
+      "sftp upload file {} from offset {}",
+      remote_filename,
+      offset
+    );
 
-  Ok(())
+    // 续传时已传输的部分不再经过管道，先单独计算摘要
+    let mut hasher = verifier.map(|_| Sha256::new());
+    if let Some(hasher) = &mut hasher {
+      local_file.seek(SeekFrom::Start(0)).await?;
+      checksum::hash_prefix(&mut local_file, offset, hasher).await?;
+    } else {
+      local_file.seek(SeekFrom::Start(offset)).await?;
+    }
+    on_progress(offset, total)?;
+
+    let mut source = HashReader::new(&mut local_file, hasher);
+    sftp_pipeline::upload(
+      sftp.clone(),
+      remote_filename,
+      offset,
+      total,
+      &mut source,
+      concurrency,
+      control,
+      |progress| on_progress(progress, total),
+    )
+    .await?;
+
+    let (Some(verifier), Some(local)) = (verifier, source.finalize()) else {
+      return Ok(());
+    };
+    match verifier.verify(remote_filename, &local).await {
+      Err(SSHError::ChecksumMismatch { .. }) if attempt < verifier.retries => {
+        log::warn!(
+          "sftp upload file {} checksum mismatch, retry",
+          remote_filename
+        );
+        attempt += 1;
+        resume = false;
+      }
+      result => return result,
+    }
+  }
 }
 
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn sftp_download_file<R: Runtime>(
   app_handle: AppHandle<R>,
   ssh_manager: State<'_, SSHManager<R>>,
@@ -375,6 +676,9 @@ pub async fn sftp_download_file<R: Runtime>(
   remote_filename: String,
   on_progress: Channel<SFTPProgressPayload>,
   task_id: Option<String>,
+  resume: Option<bool>,
+  concurrency: Option<usize>,
+  verify: Option<SftpVerifyOpts>,
 ) -> SSHResult<SSHTransferId> {
   let task_id = task_id.unwrap_or_else(|| Uuid::new_v4().to_string());
   let control = TransferControl::new();
@@ -392,6 +696,9 @@ pub async fn sftp_download_file<R: Runtime>(
     remote_filename,
     on_progress,
     control_clone,
+    resume.unwrap_or(false),
+    sftp_pipeline::concurrency(concurrency),
+    verify,
   )
   .await;
 
@@ -403,6 +710,7 @@ pub async fn sftp_download_file<R: Runtime>(
   result.map(|_| task_id)
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn download_file_inner<R: Runtime>(
   app_handle: AppHandle<R>,
   ssh_manager: &SSHManager<R>,
@@ -411,145 +719,1752 @@ async fn download_file_inner<R: Runtime>(
   remote_filename: String,
   on_progress: Channel<SFTPProgressPayload>,
   control: TransferControl,
+  resume: bool,
+  concurrency: usize,
+  verify: Option<SftpVerifyOpts>,
 ) -> SSHResult<()> {
   let sftp = get_sftp_session(ssh_manager, ssh_sftp_id).await?;
-  let mut remote_file = sftp.open(remote_filename).await?;
+  let verifier = get_verifier(ssh_manager, ssh_sftp_id, verify).await?;
 
+  // 续传时保留已下载的内容，由 prepare_download_target 决定是否截断
   let local_file = fs::File::from_std(
     app_handle.fs().open(
       local_filename,
       OpenOptions::new()
         .create(true)
+        .read(true)
         .write(true)
-        .truncate(true)
+        .truncate(false)
         .to_owned(),
     )?,
   );
 
-  let metadata = remote_file.metadata().await?;
-  let total = metadata.len() as usize;
+  download_remote_file(
+    sftp,
+    &remote_filename,
+    local_file,
+    resume,
+    concurrency,
+    &control,
+    verifier.as_ref(),
+    |progress, total| {
+      on_progress.send(SFTPProgressPayload { progress, total })?;
+      Ok(())
+    },
+  )
+  .await
+}
 
-  let mut writer = BufWriter::new(local_file);
+/// Downloads into an opened local file, `on_progress` receives the bytes transferred and the
+/// file size. With a verifier the SHA-256 is computed while streaming and compared with the
+/// remote file.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn download_remote_file<R, F>(
+  sftp: Arc<SftpSession>,
+  remote_filename: &str,
+  mut local_file: fs::File,
+  mut resume: bool,
+  concurrency: usize,
+  control: &TransferControl,
+  verifier: Option<&Verifier<'_, R>>,
+  mut on_progress: F,
+) -> SSHResult<()>
+where
+  R: Runtime,
+  F: FnMut(u64, u64) -> SSHResult<()>,
+{
+  let mut attempt = 0;
 
-  write_file(&mut remote_file, &mut writer, total, on_progress, &control).await?;
+  loop {
+    let mut remote_file = sftp.open(remote_filename).await?;
+    let metadata = remote_file.metadata().await?;
+    let total = metadata.len();
 
-  writer.flush().await?;
+    let offset = prepare_download_target(&mut local_file, &mut remote_file, total, resume).await?;
+    log::info!(
+      "sftp download file {} from offset {}",
+      remote_filename,
+      offset
+    );
 
-  Ok(())
+    // 续传时已下载的部分不再经过管道，先单独计算摘要
+    let mut hasher = verifier.map(|_| Sha256::new());
+    if let Some(hasher) = &mut hasher {
+      local_file.seek(SeekFrom::Start(0)).await?;
+      checksum::hash_prefix(&mut local_file, offset, hasher).await?;
+    } else {
+      local_file.seek(SeekFrom::Start(offset)).await?;
+    }
+    on_progress(offset, total)?;
+
+    let mut writer = HashWriter::new(BufWriter::new(&mut local_file), hasher);
+    sftp_pipeline::download(
+      sftp.clone(),
+      remote_filename,
+      offset,
+      total,
+      &mut writer,
+      concurrency,
+      control,
+      |progress| on_progress(progress, total),
+    )
+    .await?;
+    writer.flush().await?;
+
+    let (Some(verifier), Some(local)) = (verifier, writer.finalize()) else {
+      return Ok(());
+    };
+    match verifier.verify(remote_filename, &local).await {
+      Err(SSHError::ChecksumMismatch { .. }) if attempt < verifier.retries => {
+        log::warn!(
+          "sftp download file {} checksum mismatch, retry",
+          remote_filename
+        );
+        attempt += 1;
+        resume = false;
+      }
+      result => return result,
+    }
+  }
 }
 
-#[tauri::command]
-pub async fn sftp_cancel_task<R: Runtime>(
-  _app_handle: AppHandle<R>,
-  ssh_manager: State<'_, SSHManager<R>>,
-  task_id: String,
-) -> SSHResult<()> {
-  let controls = ssh_manager.transfer_controls.lock().await;
-  if let Some(control) = controls.get(&task_id) {
-    control
-      .cancel
-      .store(true, std::sync::atomic::Ordering::Relaxed);
-    control
-      .pause
-      .store(false, std::sync::atomic::Ordering::Relaxed);
-  }
-  Ok(())
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SftpDirTransferOpts {
+  /// Patterns with the `.gitignore` syntax, e.g. `node_modules/` or `*.log`
+  #[serde(default)]
+  pub excludes: Vec<String>,
+  /// Also applies the `.gitignore` files found in the source tree
+  #[serde(default)]
+  pub gitignore: bool,
+  /// Continues partially transferred files instead of overwriting them
+  #[serde(default)]
+  pub resume: bool,
+  /// Chunk requests in flight per file
+  pub concurrency: Option<usize>,
+  /// Compares the SHA-256 of every file with the remote side after it is transferred
+  pub verify: Option<SftpVerifyOpts>,
+  /// Fails before an upload starts when the remote file system lacks the space for all files
+  #[serde(default)]
+  pub check_space: bool,
 }
 
-#[tauri::command]
-pub async fn sftp_pause_task<R: Runtime>(
-  _app_handle: AppHandle<R>,
-  ssh_manager: State<'_, SSHManager<R>>,
-  task_id: String,
-) -> SSHResult<()> {
-  let controls = ssh_manager.transfer_controls.lock().await;
-  if let Some(control) = controls.get(&task_id) {
-    control
-      .pause
-      .store(true, std::sync::atomic::Ordering::Relaxed);
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SFTPDirProgressPayload {
+  files: u64,
+  total_files: u64,
+  progress: u64,
+  total: u64,
+  /// File being transferred, relative to the directory
+  current: String,
+}
+
+struct TransferEntry {
+  /// Relative to the transfer root and separated by `/`
+  path: String,
+  is_dir: bool,
+  size: u64,
+}
+
+/// Aggregates the progress of all files of a directory transfer.
+struct DirProgress {
+  payload: SFTPDirProgressPayload,
+  on_progress: Channel<SFTPDirProgressPayload>,
+}
+
+impl DirProgress {
+  fn new(entries: &[TransferEntry], on_progress: Channel<SFTPDirProgressPayload>) -> Self {
+    let files = entries.iter().filter(|entry| !entry.is_dir);
+    Self::with_totals(
+      files.clone().count() as u64,
+      files.map(|entry| entry.size).sum(),
+      on_progress,
+    )
+  }
+
+  fn with_totals(
+    total_files: u64,
+    total: u64,
+    on_progress: Channel<SFTPDirProgressPayload>,
+  ) -> Self {
+    Self {
+      payload: SFTPDirProgressPayload {
+        files: 0,
+        total_files,
+        progress: 0,
+        total,
+        current: String::new(),
+      },
+      on_progress,
+    }
+  }
+
+  fn send(&self, file_progress: u64) -> SSHResult<()> {
+    let mut payload = self.payload.clone();
+    payload.progress += file_progress;
+    self.on_progress.send(payload)?;
+    Ok(())
+  }
+
+  fn start_file(&mut self, path: &str) -> SSHResult<()> {
+    self.payload.current = path.to_string();
+    self.send(0)
+  }
+
+  fn finish_file(&mut self, size: u64) -> SSHResult<()> {
+    self.payload.files += 1;
+    self.payload.progress += size;
+    self.send(0)
   }
-  Ok(())
 }
 
-#[tauri::command]
-pub async fn sftp_resume_task<R: Runtime>(
-  _app_handle: AppHandle<R>,
-  ssh_manager: State<'_, SSHManager<R>>,
-  task_id: String,
-) -> SSHResult<()> {
-  let controls = ssh_manager.transfer_controls.lock().await;
-  if let Some(control) = controls.get(&task_id) {
-    control
-      .pause
-      .store(false, std::sync::atomic::Ordering::Relaxed);
+fn join_remote_path(root: &str, relative_path: &str) -> String {
+  if relative_path.is_empty() {
+    return root.to_string();
   }
-  Ok(())
+  format!("{}/{}", root.trim_end_matches('/'), relative_path)
 }
 
-#[tauri::command]
-pub async fn sftp_create_file<R: Runtime>(
-  _app_handle: AppHandle<R>,
-  ssh_manager: State<'_, SSHManager<R>>,
-  ssh_sftp_id: SSHSftpId,
-  filename: String,
-) -> SSHResult<SSHSftpId> {
-  let sftp = get_sftp_session(&ssh_manager, ssh_sftp_id).await?;
-  sftp.create(filename).await?;
+fn join_local_path(root: &Path, relative_path: &str) -> PathBuf {
+  let mut path = root.to_path_buf();
+  path.extend(relative_path.split('/').filter(|name| !name.is_empty()));
+  path
+}
 
-  Ok(ssh_sftp_id)
+fn join_relative_path(dirname: &str, name: &str) -> String {
+  if dirname.is_empty() {
+    return name.to_string();
+  }
+  format!("{}/{}", dirname, name)
 }
 
-#[tauri::command]
-pub async fn sftp_create_dir<R: Runtime>(
-  _app_handle: AppHandle<R>,
-  ssh_manager: State<'_, SSHManager<R>>,
-  ssh_sftp_id: SSHSftpId,
-  dirname: String,
-) -> SSHResult<SSHSftpId> {
-  let sftp = get_sftp_session(&ssh_manager, ssh_sftp_id).await?;
-  sftp.create_dir(dirname).await?;
+/// Lists the local tree depth first, directories come before their children.
+async fn walk_local_dir(root: &Path, opts: &SftpDirTransferOpts) -> SSHResult<Vec<TransferEntry>> {
+  let mut filter = TransferFilter::new(&opts.excludes)?;
+  let mut entries = Vec::new();
+  let mut pending = vec![String::new()];
+
+  while let Some(relative_dirname) = pending.pop() {
+    let dirname = join_local_path(root, &relative_dirname);
+    if opts.gitignore {
+      let gitignore = dirname.join(GITIGNORE);
+      if fs::try_exists(&gitignore).await? {
+        let content = fs::read_to_string(&gitignore).await?;
+        filter.add_gitignore(&content, &relative_dirname)?;
+      }
+    }
 
-  Ok(ssh_sftp_id)
+    let mut read_dir = fs::read_dir(&dirname).await?;
+    while let Some(dir_entry) = read_dir.next_entry().await? {
+      let name = dir_entry.file_name().to_string_lossy().into_owned();
+      let path = join_relative_path(&relative_dirname, &name);
+      let file_type = dir_entry.file_type().await?;
+
+      // 符号链接与特殊文件不参与目录传输
+      if file_type.is_dir() {
+        if !filter.is_excluded(&path, true) {
+          pending.push(path.clone());
+          entries.push(TransferEntry {
+            path,
+            is_dir: true,
+            size: 0,
+          });
+        }
+      } else if file_type.is_file() {
+        if !filter.is_excluded(&path, false) {
+          let size = dir_entry.metadata().await?.len();
+          entries.push(TransferEntry {
+            path,
+            is_dir: false,
+            size,
+          });
+        }
+      } else {
+        log::info!("sftp walk local dir skip {:?}", path);
+      }
+    }
+  }
+
+  Ok(entries)
 }
 
-#[tauri::command]
-pub async fn sftp_remove_dir<R: Runtime>(
-  _app_handle: AppHandle<R>,
-  ssh_manager: State<'_, SSHManager<R>>,
-  ssh_sftp_id: SSHSftpId,
-  dirname: String,
-) -> SSHResult<SSHSftpId> {
-  let sftp = get_sftp_session(&ssh_manager, ssh_sftp_id).await?;
-  sftp.remove_dir(dirname).await?;
+/// Lists the remote tree depth first, directories come before their children.
+async fn walk_remote_dir(
+  sftp: &SftpSession,
+  root: &str,
+  opts: &SftpDirTransferOpts,
+) -> SSHResult<Vec<TransferEntry>> {
+  let mut filter = TransferFilter::new(&opts.excludes)?;
+  let mut entries = Vec::new();
+  let mut pending = vec![String::new()];
+
+  while let Some(relative_dirname) = pending.pop() {
+    let dirname = join_remote_path(root, &relative_dirname);
+    let dir_entries: Vec<_> = sftp
+      .read_dir(&dirname)
+      .await?
+      .filter(|dir_entry| !matches!(dir_entry.file_name().as_str(), "." | ".."))
+      .collect();
+
+    if opts.gitignore
+      && dir_entries.iter().any(|dir_entry| {
+        dir_entry.file_name() == GITIGNORE
+          && matches!(dir_entry.file_type(), RusshSftpFileType::File)
+      })
+    {
+      let mut content = String::new();
+      sftp
+        .open(join_remote_path(&dirname, GITIGNORE))
+        .await?
+        .read_to_string(&mut content)
+        .await?;
+      filter.add_gitignore(&content, &relative_dirname)?;
+    }
 
-  Ok(ssh_sftp_id)
+    for dir_entry in dir_entries {
+      let path = join_relative_path(&relative_dirname, &dir_entry.file_name());
+      match dir_entry.file_type() {
+        RusshSftpFileType::Dir => {
+          if !filter.is_excluded(&path, true) {
+            pending.push(path.clone());
+            entries.push(TransferEntry {
+              path,
+              is_dir: true,
+              size: 0,
+            });
+          }
+        }
+        RusshSftpFileType::File => {
+          if !filter.is_excluded(&path, false) {
+            entries.push(TransferEntry {
+              path,
+              is_dir: false,
+              size: dir_entry.metadata().size.unwrap_or(0),
+            });
+          }
+        }
+        _ => log::info!("sftp walk remote dir skip {:?}", path),
+      }
+    }
+  }
+
+  Ok(entries)
 }
 
-#[tauri::command]
-pub async fn sftp_remove_file<R: Runtime>(
-  _app_handle: AppHandle<R>,
-  ssh_manager: State<'_, SSHManager<R>>,
-  ssh_sftp_id: SSHSftpId,
-  filename: String,
-) -> SSHResult<SSHSftpId> {
-  let sftp = get_sftp_session(&ssh_manager, ssh_sftp_id).await?;
-  sftp.remove_file(filename).await?;
+/// Returns the bytes of the files of `entries` already present under `remote_dirname`, that is
+/// what a resumed upload doesn't write again. Targets larger than their source are rewritten.
+async fn remote_present_bytes(
+  sftp: &Arc<SftpSession>,
+  remote_dirname: &str,
+  entries: &[TransferEntry],
+) -> SSHResult<u64> {
+  let sizes: Vec<_> = entries
+    .iter()
+    .filter(|entry| !entry.is_dir)
+    .map(|entry| {
+      let sftp = sftp.clone();
+      let remote_path = join_remote_path(remote_dirname, &entry.path);
+      let size = entry.size;
+      async_runtime::spawn(async move {
+        let remote_size = sftp
+          .metadata(remote_path)
+          .await
+          .ok()
+          .and_then(|metadata| metadata.size)
+          .unwrap_or(0);
+        if remote_size <= size { remote_size } else { 0 }
+      })
+    })
+    .collect();
 
-  Ok(ssh_sftp_id)
+  let mut present = 0u64;
+  for size in sizes {
+    present += size.await?;
+  }
+  Ok(present)
+}
+
+async fn create_remote_dir(sftp: &SftpSession, dirname: &str) -> SSHResult<()> {
+  if !sftp.try_exists(dirname).await? {
+    sftp.create_dir(dirname).await?;
+  }
+  Ok(())
 }
 
+/// Uploads a local directory recursively, the progress of all files is reported on `on_progress`.
 #[tauri::command]
-pub async fn sftp_rename<R: Runtime>(
+#[allow(clippy::too_many_arguments)]
+pub async fn sftp_upload_dir<R: Runtime>(
   _app_handle: AppHandle<R>,
   ssh_manager: State<'_, SSHManager<R>>,
   ssh_sftp_id: SSHSftpId,
-  old_path: String,
-  new_path: String,
-) -> SSHResult<SSHSftpId> {
-  let sftp = get_sftp_session(&ssh_manager, ssh_sftp_id).await?;
-  sftp.rename(old_path, new_path).await?;
+  local_dirname: SafeFilePath,
+  remote_dirname: String,
+  opts: Option<SftpDirTransferOpts>,
+  on_progress: Channel<SFTPDirProgressPayload>,
+  task_id: Option<String>,
+) -> SSHResult<SSHTransferId> {
+  let task_id = task_id.unwrap_or_else(|| Uuid::new_v4().to_string());
+  let control = TransferControl::new();
+  let control_clone = control.clone();
+  {
+    let mut controls = ssh_manager.transfer_controls.lock().await;
+    controls.insert(task_id.clone(), control);
+  }
 
-  Ok(ssh_sftp_id)
+  let result = upload_dir_inner(
+    &ssh_manager,
+    ssh_sftp_id,
+    local_dirname,
+    remote_dirname,
+    opts.unwrap_or_default(),
+    on_progress,
+    control_clone,
+  )
+  .await;
+
+  {
+    let mut controls = ssh_manager.transfer_controls.lock().await;
+    controls.remove(&task_id);
+  }
+
+  result.map(|_| task_id)
+}
+
+async fn upload_dir_inner<R: Runtime>(
+  ssh_manager: &SSHManager<R>,
+  ssh_sftp_id: SSHSftpId,
+  local_dirname: SafeFilePath,
+  remote_dirname: String,
+  opts: SftpDirTransferOpts,
+  on_progress: Channel<SFTPDirProgressPayload>,
+  control: TransferControl,
+) -> SSHResult<()> {
+  let sftp = get_sftp_session(ssh_manager, ssh_sftp_id).await?;
+  let local_dirname = local_dirname
+    .into_path()
+    .map_err(|err| SSHError::new(err.to_string()))?;
+
+  let entries = walk_local_dir(&local_dirname, &opts).await?;
+  log::info!(
+    "sftp upload dir {:?} -> {} {} entries",
+    local_dirname,
+    remote_dirname,
+    entries.len()
+  );
+
+  if opts.check_space {
+    let mut required: u64 = entries.iter().map(|entry| entry.size).sum();
+    // 续传只需要写入各文件剩余的部分
+    if opts.resume {
+      let present = remote_present_bytes(&sftp, &remote_dirname, &entries).await?;
+      required = required.saturating_sub(present);
+    }
+    ensure_space(ssh_manager, ssh_sftp_id, &remote_dirname, required).await?;
+  }
+
+  let concurrency = sftp_pipeline::concurrency(opts.concurrency);
+  let verifier = get_verifier(ssh_manager, ssh_sftp_id, opts.verify).await?;
+  let mut progress = DirProgress::new(&entries, on_progress);
+  progress.send(0)?;
+  create_remote_dir(&sftp, &remote_dirname).await?;
+
+  for entry in &entries {
+    check_transfer_control(&control).await?;
+
+    let remote_path = join_remote_path(&remote_dirname, &entry.path);
+    if entry.is_dir {
+      create_remote_dir(&sftp, &remote_path).await?;
+      continue;
+    }
+
+    progress.start_file(&entry.path)?;
+    let local_file = fs::File::open(join_local_path(&local_dirname, &entry.path)).await?;
+    upload_local_file(
+      sftp.clone(),
+      local_file,
+      &remote_path,
+      opts.resume,
+      concurrency,
+      &control,
+      verifier.as_ref(),
+      |file_progress, _| progress.send(file_progress),
+    )
+    .await?;
+    progress.finish_file(entry.size)?;
+  }
+
+  Ok(())
+}
+
+/// Downloads a remote directory recursively, the progress of all files is reported on `on_progress`.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn sftp_download_dir<R: Runtime>(
+  _app_handle: AppHandle<R>,
+  ssh_manager: State<'_, SSHManager<R>>,
+  ssh_sftp_id: SSHSftpId,
+  local_dirname: SafeFilePath,
+  remote_dirname: String,
+  opts: Option<SftpDirTransferOpts>,
+  on_progress: Channel<SFTPDirProgressPayload>,
+  task_id: Option<String>,
+) -> SSHResult<SSHTransferId> {
+  let task_id = task_id.unwrap_or_else(|| Uuid::new_v4().to_string());
+  let control = TransferControl::new();
+  let control_clone = control.clone();
+  {
+    let mut controls = ssh_manager.transfer_controls.lock().await;
+    controls.insert(task_id.clone(), control);
+  }
+
+  let result = download_dir_inner(
+    &ssh_manager,
+    ssh_sftp_id,
+    local_dirname,
+    remote_dirname,
+    opts.unwrap_or_default(),
+    on_progress,
+    control_clone,
+  )
+  .await;
+
+  {
+    let mut controls = ssh_manager.transfer_controls.lock().await;
+    controls.remove(&task_id);
+  }
+
+  result.map(|_| task_id)
+}
+
+async fn download_dir_inner<R: Runtime>(
+  ssh_manager: &SSHManager<R>,
+  ssh_sftp_id: SSHSftpId,
+  local_dirname: SafeFilePath,
+  remote_dirname: String,
+  opts: SftpDirTransferOpts,
+  on_progress: Channel<SFTPDirProgressPayload>,
+  control: TransferControl,
+) -> SSHResult<()> {
+  let sftp = get_sftp_session(ssh_manager, ssh_sftp_id).await?;
+  let local_dirname = local_dirname
+    .into_path()
+    .map_err(|err| SSHError::new(err.to_string()))?;
+
+  let entries = walk_remote_dir(&sftp, &remote_dirname, &opts).await?;
+  log::info!(
+    "sftp download dir {} -> {:?} {} entries",
+    remote_dirname,
+    local_dirname,
+    entries.len()
+  );
+
+  let concurrency = sftp_pipeline::concurrency(opts.concurrency);
+  let verifier = get_verifier(ssh_manager, ssh_sftp_id, opts.verify).await?;
+  let mut progress = DirProgress::new(&entries, on_progress);
+  progress.send(0)?;
+  fs::create_dir_all(&local_dirname).await?;
+
+  for entry in &entries {
+    check_transfer_control(&control).await?;
+
+    let local_path = join_local_path(&local_dirname, &entry.path);
+    if entry.is_dir {
+      fs::create_dir_all(&local_path).await?;
+      continue;
+    }
+
+    progress.start_file(&entry.path)?;
+    let remote_path = join_remote_path(&remote_dirname, &entry.path);
+    let local_file = fs::OpenOptions::new()
+      .create(true)
+      .read(true)
+      .write(true)
+      .truncate(false)
+      .open(local_path)
+      .await?;
+    download_remote_file(
+      sftp.clone(),
+      &remote_path,
+      local_file,
+      opts.resume,
+      concurrency,
+      &control,
+      verifier.as_ref(),
+      |file_progress, _| progress.send(file_progress),
+    )
+    .await?;
+    progress.finish_file(entry.size)?;
+  }
+
+  Ok(())
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SftpRemoteTransferOpts {
+  /// Gitignore style patterns relative to the source directory
+  #[serde(default)]
+  pub excludes: Vec<String>,
+  /// Also skips what `.gitignore` files in the source tree ignore
+  #[serde(default)]
+  pub gitignore: bool,
+  /// Chunk requests in flight on each side per file
+  pub concurrency: Option<usize>,
+}
+
+/// Streams a file or directory from one sftp channel to another, possibly on another host, without
+/// a temporary file. The progress of all files is reported on `on_progress`.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn sftp_remote_transfer<R: Runtime>(
+  _app_handle: AppHandle<R>,
+  ssh_manager: State<'_, SSHManager<R>>,
+  source_ssh_sftp_id: SSHSftpId,
+  source_path: String,
+  target_ssh_sftp_id: SSHSftpId,
+  target_path: String,
+  opts: Option<SftpRemoteTransferOpts>,
+  on_progress: Channel<SFTPDirProgressPayload>,
+  task_id: Option<String>,
+) -> SSHResult<SSHTransferId> {
+  let task_id = task_id.unwrap_or_else(|| Uuid::new_v4().to_string());
+  let ssh_manager = &*ssh_manager;
+  with_transfer_control(ssh_manager, Some(task_id.clone()), |control| async move {
+    remote_transfer_inner(
+      ssh_manager,
+      source_ssh_sftp_id,
+      &source_path,
+      target_ssh_sftp_id,
+      &target_path,
+      opts.unwrap_or_default(),
+      on_progress,
+      &control,
+    )
+    .await
+  })
+  .await?;
+
+  Ok(task_id)
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn remote_transfer_inner<R: Runtime>(
+  ssh_manager: &SSHManager<R>,
+  source_ssh_sftp_id: SSHSftpId,
+  source_path: &str,
+  target_ssh_sftp_id: SSHSftpId,
+  target_path: &str,
+  opts: SftpRemoteTransferOpts,
+  on_progress: Channel<SFTPDirProgressPayload>,
+  control: &TransferControl,
+) -> SSHResult<()> {
+  let source_sftp = get_sftp_session(ssh_manager, source_ssh_sftp_id).await?;
+  let target_sftp = get_sftp_session(ssh_manager, target_ssh_sftp_id).await?;
+
+  let metadata = source_sftp.metadata(source_path).await?;
+  let is_dir = metadata.is_dir();
+  let entries = if is_dir {
+    let walk_opts = SftpDirTransferOpts {
+      excludes: opts.excludes,
+      gitignore: opts.gitignore,
+      ..Default::default()
+    };
+    walk_remote_dir(&source_sftp, source_path, &walk_opts).await?
+  } else {
+    vec![TransferEntry {
+      path: String::new(),
+      is_dir: false,
+      size: metadata.size.unwrap_or(0),
+    }]
+  };
+  log::info!(
+    "sftp remote transfer {:?} {} -> {:?} {} {} entries",
+    source_ssh_sftp_id,
+    source_path,
+    target_ssh_sftp_id,
+    target_path,
+    entries.len()
+  );
+
+  let concurrency = sftp_pipeline::concurrency(opts.concurrency);
+  let mut progress = DirProgress::new(&entries, on_progress);
+  progress.send(0)?;
+  if is_dir {
+    create_remote_dir(&target_sftp, target_path).await?;
+  }
+
+  for entry in &entries {
+    check_transfer_control(control).await?;
+
+    let source = join_remote_path(source_path, &entry.path);
+    let target = join_remote_path(target_path, &entry.path);
+    if entry.is_dir {
+      create_remote_dir(&target_sftp, &target).await?;
+      continue;
+    }
+
+    progress.start_file(&entry.path)?;
+    target_sftp.create(target.as_str()).await?;
+    sftp_pipeline::copy(
+      source_sftp.clone(),
+      &source,
+      target_sftp.clone(),
+      &target,
+      entry.size,
+      concurrency,
+      control,
+      |file_progress| progress.send(file_progress),
+    )
+    .await?;
+    progress.finish_file(entry.size)?;
+  }
+
+  Ok(())
+}
+
+#[tauri::command]
+pub async fn sftp_cancel_task<R: Runtime>(
+  _app_handle: AppHandle<R>,
+  ssh_manager: State<'_, SSHManager<R>>,
+  task_id: String,
+) -> SSHResult<()> {
+  let controls = ssh_manager.transfer_controls.lock().await;
+  if let Some(control) = controls.get(&task_id) {
+    control
+      .cancel
+      .store(true, std::sync::atomic::Ordering::Relaxed);
+    control
+      .pause
+      .store(false, std::sync::atomic::Ordering::Relaxed);
+  }
+  Ok(())
+}
+
+#[tauri::command]
+pub async fn sftp_pause_task<R: Runtime>(
+  _app_handle: AppHandle<R>,
+  ssh_manager: State<'_, SSHManager<R>>,
+  task_id: String,
+) -> SSHResult<()> {
+  let controls = ssh_manager.transfer_controls.lock().await;
+  if let Some(control) = controls.get(&task_id) {
+    control
+      .pause
+      .store(true, std::sync::atomic::Ordering::Relaxed);
+  }
+  Ok(())
+}
+
+#[tauri::command]
+pub async fn sftp_resume_task<R: Runtime>(
+  _app_handle: AppHandle<R>,
+  ssh_manager: State<'_, SSHManager<R>>,
+  task_id: String,
+) -> SSHResult<()> {
+  let controls = ssh_manager.transfer_controls.lock().await;
+  if let Some(control) = controls.get(&task_id) {
+    control
+      .pause
+      .store(false, std::sync::atomic::Ordering::Relaxed);
+  }
+  Ok(())
+}
+
+#[tauri::command]
+pub async fn sftp_create_file<R: Runtime>(
+  _app_handle: AppHandle<R>,
+  ssh_manager: State<'_, SSHManager<R>>,
+  ssh_sftp_id: SSHSftpId,
+  filename: String,
+) -> SSHResult<SSHSftpId> {
+  let sftp = get_sftp_session(&ssh_manager, ssh_sftp_id).await?;
+  sftp.create(filename).await?;
+
+  Ok(ssh_sftp_id)
+}
+
+#[tauri::command]
+pub async fn sftp_create_dir<R: Runtime>(
+  _app_handle: AppHandle<R>,
+  ssh_manager: State<'_, SSHManager<R>>,
+  ssh_sftp_id: SSHSftpId,
+  dirname: String,
+) -> SSHResult<SSHSftpId> {
+  let sftp = get_sftp_session(&ssh_manager, ssh_sftp_id).await?;
+  sftp.create_dir(dirname).await?;
+
+  Ok(ssh_sftp_id)
+}
+
+#[tauri::command]
+pub async fn sftp_remove_dir<R: Runtime>(
+  _app_handle: AppHandle<R>,
+  ssh_manager: State<'_, SSHManager<R>>,
+  ssh_sftp_id: SSHSftpId,
+  dirname: String,
+) -> SSHResult<SSHSftpId> {
+  let sftp = get_sftp_session(&ssh_manager, ssh_sftp_id).await?;
+  sftp.remove_dir(dirname).await?;
+
+  Ok(ssh_sftp_id)
+}
+
+#[tauri::command]
+pub async fn sftp_remove_file<R: Runtime>(
+  _app_handle: AppHandle<R>,
+  ssh_manager: State<'_, SSHManager<R>>,
+  ssh_sftp_id: SSHSftpId,
+  filename: String,
+) -> SSHResult<SSHSftpId> {
+  let sftp = get_sftp_session(&ssh_manager, ssh_sftp_id).await?;
+  sftp.remove_file(filename).await?;
+
+  Ok(ssh_sftp_id)
+}
+
+/// Extension of OpenSSH copying between two open handles on the server
+const COPY_DATA_EXTENSION: &str = "copy-data";
+
+/// Bytes copied by a single `copy-data` request, keeps requests short and allows progress and
+/// cancellation between them.
+const COPY_DATA_CHUNK_SIZE: u64 = 64 * 1024 * 1024;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SSHSftpFailure {
+  path: String,
+  error: String,
+}
+
+impl SSHSftpFailure {
+  fn new(path: impl Into<String>, error: impl ToString) -> Self {
+    Self {
+      path: path.into(),
+      error: error.to_string(),
+    }
+  }
+}
+
+/// Entry of a remote tree copied or removed as a whole, unlike transfers symlinks and special
+/// files are included.
+struct TreeEntry {
+  /// Relative to the tree root and separated by `/`, empty for the root
+  path: String,
+  file_type: SSHSftpFileType,
+  attrs: FileAttributes,
+}
+
+/// Lists the remote tree depth first without following symlinks, directories come before their
+/// children. Directories that can't be read are added to `failures`.
+async fn walk_remote_tree(
+  sftp: &SftpSession,
+  root: &str,
+  failures: &mut Vec<SSHSftpFailure>,
+) -> Vec<TreeEntry> {
+  let mut entries = Vec::new();
+  let mut pending = vec![String::new()];
+
+  while let Some(relative_dirname) = pending.pop() {
+    let dirname = join_remote_path(root, &relative_dirname);
+    let read_dir = match sftp.read_dir(&dirname).await {
+      Ok(read_dir) => read_dir,
+      Err(err) => {
+        failures.push(SSHSftpFailure::new(dirname, err));
+        continue;
+      }
+    };
+
+    for dir_entry in read_dir {
+      let name = dir_entry.file_name();
+      if matches!(name.as_str(), "." | "..") {
+        continue;
+      }
+
+      let path = join_relative_path(&relative_dirname, &name);
+      let file_type = dir_entry.file_type().into();
+      if file_type == SSHSftpFileType::Dir {
+        pending.push(path.clone());
+      }
+      entries.push(TreeEntry {
+        path,
+        file_type,
+        attrs: dir_entry.metadata(),
+      });
+    }
+  }
+
+  entries
+}
+
+/// Registers a control under `task_id` while `f` runs, so the task can be paused and cancelled.
+async fn with_transfer_control<R, F, Fut, T>(
+  ssh_manager: &SSHManager<R>,
+  task_id: Option<String>,
+  f: F,
+) -> SSHResult<T>
+where
+  R: Runtime,
+  F: FnOnce(TransferControl) -> Fut,
+  Fut: Future<Output = SSHResult<T>>,
+{
+  let task_id = task_id.unwrap_or_else(|| Uuid::new_v4().to_string());
+  let control = TransferControl::new();
+  {
+    let mut controls = ssh_manager.transfer_controls.lock().await;
+    controls.insert(task_id.clone(), control.clone());
+  }
+
+  let result = f(control).await;
+
+  {
+    let mut controls = ssh_manager.transfer_controls.lock().await;
+    controls.remove(&task_id);
+  }
+
+  result
+}
+
+/// Removes `path` and everything below it, entries that can't be removed are returned.
+async fn remove_tree(
+  sftp: &SftpSession,
+  path: &str,
+  on_progress: Channel<SFTPDirProgressPayload>,
+  control: &TransferControl,
+) -> SSHResult<Vec<SSHSftpFailure>> {
+  let mut failures = Vec::new();
+  let is_dir = sftp.symlink_metadata(path).await?.is_dir();
+  let entries = if is_dir {
+    walk_remote_tree(sftp, path, &mut failures).await
+  } else {
+    Vec::new()
+  };
+  log::info!("sftp remove tree {} {} entries", path, entries.len());
+
+  let mut progress = DirProgress::with_totals(entries.len() as u64 + 1, 0, on_progress);
+  progress.send(0)?;
+
+  // 子项先于所在目录删除
+  let root = TreeEntry {
+    path: String::new(),
+    file_type: if is_dir {
+      SSHSftpFileType::Dir
+    } else {
+      SSHSftpFileType::File
+    },
+    attrs: FileAttributes::empty(),
+  };
+  for entry in entries.iter().rev().chain([&root]) {
+    check_transfer_control(control).await?;
+
+    let entry_path = join_remote_path(path, &entry.path);
+    progress.start_file(&entry.path)?;
+    if entry.file_type == SSHSftpFileType::Dir {
+      // 内容未删除干净的目录必然删除失败，不再重复报告
+      let prefix = format!("{}/", entry_path);
+      let blocked = failures
+        .iter()
+        .any(|failure| failure.path == entry_path || failure.path.starts_with(&prefix));
+      if !blocked && let Err(err) = sftp.remove_dir(entry_path.as_str()).await {
+        failures.push(SSHSftpFailure::new(entry_path, err));
+      }
+    } else if let Err(err) = sftp.remove_file(entry_path.as_str()).await {
+      failures.push(SSHSftpFailure::new(entry_path, err));
+    }
+    progress.finish_file(0)?;
+  }
+
+  Ok(failures)
+}
+
+/// Copies a file on the server with the `copy-data` extension.
+async fn copy_data_file<F>(
+  raw_sftp: &RawSftp,
+  source_path: &str,
+  target_path: &str,
+  size: u64,
+  control: &TransferControl,
+  mut on_progress: F,
+) -> SSHResult<()>
+where
+  F: FnMut(u64) -> SSHResult<()>,
+{
+  let source = raw_sftp
+    .open(source_path, OpenFlags::READ, FileAttributes::empty())
+    .await?;
+  let target = match raw_sftp
+    .open(
+      target_path,
+      OpenFlags::WRITE | OpenFlags::CREATE | OpenFlags::TRUNCATE,
+      FileAttributes::empty(),
+    )
+    .await
+  {
+    Ok(target) => target,
+    Err(err) => {
+      let _ = raw_sftp.close(source.handle).await;
+      return Err(err.into());
+    }
+  };
+
+  let result: SSHResult<()> = async {
+    let mut offset = 0;
+    while offset < size {
+      check_transfer_control(control).await?;
+
+      let len = COPY_DATA_CHUNK_SIZE.min(size - offset);
+      let mut data = Vec::new();
+      put_string(&mut data, source.handle.as_bytes());
+      data.extend_from_slice(&offset.to_be_bytes());
+      data.extend_from_slice(&len.to_be_bytes());
+      put_string(&mut data, target.handle.as_bytes());
+      data.extend_from_slice(&offset.to_be_bytes());
+
+      let reply = raw_sftp.extended(COPY_DATA_EXTENSION, data).await?;
+      expect_status_ok(COPY_DATA_EXTENSION, reply)?;
+      offset += len;
+      on_progress(offset)?;
+    }
+    Ok(())
+  }
+  .await;
+
+  let _ = raw_sftp.close(source.handle).await;
+  let _ = raw_sftp.close(target.handle).await;
+  result
+}
+
+/// Copies the tree with `cp -a` on the host, `None` when commands can't be run there, e.g. for
+/// sftp only accounts.
+async fn exec_copy_tree<R: Runtime>(
+  ssh_manager: &SSHManager<R>,
+  ssh_session_id: SSHSessionId,
+  source_path: &str,
+  target_path: &str,
+) -> Option<Vec<SSHSftpFailure>> {
+  let command = format!(
+    "cp -a -- {} {}",
+    exec::quote(source_path),
+    exec::quote(target_path)
+  );
+  let output = match exec::exec(ssh_manager, ssh_session_id, &command).await {
+    Ok(output) => output,
+    Err(err) => {
+      log::warn!("sftp exec copy error {:?}", err);
+      return None;
+    }
+  };
+
+  match output.exit_status {
+    Some(0) => Some(Vec::new()),
+    // 127 为找不到命令
+    Some(127) | None => None,
+    Some(_) => {
+      let stderr = String::from_utf8_lossy(&output.stderr);
+      Some(vec![SSHSftpFailure::new(source_path, stderr.trim())])
+    }
+  }
+}
+
+/// Restores the permissions and times of a copied entry, failures are only logged as the content
+/// itself was copied.
+async fn copy_attrs(sftp: &SftpSession, path: &str, attrs: &FileAttributes) {
+  let mut target_attrs = FileAttributes::empty();
+  target_attrs.permissions = attrs.permissions.map(|permissions| permissions & MODE_MASK);
+  if let (Some(atime), Some(mtime)) = (attrs.atime, attrs.mtime) {
+    target_attrs.atime = Some(atime);
+    target_attrs.mtime = Some(mtime);
+  }
+
+  if let Err(err) = sftp.set_metadata(path, target_attrs).await {
+    log::warn!("sftp copy attrs {} error {:?}", path, err);
+  }
+}
+
+/// Copies `source_path` to `target_path` on the same server. The data stays on the server with
+/// `copy-data` or `cp -a`, it is streamed through the client only when neither is available.
+async fn copy_tree<R: Runtime>(
+  ssh_manager: &SSHManager<R>,
+  ssh_sftp_id: SSHSftpId,
+  source_path: &str,
+  target_path: &str,
+  on_progress: Channel<SFTPDirProgressPayload>,
+  control: &TransferControl,
+) -> SSHResult<Vec<SSHSftpFailure>> {
+  let sftp = get_sftp_session(ssh_manager, ssh_sftp_id).await?;
+  let ssh_session_id = get_ssh_session_id(ssh_manager, ssh_sftp_id).await?;
+  if sftp.try_exists(target_path).await? {
+    return Err(SSHError::new(format!("{} already exists", target_path)));
+  }
+
+  let raw_sftp = get_raw_sftp(ssh_manager, ssh_sftp_id, COPY_DATA_EXTENSION).await?;
+  if raw_sftp.is_none() {
+    let mut progress = DirProgress::with_totals(1, 0, on_progress.clone());
+    progress.start_file("")?;
+    if let Some(failures) =
+      exec_copy_tree(ssh_manager, ssh_session_id, source_path, target_path).await
+    {
+      progress.finish_file(0)?;
+      return Ok(failures);
+    }
+  }
+
+  let mut failures = Vec::new();
+  let attrs = sftp.symlink_metadata(source_path).await?;
+  let mut entries = vec![TreeEntry {
+    path: String::new(),
+    file_type: attrs.file_type().into(),
+    attrs,
+  }];
+  if entries[0].file_type == SSHSftpFileType::Dir {
+    entries.extend(walk_remote_tree(&sftp, source_path, &mut failures).await);
+  }
+  log::info!(
+    "sftp copy tree {} -> {} {} entries copy-data {}",
+    source_path,
+    target_path,
+    entries.len(),
+    raw_sftp.is_some()
+  );
+
+  let files = entries
+    .iter()
+    .filter(|entry| entry.file_type == SSHSftpFileType::File);
+  let mut progress = DirProgress::with_totals(
+    files.clone().count() as u64,
+    files.map(|entry| entry.attrs.size.unwrap_or(0)).sum(),
+    on_progress,
+  );
+  progress.send(0)?;
+  let concurrency = sftp_pipeline::concurrency(None);
+  let symlink_reversed = is_symlink_reversed(ssh_manager, ssh_sftp_id).await?;
+
+  for entry in &entries {
+    check_transfer_control(control).await?;
+
+    let source = join_remote_path(source_path, &entry.path);
+    let target = join_remote_path(target_path, &entry.path);
+    let result = match entry.file_type {
+      SSHSftpFileType::Dir => sftp
+        .create_dir(target.as_str())
+        .await
+        .map_err(SSHError::from),
+      SSHSftpFileType::File => {
+        let size = entry.attrs.size.unwrap_or(0);
+        progress.start_file(&entry.path)?;
+        let on_file_progress = |file_progress| progress.send(file_progress);
+        let result = match &raw_sftp {
+          Some(raw_sftp) => {
+            copy_data_file(raw_sftp, &source, &target, size, control, on_file_progress).await
+          }
+          None => match sftp.create(target.as_str()).await {
+            Ok(_) => {
+              sftp_pipeline::copy(
+                sftp.clone(),
+                &source,
+                sftp.clone(),
+                &target,
+                size,
+                concurrency,
+                control,
+                on_file_progress,
+              )
+              .await
+            }
+            Err(err) => Err(err.into()),
+          },
+        };
+        if result.is_ok() {
+          copy_attrs(&sftp, &target, &entry.attrs).await;
+        }
+        progress.finish_file(size)?;
+        result
+      }
+      SSHSftpFileType::Symlink => match sftp.read_link(source.as_str()).await {
+        Ok(link_target) => create_symlink(&sftp, symlink_reversed, &target, &link_target).await,
+        Err(err) => Err(err.into()),
+      },
+      SSHSftpFileType::Other => Err(SSHError::new("Unsupported file type")),
+    };
+
+    match result {
+      Ok(()) => {}
+      Err(SSHError::TransferCancelled) => return Err(SSHError::TransferCancelled),
+      Err(err) => failures.push(SSHSftpFailure::new(source, err)),
+    }
+  }
+
+  // 目录属性最后设置，只读目录也能先写入内容
+  for entry in entries.iter().rev() {
+    if entry.file_type == SSHSftpFileType::Dir {
+      copy_attrs(
+        &sftp,
+        &join_remote_path(target_path, &entry.path),
+        &entry.attrs,
+      )
+      .await;
+    }
+  }
+
+  Ok(failures)
+}
+
+/// Removes a path recursively, for a directory also everything below it. Progress counts removed
+/// entries, the entries that couldn't be removed are returned.
+#[tauri::command]
+pub async fn sftp_remove_all<R: Runtime>(
+  _app_handle: AppHandle<R>,
+  ssh_manager: State<'_, SSHManager<R>>,
+  ssh_sftp_id: SSHSftpId,
+  path: String,
+  on_progress: Channel<SFTPDirProgressPayload>,
+  task_id: Option<String>,
+) -> SSHResult<Vec<SSHSftpFailure>> {
+  let sftp = get_sftp_session(&ssh_manager, ssh_sftp_id).await?;
+  with_transfer_control(&ssh_manager, task_id, |control| async move {
+    remove_tree(&sftp, &path, on_progress, &control).await
+  })
+  .await
+}
+
+/// Copies a file or directory recursively on the server, the entries that couldn't be copied are
+/// returned.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn sftp_copy<R: Runtime>(
+  _app_handle: AppHandle<R>,
+  ssh_manager: State<'_, SSHManager<R>>,
+  ssh_sftp_id: SSHSftpId,
+  source_path: String,
+  target_path: String,
+  on_progress: Channel<SFTPDirProgressPayload>,
+  task_id: Option<String>,
+) -> SSHResult<Vec<SSHSftpFailure>> {
+  let ssh_manager = &*ssh_manager;
+  with_transfer_control(ssh_manager, task_id, |control| async move {
+    copy_tree(
+      ssh_manager,
+      ssh_sftp_id,
+      &source_path,
+      &target_path,
+      on_progress,
+      &control,
+    )
+    .await
+  })
+  .await
+}
+
+/// Moves a file or directory, across file systems it is copied and the source removed once
+/// everything was copied.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn sftp_move<R: Runtime>(
+  _app_handle: AppHandle<R>,
+  ssh_manager: State<'_, SSHManager<R>>,
+  ssh_sftp_id: SSHSftpId,
+  source_path: String,
+  target_path: String,
+  on_progress: Channel<SFTPDirProgressPayload>,
+  task_id: Option<String>,
+) -> SSHResult<Vec<SSHSftpFailure>> {
+  let sftp = get_sftp_session(&ssh_manager, ssh_sftp_id).await?;
+  if sftp.try_exists(target_path.as_str()).await? {
+    return Err(SSHError::new(format!("{} already exists", target_path)));
+  }
+
+  // sftp 只返回通用错误，无法区分跨文件系统，重命名失败后都尝试复制
+  match sftp
+    .rename(source_path.as_str(), target_path.as_str())
+    .await
+  {
+    Ok(()) => return Ok(Vec::new()),
+    Err(err) => log::info!(
+      "sftp move rename {} -> {} error {:?}, copy instead",
+      source_path,
+      target_path,
+      err
+    ),
+  }
+
+  let ssh_manager = &*ssh_manager;
+  with_transfer_control(ssh_manager, task_id, |control| async move {
+    let failures = copy_tree(
+      ssh_manager,
+      ssh_sftp_id,
+      &source_path,
+      &target_path,
+      on_progress.clone(),
+      &control,
+    )
+    .await?;
+    if !failures.is_empty() {
+      return Ok(failures);
+    }
+
+    remove_tree(&sftp, &source_path, on_progress, &control).await
+  })
+  .await
+}
+
+#[tauri::command]
+pub async fn sftp_rename<R: Runtime>(
+  _app_handle: AppHandle<R>,
+  ssh_manager: State<'_, SSHManager<R>>,
+  ssh_sftp_id: SSHSftpId,
+  old_path: String,
+  new_path: String,
+) -> SSHResult<SSHSftpId> {
+  let sftp = get_sftp_session(&ssh_manager, ssh_sftp_id).await?;
+  sftp.rename(old_path, new_path).await?;
+
+  Ok(ssh_sftp_id)
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SSHSftpSetAttrResult {
+  path: String,
+  /// None when the attributes of the path were changed
+  error: Option<String>,
+}
+
+/// Lists `path` and with `recursive` everything below it. Children come before their directory,
+/// so a directory is changed after its content is, e.g. when its permissions are removed.
+async fn collect_attr_paths(
+  sftp: &SftpSession,
+  path: &str,
+  recursive: bool,
+) -> SSHResult<Vec<String>> {
+  let mut paths = Vec::new();
+  if recursive && sftp.metadata(path).await?.is_dir() {
+    let entries = walk_remote_dir(sftp, path, &SftpDirTransferOpts::default()).await?;
+    paths.extend(
+      entries
+        .iter()
+        .rev()
+        .map(|entry| join_remote_path(path, &entry.path)),
+    );
+  }
+  paths.push(path.to_string());
+  Ok(paths)
+}
+
+/// Runs `set_attr` on every path, failures are reported per path instead of stopping.
+async fn set_attrs<F, Fut>(
+  sftp: &SftpSession,
+  paths: Vec<String>,
+  recursive: bool,
+  set_attr: F,
+) -> Vec<SSHSftpSetAttrResult>
+where
+  F: Fn(String) -> Fut,
+  Fut: Future<Output = SSHResult<()>>,
+{
+  let mut results = Vec::new();
+  for path in paths {
+    let targets = match collect_attr_paths(sftp, &path, recursive).await {
+      Ok(targets) => targets,
+      Err(err) => {
+        results.push(SSHSftpSetAttrResult {
+          path,
+          error: Some(err.to_string()),
+        });
+        continue;
+      }
+    };
+
+    for target in targets {
+      let error = set_attr(target.clone()).await.err();
+      if let Some(error) = &error {
+        log::error!("sftp set attr {} error {:?}", target, error);
+      }
+      results.push(SSHSftpSetAttrResult {
+        path: target,
+        error: error.map(|error| error.to_string()),
+      });
+    }
+  }
+  results
+}
+
+/// Resolves a user or group name to its id on the host, numeric ids are used as they are.
+async fn resolve_id<R: Runtime>(
+  ssh_manager: &SSHManager<R>,
+  ssh_session_id: SSHSessionId,
+  name: &str,
+  is_group: bool,
+) -> SSHResult<u32> {
+  if let Ok(id) = name.parse() {
+    return Ok(id);
+  }
+
+  let command = if is_group {
+    format!("getent group {} | cut -d: -f3", exec::quote(name))
+  } else {
+    format!("id -u -- {}", exec::quote(name))
+  };
+  let output = exec::exec(ssh_manager, ssh_session_id, &command).await?;
+
+  String::from_utf8_lossy(&output.stdout)
+    .trim()
+    .parse()
+    .map_err(|_| {
+      let kind = if is_group { "group" } else { "user" };
+      SSHError::new(format!("Unknown {} {}", kind, name))
+    })
+}
+
+/// Resolves a user or group id to its name on the host, `None` when it has no entry.
+async fn resolve_name<R: Runtime>(
+  ssh_manager: &SSHManager<R>,
+  ssh_session_id: SSHSessionId,
+  id: u32,
+  is_group: bool,
+) -> Option<String> {
+  let database = if is_group { "group" } else { "passwd" };
+  let command = format!("getent {} {} | cut -d: -f1", database, id);
+  let output = exec::exec(ssh_manager, ssh_session_id, &command)
+    .await
+    .ok()?;
+
+  let name = String::from_utf8_lossy(&output.stdout).trim().to_string();
+  (!name.is_empty()).then_some(name)
+}
+
+/// Fills in the owner and group names the server didn't send, sftp v3 only carries the ids.
+async fn resolve_owner<R: Runtime>(
+  ssh_manager: &SSHManager<R>,
+  ssh_sftp_id: SSHSftpId,
+  file: &mut SSHSftpFile,
+) {
+  if file.user.is_some() && file.group.is_some() {
+    return;
+  }
+  let Ok(ssh_session_id) = get_ssh_session_id(ssh_manager, ssh_sftp_id).await else {
+    return;
+  };
+
+  if let (None, Some(uid)) = (&file.user, file.uid) {
+    file.user = resolve_name(ssh_manager, ssh_session_id, uid, false).await;
+  }
+  if let (None, Some(gid)) = (&file.group, file.gid) {
+    file.group = resolve_name(ssh_manager, ssh_session_id, gid, true).await;
+  }
+}
+
+/// Changes the permissions with an octal (`755`) or symbolic (`u+x,go-w`) mode.
+#[tauri::command]
+pub async fn sftp_chmod<R: Runtime>(
+  _app_handle: AppHandle<R>,
+  ssh_manager: State<'_, SSHManager<R>>,
+  ssh_sftp_id: SSHSftpId,
+  paths: Vec<String>,
+  mode: String,
+  recursive: Option<bool>,
+) -> SSHResult<Vec<SSHSftpSetAttrResult>> {
+  let sftp = get_sftp_session(&ssh_manager, ssh_sftp_id).await?;
+  let mode = FileMode::parse(&mode)?;
+
+  let results = set_attrs(&sftp, paths, recursive.unwrap_or(false), |path| {
+    let sftp = sftp.clone();
+    let mode = mode.clone();
+    async move {
+      let permissions = if mode.is_relative() {
+        let metadata = sftp.metadata(&path).await?;
+        mode.apply(metadata.permissions.unwrap_or(0), metadata.is_dir())
+      } else {
+        mode.apply(0, false)
+      };
+
+      let mut attrs = FileAttributes::empty();
+      attrs.permissions = Some(permissions);
+      sftp.set_metadata(path, attrs).await?;
+      Ok(())
+    }
+  })
+  .await;
+
+  Ok(results)
+}
+
+/// Changes the owner and/or group, given as names or numeric ids.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn sftp_chown<R: Runtime>(
+  _app_handle: AppHandle<R>,
+  ssh_manager: State<'_, SSHManager<R>>,
+  ssh_sftp_id: SSHSftpId,
+  paths: Vec<String>,
+  owner: Option<String>,
+  group: Option<String>,
+  recursive: Option<bool>,
+) -> SSHResult<Vec<SSHSftpSetAttrResult>> {
+  let sftp = get_sftp_session(&ssh_manager, ssh_sftp_id).await?;
+  let ssh_session_id = get_ssh_session_id(&ssh_manager, ssh_sftp_id).await?;
+
+  let uid = match owner.as_deref().filter(|owner| !owner.is_empty()) {
+    Some(owner) => Some(resolve_id(&ssh_manager, ssh_session_id, owner, false).await?),
+    None => None,
+  };
+  let gid = match group.as_deref().filter(|group| !group.is_empty()) {
+    Some(group) => Some(resolve_id(&ssh_manager, ssh_session_id, group, true).await?),
+    None => None,
+  };
+  if uid.is_none() && gid.is_none() {
+    return Err(SSHError::new("Owner or group is required"));
+  }
+
+  let results = set_attrs(&sftp, paths, recursive.unwrap_or(false), |path| {
+    let sftp = sftp.clone();
+    async move {
+      // sftp v3 只能同时设置 uid 与 gid，未指定的一项保持原值
+      let (uid, gid) = match (uid, gid) {
+        (Some(uid), Some(gid)) => (uid, gid),
+        _ => {
+          let metadata = sftp.metadata(&path).await?;
+          let missing = || SSHError::new(format!("Owner of {} is unknown", path));
+          (
+            uid.or(metadata.uid).ok_or_else(missing)?,
+            gid.or(metadata.gid).ok_or_else(missing)?,
+          )
+        }
+      };
+
+      let mut attrs = FileAttributes::empty();
+      attrs.uid = Some(uid);
+      attrs.gid = Some(gid);
+      sftp.set_metadata(path, attrs).await?;
+      Ok(())
+    }
+  })
+  .await;
+
+  Ok(results)
+}
+
+/// Changes the access and/or modification time, in seconds since the epoch.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn sftp_set_times<R: Runtime>(
+  _app_handle: AppHandle<R>,
+  ssh_manager: State<'_, SSHManager<R>>,
+  ssh_sftp_id: SSHSftpId,
+  paths: Vec<String>,
+  atime: Option<u32>,
+  mtime: Option<u32>,
+  recursive: Option<bool>,
+) -> SSHResult<Vec<SSHSftpSetAttrResult>> {
+  let sftp = get_sftp_session(&ssh_manager, ssh_sftp_id).await?;
+  if atime.is_none() && mtime.is_none() {
+    return Err(SSHError::new("Access or modification time is required"));
+  }
+
+  let results = set_attrs(&sftp, paths, recursive.unwrap_or(false), |path| {
+    let sftp = sftp.clone();
+    async move {
+      // 与 uid、gid 相同，两个时间需要一起设置
+      let (atime, mtime) = match (atime, mtime) {
+        (Some(atime), Some(mtime)) => (atime, mtime),
+        _ => {
+          let metadata = sftp.metadata(&path).await?;
+          (
+            atime.or(metadata.atime).unwrap_or_default(),
+            mtime.or(metadata.mtime).unwrap_or_default(),
+          )
+        }
+      };
+
+      let mut attrs = FileAttributes::empty();
+      attrs.atime = Some(atime);
+      attrs.mtime = Some(mtime);
+      sftp.set_metadata(path, attrs).await?;
+      Ok(())
+    }
+  })
+  .await;
+
+  Ok(results)
+}
+
+fn path_name(path: &str) -> String {
+  let path = path.trim_end_matches('/');
+  match path.rsplit_once('/') {
+    Some((_, name)) => name.to_string(),
+    None if path.is_empty() => "/".to_string(),
+    None => path.to_string(),
+  }
+}
+
+/// Returns the metadata of the file `path` points to, symlinks are followed.
+#[tauri::command]
+pub async fn sftp_stat<R: Runtime>(
+  _app_handle: AppHandle<R>,
+  ssh_manager: State<'_, SSHManager<R>>,
+  ssh_sftp_id: SSHSftpId,
+  path: String,
+) -> SSHResult<SSHSftpFile> {
+  let sftp = get_sftp_session(&ssh_manager, ssh_sftp_id).await?;
+  let metadata = sftp.metadata(path.as_str()).await?;
+  let name = path_name(&path);
+  let mut file = SSHSftpFile::new(path, name, &metadata);
+  resolve_owner(&ssh_manager, ssh_sftp_id, &mut file).await;
+  Ok(file)
+}
+
+/// Returns the metadata of `path` itself, for a symlink with its target resolved.
+#[tauri::command]
+pub async fn sftp_lstat<R: Runtime>(
+  _app_handle: AppHandle<R>,
+  ssh_manager: State<'_, SSHManager<R>>,
+  ssh_sftp_id: SSHSftpId,
+  path: String,
+) -> SSHResult<SSHSftpFile> {
+  let sftp = get_sftp_session(&ssh_manager, ssh_sftp_id).await?;
+  let metadata = sftp.symlink_metadata(path.as_str()).await?;
+  let name = path_name(&path);
+  let mut file = SSHSftpFile::new(path, name, &metadata);
+  resolve_owner(&ssh_manager, ssh_sftp_id, &mut file).await;
+
+  if file.file_type == SSHSftpFileType::Symlink {
+    let (link_target, target_type) = resolve_link(sftp, file.path.clone()).await;
+    file.link_target = link_target;
+    file.target_type = target_type;
+  }
+
+  Ok(file)
+}
+
+#[tauri::command]
+pub async fn sftp_read_link<R: Runtime>(
+  _app_handle: AppHandle<R>,
+  ssh_manager: State<'_, SSHManager<R>>,
+  ssh_sftp_id: SSHSftpId,
+  path: String,
+) -> SSHResult<String> {
+  let sftp = get_sftp_session(&ssh_manager, ssh_sftp_id).await?;
+  Ok(sftp.read_link(path).await?)
+}
+
+/// Creates a symlink at `link_path` pointing to `target`, relative targets are kept as they are.
+#[tauri::command]
+pub async fn sftp_symlink<R: Runtime>(
+  _app_handle: AppHandle<R>,
+  ssh_manager: State<'_, SSHManager<R>>,
+  ssh_sftp_id: SSHSftpId,
+  target: String,
+  link_path: String,
+) -> SSHResult<SSHSftpId> {
+  let sftp = get_sftp_session(&ssh_manager, ssh_sftp_id).await?;
+  let reversed = is_symlink_reversed(&ssh_manager, ssh_sftp_id).await?;
+  create_symlink(&sftp, reversed, &link_path, &target).await?;
+  Ok(ssh_sftp_id)
+}
+
+/// Creates a hard link with the `hardlink@openssh.com` extension.
+#[tauri::command]
+pub async fn sftp_hardlink<R: Runtime>(
+  _app_handle: AppHandle<R>,
+  ssh_manager: State<'_, SSHManager<R>>,
+  ssh_sftp_id: SSHSftpId,
+  target: String,
+  link_path: String,
+) -> SSHResult<SSHSftpId> {
+  let sftp = get_sftp_session(&ssh_manager, ssh_sftp_id).await?;
+  if !sftp.hardlink(target, link_path).await? {
+    return Err(SSHError::new(
+      "The server doesn't support hard links (hardlink@openssh.com)",
+    ));
+  }
+  Ok(ssh_sftp_id)
+}
+
+/// Extension of OpenSSH returning the statvfs of the file system holding a path
+const STATVFS_EXTENSION: &str = "statvfs@openssh.com";
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SSHSftpStatvfs {
+  total_bytes: u64,
+  free_bytes: u64,
+  /// Free bytes usable without root, excludes the reserved blocks
+  available_bytes: u64,
+  total_inodes: u64,
+  free_inodes: u64,
+  available_inodes: u64,
+}
+
+/// Returns the space of the file system holding `path`, `None` when the server doesn't support
+/// `statvfs@openssh.com`.
+async fn statvfs<R: Runtime>(
+  ssh_manager: &SSHManager<R>,
+  ssh_sftp_id: SSHSftpId,
+  path: &str,
+) -> SSHResult<Option<SSHSftpStatvfs>> {
+  let sftp = get_sftp_session(ssh_manager, ssh_sftp_id).await?;
+  let Some(statvfs) = sftp.fs_info(path).await? else {
+    return Ok(None);
+  };
+
+  // 块数以 f_frsize 为单位，部分系统不设置 f_frsize
+  let block_size = if statvfs.fragment_size > 0 {
+    statvfs.fragment_size
+  } else {
+    statvfs.block_size
+  };
+
+  Ok(Some(SSHSftpStatvfs {
+    total_bytes: statvfs.blocks.saturating_mul(block_size),
+    free_bytes: statvfs.blocks_free.saturating_mul(block_size),
+    available_bytes: statvfs.blocks_avail.saturating_mul(block_size),
+    total_inodes: statvfs.inodes,
+    free_inodes: statvfs.inodes_free,
+    available_inodes: statvfs.inodes_avail,
+  }))
+}
+
+/// Fails with `InsufficientSpace` when the file system `remote_path` is written to can't take
+/// `required` bytes. The parent is checked as the target may not exist yet, servers without
+/// statvfs aren't checked.
+async fn ensure_space<R: Runtime>(
+  ssh_manager: &SSHManager<R>,
+  ssh_sftp_id: SSHSftpId,
+  remote_path: &str,
+  required: u64,
+) -> SSHResult<()> {
+  let parent = match remote_path.trim_end_matches('/').rsplit_once('/') {
+    Some(("", _)) => "/",
+    Some((parent, _)) => parent,
+    None => ".",
+  };
+
+  let Some(statvfs) = statvfs(ssh_manager, ssh_sftp_id, parent).await? else {
+    log::info!("sftp check space {} skipped, no statvfs", remote_path);
+    return Ok(());
+  };
+  if statvfs.available_bytes < required {
+    return Err(SSHError::InsufficientSpace {
+      path: parent.to_string(),
+      required,
+      available: statvfs.available_bytes,
+    });
+  }
+
+  Ok(())
+}
+
+/// Returns the space of the file system holding `path`.
+#[tauri::command]
+pub async fn sftp_statvfs<R: Runtime>(
+  _app_handle: AppHandle<R>,
+  ssh_manager: State<'_, SSHManager<R>>,
+  ssh_sftp_id: SSHSftpId,
+  path: String,
+) -> SSHResult<SSHSftpStatvfs> {
+  statvfs(&ssh_manager, ssh_sftp_id, &path)
+    .await?
+    .ok_or_else(|| SSHError::new(format!("The server doesn't support {}", STATVFS_EXTENSION)))
 }
 
 #[tauri::command]
@@ -1,20 +1,46 @@
-use std::{collections::HashMap, env, sync::Arc, time::Duration};
+use std::{
+  collections::HashMap,
+  env,
+  sync::{Arc, Mutex, Weak},
+  time::Duration,
+};
 
-use russh::{Channel as RusshChannel, ChannelId, client};
+use russh::{ChannelId, ChannelMsg, ChannelReadHalf, ChannelWriteHalf, Sig, client};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use strum::AsRefStr;
 use tauri::{
-  AppHandle, Runtime, State,
+  AppHandle, Emitter, Manager, Runtime, State, async_runtime,
   ipc::{Channel, InvokeResponseBody, IpcResponse},
 };
-use tokio::{sync::Mutex as AsyncMutex, time::timeout};
+use tauri_plugin_fs::{FsExt, OpenOptions, SafeFilePath};
+use terminal_core::{
+  encoding::{TerminalEncoding, encode_input},
+  screen::{
+    ScreenExportFormat, ScreenSearchMatch, ScreenSearchOpts, ScreenSnapshot, TerminalScreen,
+  },
+  shell_integration::ShellIntegrationEvent,
+  trigger::{TriggerEngine, TriggerEvent, TriggerRule},
+};
+use tokio::{
+  fs,
+  sync::{Mutex as AsyncMutex, mpsc},
+  time::timeout,
+};
 use uuid::Uuid;
 
 use crate::{
-  commands::session::SSHSessionId,
+  commands::{
+    port_forwarding::forward_local_url, session::SSHSessionId, sftp::SFTPProgressPayload,
+  },
+  container::{self, ContainerRuntime},
   error::{SSHError, SSHResult},
-  ssh_manager::SSHManager,
+  shell_output::{SHELL_WINDOW_SIZE, ShellOutput, ShellOutputAction},
+  ssh_manager::{SSHManager, TransferControl},
+  startup_script::{StartupScript, StartupScriptRunner},
+  tmux::TmuxEvent,
+  url_forwarding::{LocalUrlDetector, UrlForwardingEvent, UrlForwardingMode},
+  zmodem::{self, Zmodem, ZmodemDirection, ZmodemFile, ZmodemJob},
 };
 
 #[derive(Debug, Clone, AsRefStr)]
@@ -22,12 +48,56 @@ pub enum SHHShellIpcChannelData {
   Eof,
   Close,
   Data(Vec<u8>),
+  Trigger(TriggerEvent),
+  /// `rz` or `sz` started in the shell, its output is held until the webview answers
+  Zmodem(ZmodemDirection),
+  /// Notification of tmux running in control mode
+  Tmux(TmuxEvent),
+  /// Command boundaries and working directory reported by the shell
+  ShellIntegration(ShellIntegrationEvent),
+  /// Loopback url printed by a remote program and its local forwarding
+  UrlForwarding(UrlForwardingEvent),
 }
 
 impl IpcResponse for SHHShellIpcChannelData {
   fn body(self) -> tauri::Result<InvokeResponseBody> {
     match self {
       SHHShellIpcChannelData::Data(data) => Ok(InvokeResponseBody::Raw(data)),
+      SHHShellIpcChannelData::Trigger(trigger_event) => {
+        let body = json!({
+          "type": "Trigger",
+          "event": trigger_event,
+        });
+        Ok(InvokeResponseBody::Json(body.to_string()))
+      }
+      SHHShellIpcChannelData::Zmodem(direction) => {
+        let body = json!({
+          "type": "Zmodem",
+          "direction": direction,
+        });
+        Ok(InvokeResponseBody::Json(body.to_string()))
+      }
+      SHHShellIpcChannelData::Tmux(tmux_event) => {
+        let body = json!({
+          "type": "Tmux",
+          "event": tmux_event,
+        });
+        Ok(InvokeResponseBody::Json(body.to_string()))
+      }
+      SHHShellIpcChannelData::ShellIntegration(shell_integration_event) => {
+        let body = json!({
+          "type": "ShellIntegration",
+          "event": shell_integration_event,
+        });
+        Ok(InvokeResponseBody::Json(body.to_string()))
+      }
+      SHHShellIpcChannelData::UrlForwarding(url_forwarding_event) => {
+        let body = json!({
+          "type": "UrlForwarding",
+          "event": url_forwarding_event,
+        });
+        Ok(InvokeResponseBody::Json(body.to_string()))
+      }
       val => {
         let body = json!({
           "type": val.as_ref(),
@@ -41,34 +111,81 @@ fn body(self) -> tauri::Result<InvokeResponseBody> {
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Serialize, Deserialize)]
 pub struct SSHShellId(Uuid);
 
+/// Emitted with the shell id once a shell has closed, so the app can drop what it keeps for it
+pub const SHELL_CLOSE_EVENT: &str = "ssh://shell_close";
+
 pub struct SSHShell {
+  #[allow(unused)]
   pub ssh_session_id: SSHSessionId,
   #[allow(unused)]
   pub ssh_shell_id: SSHShellId,
   pub shell_channel_id: ChannelId,
-  pub ipc_channel: Channel<SHHShellIpcChannelData>,
-  pub shell_channel: Arc<AsyncMutex<RusshChannel<client::Msg>>>,
+  pub shell_channel: Arc<AsyncMutex<ChannelWriteHalf<client::Msg>>>,
+  pub output: Arc<ShellOutput>,
+  pub trigger_engine: Arc<Mutex<TriggerEngine>>,
+  pub screen: Arc<Mutex<TerminalScreen>>,
 }
 
 impl SSHShell {
+  /// Creates the shell and the receiver of the actions found in its output.
+  #[allow(clippy::too_many_arguments)]
   pub fn new(
     ssh_session_id: SSHSessionId,
     ssh_shell_id: SSHShellId,
     ipc_channel: Channel<SHHShellIpcChannelData>,
-    shell_channel: RusshChannel<client::Msg>,
-  ) -> Self {
+    shell_channel: ChannelWriteHalf<client::Msg>,
+    trigger_engine: TriggerEngine,
+    size: &ShellSize,
+    encoding: Option<Arc<TerminalEncoding>>,
+    url_forwarding: Option<(UrlForwardingMode, LocalUrlDetector)>,
+  ) -> (Self, mpsc::UnboundedReceiver<ShellOutputAction>) {
     let shell_channel_id = shell_channel.id();
+    let trigger_engine = Arc::new(Mutex::new(trigger_engine));
+    let screen = Arc::new(Mutex::new(TerminalScreen::new(size.row, size.col)));
+    let (output, actions) = ShellOutput::new(
+      ssh_shell_id,
+      ipc_channel,
+      encoding,
+      screen.clone(),
+      trigger_engine.clone(),
+      url_forwarding,
+    );
 
-    Self {
+    let shell = Self {
       ssh_session_id,
       ssh_shell_id,
       shell_channel_id,
-      ipc_channel,
       shell_channel: Arc::new(AsyncMutex::new(shell_channel)),
-    }
+      output: Arc::new(output),
+      trigger_engine,
+      screen,
+    };
+
+    (shell, actions)
   }
 }
 
+/// What runs on the pty channel, a login shell unless a command or subsystem is given.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(tag = "type", rename_all_fields = "camelCase")]
+pub enum ShellRequest {
+  #[default]
+  Shell,
+  /// Runs the command with `exec`, the channel closes when the command exits
+  Exec {
+    command: String,
+  },
+  Subsystem {
+    name: String,
+  },
+  /// Shell inside a container started with `docker exec -it`, bash when available
+  Container {
+    runtime: ContainerRuntime,
+    container_id: String,
+    shell: Option<String>,
+  },
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct ShellSize {
   pub col: u32,
@@ -77,13 +194,48 @@ pub struct ShellSize {
   pub height: u32,
 }
 
-fn prepare_envs(custom_envs: HashMap<String, String>) -> HashMap<String, String> {
-  let mut envs = env::vars()
-    .filter(|(key, _)| key.starts_with("LC_") || key.starts_with("LANG_"))
-    .collect::<HashMap<String, String>>();
+/// Parses a signal name such as `INT` or `SIGINT`, unknown names are sent as is.
+fn parse_signal(signal: &str) -> Sig {
+  let name = signal.trim().to_uppercase();
+  let name = name.strip_prefix("SIG").unwrap_or(&name);
+
+  match name {
+    "ABRT" => Sig::ABRT,
+    "ALRM" => Sig::ALRM,
+    "FPE" => Sig::FPE,
+    "HUP" => Sig::HUP,
+    "ILL" => Sig::ILL,
+    "INT" => Sig::INT,
+    "KILL" => Sig::KILL,
+    "PIPE" => Sig::PIPE,
+    "QUIT" => Sig::QUIT,
+    "SEGV" => Sig::SEGV,
+    "TERM" => Sig::TERM,
+    "USR1" => Sig::USR1,
+    name => Sig::Custom(name.to_string()),
+  }
+}
+
+fn prepare_envs(
+  custom_envs: HashMap<String, String>,
+  encoding: Option<&TerminalEncoding>,
+) -> HashMap<String, String> {
+  let mut envs = match encoding {
+    // 本地的区域设置是 UTF-8，使用旧编码时不传递
+    Some(encoding) => encoding
+      .locale()
+      .map(|locale| HashMap::from([("LANG".to_string(), locale.to_string())]))
+      .unwrap_or_default(),
+    None => {
+      let mut envs = env::vars()
+        .filter(|(key, _)| key.starts_with("LC_") || key.starts_with("LANG_"))
+        .collect::<HashMap<String, String>>();
 
-  let lang = env::var("LANG").unwrap_or("C.UTF-8".to_string());
-  envs.insert("LANG".to_string(), lang);
+      let lang = env::var("LANG").unwrap_or("C.UTF-8".to_string());
+      envs.insert("LANG".to_string(), lang);
+      envs
+    }
+  };
 
   envs.extend(custom_envs);
 
@@ -93,17 +245,161 @@ fn prepare_envs(custom_envs: HashMap<String, String>) -> HashMap<String, String>
 async fn get_shell_channel<R: Runtime>(
   ssh_manager: &SSHManager<R>,
   ssh_shell_id: SSHShellId,
-) -> Option<Arc<AsyncMutex<RusshChannel<client::Msg>>>> {
+) -> Option<Arc<AsyncMutex<ChannelWriteHalf<client::Msg>>>> {
   let shells = ssh_manager.shells.lock().await;
   shells
     .get(&ssh_shell_id)
     .map(|shell| shell.shell_channel.clone())
 }
 
+/// Sends input to the shell, encoded when the terminal uses a legacy encoding.
+async fn send_input(
+  shell_channel: &AsyncMutex<ChannelWriteHalf<client::Msg>>,
+  shell_output: &ShellOutput,
+  data: &[u8],
+) -> SSHResult<()> {
+  let data = encode_input(shell_output.encoding.as_deref(), data)?;
+  shell_channel.lock().await.data(&data[..]).await?;
+
+  Ok(())
+}
+
+/// Reads the shell channel in a task of its own and queues the output for the webview.
+///
+/// The channel window only grows by the output handed on, so while the webview is behind the
+/// server stops sending once the window is used up, and the ssh session keeps serving its other
+/// channels. `window` is a handle of its own as the shared one may wait for the server to read
+/// input. Eof and close are handled here too, after the output before them.
+async fn read_shell_output<R: Runtime>(
+  app_handle: AppHandle<R>,
+  ssh_session_id: SSHSessionId,
+  shell_channel_id: ChannelId,
+  shell_output: Arc<ShellOutput>,
+  mut reader: ChannelReadHalf,
+  window: ChannelWriteHalf<client::Msg>,
+) {
+  let ssh_manager = app_handle.state::<SSHManager<R>>();
+
+  while let Some(msg) = reader.wait().await {
+    let result = match msg {
+      ChannelMsg::Data { data } => match shell_output.write(&data).await {
+        Ok(()) => window
+          .adjust_window(data.len() as u32)
+          .await
+          .map_err(SSHError::from),
+        Err(err) => Err(err),
+      },
+      // 标准错误同样占用窗口，不展示也要归还
+      ChannelMsg::ExtendedData { data, .. } => window
+        .adjust_window(data.len() as u32)
+        .await
+        .map_err(SSHError::from),
+      ChannelMsg::Eof => ssh_manager
+        .shell_channel_eof(ssh_session_id, shell_channel_id)
+        .await
+        .map(|_| ()),
+      ChannelMsg::Close => break,
+      _ => Ok(()),
+    };
+
+    if let Err(err) = result {
+      log::error!(
+        "shell {:?} output failed: {}",
+        shell_output.ssh_shell_id,
+        err
+      );
+    }
+  }
+
+  match ssh_manager
+    .shell_channel_close(ssh_session_id, shell_channel_id)
+    .await
+  {
+    Ok(true) => {
+      if let Err(err) = app_handle.emit(SHELL_CLOSE_EVENT, shell_output.ssh_shell_id) {
+        log::error!(
+          "shell {:?} close event failed: {}",
+          shell_output.ssh_shell_id,
+          err
+        );
+      }
+    }
+    Ok(false) => {}
+    Err(err) => log::error!(
+      "shell {:?} close failed: {}",
+      shell_output.ssh_shell_id,
+      err
+    ),
+  }
+}
+
+/// Runs the work found in the shell output in the order it was found: the text of matched
+/// triggers and the urls forwarded in auto mode. Ends once the shell output is dropped.
+async fn run_output_actions<R: Runtime>(
+  app_handle: AppHandle<R>,
+  ssh_session_id: SSHSessionId,
+  shell_channel: Arc<AsyncMutex<ChannelWriteHalf<client::Msg>>>,
+  shell_output: Weak<ShellOutput>,
+  mut actions: mpsc::UnboundedReceiver<ShellOutputAction>,
+) {
+  while let Some(action) = actions.recv().await {
+    let Some(shell_output) = shell_output.upgrade() else {
+      return;
+    };
+
+    let result = match action {
+      ShellOutputAction::SendText(text) => {
+        send_input(&shell_channel, &shell_output, text.as_bytes()).await
+      }
+      ShellOutputAction::ForwardUrl(local_url) => {
+        let event = match forward_local_url(&app_handle, ssh_session_id, &local_url).await {
+          Ok(forwarded_url) => UrlForwardingEvent::Forwarded(forwarded_url),
+          Err(err) => UrlForwardingEvent::Failed {
+            url: local_url.url,
+            message: err.to_string(),
+          },
+        };
+        log::info!(
+          "shell {:?} url forwarding {:?}",
+          shell_output.ssh_shell_id,
+          event
+        );
+        shell_output.send(SHHShellIpcChannelData::UrlForwarding(event))
+      }
+    };
+
+    if let Err(err) = result {
+      log::error!(
+        "shell {:?} output action failed: {}",
+        shell_output.ssh_shell_id,
+        err
+      );
+    }
+  }
+}
+
+/// Writes data to an opened shell, used by the app to send input outside of the webview.
+pub async fn write_shell<R: Runtime>(
+  app_handle: &AppHandle<R>,
+  ssh_shell_id: SSHShellId,
+  data: &[u8],
+) -> SSHResult<()> {
+  let ssh_manager = app_handle.state::<SSHManager<R>>();
+  let (shell_channel, shell_output) = get_shell_channel_output(&ssh_manager, ssh_shell_id).await?;
+
+  timeout(
+    Duration::from_secs(5),
+    send_input(&shell_channel, &shell_output, data),
+  )
+  .await??;
+
+  Ok(())
+}
+
 #[tauri::command]
 #[allow(clippy::too_many_arguments)]
 pub async fn shell_open<R: Runtime>(
-  _app_handle: AppHandle<R>,
+  app_handle: AppHandle<R>,
   ssh_manager: State<'_, SSHManager<R>>,
   ssh_session_id: SSHSessionId,
   ssh_shell_id: SSHShellId,
@@ -111,10 +407,31 @@ pub async fn shell_open<R: Runtime>(
   term: Option<String>,
   envs: Option<HashMap<String, String>>,
   size: ShellSize,
+  startup_script: Option<StartupScript>,
+  triggers: Option<Vec<TriggerRule>>,
+  request: Option<ShellRequest>,
+  encoding: Option<String>,
+  url_forwarding: Option<UrlForwardingMode>,
 ) -> SSHResult<SSHShellId> {
+  let trigger_engine = TriggerEngine::new(triggers.unwrap_or_default())?;
+  let url_forwarding = url_forwarding
+    .map(|mode| Ok::<_, SSHError>((mode, LocalUrlDetector::new()?)))
+    .transpose()?;
+  let encoding = encoding
+    .as_deref()
+    .map(TerminalEncoding::new)
+    .transpose()?
+    .flatten()
+    .map(Arc::new);
+  let startup_script_runner = startup_script
+    .as_ref()
+    .filter(|startup_script| !startup_script.script.trim().is_empty())
+    .map(StartupScriptRunner::new)
+    .transpose()?;
+
   timeout(Duration::from_secs(5), async {
     log::info!("shell open {:?} {:?}", ssh_session_id, ssh_shell_id);
-    let shell = {
+    let (shell, actions, reader, window) = {
       let session = {
         let sessions = ssh_manager.sessions.lock().await;
         sessions
@@ -124,12 +441,28 @@ pub async fn shell_open<R: Runtime>(
           .clone()
       };
 
-      let shell_channel = session.lock().await.channel_open_session().await?;
+      let (reader, shell_channel) = session
+        .lock()
+        .await
+        .channel_open_session_with_window(SHELL_WINDOW_SIZE)
+        .await?
+        .split();
+      let window = shell_channel.clone();
 
-      SSHShell::new(ssh_session_id, ssh_shell_id, ipc_channel, shell_channel)
+      let (shell, actions) = SSHShell::new(
+        ssh_session_id,
+        ssh_shell_id,
+        ipc_channel,
+        shell_channel,
+        trigger_engine,
+        &size,
+        encoding.clone(),
+        url_forwarding,
+      );
+      (shell, actions, reader, window)
     };
 
-    let envs = prepare_envs(envs.unwrap_or_default());
+    let envs = prepare_envs(envs.unwrap_or_default(), encoding.as_deref());
 
     log::info!(
       "shell open {:?} {:?} set env {:?}",
@@ -169,12 +502,90 @@ pub async fn shell_open<R: Runtime>(
       )
       .await?;
 
+    let request = request.unwrap_or_default();
     log::info!(
-      "shell open {:?} {:?} request shell",
+      "shell open {:?} {:?} request {:?}",
       ssh_session_id,
-      ssh_shell_id
+      ssh_shell_id,
+      request
     );
-    shell.shell_channel.lock().await.request_shell(true).await?;
+    match request {
+      ShellRequest::Shell => shell.shell_channel.lock().await.request_shell(true).await?,
+      ShellRequest::Exec { command } => {
+        shell
+          .shell_channel
+          .lock()
+          .await
+          .exec(true, command.as_bytes())
+          .await?
+      }
+      ShellRequest::Subsystem { name } => {
+        shell
+          .shell_channel
+          .lock()
+          .await
+          .request_subsystem(true, &name)
+          .await?
+      }
+      ShellRequest::Container {
+        runtime,
+        container_id,
+        shell: container_shell,
+      } => {
+        let command =
+          container::exec_command(runtime, &container_id, &term, container_shell.as_deref())?;
+        shell
+          .shell_channel
+          .lock()
+          .await
+          .exec(true, command.as_bytes())
+          .await?
+      }
+    }
+
+    if let Some(startup_script_runner) = startup_script_runner {
+      log::info!(
+        "shell open {:?} {:?} run startup script",
+        ssh_session_id,
+        ssh_shell_id
+      );
+      let shell_channel = shell.shell_channel.clone();
+      let output_receiver = shell.output.subscribe();
+      let encoding = shell.output.encoding.clone();
+      async_runtime::spawn(async move {
+        if let Err(err) = startup_script_runner
+          .run(shell_channel, encoding, output_receiver)
+          .await
+        {
+          log::error!("shell {:?} startup script failed: {}", ssh_shell_id, err);
+        }
+      });
+    }
+
+    async_runtime::spawn(run_output_actions(
+      app_handle.clone(),
+      ssh_session_id,
+      shell.shell_channel.clone(),
+      Arc::downgrade(&shell.output),
+      actions,
+    ));
+
+    {
+      let mut shell_outputs = ssh_manager.shell_outputs.lock().await;
+      shell_outputs.insert(
+        (ssh_session_id, shell.shell_channel_id),
+        shell.output.clone(),
+      );
+    }
+
+    async_runtime::spawn(read_shell_output(
+      app_handle,
+      ssh_session_id,
+      shell.shell_channel_id,
+      shell.output.clone(),
+      reader,
+      window,
+    ));
 
     {
       let mut shells = ssh_manager.shells.lock().await;
@@ -217,6 +628,61 @@ pub async fn shell_resize<R: Runtime>(
         .window_change(size.col, size.row, size.width, size.height)
         .await?;
     }
+    if let Ok(screen) = get_shell_screen(&ssh_manager, ssh_shell_id).await {
+      screen.lock()?.resize(size.row, size.col);
+    }
+
+    Ok(ssh_shell_id)
+  })
+  .await?
+}
+
+/// Delivers a signal to the remote process with the channel signal request,
+/// works even when the terminal is in raw mode and ignores Ctrl-C.
+#[tauri::command]
+pub async fn shell_signal<R: Runtime>(
+  _app_handle: AppHandle<R>,
+  ssh_manager: State<'_, SSHManager<R>>,
+  ssh_shell_id: SSHShellId,
+  signal: String,
+) -> SSHResult<SSHShellId> {
+  timeout(Duration::from_secs(5), async {
+    let shell_channel = get_shell_channel(&ssh_manager, ssh_shell_id)
+      .await
+      .ok_or(SSHError::NotFoundShell)?;
+
+    log::info!("shell signal {:?} {}", ssh_shell_id, signal);
+    shell_channel
+      .lock()
+      .await
+      .signal(parse_signal(&signal))
+      .await?;
+
+    Ok(ssh_shell_id)
+  })
+  .await?
+}
+
+/// Default length of a BREAK, the one OpenSSH sends for `~B`
+const BREAK_LENGTH: u32 = 1000;
+
+/// Sends a BREAK with the channel break request (RFC 4335), for serial consoles behind the
+/// server. `length` is in milliseconds.
+#[tauri::command]
+pub async fn shell_break<R: Runtime>(
+  _app_handle: AppHandle<R>,
+  ssh_manager: State<'_, SSHManager<R>>,
+  ssh_shell_id: SSHShellId,
+  length: Option<u32>,
+) -> SSHResult<SSHShellId> {
+  timeout(Duration::from_secs(5), async {
+    let shell_channel = get_shell_channel(&ssh_manager, ssh_shell_id)
+      .await
+      .ok_or(SSHError::NotFoundShell)?;
+
+    let length = length.unwrap_or(BREAK_LENGTH);
+    log::info!("shell break {:?} {}ms", ssh_shell_id, length);
+    shell_channel.lock().await.send_break(length).await?;
 
     Ok(ssh_shell_id)
   })
@@ -231,11 +697,288 @@ pub async fn shell_send<R: Runtime>(
   data: Vec<u8>,
 ) -> SSHResult<SSHShellId> {
   timeout(Duration::from_secs(5), async {
-    if let Some(shell_channel) = get_shell_channel(&ssh_manager, ssh_shell_id).await {
-      shell_channel.lock().await.data(&data[..]).await?;
+    if let Ok((shell_channel, shell_output)) =
+      get_shell_channel_output(&ssh_manager, ssh_shell_id).await
+    {
+      send_input(&shell_channel, &shell_output, &data).await?;
     }
 
     Ok(ssh_shell_id)
   })
   .await?
 }
+
+async fn get_shell_output<R: Runtime>(
+  ssh_manager: &SSHManager<R>,
+  ssh_shell_id: SSHShellId,
+) -> Option<Arc<ShellOutput>> {
+  let shells = ssh_manager.shells.lock().await;
+  shells.get(&ssh_shell_id).map(|shell| shell.output.clone())
+}
+
+#[tauri::command]
+pub async fn shell_ack<R: Runtime>(
+  _app_handle: AppHandle<R>,
+  ssh_manager: State<'_, SSHManager<R>>,
+  ssh_shell_id: SSHShellId,
+  size: usize,
+) -> SSHResult<SSHShellId> {
+  if let Some(shell_output) = get_shell_output(&ssh_manager, ssh_shell_id).await {
+    shell_output.ack(size)?;
+  }
+
+  Ok(ssh_shell_id)
+}
+
+/// Attaches a new channel to a running shell, the recent output is replayed first.
+#[tauri::command]
+pub async fn shell_attach<R: Runtime>(
+  _app_handle: AppHandle<R>,
+  ssh_manager: State<'_, SSHManager<R>>,
+  ssh_shell_id: SSHShellId,
+  ipc_channel: Channel<SHHShellIpcChannelData>,
+) -> SSHResult<SSHShellId> {
+  log::info!("shell attach {:?}", ssh_shell_id);
+  get_shell_output(&ssh_manager, ssh_shell_id)
+    .await
+    .ok_or(SSHError::NotFoundShell)?
+    .attach(ipc_channel)?;
+
+  Ok(ssh_shell_id)
+}
+
+/// Keeps the shell running without a channel, e.g. while the webview reloads.
+#[tauri::command]
+pub async fn shell_detach<R: Runtime>(
+  _app_handle: AppHandle<R>,
+  ssh_manager: State<'_, SSHManager<R>>,
+  ssh_shell_id: SSHShellId,
+) -> SSHResult<SSHShellId> {
+  log::info!("shell detach {:?}", ssh_shell_id);
+  get_shell_output(&ssh_manager, ssh_shell_id)
+    .await
+    .ok_or(SSHError::NotFoundShell)?
+    .detach()?;
+
+  Ok(ssh_shell_id)
+}
+
+/// Replaces the trigger rules of a running shell.
+#[tauri::command]
+pub async fn shell_set_triggers<R: Runtime>(
+  _app_handle: AppHandle<R>,
+  ssh_manager: State<'_, SSHManager<R>>,
+  ssh_shell_id: SSHShellId,
+  triggers: Vec<TriggerRule>,
+) -> SSHResult<SSHShellId> {
+  let trigger_engine = {
+    let shells = ssh_manager.shells.lock().await;
+    shells
+      .get(&ssh_shell_id)
+      .map(|shell| shell.trigger_engine.clone())
+      .ok_or(SSHError::NotFoundShell)?
+  };
+
+  trigger_engine.lock()?.set_rules(triggers)?;
+
+  Ok(ssh_shell_id)
+}
+
+async fn get_shell_screen<R: Runtime>(
+  ssh_manager: &SSHManager<R>,
+  ssh_shell_id: SSHShellId,
+) -> SSHResult<Arc<Mutex<TerminalScreen>>> {
+  let shells = ssh_manager.shells.lock().await;
+  shells
+    .get(&ssh_shell_id)
+    .map(|shell| shell.screen.clone())
+    .ok_or(SSHError::NotFoundShell)
+}
+
+/// Returns the visible screen as the backend renders it, also while no webview is attached.
+#[tauri::command]
+pub async fn shell_screen<R: Runtime>(
+  _app_handle: AppHandle<R>,
+  ssh_manager: State<'_, SSHManager<R>>,
+  ssh_shell_id: SSHShellId,
+) -> SSHResult<ScreenSnapshot> {
+  let screen = get_shell_screen(&ssh_manager, ssh_shell_id).await?;
+  let snapshot = screen.lock()?.snapshot();
+
+  Ok(snapshot)
+}
+
+/// Searches the scrollback and the screen, wrapped rows are matched as one line.
+#[tauri::command]
+pub async fn shell_screen_search<R: Runtime>(
+  _app_handle: AppHandle<R>,
+  ssh_manager: State<'_, SSHManager<R>>,
+  ssh_shell_id: SSHShellId,
+  opts: ScreenSearchOpts,
+) -> SSHResult<Vec<ScreenSearchMatch>> {
+  let screen = get_shell_screen(&ssh_manager, ssh_shell_id).await?;
+  let matches = screen.lock()?.search(&opts)?;
+
+  Ok(matches)
+}
+
+/// Exports the scrollback and the screen as plain text or as HTML keeping the colors.
+#[tauri::command]
+pub async fn shell_screen_export<R: Runtime>(
+  _app_handle: AppHandle<R>,
+  ssh_manager: State<'_, SSHManager<R>>,
+  ssh_shell_id: SSHShellId,
+  format: ScreenExportFormat,
+) -> SSHResult<String> {
+  let screen = get_shell_screen(&ssh_manager, ssh_shell_id).await?;
+  let transcript = screen.lock()?.export(format);
+
+  Ok(transcript)
+}
+
+pub(crate) async fn get_shell_channel_output<R: Runtime>(
+  ssh_manager: &SSHManager<R>,
+  ssh_shell_id: SSHShellId,
+) -> SSHResult<(
+  Arc<AsyncMutex<ChannelWriteHalf<client::Msg>>>,
+  Arc<ShellOutput>,
+)> {
+  let shells = ssh_manager.shells.lock().await;
+  shells
+    .get(&ssh_shell_id)
+    .map(|shell| (shell.shell_channel.clone(), shell.output.clone()))
+    .ok_or(SSHError::NotFoundShell)
+}
+
+fn local_file_name(local_filename: &SafeFilePath) -> String {
+  let name = match local_filename {
+    SafeFilePath::Url(url) => url
+      .path_segments()
+      .and_then(|mut segments| segments.next_back())
+      .map(|segment| segment.to_string()),
+    SafeFilePath::Path(path) => path
+      .as_ref()
+      .file_name()
+      .map(|name| name.to_string_lossy().to_string()),
+  };
+
+  name.unwrap_or_else(|| local_filename.to_string())
+}
+
+async fn open_zmodem_files<R: Runtime>(
+  app_handle: &AppHandle<R>,
+  local_filenames: Vec<SafeFilePath>,
+) -> SSHResult<Vec<ZmodemFile>> {
+  let mut files = Vec::with_capacity(local_filenames.len());
+  for local_filename in local_filenames {
+    let name = local_file_name(&local_filename);
+    let file = app_handle
+      .fs()
+      .open(local_filename, OpenOptions::new().read(true).to_owned())?;
+    files.push(ZmodemFile::new(name, fs::File::from_std(file)).await?);
+  }
+
+  Ok(files)
+}
+
+/// Runs the transfer detected in the shell and gives the output back to the terminal.
+///
+/// The transfer is registered as a task, so it can be paused or cancelled like sftp transfers.
+async fn zmodem_transfer<R: Runtime>(
+  ssh_manager: &SSHManager<R>,
+  ssh_shell_id: SSHShellId,
+  job: SSHResult<ZmodemJob>,
+  on_progress: Channel<SFTPProgressPayload>,
+  task_id: Option<String>,
+) -> SSHResult<Vec<String>> {
+  let (shell_channel, shell_output) = get_shell_channel_output(ssh_manager, ssh_shell_id).await?;
+  let receiver = shell_output.take_zmodem()?;
+
+  let task_id = task_id.unwrap_or_else(|| Uuid::new_v4().to_string());
+  let control = TransferControl::new();
+  {
+    let mut controls = ssh_manager.transfer_controls.lock().await;
+    controls.insert(task_id.clone(), control.clone());
+  }
+
+  let (result, receiver, leftover) = match job {
+    Ok(job) => {
+      let mut zmodem = Zmodem::new(shell_channel, receiver, on_progress, control);
+      let result = zmodem.run(job).await;
+      let (receiver, leftover) = zmodem.into_parts();
+      (result, receiver, leftover)
+    }
+    // 本地文件无法打开时同样需要结束对端的 rz/sz
+    Err(err) => {
+      let mut receiver = receiver;
+      let result = zmodem::abort(&shell_channel, &mut receiver)
+        .await
+        .and(Err(err));
+      (result, receiver, Vec::new())
+    }
+  };
+
+  {
+    let mut controls = ssh_manager.transfer_controls.lock().await;
+    controls.remove(&task_id);
+  }
+
+  shell_output.end_zmodem(receiver, leftover)?;
+
+  if let Err(err) = &result {
+    log::error!("shell {:?} zmodem transfer failed: {}", ssh_shell_id, err);
+  }
+  result
+}
+
+/// Sends local files to `rz` running in the shell, returns the names of the sent files.
+#[tauri::command]
+pub async fn shell_zmodem_upload<R: Runtime>(
+  app_handle: AppHandle<R>,
+  ssh_manager: State<'_, SSHManager<R>>,
+  ssh_shell_id: SSHShellId,
+  local_filenames: Vec<SafeFilePath>,
+  on_progress: Channel<SFTPProgressPayload>,
+  task_id: Option<String>,
+) -> SSHResult<Vec<String>> {
+  let job = open_zmodem_files(&app_handle, local_filenames)
+    .await
+    .map(ZmodemJob::Upload);
+
+  zmodem_transfer(&ssh_manager, ssh_shell_id, job, on_progress, task_id).await
+}
+
+/// Saves the files `sz` sends into a local directory, returns the local paths.
+#[tauri::command]
+pub async fn shell_zmodem_download<R: Runtime>(
+  _app_handle: AppHandle<R>,
+  ssh_manager: State<'_, SSHManager<R>>,
+  ssh_shell_id: SSHShellId,
+  local_dirname: SafeFilePath,
+  on_progress: Channel<SFTPProgressPayload>,
+  task_id: Option<String>,
+) -> SSHResult<Vec<String>> {
+  let job = local_dirname
+    .into_path()
+    .map(ZmodemJob::Download)
+    .map_err(SSHError::new);
+
+  zmodem_transfer(&ssh_manager, ssh_shell_id, job, on_progress, task_id).await
+}
+
+/// Declines a detected transfer, the remote `rz` or `sz` is aborted.
+#[tauri::command]
+pub async fn shell_zmodem_cancel<R: Runtime>(
+  _app_handle: AppHandle<R>,
+  ssh_manager: State<'_, SSHManager<R>>,
+  ssh_shell_id: SSHShellId,
+) -> SSHResult<SSHShellId> {
+  log::info!("shell zmodem cancel {:?}", ssh_shell_id);
+  let (shell_channel, shell_output) = get_shell_channel_output(&ssh_manager, ssh_shell_id).await?;
+  let mut receiver = shell_output.take_zmodem()?;
+
+  let result = zmodem::abort(&shell_channel, &mut receiver).await;
+  shell_output.end_zmodem(receiver, Vec::new())?;
+
+  result.map(|_| ssh_shell_id)
+}
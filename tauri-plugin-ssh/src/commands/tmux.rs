@@ -0,0 +1,303 @@
+use std::{fmt::Write, sync::Arc, time::Duration};
+
+use russh::{ChannelWriteHalf, client};
+use serde::Serialize;
+use tauri::{AppHandle, Runtime, State, ipc::Channel};
+use tokio::{
+  sync::{Mutex as AsyncMutex, oneshot},
+  time::timeout,
+};
+
+use crate::{
+  commands::shell::{SSHShellId, ShellSize, get_shell_channel_output},
+  error::{SSHError, SSHResult},
+  shell_output::ShellOutput,
+  ssh_manager::SSHManager,
+  tmux::{TmuxPaneId, TmuxPaneIpcChannelData, TmuxPending, TmuxWindowId},
+};
+
+/// Lines of history replayed when a pane is opened
+const HISTORY_LINES: u32 = 2000;
+/// Input bytes sent with one `send-keys` command
+const SEND_KEYS_SIZE: usize = 256;
+const COMMAND_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TmuxPane {
+  pub window_id: TmuxWindowId,
+  pub window_name: String,
+  pub window_active: bool,
+  pub pane_id: TmuxPaneId,
+  pub pane_active: bool,
+  pub width: u32,
+  pub height: u32,
+  pub x: u32,
+  pub y: u32,
+}
+
+type ShellChannel = Arc<AsyncMutex<ChannelWriteHalf<client::Msg>>>;
+
+async fn get_tmux<R: Runtime>(
+  ssh_manager: &SSHManager<R>,
+  ssh_shell_id: SSHShellId,
+) -> SSHResult<(ShellChannel, Arc<ShellOutput>)> {
+  let (shell_channel, shell_output) = get_shell_channel_output(ssh_manager, ssh_shell_id).await?;
+  if !shell_output.is_tmux()? {
+    return Err(SSHError::NotFoundTmux);
+  }
+
+  Ok((shell_channel, shell_output))
+}
+
+/// Sends commands to tmux, the reply handlers are queued under the channel lock to keep their order.
+async fn send_commands(
+  shell_channel: &ShellChannel,
+  shell_output: &ShellOutput,
+  commands: Vec<(String, TmuxPending)>,
+) -> SSHResult<()> {
+  let (lines, pending): (Vec<String>, Vec<TmuxPending>) = commands.into_iter().unzip();
+  let mut data = lines.join("\n");
+  data.push('\n');
+
+  let shell_channel = shell_channel.lock().await;
+  shell_output.tmux_pending(pending)?;
+  timeout(COMMAND_TIMEOUT, shell_channel.data(data.as_bytes())).await??;
+
+  Ok(())
+}
+
+async fn run_command(
+  shell_channel: &ShellChannel,
+  shell_output: &ShellOutput,
+  command: String,
+) -> SSHResult<Vec<String>> {
+  let (sender, receiver) = oneshot::channel();
+  send_commands(
+    shell_channel,
+    shell_output,
+    vec![(command, TmuxPending::Reply(sender))],
+  )
+  .await?;
+
+  // tmux 退出时回调被丢弃
+  timeout(COMMAND_TIMEOUT, receiver)
+    .await?
+    .map_err(|_| SSHError::NotFoundTmux)?
+    .map_err(SSHError::TmuxError)
+}
+
+/// Pane ids are interpolated into commands, only `%<number>` is accepted.
+fn check_pane_id(pane_id: &str) -> SSHResult<()> {
+  let valid = pane_id
+    .strip_prefix('%')
+    .is_some_and(|id| !id.is_empty() && id.bytes().all(|byte| byte.is_ascii_digit()));
+  if !valid {
+    return Err(SSHError::new(format!("Invalid tmux pane {}", pane_id)));
+  }
+
+  Ok(())
+}
+
+fn check_window_id(window_id: &str) -> SSHResult<()> {
+  let valid = window_id
+    .strip_prefix('@')
+    .is_some_and(|id| !id.is_empty() && id.bytes().all(|byte| byte.is_ascii_digit()));
+  if !valid {
+    return Err(SSHError::new(format!("Invalid tmux window {}", window_id)));
+  }
+
+  Ok(())
+}
+
+/// Runs a tmux command such as `new-window` and returns its output lines.
+#[tauri::command]
+pub async fn tmux_command<R: Runtime>(
+  _app_handle: AppHandle<R>,
+  ssh_manager: State<'_, SSHManager<R>>,
+  ssh_shell_id: SSHShellId,
+  command: String,
+) -> SSHResult<Vec<String>> {
+  if command.contains(['\r', '\n']) {
+    return Err(SSHError::new("Tmux command must be a single line"));
+  }
+
+  let (shell_channel, shell_output) = get_tmux(&ssh_manager, ssh_shell_id).await?;
+  run_command(&shell_channel, &shell_output, command).await
+}
+
+/// Lists the panes of all windows in the attached session.
+#[tauri::command]
+pub async fn tmux_list_panes<R: Runtime>(
+  _app_handle: AppHandle<R>,
+  ssh_manager: State<'_, SSHManager<R>>,
+  ssh_shell_id: SSHShellId,
+) -> SSHResult<Vec<TmuxPane>> {
+  let (shell_channel, shell_output) = get_tmux(&ssh_manager, ssh_shell_id).await?;
+  // 窗口名可能包含空格，放在最后
+  let command = "list-panes -s -F '#{window_id} #{window_active} #{pane_id} #{pane_active} \
+    #{pane_width} #{pane_height} #{pane_left} #{pane_top} #{window_name}'";
+  let lines = run_command(&shell_channel, &shell_output, command.to_string()).await?;
+
+  let panes = lines
+    .iter()
+    .filter_map(|line| {
+      let mut fields = line.splitn(9, ' ');
+      Some(TmuxPane {
+        window_id: fields.next()?.to_string(),
+        window_active: fields.next()? == "1",
+        pane_id: fields.next()?.to_string(),
+        pane_active: fields.next()? == "1",
+        width: fields.next()?.parse().ok()?,
+        height: fields.next()?.parse().ok()?,
+        x: fields.next()?.parse().ok()?,
+        y: fields.next()?.parse().ok()?,
+        window_name: fields.next().unwrap_or_default().to_string(),
+      })
+    })
+    .collect();
+
+  Ok(panes)
+}
+
+/// Opens a pane as a virtual terminal, its history and screen are sent before the live output.
+#[tauri::command]
+pub async fn tmux_pane_open<R: Runtime>(
+  _app_handle: AppHandle<R>,
+  ssh_manager: State<'_, SSHManager<R>>,
+  ssh_shell_id: SSHShellId,
+  pane_id: TmuxPaneId,
+  ipc_channel: Channel<TmuxPaneIpcChannelData>,
+) -> SSHResult<TmuxPaneId> {
+  check_pane_id(&pane_id)?;
+  log::info!("tmux pane open {:?} {}", ssh_shell_id, pane_id);
+
+  let (shell_channel, shell_output) = get_tmux(&ssh_manager, ssh_shell_id).await?;
+  shell_output.tmux_open_pane(pane_id.clone(), ipc_channel)?;
+
+  send_commands(
+    &shell_channel,
+    &shell_output,
+    vec![
+      (
+        format!("capture-pane -p -e -S -{} -t {}", HISTORY_LINES, pane_id),
+        TmuxPending::Capture(pane_id.clone()),
+      ),
+      (
+        format!(
+          "display-message -p -t {} '#{{cursor_x}} #{{cursor_y}}'",
+          pane_id
+        ),
+        TmuxPending::Cursor(pane_id.clone()),
+      ),
+    ],
+  )
+  .await?;
+
+  Ok(pane_id)
+}
+
+/// Stops forwarding the output of a pane, the pane keeps running in tmux.
+#[tauri::command]
+pub async fn tmux_pane_close<R: Runtime>(
+  _app_handle: AppHandle<R>,
+  ssh_manager: State<'_, SSHManager<R>>,
+  ssh_shell_id: SSHShellId,
+  pane_id: TmuxPaneId,
+) -> SSHResult<TmuxPaneId> {
+  log::info!("tmux pane close {:?} {}", ssh_shell_id, pane_id);
+  let (_, shell_output) = get_shell_channel_output(&ssh_manager, ssh_shell_id).await?;
+  shell_output.tmux_close_pane(&pane_id)?;
+
+  Ok(pane_id)
+}
+
+/// Sends input to a pane as hex encoded keys, requires tmux 3.0 or later.
+#[tauri::command]
+pub async fn tmux_pane_send<R: Runtime>(
+  _app_handle: AppHandle<R>,
+  ssh_manager: State<'_, SSHManager<R>>,
+  ssh_shell_id: SSHShellId,
+  pane_id: TmuxPaneId,
+  data: Vec<u8>,
+) -> SSHResult<TmuxPaneId> {
+  check_pane_id(&pane_id)?;
+  if data.is_empty() {
+    return Ok(pane_id);
+  }
+
+  let (shell_channel, shell_output) = get_tmux(&ssh_manager, ssh_shell_id).await?;
+  let commands = data
+    .chunks(SEND_KEYS_SIZE)
+    .map(|chunk| {
+      let mut command = format!("send-keys -t {} -H", pane_id);
+      for byte in chunk {
+        let _ = write!(command, " {:02x}", byte);
+      }
+      (command, TmuxPending::Ignore)
+    })
+    .collect();
+  send_commands(&shell_channel, &shell_output, commands).await?;
+
+  Ok(pane_id)
+}
+
+/// Resizes a pane inside its window.
+#[tauri::command]
+pub async fn tmux_pane_resize<R: Runtime>(
+  _app_handle: AppHandle<R>,
+  ssh_manager: State<'_, SSHManager<R>>,
+  ssh_shell_id: SSHShellId,
+  pane_id: TmuxPaneId,
+  size: ShellSize,
+) -> SSHResult<TmuxPaneId> {
+  check_pane_id(&pane_id)?;
+
+  let (shell_channel, shell_output) = get_tmux(&ssh_manager, ssh_shell_id).await?;
+  let command = format!("resize-pane -t {} -x {} -y {}", pane_id, size.col, size.row);
+  run_command(&shell_channel, &shell_output, command).await?;
+
+  Ok(pane_id)
+}
+
+/// Sets the size of a window, tmux lays out its panes and reports them with a layout change.
+#[tauri::command]
+pub async fn tmux_window_resize<R: Runtime>(
+  _app_handle: AppHandle<R>,
+  ssh_manager: State<'_, SSHManager<R>>,
+  ssh_shell_id: SSHShellId,
+  window_id: TmuxWindowId,
+  size: ShellSize,
+) -> SSHResult<TmuxWindowId> {
+  check_window_id(&window_id)?;
+
+  let (shell_channel, shell_output) = get_tmux(&ssh_manager, ssh_shell_id).await?;
+  let command = format!("refresh-client -C {}:{}x{}", window_id, size.col, size.row);
+  // tmux 3.2 之前只能设置整个客户端的大小
+  if let Err(err) = run_command(&shell_channel, &shell_output, command).await {
+    log::warn!("tmux window resize failed, resize client: {}", err);
+    let command = format!("refresh-client -C {}x{}", size.col, size.row);
+    run_command(&shell_channel, &shell_output, command).await?;
+  }
+
+  Ok(window_id)
+}
+
+/// Detaches from tmux, the session keeps running on the server and the shell prompt returns.
+#[tauri::command]
+pub async fn tmux_detach<R: Runtime>(
+  _app_handle: AppHandle<R>,
+  ssh_manager: State<'_, SSHManager<R>>,
+  ssh_shell_id: SSHShellId,
+) -> SSHResult<SSHShellId> {
+  log::info!("tmux detach {:?}", ssh_shell_id);
+  let (shell_channel, shell_output) = get_tmux(&ssh_manager, ssh_shell_id).await?;
+  send_commands(
+    &shell_channel,
+    &shell_output,
+    vec![("detach-client".to_string(), TmuxPending::Ignore)],
+  )
+  .await?;
+
+  Ok(ssh_shell_id)
+}
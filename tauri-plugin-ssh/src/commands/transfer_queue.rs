@@ -0,0 +1,277 @@
+use serde::Deserialize;
+use tauri::{AppHandle, Emitter, Manager, Runtime, State, async_runtime, ipc::Channel};
+use tauri_plugin_fs::{FsExt, OpenOptions, SafeFilePath};
+use tokio::fs;
+use uuid::Uuid;
+
+use crate::{
+  checksum::SftpVerifyOpts,
+  commands::{
+    session::SSHSessionId,
+    sftp::{self, SSHSftpId, SSHTransferId},
+  },
+  error::{SSHError, SSHResult},
+  sftp_pipeline,
+  ssh_manager::{SSHManager, TransferControl},
+  transfer_queue::{TransferKind, TransferQueueEvent, TransferQueueItem},
+};
+
+/// Emitted with the [`TransferQueueItem`] once a transfer completes, fails or is cancelled
+/// while running, the app records the transfer history from it.
+pub const TRANSFER_FINISH_EVENT: &str = "ssh://transfer_finish";
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferQueueEnqueueOpts {
+  /// Puts the transfer before the other queued ones
+  #[serde(default)]
+  pub priority: bool,
+  /// Continues from the partial target instead of overwriting it
+  #[serde(default)]
+  pub resume: bool,
+  /// Compares the SHA-256 with the remote file after the transfer
+  pub verify: Option<SftpVerifyOpts>,
+}
+
+/// Starts the queued transfers that fit in the concurrency of their session.
+fn schedule<R: Runtime>(app_handle: &AppHandle<R>) -> SSHResult<()> {
+  let ssh_manager = app_handle.state::<SSHManager<R>>();
+  let runnable = ssh_manager.transfer_queue.lock()?.start_runnable();
+
+  for (item, control) in runnable {
+    let app_handle = app_handle.clone();
+    async_runtime::spawn(async move {
+      log::info!("transfer queue start {} {:?}", item.id, item.kind);
+      let result = run_transfer(&app_handle, &item, &control).await;
+
+      let ssh_manager = app_handle.state::<SSHManager<R>>();
+      let finished = ssh_manager
+        .transfer_queue
+        .lock()
+        .map_err(SSHError::from)
+        .and_then(|mut transfer_queue| transfer_queue.finish(&item.id, result));
+      match finished {
+        Ok(item) => {
+          if let Err(err) = app_handle.emit(TRANSFER_FINISH_EVENT, item) {
+            log::error!("transfer queue emit finish error {:?}", err);
+          }
+        }
+        Err(err) => log::error!("transfer queue finish error {:?}", err),
+      }
+      if let Err(err) = schedule(&app_handle) {
+        log::error!("transfer queue schedule error {:?}", err);
+      }
+    });
+  }
+
+  Ok(())
+}
+
+async fn run_transfer<R: Runtime>(
+  app_handle: &AppHandle<R>,
+  item: &TransferQueueItem,
+  control: &TransferControl,
+) -> SSHResult<()> {
+  let ssh_manager = app_handle.state::<SSHManager<R>>();
+  let sftp = sftp::get_sftp_session(&ssh_manager, item.ssh_sftp_id).await?;
+  let verifier = sftp::get_verifier(&ssh_manager, item.ssh_sftp_id, item.verify).await?;
+  let concurrency = sftp_pipeline::concurrency(None);
+  let local_path = item
+    .local_path
+    .clone()
+    .ok_or_else(|| SSHError::new("Local file of the transfer is unknown"))?;
+  let on_progress = |progress: u64, total: u64| -> SSHResult<()> {
+    ssh_manager
+      .transfer_queue
+      .lock()?
+      .progress(&item.id, progress, total)
+  };
+
+  match item.kind {
+    TransferKind::Upload => {
+      let local_file = fs::File::from_std(
+        app_handle
+          .fs()
+          .open(local_path, OpenOptions::new().read(true).to_owned())?,
+      );
+      sftp::upload_local_file(
+        sftp,
+        local_file,
+        &item.remote_filename,
+        item.resume,
+        concurrency,
+        control,
+        verifier.as_ref(),
+        on_progress,
+      )
+      .await
+    }
+    TransferKind::Download => {
+      let local_file = fs::File::from_std(
+        app_handle.fs().open(
+          local_path,
+          OpenOptions::new()
+            .create(true)
+            .read(true)
+            .write(true)
+            .truncate(false)
+            .to_owned(),
+        )?,
+      );
+      sftp::download_remote_file(
+        sftp,
+        &item.remote_filename,
+        local_file,
+        item.resume,
+        concurrency,
+        control,
+        verifier.as_ref(),
+        on_progress,
+      )
+      .await
+    }
+  }
+}
+
+/// Sets the channel receiving the queue events and returns the current items, a reloaded window
+/// subscribes again to pick up the transfers still running.
+#[tauri::command]
+pub async fn transfer_queue_subscribe<R: Runtime>(
+  _app_handle: AppHandle<R>,
+  ssh_manager: State<'_, SSHManager<R>>,
+  ipc_channel: Channel<TransferQueueEvent>,
+) -> SSHResult<Vec<TransferQueueItem>> {
+  Ok(ssh_manager.transfer_queue.lock()?.subscribe(ipc_channel))
+}
+
+#[tauri::command]
+pub async fn transfer_queue_list<R: Runtime>(
+  _app_handle: AppHandle<R>,
+  ssh_manager: State<'_, SSHManager<R>>,
+) -> SSHResult<Vec<TransferQueueItem>> {
+  Ok(ssh_manager.transfer_queue.lock()?.list())
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn transfer_queue_enqueue<R: Runtime>(
+  app_handle: AppHandle<R>,
+  ssh_manager: State<'_, SSHManager<R>>,
+  ssh_sftp_id: SSHSftpId,
+  kind: TransferKind,
+  local_filename: SafeFilePath,
+  remote_filename: String,
+  opts: Option<TransferQueueEnqueueOpts>,
+  task_id: Option<String>,
+  host_id: Option<String>,
+) -> SSHResult<SSHTransferId> {
+  let opts = opts.unwrap_or_default();
+  let task_id = task_id.unwrap_or_else(|| Uuid::new_v4().to_string());
+  let ssh_session_id = {
+    let sftps = ssh_manager.sftps.lock().await;
+    sftps
+      .get(&ssh_sftp_id)
+      .ok_or(SSHError::NotFoundSftp)?
+      .ssh_session_id
+  };
+  let item = TransferQueueItem::new(
+    task_id.clone(),
+    ssh_session_id,
+    ssh_sftp_id,
+    host_id,
+    kind,
+    local_filename,
+    remote_filename,
+    opts.resume,
+    opts.verify,
+  );
+  ssh_manager
+    .transfer_queue
+    .lock()?
+    .enqueue(item, opts.priority);
+  schedule(&app_handle)?;
+
+  Ok(task_id)
+}
+
+#[tauri::command]
+pub async fn transfer_queue_cancel<R: Runtime>(
+  _app_handle: AppHandle<R>,
+  ssh_manager: State<'_, SSHManager<R>>,
+  task_id: String,
+) -> SSHResult<()> {
+  ssh_manager.transfer_queue.lock()?.cancel(&task_id)
+}
+
+#[tauri::command]
+pub async fn transfer_queue_pause<R: Runtime>(
+  _app_handle: AppHandle<R>,
+  ssh_manager: State<'_, SSHManager<R>>,
+  task_id: String,
+) -> SSHResult<()> {
+  ssh_manager.transfer_queue.lock()?.pause(&task_id)
+}
+
+#[tauri::command]
+pub async fn transfer_queue_resume<R: Runtime>(
+  app_handle: AppHandle<R>,
+  ssh_manager: State<'_, SSHManager<R>>,
+  task_id: String,
+) -> SSHResult<()> {
+  ssh_manager.transfer_queue.lock()?.resume(&task_id)?;
+  schedule(&app_handle)
+}
+
+#[tauri::command]
+pub async fn transfer_queue_retry<R: Runtime>(
+  app_handle: AppHandle<R>,
+  ssh_manager: State<'_, SSHManager<R>>,
+  task_id: String,
+) -> SSHResult<()> {
+  ssh_manager.transfer_queue.lock()?.retry(&task_id)?;
+  schedule(&app_handle)
+}
+
+/// Moves the transfer to `index` in the queue, `0` starts it next.
+#[tauri::command]
+pub async fn transfer_queue_move<R: Runtime>(
+  _app_handle: AppHandle<R>,
+  ssh_manager: State<'_, SSHManager<R>>,
+  task_id: String,
+  index: usize,
+) -> SSHResult<()> {
+  ssh_manager.transfer_queue.lock()?.move_to(&task_id, index)
+}
+
+/// Removes a finished transfer.
+#[tauri::command]
+pub async fn transfer_queue_remove<R: Runtime>(
+  _app_handle: AppHandle<R>,
+  ssh_manager: State<'_, SSHManager<R>>,
+  task_id: String,
+) -> SSHResult<()> {
+  ssh_manager.transfer_queue.lock()?.remove(&task_id)
+}
+
+/// Removes the finished transfers, returns their ids.
+#[tauri::command]
+pub async fn transfer_queue_clear<R: Runtime>(
+  _app_handle: AppHandle<R>,
+  ssh_manager: State<'_, SSHManager<R>>,
+) -> SSHResult<Vec<String>> {
+  Ok(ssh_manager.transfer_queue.lock()?.clear())
+}
+
+#[tauri::command]
+pub async fn transfer_queue_set_concurrency<R: Runtime>(
+  app_handle: AppHandle<R>,
+  ssh_manager: State<'_, SSHManager<R>>,
+  ssh_session_id: SSHSessionId,
+  concurrency: usize,
+) -> SSHResult<()> {
+  ssh_manager
+    .transfer_queue
+    .lock()?
+    .set_concurrency(ssh_session_id, concurrency);
+  schedule(&app_handle)
+}
@@ -0,0 +1,200 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+  error::{SSHError, SSHResult},
+  exec::quote,
+};
+
+/// Prints the path of the first container cli installed on the host
+pub const DETECT_RUNTIME_COMMAND: &str = "command -v docker || command -v podman";
+
+/// Go template understood by both `docker ps` and `podman ps`, fields are tab separated
+const LIST_FORMAT: &str = "{{.ID}}\t{{.Names}}\t{{.Image}}\t{{.State}}\t{{.Status}}\t{{.Ports}}";
+
+/// Starts bash when the image has it, busybox based images only ship sh.
+const DEFAULT_SHELL: &str = "if command -v bash >/dev/null 2>&1; then exec bash; else exec sh; fi";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ContainerRuntime {
+  Docker,
+  Podman,
+}
+
+impl ContainerRuntime {
+  pub fn program(&self) -> &'static str {
+    match self {
+      ContainerRuntime::Docker => "docker",
+      ContainerRuntime::Podman => "podman",
+    }
+  }
+
+  /// Parses the output of [`DETECT_RUNTIME_COMMAND`].
+  pub fn detect(output: &str) -> Option<Self> {
+    let path = output.lines().next()?.trim();
+    match path.rsplit('/').next()? {
+      "docker" => Some(ContainerRuntime::Docker),
+      "podman" => Some(ContainerRuntime::Podman),
+      _ => None,
+    }
+  }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Container {
+  pub id: String,
+  pub name: String,
+  pub image: String,
+  /// Machine readable state such as `running` or `exited`
+  pub state: String,
+  /// Human readable status such as `Up 2 hours`
+  pub status: String,
+  pub ports: String,
+  pub running: bool,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub enum ContainerAction {
+  Start,
+  Stop,
+  Restart,
+}
+
+impl ContainerAction {
+  fn as_arg(&self) -> &'static str {
+    match self {
+      ContainerAction::Start => "start",
+      ContainerAction::Stop => "stop",
+      ContainerAction::Restart => "restart",
+    }
+  }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContainerLogsOpts {
+  /// Lines printed before following, all lines when empty
+  pub tail: Option<u32>,
+  #[serde(default)]
+  pub follow: bool,
+  #[serde(default)]
+  pub timestamps: bool,
+}
+
+/// Container ids and names are interpolated into commands, only the characters
+/// allowed by docker are accepted.
+pub fn check_container_id(container_id: &str) -> SSHResult<()> {
+  let valid = container_id
+    .chars()
+    .next()
+    .is_some_and(|char| char.is_ascii_alphanumeric())
+    && container_id
+      .chars()
+      .all(|char| char.is_ascii_alphanumeric() || matches!(char, '_' | '.' | '-'));
+  if !valid {
+    return Err(SSHError::new(format!("Invalid container {}", container_id)));
+  }
+
+  Ok(())
+}
+
+pub fn list_command(runtime: ContainerRuntime) -> String {
+  format!(
+    "{} ps -a --format {}",
+    runtime.program(),
+    quote(LIST_FORMAT)
+  )
+}
+
+pub fn parse_list(output: &str) -> Vec<Container> {
+  output
+    .lines()
+    .filter_map(|line| {
+      let mut fields = line.splitn(6, '\t');
+      let id = fields.next()?.trim();
+      if id.is_empty() {
+        return None;
+      }
+      let mut next = || fields.next().unwrap_or_default().trim().to_string();
+      let name = next();
+      let image = next();
+      let state = next();
+      let status = next();
+      let ports = next();
+
+      // podman 的 State 与 Status 格式不同，两者都检查
+      let running = state.eq_ignore_ascii_case("running") || status.starts_with("Up");
+      Some(Container {
+        id: id.to_string(),
+        name,
+        image,
+        state,
+        status,
+        ports,
+        running,
+      })
+    })
+    .collect()
+}
+
+pub fn action_command(
+  runtime: ContainerRuntime,
+  container_id: &str,
+  action: ContainerAction,
+) -> SSHResult<String> {
+  check_container_id(container_id)?;
+
+  Ok(format!(
+    "{} {} {}",
+    runtime.program(),
+    action.as_arg(),
+    container_id
+  ))
+}
+
+pub fn logs_command(
+  runtime: ContainerRuntime,
+  container_id: &str,
+  opts: &ContainerLogsOpts,
+) -> SSHResult<String> {
+  check_container_id(container_id)?;
+
+  let mut command = format!("{} logs", runtime.program());
+  if let Some(tail) = opts.tail {
+    command.push_str(&format!(" --tail {}", tail));
+  }
+  if opts.follow {
+    command.push_str(" --follow");
+  }
+  if opts.timestamps {
+    command.push_str(" --timestamps");
+  }
+  command.push(' ');
+  command.push_str(container_id);
+
+  Ok(command)
+}
+
+/// Command run on the pty channel to open a shell in the container.
+pub fn exec_command(
+  runtime: ContainerRuntime,
+  container_id: &str,
+  term: &str,
+  shell: Option<&str>,
+) -> SSHResult<String> {
+  check_container_id(container_id)?;
+
+  let shell = shell
+    .map(str::trim)
+    .filter(|shell| !shell.is_empty())
+    .map(|shell| format!("exec {}", shell))
+    .unwrap_or_else(|| DEFAULT_SHELL.to_string());
+
+  Ok(format!(
+    "{} exec -it -e {} {} sh -c {}",
+    runtime.program(),
+    quote(&format!("TERM={}", term)),
+    container_id,
+    quote(&shell)
+  ))
+}
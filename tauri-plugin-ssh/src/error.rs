@@ -161,15 +161,62 @@ pub enum SSHError {
   #[error("Not found sftp")]
   NotFoundSftp,
 
+  #[error("Not found shell")]
+  NotFoundShell,
+
+  #[error("Not found zmodem transfer")]
+  NotFoundZmodem,
+
+  #[error("Not found tmux control mode")]
+  NotFoundTmux,
+
+  #[error("Docker or Podman not found")]
+  NotFoundContainerRuntime,
+
+  #[error("Not found container logs")]
+  NotFoundContainerLogs,
+
+  #[error("Not found transfer")]
+  NotFoundTransfer,
+
   #[error("Transfer cancelled")]
   TransferCancelled,
 
+  #[error("Checksum mismatch for {filename}: local {local}, remote {remote}")]
+  ChecksumMismatch {
+    filename: String,
+    local: String,
+    remote: String,
+  },
+
+  #[error("Insufficient space on {path}: {required} bytes required, {available} bytes available")]
+  InsufficientSpace {
+    path: String,
+    required: u64,
+    available: u64,
+  },
+
+  #[error("Zmodem transfer failed: {0}")]
+  ZmodemError(String),
+
+  #[error("Tmux command failed: {0}")]
+  TmuxError(String),
+
+  #[error("Container command failed: {0}")]
+  ContainerError(String),
+
   #[error(transparent)]
   StdStrUtf8Error(#[from] std::str::Utf8Error),
 
   #[error(transparent)]
   UuidError(#[from] uuid::Error),
 
+  #[error(transparent)]
+  RegexError(#[from] regex::Error),
+
+  #[error(transparent)]
+  TerminalError(#[from] terminal_core::TerminalError),
+
   #[error("{0}")]
   Error(String),
 }
@@ -189,6 +236,28 @@ fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
         "algorithm": algorithm,
         "fingerprint": fingerprint.to_string(),
       }),
+      SSHError::ChecksumMismatch {
+        filename,
+        local,
+        remote,
+      } => json!({
+        "type": self.as_ref(),
+        "message": self.to_string(),
+        "filename": filename,
+        "local": local,
+        "remote": remote,
+      }),
+      SSHError::InsufficientSpace {
+        path,
+        required,
+        available,
+      } => json!({
+        "type": self.as_ref(),
+        "message": self.to_string(),
+        "path": path,
+        "required": required,
+        "available": available,
+      }),
       _ => json!({
         "type": self.as_ref(),
         "message": self.to_string(),
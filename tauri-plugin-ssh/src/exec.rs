@@ -0,0 +1,67 @@
+use russh::{Channel as RusshChannel, ChannelMsg, client};
+use tauri::Runtime;
+
+use crate::{
+  commands::session::SSHSessionId,
+  error::{SSHError, SSHResult},
+  ssh_manager::SSHManager,
+};
+
+pub struct ExecOutput {
+  pub stdout: Vec<u8>,
+  pub stderr: Vec<u8>,
+  pub exit_status: Option<u32>,
+}
+
+/// Quotes an argument for a POSIX shell.
+pub fn quote(arg: &str) -> String {
+  format!("'{}'", arg.replace('\'', r#"'\''"#))
+}
+
+pub async fn open_exec_channel<R: Runtime>(
+  ssh_manager: &SSHManager<R>,
+  ssh_session_id: SSHSessionId,
+  command: &str,
+) -> SSHResult<RusshChannel<client::Msg>> {
+  let session = {
+    let sessions = ssh_manager.sessions.lock().await;
+    sessions
+      .get(&ssh_session_id)
+      .ok_or(SSHError::NotFoundSession)?
+      .handle_ssh_client
+      .clone()
+  };
+
+  let channel = session.lock().await.channel_open_session().await?;
+  channel.exec(true, command.as_bytes()).await?;
+
+  Ok(channel)
+}
+
+/// Runs a command on its own exec channel and collects the output.
+pub async fn exec<R: Runtime>(
+  ssh_manager: &SSHManager<R>,
+  ssh_session_id: SSHSessionId,
+  command: &str,
+) -> SSHResult<ExecOutput> {
+  log::info!("exec {:?} {}", ssh_session_id, command);
+  let mut channel = open_exec_channel(ssh_manager, ssh_session_id, command).await?;
+  // 命令不读取标准输入，关闭后等待输入的程序（如被强制为 sftp 的账号）会直接退出
+  channel.eof().await?;
+
+  let mut output = ExecOutput {
+    stdout: Vec::new(),
+    stderr: Vec::new(),
+    exit_status: None,
+  };
+  while let Some(msg) = channel.wait().await {
+    match msg {
+      ChannelMsg::Data { data } => output.stdout.extend_from_slice(&data),
+      ChannelMsg::ExtendedData { data, .. } => output.stderr.extend_from_slice(&data),
+      ChannelMsg::ExitStatus { exit_status } => output.exit_status = Some(exit_status),
+      _ => {}
+    }
+  }
+
+  Ok(output)
+}
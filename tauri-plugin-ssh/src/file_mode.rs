@@ -0,0 +1,229 @@
+use crate::error::{SSHError, SSHResult};
+
+/// Permission, set-id and sticky bits, the file type bits are never changed.
+pub const MODE_MASK: u32 = 0o7777;
+
+const WHO_USER: u32 = 0o4700;
+const WHO_GROUP: u32 = 0o2070;
+const WHO_OTHER: u32 = 0o1007;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+  Add,
+  Remove,
+  Set,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Perm {
+  Read,
+  Write,
+  Execute,
+  /// Execute only for directories or files already executable by someone
+  SearchExecute,
+  SetId,
+  Sticky,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Action {
+  op: Op,
+  perms: Vec<Perm>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Clause {
+  who: u32,
+  actions: Vec<Action>,
+}
+
+/// A mode accepted by chmod, either octal like `755` or symbolic like `u+x,go-w`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileMode {
+  Octal(u32),
+  Symbolic(Vec<Clause>),
+}
+
+impl FileMode {
+  pub fn parse(mode: &str) -> SSHResult<Self> {
+    let mode = mode.trim();
+    let invalid = || SSHError::new(format!("Invalid file mode {}", mode));
+
+    if !mode.is_empty() && mode.len() <= 4 && mode.chars().all(|char| ('0'..='7').contains(&char)) {
+      return u32::from_str_radix(mode, 8)
+        .map(FileMode::Octal)
+        .map_err(|_| invalid());
+    }
+
+    let mut clauses = Vec::new();
+    for clause in mode.split(',') {
+      let mut chars = clause.chars().peekable();
+
+      let mut who = 0;
+      while let Some(char) = chars.peek() {
+        who |= match char {
+          'u' => WHO_USER,
+          'g' => WHO_GROUP,
+          'o' => WHO_OTHER,
+          'a' => MODE_MASK,
+          _ => break,
+        };
+        chars.next();
+      }
+      if who == 0 {
+        who = MODE_MASK;
+      }
+
+      let mut actions: Vec<Action> = Vec::new();
+      for char in chars {
+        let op = match char {
+          '+' => Some(Op::Add),
+          '-' => Some(Op::Remove),
+          '=' => Some(Op::Set),
+          _ => None,
+        };
+        if let Some(op) = op {
+          actions.push(Action {
+            op,
+            perms: Vec::new(),
+          });
+          continue;
+        }
+
+        let perm = match char {
+          'r' => Perm::Read,
+          'w' => Perm::Write,
+          'x' => Perm::Execute,
+          'X' => Perm::SearchExecute,
+          's' => Perm::SetId,
+          't' => Perm::Sticky,
+          _ => return Err(invalid()),
+        };
+        actions.last_mut().ok_or_else(invalid)?.perms.push(perm);
+      }
+
+      if actions.is_empty() {
+        return Err(invalid());
+      }
+      clauses.push(Clause { who, actions });
+    }
+
+    Ok(FileMode::Symbolic(clauses))
+  }
+
+  /// Whether the result depends on the current mode, i.e. the path has to be stat first.
+  pub fn is_relative(&self) -> bool {
+    matches!(self, FileMode::Symbolic(_))
+  }
+
+  /// Returns the permission bits for a path currently having `mode`.
+  pub fn apply(&self, mode: u32, is_dir: bool) -> u32 {
+    let clauses = match self {
+      FileMode::Octal(bits) => return *bits & MODE_MASK,
+      FileMode::Symbolic(clauses) => clauses,
+    };
+
+    let mut mode = mode & MODE_MASK;
+    for clause in clauses {
+      for action in &clause.actions {
+        let mut bits = 0;
+        for perm in &action.perms {
+          bits |= match perm {
+            Perm::Read => 0o444,
+            Perm::Write => 0o222,
+            Perm::Execute => 0o111,
+            Perm::SearchExecute if is_dir || mode & 0o111 != 0 => 0o111,
+            Perm::SearchExecute => 0,
+            Perm::SetId => 0o6000,
+            Perm::Sticky => 0o1000,
+          };
+        }
+        let bits = bits & clause.who;
+
+        mode = match action.op {
+          Op::Add => mode | bits,
+          Op::Remove => mode & !bits,
+          Op::Set => (mode & !clause.who) | bits,
+        };
+      }
+    }
+
+    mode
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn apply(mode: &str, current: u32, is_dir: bool) -> u32 {
+    FileMode::parse(mode).unwrap().apply(current, is_dir)
+  }
+
+  #[test]
+  fn parse_octal() {
+    assert_eq!(FileMode::parse("755").unwrap(), FileMode::Octal(0o755));
+    assert_eq!(FileMode::parse(" 0644 ").unwrap(), FileMode::Octal(0o644));
+    assert_eq!(FileMode::parse("4755").unwrap(), FileMode::Octal(0o4755));
+    assert!(!FileMode::parse("755").unwrap().is_relative());
+  }
+
+  #[test]
+  fn parse_symbolic() {
+    assert!(FileMode::parse("u+x").unwrap().is_relative());
+    assert!(FileMode::parse("u+x,go-w").is_ok());
+    assert!(FileMode::parse("a=rw").is_ok());
+    assert!(FileMode::parse("+X").is_ok());
+    assert!(FileMode::parse("u=").is_ok());
+    assert!(FileMode::parse("u+r-w").is_ok());
+  }
+
+  #[test]
+  fn parse_rejects_invalid_modes() {
+    for mode in ["", "8", "12345", "u", "u+z", "rw", "u+x,", "x+r"] {
+      assert!(FileMode::parse(mode).is_err(), "{}", mode);
+    }
+  }
+
+  #[test]
+  fn apply_octal_replaces_the_permission_bits() {
+    assert_eq!(apply("644", 0o755, false), 0o644);
+    assert_eq!(apply("7777", 0, false), 0o7777);
+    // 文件类型位不会被修改，也不会出现在结果中
+    assert_eq!(apply("644", 0o100755, false), 0o644);
+  }
+
+  #[test]
+  fn apply_adds_and_removes_bits() {
+    assert_eq!(apply("u+x", 0o644, false), 0o744);
+    assert_eq!(apply("go-w", 0o666, false), 0o644);
+    assert_eq!(apply("+x", 0o644, false), 0o755);
+    assert_eq!(apply("a-r", 0o644, false), 0o200);
+    assert_eq!(apply("u+r-w", 0o200, false), 0o400);
+    assert_eq!(apply("u+x,g+w", 0o600, false), 0o720);
+  }
+
+  #[test]
+  fn apply_set_replaces_the_bits_of_who() {
+    assert_eq!(apply("u=rw", 0o755, false), 0o655);
+    assert_eq!(apply("go=", 0o777, false), 0o700);
+    assert_eq!(apply("a=r", 0o777, false), 0o444);
+    assert_eq!(apply("u=rwx", 0o4644, false), 0o744);
+  }
+
+  #[test]
+  fn apply_search_execute() {
+    assert_eq!(apply("a+X", 0o644, false), 0o644);
+    assert_eq!(apply("a+X", 0o744, false), 0o755);
+    assert_eq!(apply("a+X", 0o644, true), 0o755);
+  }
+
+  #[test]
+  fn apply_set_id_and_sticky() {
+    assert_eq!(apply("u+s", 0o755, false), 0o4755);
+    assert_eq!(apply("g+s", 0o755, true), 0o2755);
+    assert_eq!(apply("+t", 0o777, true), 0o1777);
+    assert_eq!(apply("o+t", 0o777, true), 0o1777);
+    assert_eq!(apply("u-s", 0o6755, false), 0o2755);
+  }
+}
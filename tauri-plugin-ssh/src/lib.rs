@@ -1,8 +1,21 @@
+pub(crate) mod checksum;
 pub(crate) mod commands;
+pub(crate) mod container;
 pub(crate) mod error;
+pub(crate) mod exec;
+pub(crate) mod file_mode;
+pub(crate) mod sftp_pipeline;
+pub(crate) mod sftp_raw;
+pub(crate) mod shell_output;
 pub(crate) mod ssh_client;
 pub(crate) mod ssh_manager;
+pub(crate) mod startup_script;
+pub(crate) mod tmux;
+pub(crate) mod transfer_filter;
+pub(crate) mod transfer_queue;
+pub(crate) mod url_forwarding;
 pub(crate) mod utils;
+pub(crate) mod zmodem;
 
 use ssh_manager::SSHManager;
 use tauri::{
@@ -10,7 +23,12 @@
   plugin::{Builder, TauriPlugin},
 };
 
+pub use commands::{
+  shell::{SHELL_CLOSE_EVENT, SSHShellId, write_shell},
+  transfer_queue::TRANSFER_FINISH_EVENT,
+};
 pub use error::{SSHError, SSHResult};
+pub use transfer_queue::{TransferKind, TransferQueueItem, TransferStatus};
 
 /// Initializes the plugin.
 pub fn init<R: Runtime>() -> TauriPlugin<R> {
@@ -23,17 +41,45 @@ pub fn init<R: Runtime>() -> TauriPlugin<R> {
       commands::shell::shell_close,
       commands::shell::shell_resize,
       commands::shell::shell_send,
+      commands::shell::shell_signal,
+      commands::shell::shell_break,
+      commands::shell::shell_ack,
+      commands::shell::shell_attach,
+      commands::shell::shell_detach,
+      commands::shell::shell_set_triggers,
+      commands::shell::shell_screen,
+      commands::shell::shell_screen_search,
+      commands::shell::shell_screen_export,
+      commands::shell::shell_zmodem_upload,
+      commands::shell::shell_zmodem_download,
+      commands::shell::shell_zmodem_cancel,
+      commands::tmux::tmux_command,
+      commands::tmux::tmux_list_panes,
+      commands::tmux::tmux_pane_open,
+      commands::tmux::tmux_pane_close,
+      commands::tmux::tmux_pane_send,
+      commands::tmux::tmux_pane_resize,
+      commands::tmux::tmux_window_resize,
+      commands::tmux::tmux_detach,
       commands::port_forwarding::port_forwarding_local_open,
+      commands::port_forwarding::port_forwarding_url_open,
       commands::port_forwarding::port_forwarding_local_close,
       commands::port_forwarding::port_forwarding_remote_open,
       commands::port_forwarding::port_forwarding_remote_close,
       commands::port_forwarding::port_forwarding_dynamic_open,
       commands::port_forwarding::port_forwarding_dynamic_close,
+      commands::container::container_list,
+      commands::container::container_action,
+      commands::container::container_logs_open,
+      commands::container::container_logs_close,
       commands::sftp::sftp_open,
       commands::sftp::sftp_close,
       commands::sftp::sftp_read_dir,
       commands::sftp::sftp_upload_file,
       commands::sftp::sftp_download_file,
+      commands::sftp::sftp_upload_dir,
+      commands::sftp::sftp_download_dir,
+      commands::sftp::sftp_remote_transfer,
       commands::sftp::sftp_cancel_task,
       commands::sftp::sftp_pause_task,
       commands::sftp::sftp_resume_task,
@@ -42,10 +88,33 @@ pub fn init<R: Runtime>() -> TauriPlugin<R> {
       commands::sftp::sftp_remove_dir,
       commands::sftp::sftp_remove_file,
       commands::sftp::sftp_rename,
+      commands::sftp::sftp_remove_all,
+      commands::sftp::sftp_copy,
+      commands::sftp::sftp_move,
+      commands::sftp::sftp_chmod,
+      commands::sftp::sftp_chown,
+      commands::sftp::sftp_set_times,
+      commands::sftp::sftp_stat,
+      commands::sftp::sftp_lstat,
+      commands::sftp::sftp_read_link,
+      commands::sftp::sftp_symlink,
+      commands::sftp::sftp_hardlink,
+      commands::sftp::sftp_statvfs,
       commands::sftp::sftp_exists,
       commands::sftp::sftp_canonicalize,
       commands::sftp::sftp_read_text_file,
       commands::sftp::sftp_write_text_file,
+      commands::transfer_queue::transfer_queue_subscribe,
+      commands::transfer_queue::transfer_queue_list,
+      commands::transfer_queue::transfer_queue_enqueue,
+      commands::transfer_queue::transfer_queue_cancel,
+      commands::transfer_queue::transfer_queue_pause,
+      commands::transfer_queue::transfer_queue_resume,
+      commands::transfer_queue::transfer_queue_retry,
+      commands::transfer_queue::transfer_queue_move,
+      commands::transfer_queue::transfer_queue_remove,
+      commands::transfer_queue::transfer_queue_clear,
+      commands::transfer_queue::transfer_queue_set_concurrency,
     ])
     .setup(|app, _api| {
       app.manage(SSHManager::<R>::init());
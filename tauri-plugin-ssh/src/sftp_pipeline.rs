@@ -0,0 +1,341 @@
+use std::{
+  collections::VecDeque,
+  sync::{Arc, Mutex, atomic::Ordering},
+  time::Duration,
+};
+
+use russh_sftp::{
+  client::{SftpSession, fs::File as SftpFile},
+  protocol::OpenFlags,
+};
+use tauri::async_runtime::{self, JoinHandle};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncSeekExt, AsyncWriteExt, SeekFrom};
+
+use crate::{
+  error::{SSHError, SSHResult},
+  ssh_manager::TransferControl,
+};
+
+/// Size of a single chunk request, servers commonly cap reads at 256 KiB.
+const CHUNK_SIZE: u64 = 256 * 1024;
+
+pub const DEFAULT_CONCURRENCY: usize = 16;
+
+const MAX_CONCURRENCY: usize = 64;
+
+/// Chunks of an upload complete out of order, an interrupted upload may have holes within this
+/// distance before the end of the remote file.
+pub const UPLOAD_RESUME_MARGIN: u64 = CHUNK_SIZE * MAX_CONCURRENCY as u64;
+
+pub fn concurrency(concurrency: Option<usize>) -> usize {
+  concurrency
+    .unwrap_or(DEFAULT_CONCURRENCY)
+    .clamp(1, MAX_CONCURRENCY)
+}
+
+/// Waits while the transfer is paused, fails once it is cancelled.
+pub async fn check_transfer_control(control: &TransferControl) -> SSHResult<()> {
+  if control.cancel.load(Ordering::Relaxed) {
+    return Err(SSHError::TransferCancelled);
+  }
+
+  while control.pause.load(Ordering::Relaxed) {
+    tokio::time::sleep(Duration::from_millis(200)).await;
+  }
+
+  if control.cancel.load(Ordering::Relaxed) {
+    return Err(SSHError::TransferCancelled);
+  }
+
+  Ok(())
+}
+
+/// Reads up to `len` bytes, fewer only when `reader` reaches EOF.
+async fn read_up_to<R>(reader: &mut R, len: u64) -> SSHResult<Vec<u8>>
+where
+  R: AsyncRead + Unpin,
+{
+  let mut data = vec![0; len as usize];
+  let mut filled = 0;
+  while filled < data.len() {
+    let n = reader.read(&mut data[filled..]).await?;
+    if n == 0 {
+      break;
+    }
+    filled += n;
+  }
+  data.truncate(filled);
+  Ok(data)
+}
+
+/// Handles of the remote file, every chunk in flight seeks its own handle so requests at
+/// different offsets don't wait for each other.
+struct HandlePool {
+  sftp: Arc<SftpSession>,
+  filename: String,
+  flags: OpenFlags,
+  files: Mutex<Vec<SftpFile>>,
+}
+
+impl HandlePool {
+  fn new(sftp: Arc<SftpSession>, filename: &str, flags: OpenFlags) -> Self {
+    Self {
+      sftp,
+      filename: filename.to_string(),
+      flags,
+      files: Mutex::default(),
+    }
+  }
+
+  async fn take(&self) -> SSHResult<SftpFile> {
+    let file = self.files.lock()?.pop();
+    match file {
+      Some(file) => Ok(file),
+      None => Ok(
+        self
+          .sftp
+          .open_with_flags(self.filename.clone(), self.flags)
+          .await?,
+      ),
+    }
+  }
+
+  fn put(&self, file: SftpFile) -> SSHResult<()> {
+    self.files.lock()?.push(file);
+    Ok(())
+  }
+
+  async fn close(&self) -> SSHResult<()> {
+    let files = std::mem::take(&mut *self.files.lock()?);
+    for mut file in files {
+      file.shutdown().await?;
+    }
+    Ok(())
+  }
+
+  async fn read_chunk(&self, offset: u64, len: u64) -> SSHResult<Vec<u8>> {
+    let mut file = self.take().await?;
+    file.seek(SeekFrom::Start(offset)).await?;
+    let data = read_up_to(&mut file, len).await?;
+    self.put(file)?;
+    Ok(data)
+  }
+
+  async fn write_chunk(&self, offset: u64, data: Vec<u8>) -> SSHResult<u64> {
+    let mut file = self.take().await?;
+    file.seek(SeekFrom::Start(offset)).await?;
+    file.write_all(&data).await?;
+    // flush 等待服务端确认写入
+    file.flush().await?;
+    self.put(file)?;
+    Ok(data.len() as u64)
+  }
+}
+
+/// Chunk requests in flight, in file order. The ones still running are aborted when the
+/// transfer stops early.
+struct Window<T>(VecDeque<JoinHandle<SSHResult<T>>>);
+
+impl<T> Drop for Window<T> {
+  fn drop(&mut self) {
+    for handle in &self.0 {
+      handle.abort();
+    }
+  }
+}
+
+/// Downloads `remote_filename` from `offset` with `concurrency` reads in flight, chunks are
+/// written to `target` in order. `total` is only the size seen by stat, the file is read until
+/// EOF, sequentially past `total`. `on_progress` receives the bytes of the file transferred so
+/// far.
+#[allow(clippy::too_many_arguments)]
+pub async fn download<W, F>(
+  sftp: Arc<SftpSession>,
+  remote_filename: &str,
+  offset: u64,
+  total: u64,
+  target: &mut W,
+  concurrency: usize,
+  control: &TransferControl,
+  mut on_progress: F,
+) -> SSHResult<()>
+where
+  W: AsyncWriteExt + Unpin,
+  F: FnMut(u64) -> SSHResult<()>,
+{
+  let pool = Arc::new(HandlePool::new(sftp, remote_filename, OpenFlags::READ));
+  let mut window = Window(VecDeque::new());
+  let mut next = offset;
+  let mut progress = offset;
+  let mut eof = false;
+
+  while progress < total {
+    check_transfer_control(control).await?;
+
+    while window.0.len() < concurrency && next < total {
+      let len = CHUNK_SIZE.min(total - next);
+      let pool = pool.clone();
+      let chunk_offset = next;
+      window.0.push_back(async_runtime::spawn(async move {
+        pool.read_chunk(chunk_offset, len).await
+      }));
+      next += len;
+    }
+
+    let Some(handle) = window.0.pop_front() else {
+      break;
+    };
+    let len = CHUNK_SIZE.min(total - progress);
+    let data = handle.await??;
+    target.write_all(&data).await?;
+    progress += data.len() as u64;
+    on_progress(progress)?;
+
+    // 文件在传输中变小，后面的分块都已越过 EOF
+    if (data.len() as u64) < len {
+      eof = true;
+      break;
+    }
+  }
+  drop(window);
+
+  // 文件在传输中变大，或者 stat 没有给出大小（例如 /proc 下的文件），顺序读取剩余部分
+  if !eof {
+    let mut file = pool.take().await?;
+    file.seek(SeekFrom::Start(progress)).await?;
+    loop {
+      check_transfer_control(control).await?;
+      let data = read_up_to(&mut file, CHUNK_SIZE).await?;
+      if data.is_empty() {
+        break;
+      }
+      target.write_all(&data).await?;
+      progress += data.len() as u64;
+      on_progress(progress)?;
+    }
+    pool.put(file)?;
+  }
+
+  pool.close().await
+}
+
+/// Uploads `source` from `offset` with `concurrency` writes in flight, `source` must already be
+/// positioned at `offset`. `total` is only the expected size, `source` is read until EOF,
+/// sequentially past `total`. `on_progress` receives the bytes of the file transferred so far.
+#[allow(clippy::too_many_arguments)]
+pub async fn upload<R, F>(
+  sftp: Arc<SftpSession>,
+  remote_filename: &str,
+  offset: u64,
+  total: u64,
+  source: &mut R,
+  concurrency: usize,
+  control: &TransferControl,
+  mut on_progress: F,
+) -> SSHResult<()>
+where
+  R: AsyncReadExt + Unpin,
+  F: FnMut(u64) -> SSHResult<()>,
+{
+  let pool = Arc::new(HandlePool::new(sftp, remote_filename, OpenFlags::WRITE));
+  let mut window = Window(VecDeque::new());
+  let mut next = offset;
+  let mut progress = offset;
+  let mut eof = false;
+
+  loop {
+    check_transfer_control(control).await?;
+
+    while window.0.len() < concurrency && !eof && next < total {
+      let len = CHUNK_SIZE.min(total - next);
+      let data = read_up_to(source, len).await?;
+      // 文件在传输中变小，读到 EOF 后不再发起新的分块
+      eof = (data.len() as u64) < len;
+      if data.is_empty() {
+        break;
+      }
+
+      let pool = pool.clone();
+      let chunk_offset = next;
+      next += data.len() as u64;
+      window.0.push_back(async_runtime::spawn(async move {
+        pool.write_chunk(chunk_offset, data).await
+      }));
+    }
+
+    let Some(handle) = window.0.pop_front() else {
+      break;
+    };
+    progress += handle.await??;
+    on_progress(progress)?;
+  }
+
+  // 文件在传输中变大，或者大小未知，顺序上传剩余部分
+  if !eof {
+    loop {
+      check_transfer_control(control).await?;
+      let data = read_up_to(source, CHUNK_SIZE).await?;
+      if data.is_empty() {
+        break;
+      }
+      progress += pool.write_chunk(progress, data).await?;
+      on_progress(progress)?;
+    }
+  }
+
+  pool.close().await
+}
+
+/// Pipe buffer between the download and the upload of a remote copy.
+const COPY_BUFFER_SIZE: usize = 4 * 1024 * 1024;
+
+/// Copies a remote file to another remote path, possibly over another sftp session, without a
+/// temporary file. `target_filename` must already exist, it is written from the start.
+#[allow(clippy::too_many_arguments)]
+pub async fn copy<F>(
+  source_sftp: Arc<SftpSession>,
+  source_filename: &str,
+  target_sftp: Arc<SftpSession>,
+  target_filename: &str,
+  total: u64,
+  concurrency: usize,
+  control: &TransferControl,
+  on_progress: F,
+) -> SSHResult<()>
+where
+  F: FnMut(u64) -> SSHResult<()>,
+{
+  let (mut writer, mut reader) = tokio::io::duplex(COPY_BUFFER_SIZE);
+
+  // 上传按下载写入的顺序读取，任一端失败时另一端随之结束
+  // 下载结束后关闭写端，上传读到 EOF 才结束
+  tokio::try_join!(
+    async move {
+      download(
+        source_sftp,
+        source_filename,
+        0,
+        total,
+        &mut writer,
+        concurrency,
+        control,
+        |_| Ok(()),
+      )
+      .await?;
+      writer.shutdown().await?;
+      Ok::<_, SSHError>(())
+    },
+    upload(
+      target_sftp,
+      target_filename,
+      0,
+      total,
+      &mut reader,
+      concurrency,
+      control,
+      on_progress,
+    ),
+  )?;
+
+  Ok(())
+}
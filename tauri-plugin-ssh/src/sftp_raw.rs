@@ -0,0 +1,95 @@
+use std::ops::Deref;
+
+use russh_sftp::{
+  client::RawSftpSession,
+  protocol::{Packet, StatusCode},
+};
+use tauri::Runtime;
+
+use crate::{
+  commands::session::SSHSessionId,
+  error::{SSHError, SSHResult},
+  ssh_manager::SSHManager,
+};
+
+/// Raw sftp session next to the sftp channel, extension requests need it. Opened once with the
+/// sftp channel and shared by every extension request on it.
+pub struct RawSftp {
+  pub session: RawSftpSession,
+  /// Extensions the server announced
+  pub extensions: Vec<String>,
+}
+
+impl RawSftp {
+  pub fn supports(&self, extension: &str) -> bool {
+    self.extensions.iter().any(|name| name == extension)
+  }
+
+  /// OpenSSH's sftp-server announces its extensions with the `@openssh.com` suffix.
+  pub fn is_openssh(&self) -> bool {
+    self
+      .extensions
+      .iter()
+      .any(|name| name.ends_with("@openssh.com"))
+  }
+}
+
+impl Deref for RawSftp {
+  type Target = RawSftpSession;
+
+  fn deref(&self) -> &Self::Target {
+    &self.session
+  }
+}
+
+/// Opens a raw sftp session on a new channel and probes the extensions of the server.
+pub async fn open_raw_sftp<R: Runtime>(
+  ssh_manager: &SSHManager<R>,
+  ssh_session_id: SSHSessionId,
+) -> SSHResult<RawSftp> {
+  let session = {
+    let sessions = ssh_manager.sessions.lock().await;
+    sessions
+      .get(&ssh_session_id)
+      .ok_or(SSHError::NotFoundSession)?
+      .handle_ssh_client
+      .clone()
+  };
+
+  let channel = session.lock().await.channel_open_session().await?;
+  channel.request_subsystem(true, "sftp").await?;
+  let session = RawSftpSession::new(channel.into_stream());
+  let version = session.init().await?;
+
+  Ok(RawSftp {
+    session,
+    extensions: version.extensions.into_keys().collect(),
+  })
+}
+
+pub fn put_string(data: &mut Vec<u8>, value: &[u8]) {
+  data.extend_from_slice(&(value.len() as u32).to_be_bytes());
+  data.extend_from_slice(value);
+}
+
+pub fn take_string<'a>(data: &mut &'a [u8]) -> Option<&'a [u8]> {
+  let len = u32::from_be_bytes(data.get(..4)?.try_into().ok()?) as usize;
+  let value = data.get(4..4 + len)?;
+  *data = &data[4 + len..];
+  Some(value)
+}
+
+/// Checks the reply of an extension request answered with a status only.
+pub fn expect_status_ok(request: &str, packet: Packet) -> SSHResult<()> {
+  match packet {
+    Packet::Status(status) if status.status_code == StatusCode::Ok => Ok(()),
+    Packet::Status(status) => Err(SSHError::new(format!(
+      "{} failed: {}",
+      request, status.error_message
+    ))),
+    _ => Err(SSHError::new(format!(
+      "{} got an unexpected reply",
+      request
+    ))),
+  }
+}
@@ -0,0 +1,495 @@
+use std::sync::{Arc, Mutex};
+
+use tauri::{async_runtime, ipc::Channel};
+use terminal_core::{
+  encoding::{TerminalEncoding, decode_output},
+  output_flow::{FRAME_INTERVAL, OutputFlow},
+  screen::TerminalScreen,
+  shell_integration::ShellIntegration,
+  trigger::{TriggerAction, TriggerEngine},
+};
+use tokio::{
+  sync::{Notify, broadcast, mpsc},
+  time::sleep,
+};
+
+use crate::{
+  commands::shell::{SHHShellIpcChannelData, SSHShellId},
+  error::{SSHError, SSHResult},
+  tmux::{self, TmuxControl, TmuxPaneId, TmuxPaneIpcChannelData, TmuxPending},
+  url_forwarding::{LocalUrl, LocalUrlDetector, UrlForwardingEvent, UrlForwardingMode},
+  zmodem,
+};
+
+/// Window of the shell channel, the server can't send more until the output read is handed on
+pub(crate) const SHELL_WINDOW_SIZE: u32 = 512 * 1024;
+/// Packets queued for a zmodem transfer before the shell stops reading
+const ZMODEM_QUEUE_SIZE: usize = 64;
+
+/// Output taken over by a zmodem transfer
+struct ZmodemState {
+  sender: mpsc::Sender<Vec<u8>>,
+  /// Taken by the transfer once the webview chooses the files
+  receiver: Option<mpsc::Receiver<Vec<u8>>>,
+}
+
+/// Work found in the output that needs the shell channel or the ssh session, run in order by the
+/// shell's action task.
+pub enum ShellOutputAction {
+  /// Text of a matched trigger, sent to the shell
+  SendText(String),
+  /// Loopback url printed while url forwarding runs in auto mode
+  ForwardUrl(LocalUrl),
+}
+
+struct ShellOutputState {
+  /// None while the shell runs detached from any webview
+  ipc_channel: Option<Channel<SHHShellIpcChannelData>>,
+  flow: OutputFlow,
+  closed: bool,
+  zmodem: Option<ZmodemState>,
+  /// Set while `tmux -CC` runs in the shell, its output is parsed instead of shown
+  tmux: Option<TmuxControl>,
+  shell_integration: ShellIntegration,
+  /// Set when the host forwards the loopback urls printed in the shell
+  url_forwarding: Option<(UrlForwardingMode, LocalUrlDetector)>,
+}
+
+/// Coalesces shell output into frames and applies acknowledgement based flow control.
+pub struct ShellOutput {
+  pub ssh_shell_id: SSHShellId,
+  /// Set when the terminal uses a legacy encoding, the output is decoded before it is shown
+  pub encoding: Option<Arc<TerminalEncoding>>,
+  pub screen: Arc<Mutex<TerminalScreen>>,
+  pub trigger_engine: Arc<Mutex<TriggerEngine>>,
+  state: Mutex<ShellOutputState>,
+  writable: Notify,
+  observers: broadcast::Sender<Arc<[u8]>>,
+  actions: mpsc::UnboundedSender<ShellOutputAction>,
+}
+
+impl ShellOutput {
+  /// Creates the output and the receiver of the actions found in it.
+  pub fn new(
+    ssh_shell_id: SSHShellId,
+    ipc_channel: Channel<SHHShellIpcChannelData>,
+    encoding: Option<Arc<TerminalEncoding>>,
+    screen: Arc<Mutex<TerminalScreen>>,
+    trigger_engine: Arc<Mutex<TriggerEngine>>,
+    url_forwarding: Option<(UrlForwardingMode, LocalUrlDetector)>,
+  ) -> (Self, mpsc::UnboundedReceiver<ShellOutputAction>) {
+    let (observers, _) = broadcast::channel(256);
+    let (actions, actions_receiver) = mpsc::unbounded_channel();
+
+    let shell_output = Self {
+      ssh_shell_id,
+      encoding,
+      screen,
+      trigger_engine,
+      state: Mutex::new(ShellOutputState {
+        ipc_channel: Some(ipc_channel),
+        flow: OutputFlow::default(),
+        closed: false,
+        zmodem: None,
+        tmux: None,
+        shell_integration: ShellIntegration::default(),
+        url_forwarding,
+      }),
+      writable: Notify::new(),
+      observers,
+      actions,
+    };
+
+    (shell_output, actions_receiver)
+  }
+
+  /// Subscribes to the output, used by the startup script which only needs the latest output.
+  ///
+  /// Slow receivers miss output, features that must see all of it are fed in `push`.
+  pub fn subscribe(&self) -> broadcast::Receiver<Arc<[u8]>> {
+    self.observers.subscribe()
+  }
+
+  /// Queues output for the webview and waits while the webview is behind.
+  ///
+  /// Called from the shell's own reader task, which grows the channel window only after this
+  /// returns, so waiting here stops the server once the window is used up.
+  /// While a zmodem transfer runs the output goes to the transfer instead,
+  /// while tmux runs in control mode it goes to the tmux panes.
+  pub async fn write(self: &Arc<Self>, data: &[u8]) -> SSHResult<()> {
+    let zmodem_sender = {
+      let state = self.state.lock()?;
+      state.zmodem.as_ref().map(|zmodem| zmodem.sender.clone())
+    };
+    // 传输结束后接收端已关闭，数据重新交给终端
+    if let Some(zmodem_sender) = zmodem_sender
+      && zmodem_sender.send(data.to_vec()).await.is_ok()
+    {
+      return Ok(());
+    }
+
+    let tmux_rest;
+    let data = match self.feed_tmux(data)? {
+      Some(rest) => {
+        tmux_rest = rest;
+        tmux_rest.as_slice()
+      }
+      None => data,
+    };
+    if data.is_empty() {
+      return Ok(());
+    }
+
+    if let Some((pos, direction)) = zmodem::detect(data)
+      && self.start_zmodem(&data[pos..])?
+    {
+      log::info!(
+        "shell {:?} zmodem {:?} detected",
+        self.ssh_shell_id,
+        direction
+      );
+      if pos > 0 {
+        let mut state = self.state.lock()?;
+        self.push(&mut state, &data[..pos])?;
+      }
+      return self.send(SHHShellIpcChannelData::Zmodem(direction));
+    }
+
+    let flush_now = {
+      let mut state = self.state.lock()?;
+      self.push(&mut state, data)?
+    };
+
+    if flush_now {
+      self.flush()?;
+    }
+
+    self.wait_writable().await
+  }
+
+  /// Queues output for the webview, returns true when it should be flushed right away.
+  ///
+  /// Output of a legacy encoding is decoded here, after zmodem and tmux have seen the raw bytes.
+  fn push(self: &Arc<Self>, state: &mut ShellOutputState, data: &[u8]) -> SSHResult<bool> {
+    let data = decode_output(self.encoding.as_deref(), data)?;
+    let data = data.as_ref();
+
+    // 没有订阅者时发送会失败，忽略即可
+    let _ = self.observers.send(Arc::from(data));
+
+    let flush_now = state.flow.push(data);
+    // 事件在对应的输出之后发送，webview 处理事件时已收到匹配的内容
+    self.watch(state, data)?;
+    if flush_now {
+      return Ok(true);
+    }
+
+    if state.flow.schedule_flush() {
+      let shell_output = self.clone();
+      async_runtime::spawn(async move {
+        sleep(FRAME_INTERVAL).await;
+        if let Err(err) = shell_output.flush() {
+          log::error!(
+            "shell {:?} flush output failed: {}",
+            shell_output.ssh_shell_id,
+            err
+          );
+        }
+      });
+    }
+    Ok(false)
+  }
+
+  /// Feeds the output to the screen, the triggers, the shell integration and the url detector.
+  ///
+  /// Runs while the output is queued so nothing is skipped when the shell is busy, work that
+  /// needs the channel or the session goes to the action task.
+  fn watch(&self, state: &mut ShellOutputState, data: &[u8]) -> SSHResult<()> {
+    let trigger_events = {
+      let mut trigger_engine = self.trigger_engine.lock()?;
+      let mut screen = self.screen.lock()?;
+      // 没有触发器时不必渲染输出行
+      if trigger_engine.is_empty() {
+        screen.process(data);
+        Vec::new()
+      } else {
+        trigger_engine.feed(screen.process_lines(data))
+      }
+    };
+    for trigger_event in trigger_events {
+      log::info!(
+        "shell {:?} trigger {} matched",
+        self.ssh_shell_id,
+        trigger_event.rule_id
+      );
+      match trigger_event.action {
+        TriggerAction::SendText { text } => self.run_action(ShellOutputAction::SendText(text)),
+        _ => state.send(SHHShellIpcChannelData::Trigger(trigger_event))?,
+      }
+    }
+
+    for event in state.shell_integration.feed(data) {
+      state.send(SHHShellIpcChannelData::ShellIntegration(event))?;
+    }
+
+    let Some((mode, detector)) = &mut state.url_forwarding else {
+      return Ok(());
+    };
+    let mode = *mode;
+    for local_url in detector.feed(data) {
+      match mode {
+        UrlForwardingMode::Prompt => {
+          log::info!(
+            "shell {:?} url detected {}",
+            self.ssh_shell_id,
+            local_url.url
+          );
+          state.send(SHHShellIpcChannelData::UrlForwarding(
+            UrlForwardingEvent::Detected(local_url),
+          ))?;
+        }
+        UrlForwardingMode::Auto => self.run_action(ShellOutputAction::ForwardUrl(local_url)),
+      }
+    }
+
+    Ok(())
+  }
+
+  fn run_action(&self, action: ShellOutputAction) {
+    // 动作任务随 shell 结束，此时丢弃即可
+    if self.actions.send(action).is_err() {
+      log::warn!("shell {:?} output action dropped", self.ssh_shell_id);
+    }
+  }
+
+  /// Routes the output to a new zmodem transfer, unless no webview can handle it.
+  fn start_zmodem(&self, data: &[u8]) -> SSHResult<bool> {
+    let mut state = self.state.lock()?;
+    if state.ipc_channel.is_none() || state.zmodem.is_some() {
+      return Ok(false);
+    }
+
+    let (sender, receiver) = mpsc::channel(ZMODEM_QUEUE_SIZE);
+    // 新建的队列一定有空间
+    let _ = sender.try_send(data.to_vec());
+    state.zmodem = Some(ZmodemState {
+      sender,
+      receiver: Some(receiver),
+    });
+
+    Ok(true)
+  }
+
+  /// Takes the output of the detected zmodem transfer.
+  pub fn take_zmodem(&self) -> SSHResult<mpsc::Receiver<Vec<u8>>> {
+    let mut state = self.state.lock()?;
+    state
+      .zmodem
+      .as_mut()
+      .and_then(|zmodem| zmodem.receiver.take())
+      .ok_or(SSHError::NotFoundZmodem)
+  }
+
+  /// Hands the output back to the terminal, starting with the input the transfer didn't use.
+  pub fn end_zmodem(
+    self: &Arc<Self>,
+    mut receiver: mpsc::Receiver<Vec<u8>>,
+    mut leftover: Vec<u8>,
+  ) -> SSHResult<()> {
+    let flush_now = {
+      let mut state = self.state.lock()?;
+      state.zmodem = None;
+      // 关闭后写入方会收到错误并改为写入终端，已排队的数据先输出
+      receiver.close();
+      while let Ok(data) = receiver.try_recv() {
+        leftover.extend(data);
+      }
+      !leftover.is_empty() && self.push(&mut state, &leftover)?
+    };
+
+    if flush_now {
+      self.flush()?;
+    }
+
+    Ok(())
+  }
+
+  /// Parses the output while tmux runs in control mode.
+  ///
+  /// Returns None when tmux isn't running, otherwise the output that still
+  /// belongs to the terminal, i.e. what came before tmux started or after it exited.
+  fn feed_tmux(self: &Arc<Self>, data: &[u8]) -> SSHResult<Option<Vec<u8>>> {
+    let mut state = self.state.lock()?;
+    let mut rest = Vec::new();
+    let data = match state.tmux {
+      Some(_) => data,
+      // 没有 webview 时无法展示面板，按普通输出处理
+      None => match tmux::detect(data) {
+        Some((pos, end)) if state.ipc_channel.is_some() => {
+          log::info!("shell {:?} tmux control mode detected", self.ssh_shell_id);
+          rest.extend_from_slice(&data[..pos]);
+          state.tmux = Some(TmuxControl::default());
+          &data[end..]
+        }
+        _ => return Ok(None),
+      },
+    };
+
+    let feed = match &mut state.tmux {
+      Some(tmux) => tmux.feed(data),
+      None => return Ok(None),
+    };
+    if !rest.is_empty() && self.push(&mut state, &rest)? {
+      state.flush()?;
+    }
+    rest.clear();
+    for event in feed.events {
+      state.send(SHHShellIpcChannelData::Tmux(event))?;
+    }
+    if let Some(exit) = feed.exit {
+      log::info!("shell {:?} tmux control mode exited", self.ssh_shell_id);
+      state.tmux = None;
+      rest = exit;
+    }
+
+    Ok(Some(rest))
+  }
+
+  pub fn is_tmux(&self) -> SSHResult<bool> {
+    Ok(self.state.lock()?.tmux.is_some())
+  }
+
+  /// Registers how the replies of the commands about to be sent to tmux are handled.
+  pub fn tmux_pending(&self, pending: Vec<TmuxPending>) -> SSHResult<()> {
+    let mut state = self.state.lock()?;
+    state
+      .tmux
+      .as_mut()
+      .ok_or(SSHError::NotFoundTmux)?
+      .push_pending(pending);
+
+    Ok(())
+  }
+
+  pub fn tmux_open_pane(
+    &self,
+    pane_id: TmuxPaneId,
+    ipc_channel: Channel<TmuxPaneIpcChannelData>,
+  ) -> SSHResult<()> {
+    let mut state = self.state.lock()?;
+    state
+      .tmux
+      .as_mut()
+      .ok_or(SSHError::NotFoundTmux)?
+      .open_pane(pane_id, ipc_channel);
+
+    Ok(())
+  }
+
+  pub fn tmux_close_pane(&self, pane_id: &str) -> SSHResult<()> {
+    let mut state = self.state.lock()?;
+    if let Some(tmux) = &mut state.tmux {
+      tmux.close_pane(pane_id);
+    }
+
+    Ok(())
+  }
+
+  /// Sends the pending output as one frame.
+  pub fn flush(&self) -> SSHResult<()> {
+    let mut state = self.state.lock()?;
+    let frame = state.flow.take_scheduled_frame();
+    state.send_frame(frame)
+  }
+
+  /// Flushes the pending output before sending a control message, keeping the order.
+  pub fn send(&self, data: SHHShellIpcChannelData) -> SSHResult<()> {
+    self.state.lock()?.send(data)
+  }
+
+  /// Replaces the channel, replaying the scrollback before any new output.
+  pub fn attach(&self, ipc_channel: Channel<SHHShellIpcChannelData>) -> SSHResult<()> {
+    let mut state = self.state.lock()?;
+    let replay = state.flow.attach();
+    let ipc_channel = state.ipc_channel.insert(ipc_channel);
+    if !replay.is_empty() {
+      ipc_channel.send(SHHShellIpcChannelData::Data(replay))?;
+    }
+    self.writable.notify_waiters();
+
+    Ok(())
+  }
+
+  /// Drops the channel, the shell keeps running and its output goes to the scrollback only.
+  pub fn detach(&self) -> SSHResult<()> {
+    let mut state = self.state.lock()?;
+    state.ipc_channel = None;
+    state.flow.detach();
+    self.writable.notify_waiters();
+
+    Ok(())
+  }
+
+  /// Called by the webview after it has processed `size` bytes of output.
+  pub fn ack(&self, size: usize) -> SSHResult<()> {
+    if self.state.lock()?.flow.ack(size) {
+      self.writable.notify_waiters();
+    }
+
+    Ok(())
+  }
+
+  /// Releases writers waiting for acknowledgements, the shell is gone.
+  pub fn close(&self) -> SSHResult<()> {
+    {
+      let mut state = self.state.lock()?;
+      state.closed = true;
+      if let Some(mut tmux) = state.tmux.take() {
+        tmux.close();
+      }
+    }
+    self.writable.notify_waiters();
+
+    Ok(())
+  }
+
+  async fn wait_writable(&self) -> SSHResult<()> {
+    loop {
+      let notified = self.writable.notified();
+      {
+        let state = self.state.lock()?;
+        if state.closed || state.flow.is_writable() {
+          return Ok(());
+        }
+      }
+
+      notified.await;
+    }
+  }
+}
+
+impl ShellOutputState {
+  fn flush(&mut self) -> SSHResult<()> {
+    let frame = self.flow.take_frame();
+    self.send_frame(frame)
+  }
+
+  fn send_frame(&self, frame: Option<Vec<u8>>) -> SSHResult<()> {
+    if let Some(frame) = frame
+      && let Some(ipc_channel) = &self.ipc_channel
+    {
+      ipc_channel.send(SHHShellIpcChannelData::Data(frame))?;
+    }
+
+    Ok(())
+  }
+
+  fn send(&mut self, data: SHHShellIpcChannelData) -> SSHResult<()> {
+    self.flush()?;
+    if let Some(ipc_channel) = &self.ipc_channel {
+      ipc_channel.send(data)?;
+    }
+
+    Ok(())
+  }
+}
@@ -80,22 +80,6 @@ fn check_server_key(
     }
   }
 
-  fn data(
-    &mut self,
-    channel_id: ChannelId,
-    data: &[u8],
-    _session: &mut client::Session,
-  ) -> impl Future<Output = Result<(), Self::Error>> + Send {
-    async move {
-      let ssh_manager = self.ssh_manager();
-
-      ssh_manager
-        .shell_channel_data(self.ssh_session_id, channel_id, data)
-        .await?;
-      Ok(())
-    }
-  }
-
   fn channel_eof(
     &mut self,
     channel_id: ChannelId,
@@ -104,13 +88,7 @@ fn channel_eof(
     async move {
       let ssh_manager = self.ssh_manager();
 
-      if ssh_manager
-        .shell_channel_eof(self.ssh_session_id, channel_id)
-        .await?
-      {
-        return Ok(());
-      }
-
+      // shell 的 eof 和 close 由各自的读取任务处理，保证在剩余输出之后
       if ssh_manager
         .sftp_channel_eof(self.ssh_session_id, channel_id)
         .await?
@@ -130,13 +108,6 @@ fn channel_close(
     async move {
       let ssh_manager = self.ssh_manager();
 
-      if ssh_manager
-        .shell_channel_close(self.ssh_session_id, channel_id)
-        .await?
-      {
-        return Ok(());
-      }
-
       if ssh_manager
         .sftp_channel_close(self.ssh_session_id, channel_id)
         .await?
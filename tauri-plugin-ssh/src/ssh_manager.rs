@@ -8,17 +8,23 @@
 use crate::{
   SSHResult,
   commands::{
+    container::{SSHContainerLogs, SSHContainerLogsId},
     port_forwarding::{SSHPortForwarding, SSHPortForwardingId},
     session::{SSHSession, SSHSessionId},
     sftp::{SSHSftp, SSHSftpId, SSHSftpIpcChannelData},
     shell::{SHHShellIpcChannelData, SSHShell, SSHShellId},
   },
+  shell_output::ShellOutput,
+  transfer_queue::TransferQueue,
 };
 
 pub type Sessions<R> = Mutex<HashMap<SSHSessionId, SSHSession<R>>>;
 pub type Shells = Mutex<HashMap<SSHShellId, SSHShell>>;
+/// Index of shell outputs by ssh channel, used by the data path to avoid scanning all shells
+pub type ShellOutputs = Mutex<HashMap<(SSHSessionId, ChannelId), Arc<ShellOutput>>>;
 pub type SftpChannels = Mutex<HashMap<SSHSftpId, SSHSftp>>;
 pub type PortForwardings = Mutex<HashMap<SSHPortForwardingId, SSHPortForwarding>>;
+pub type ContainerLogs = Mutex<HashMap<SSHContainerLogsId, SSHContainerLogs>>;
 
 #[derive(Clone)]
 pub struct TransferControl {
@@ -36,13 +42,18 @@ pub fn new() -> Self {
 }
 
 pub type TransferControls = Mutex<HashMap<String, TransferControl>>;
+/// Std mutex, progress callbacks of running transfers update it synchronously
+pub type TransferQueueState = std::sync::Mutex<TransferQueue>;
 
 pub struct SSHManager<R: Runtime> {
   pub sessions: Sessions<R>,
   pub shells: Shells,
+  pub shell_outputs: ShellOutputs,
   pub sftps: SftpChannels,
   pub port_forwardings: PortForwardings,
+  pub container_logs: ContainerLogs,
   pub transfer_controls: TransferControls,
+  pub transfer_queue: TransferQueueState,
 }
 
 impl<R: Runtime> SSHManager<R> {
@@ -50,32 +61,22 @@ pub fn init() -> Self {
     Self {
       sessions: Mutex::default(),
       shells: Mutex::default(),
+      shell_outputs: Mutex::default(),
       sftps: Mutex::default(),
       port_forwardings: Mutex::default(),
+      container_logs: Mutex::default(),
       transfer_controls: Mutex::default(),
+      transfer_queue: TransferQueueState::default(),
     }
   }
 
-  pub async fn shell_channel_data(
+  async fn get_shell_output(
     &self,
     ssh_session_id: SSHSessionId,
     channel_id: ChannelId,
-    data: &[u8],
-  ) -> SSHResult<bool> {
-    let shells = self.shells.lock().await;
-
-    let mut count = 0;
-
-    for shell in shells.values() {
-      if shell.ssh_session_id == ssh_session_id && shell.shell_channel_id == channel_id {
-        count += 1;
-        shell
-          .ipc_channel
-          .send(SHHShellIpcChannelData::Data(data.to_vec()))?;
-      }
-    }
-
-    Ok(count > 0)
+  ) -> Option<Arc<ShellOutput>> {
+    let shell_outputs = self.shell_outputs.lock().await;
+    shell_outputs.get(&(ssh_session_id, channel_id)).cloned()
   }
 
   pub async fn shell_channel_eof(
@@ -83,17 +84,13 @@ pub async fn shell_channel_eof(
     ssh_session_id: SSHSessionId,
     channel_id: ChannelId,
   ) -> SSHResult<bool> {
-    let shells = self.shells.lock().await;
+    let Some(shell_output) = self.get_shell_output(ssh_session_id, channel_id).await else {
+      return Ok(false);
+    };
 
-    let mut count = 0;
-    for shell in shells.values() {
-      if shell.ssh_session_id == ssh_session_id && shell.shell_channel_id == channel_id {
-        count += 1;
-        shell.ipc_channel.send(SHHShellIpcChannelData::Eof)?;
-      }
-    }
+    shell_output.send(SHHShellIpcChannelData::Eof)?;
 
-    Ok(count > 0)
+    Ok(true)
   }
 
   pub async fn shell_channel_close(
@@ -101,19 +98,24 @@ pub async fn shell_channel_close(
     ssh_session_id: SSHSessionId,
     channel_id: ChannelId,
   ) -> SSHResult<bool> {
-    let mut shells = self.shells.lock().await;
-
-    let extracted = shells.extract_if(|_shell_id, shell| {
-      shell.ssh_session_id == ssh_session_id && shell.shell_channel_id == channel_id
-    });
-
-    let mut count = 0;
-    for (_shell_id, shell) in extracted {
-      count += 1;
-      shell.ipc_channel.send(SHHShellIpcChannelData::Close)?;
+    let shell_output = {
+      let mut shell_outputs = self.shell_outputs.lock().await;
+      shell_outputs.remove(&(ssh_session_id, channel_id))
+    };
+
+    let Some(shell_output) = shell_output else {
+      return Ok(false);
+    };
+
+    {
+      let mut shells = self.shells.lock().await;
+      shells.remove(&shell_output.ssh_shell_id);
     }
 
-    Ok(count > 0)
+    shell_output.close()?;
+    shell_output.send(SHHShellIpcChannelData::Close)?;
+
+    Ok(true)
   }
 
   pub async fn sftp_channel_eof(
@@ -0,0 +1,155 @@
+use std::{sync::Arc, time::Duration};
+
+use regex::Regex;
+use russh::{ChannelWriteHalf, client};
+use serde::Deserialize;
+use terminal_core::{
+  encoding::{TerminalEncoding, encode_input},
+  utils::strip_ansi,
+};
+use tokio::{
+  sync::{Mutex as AsyncMutex, broadcast},
+  time::{Instant, sleep, sleep_until},
+};
+
+use crate::error::SSHResult;
+
+const DEFAULT_QUIESCENCE: u64 = 300;
+const READY_TIMEOUT: Duration = Duration::from_secs(10);
+const TAIL_SIZE: usize = 4096;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StartupScript {
+  /// Lines are sent one by one, `#delay <ms>` lines pause the script
+  pub script: String,
+  /// Regex matched against the last output line to detect the shell prompt
+  pub prompt_pattern: Option<String>,
+  /// Milliseconds without output after which the shell is considered ready
+  pub quiescence: Option<u64>,
+  /// Milliseconds to wait after each sent line
+  pub line_delay: Option<u64>,
+}
+
+enum StartupScriptLine {
+  Command(String),
+  Delay(Duration),
+}
+
+impl StartupScript {
+  fn lines(&self) -> Vec<StartupScriptLine> {
+    self
+      .script
+      .lines()
+      .map(str::trim_end)
+      .filter(|line| !line.trim().is_empty())
+      .map(|line| {
+        line
+          .trim()
+          .strip_prefix("#delay")
+          .and_then(|delay| delay.trim().parse::<u64>().ok())
+          .map(|delay| StartupScriptLine::Delay(Duration::from_millis(delay)))
+          .unwrap_or_else(|| StartupScriptLine::Command(line.to_string()))
+      })
+      .collect()
+  }
+}
+
+pub struct StartupScriptRunner {
+  lines: Vec<StartupScriptLine>,
+  prompt_pattern: Option<Regex>,
+  quiescence: Duration,
+  line_delay: Duration,
+}
+
+impl StartupScriptRunner {
+  pub fn new(startup_script: &StartupScript) -> SSHResult<Self> {
+    let prompt_pattern = startup_script
+      .prompt_pattern
+      .as_deref()
+      .filter(|pattern| !pattern.is_empty())
+      .map(Regex::new)
+      .transpose()?;
+
+    Ok(Self {
+      lines: startup_script.lines(),
+      prompt_pattern,
+      quiescence: Duration::from_millis(startup_script.quiescence.unwrap_or(DEFAULT_QUIESCENCE)),
+      line_delay: Duration::from_millis(startup_script.line_delay.unwrap_or(0)),
+    })
+  }
+
+  pub async fn run(
+    self,
+    shell_channel: Arc<AsyncMutex<ChannelWriteHalf<client::Msg>>>,
+    encoding: Option<Arc<TerminalEncoding>>,
+    mut output_receiver: broadcast::Receiver<Arc<[u8]>>,
+  ) -> SSHResult<()> {
+    self.wait_ready(&mut output_receiver).await;
+
+    for line in &self.lines {
+      match line {
+        StartupScriptLine::Delay(delay) => sleep(*delay).await,
+        StartupScriptLine::Command(command) => {
+          let data = format!("{}\r", command);
+          let data = encode_input(encoding.as_deref(), data.as_bytes())?;
+          shell_channel.lock().await.data(&data[..]).await?;
+
+          if !self.line_delay.is_zero() {
+            sleep(self.line_delay).await;
+          }
+
+          // 设置了提示符匹配时，等待上一行命令执行完成后再发送下一行
+          if self.prompt_pattern.is_some() {
+            self.wait_ready(&mut output_receiver).await;
+          }
+        }
+      }
+    }
+
+    Ok(())
+  }
+
+  /// Waits until the prompt pattern matches or the output has been quiet for a while.
+  async fn wait_ready(&self, output_receiver: &mut broadcast::Receiver<Arc<[u8]>>) {
+    let deadline = Instant::now() + READY_TIMEOUT;
+    let mut tail: Vec<u8> = Vec::new();
+    let mut last_output: Option<Instant> = None;
+
+    loop {
+      let quiescence_deadline = last_output
+        .map(|last_output| last_output + self.quiescence)
+        .unwrap_or(deadline)
+        .min(deadline);
+
+      tokio::select! {
+        output = output_receiver.recv() => match output {
+          Ok(data) => {
+            tail.extend_from_slice(&data);
+            if tail.len() > TAIL_SIZE {
+              tail.drain(..tail.len() - TAIL_SIZE);
+            }
+            last_output = Some(Instant::now());
+
+            if let Some(prompt_pattern) = &self.prompt_pattern {
+              let text = strip_ansi(&tail);
+              let last_line = text.rsplit(['\r', '\n']).next().unwrap_or_default();
+              if prompt_pattern.is_match(last_line) {
+                return;
+              }
+            }
+          }
+          Err(broadcast::error::RecvError::Lagged(_)) => continue,
+          Err(broadcast::error::RecvError::Closed) => return,
+        },
+        _ = sleep_until(quiescence_deadline) => {
+          // 只有提示符匹配失败且输出静默时才认为 shell 已就绪
+          if self.prompt_pattern.is_none() || Instant::now() >= deadline {
+            return;
+          }
+          last_output = None;
+        }
+      }
+    }
+  }
+}
@@ -0,0 +1,742 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use serde::Serialize;
+use serde_json::json;
+use strum::AsRefStr;
+use tauri::ipc::{Channel, InvokeResponseBody, IpcResponse};
+use tokio::sync::oneshot;
+
+/// Sent by `tmux -CC` before the first control line
+const START: &[u8] = b"\x1bP1000p";
+/// Ends the control mode after %exit
+const END: &[u8] = b"\x1b\\";
+/// Longest control line kept, longer lines are dropped
+const LINE_SIZE: usize = 1024 * 1024;
+
+pub type TmuxPaneId = String;
+pub type TmuxWindowId = String;
+
+#[derive(Debug, Clone, AsRefStr)]
+pub enum TmuxPaneIpcChannelData {
+  Data(Vec<u8>),
+  Close,
+}
+
+impl IpcResponse for TmuxPaneIpcChannelData {
+  fn body(self) -> tauri::Result<InvokeResponseBody> {
+    match self {
+      TmuxPaneIpcChannelData::Data(data) => Ok(InvokeResponseBody::Raw(data)),
+      val => {
+        let body = json!({
+          "type": val.as_ref(),
+        });
+        Ok(InvokeResponseBody::Json(body.to_string()))
+      }
+    }
+  }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TmuxPaneLayout {
+  pub pane_id: TmuxPaneId,
+  pub width: u32,
+  pub height: u32,
+  pub x: u32,
+  pub y: u32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all_fields = "camelCase")]
+pub enum TmuxEvent {
+  /// tmux is ready to accept commands
+  Start,
+  Exit {
+    reason: Option<String>,
+  },
+  WindowAdd {
+    window_id: TmuxWindowId,
+  },
+  WindowClose {
+    window_id: TmuxWindowId,
+  },
+  WindowRenamed {
+    window_id: TmuxWindowId,
+    name: String,
+  },
+  /// The active pane of the window changed
+  WindowPaneChanged {
+    window_id: TmuxWindowId,
+    pane_id: TmuxPaneId,
+  },
+  LayoutChange {
+    window_id: TmuxWindowId,
+    panes: Vec<TmuxPaneLayout>,
+  },
+  SessionChanged {
+    session_id: String,
+    name: String,
+  },
+}
+
+/// What to do with the reply of a command, replies arrive in the order the commands are sent.
+pub enum TmuxPending {
+  Reply(oneshot::Sender<Result<Vec<String>, String>>),
+  /// History and screen of a pane that is being opened
+  Capture(TmuxPaneId),
+  /// Cursor position of a pane that is being opened, its output is forwarded afterwards
+  Cursor(TmuxPaneId),
+  Ignore,
+}
+
+struct TmuxPaneChannel {
+  ipc_channel: Channel<TmuxPaneIpcChannelData>,
+  window_id: Option<TmuxWindowId>,
+  /// Output is dropped until the captured screen is replayed, it is already part of it
+  live: bool,
+}
+
+struct TmuxBlock {
+  lines: Vec<String>,
+  pending: Option<TmuxPending>,
+}
+
+#[derive(Default)]
+pub struct TmuxFeed {
+  pub events: Vec<TmuxEvent>,
+  /// Output after tmux exited, it belongs to the terminal again
+  pub exit: Option<Vec<u8>>,
+}
+
+/// Parses the output of a shell running `tmux -CC`.
+#[derive(Default)]
+pub struct TmuxControl {
+  line: Vec<u8>,
+  block: Option<TmuxBlock>,
+  pending: VecDeque<TmuxPending>,
+  /// Set after the reply of the command tmux was started with, which nobody waits for
+  ready: bool,
+  panes: HashMap<TmuxPaneId, TmuxPaneChannel>,
+}
+
+/// Finds the sequence `tmux -CC` starts the control mode with, returns the position after it.
+pub fn detect(data: &[u8]) -> Option<(usize, usize)> {
+  data
+    .windows(START.len())
+    .position(|window| window == START)
+    .map(|pos| (pos, pos + START.len()))
+}
+
+impl TmuxControl {
+  pub fn push_pending(&mut self, pending: Vec<TmuxPending>) {
+    self.pending.extend(pending);
+  }
+
+  pub fn open_pane(&mut self, pane_id: TmuxPaneId, ipc_channel: Channel<TmuxPaneIpcChannelData>) {
+    self.panes.insert(
+      pane_id,
+      TmuxPaneChannel {
+        ipc_channel,
+        window_id: None,
+        live: false,
+      },
+    );
+  }
+
+  pub fn close_pane(&mut self, pane_id: &str) {
+    self.panes.remove(pane_id);
+  }
+
+  /// Tells every opened pane that tmux is gone.
+  pub fn close(&mut self) {
+    for (_, pane) in self.panes.drain() {
+      let _ = pane.ipc_channel.send(TmuxPaneIpcChannelData::Close);
+    }
+  }
+
+  pub fn feed(&mut self, data: &[u8]) -> TmuxFeed {
+    let mut feed = TmuxFeed::default();
+    // 同一批数据中同一面板的输出合并发送
+    let mut outputs = HashMap::<TmuxPaneId, Vec<u8>>::new();
+
+    let mut rest = data;
+    while let Some(pos) = rest.iter().position(|byte| *byte == b'\n') {
+      let mut line = std::mem::take(&mut self.line);
+      line.extend_from_slice(&rest[..pos]);
+      rest = &rest[pos + 1..];
+      if line.last() == Some(&b'\r') {
+        line.pop();
+      }
+
+      if self.handle_line(&line, &mut feed.events, &mut outputs) {
+        self.send_outputs(outputs);
+        self.close();
+        let rest = rest.strip_prefix(END).unwrap_or(rest);
+        feed.exit = Some(rest.to_vec());
+        return feed;
+      }
+    }
+
+    if self.line.len() + rest.len() > LINE_SIZE {
+      log::warn!("tmux control line too long, dropped");
+      self.line.clear();
+    } else {
+      self.line.extend_from_slice(rest);
+    }
+    self.send_outputs(outputs);
+
+    feed
+  }
+
+  /// Returns true when tmux exits.
+  fn handle_line(
+    &mut self,
+    line: &[u8],
+    events: &mut Vec<TmuxEvent>,
+    outputs: &mut HashMap<TmuxPaneId, Vec<u8>>,
+  ) -> bool {
+    if let Some(block) = &mut self.block {
+      if line.starts_with(b"%end ") || line.starts_with(b"%error ") {
+        let error = line.starts_with(b"%error ");
+        if let Some(block) = self.block.take() {
+          self.finish_block(block, error, events);
+        }
+      } else {
+        block.lines.push(String::from_utf8_lossy(line).to_string());
+      }
+      return false;
+    }
+
+    // 输出内容按字节处理，不能先转换为字符串
+    if let Some(args) = line.strip_prefix(b"%output ") {
+      if let Some((pane_id, value)) = split_once(args, b' ') {
+        self.push_output(pane_id, value, outputs);
+      }
+      return false;
+    }
+    if let Some(args) = line.strip_prefix(b"%extended-output ") {
+      if let Some((pane_id, args)) = split_once(args, b' ')
+        && let Some(pos) = args.windows(3).position(|window| window == b" : ")
+      {
+        self.push_output(pane_id, &args[pos + 3..], outputs);
+      }
+      return false;
+    }
+
+    let line = String::from_utf8_lossy(line);
+    let (name, args) = line.split_once(' ').unwrap_or((&line, ""));
+    let mut words = args.split_whitespace();
+    match name {
+      "%begin" => {
+        self.block = Some(TmuxBlock {
+          lines: Vec::new(),
+          pending: if self.ready {
+            self.pending.pop_front()
+          } else {
+            None
+          },
+        });
+      }
+      "%exit" => {
+        let reason = Some(args.to_string()).filter(|reason| !reason.is_empty());
+        events.push(TmuxEvent::Exit { reason });
+        return true;
+      }
+      "%window-add" => {
+        if let Some(window_id) = words.next() {
+          events.push(TmuxEvent::WindowAdd {
+            window_id: window_id.to_string(),
+          });
+        }
+      }
+      "%window-close" | "%unlinked-window-close" => {
+        if let Some(window_id) = words.next() {
+          self.close_window_panes(window_id, &HashSet::new());
+          events.push(TmuxEvent::WindowClose {
+            window_id: window_id.to_string(),
+          });
+        }
+      }
+      "%window-renamed" => {
+        if let Some((window_id, name)) = args.split_once(' ') {
+          events.push(TmuxEvent::WindowRenamed {
+            window_id: window_id.to_string(),
+            name: name.to_string(),
+          });
+        }
+      }
+      "%window-pane-changed" => {
+        if let (Some(window_id), Some(pane_id)) = (words.next(), words.next()) {
+          events.push(TmuxEvent::WindowPaneChanged {
+            window_id: window_id.to_string(),
+            pane_id: pane_id.to_string(),
+          });
+        }
+      }
+      "%layout-change" => {
+        if let (Some(window_id), Some(layout)) = (words.next(), words.next()) {
+          let panes = parse_layout(layout);
+          self.update_window_panes(window_id, &panes);
+          events.push(TmuxEvent::LayoutChange {
+            window_id: window_id.to_string(),
+            panes,
+          });
+        }
+      }
+      "%session-changed" => {
+        if let Some((session_id, name)) = args.split_once(' ') {
+          events.push(TmuxEvent::SessionChanged {
+            session_id: session_id.to_string(),
+            name: name.to_string(),
+          });
+        }
+      }
+      _ => log::debug!("tmux notification ignored: {}", line),
+    }
+
+    false
+  }
+
+  fn finish_block(&mut self, block: TmuxBlock, error: bool, events: &mut Vec<TmuxEvent>) {
+    if !self.ready {
+      self.ready = true;
+      events.push(TmuxEvent::Start);
+      return;
+    }
+
+    match block.pending {
+      Some(TmuxPending::Reply(sender)) => {
+        let _ = sender.send(if error {
+          Err(block.lines.join("\n"))
+        } else {
+          Ok(block.lines)
+        });
+      }
+      Some(TmuxPending::Capture(pane_id)) => {
+        if let Some(pane) = self.panes.get(&pane_id)
+          && !error
+        {
+          let data = block.lines.join("\r\n").into_bytes();
+          let _ = pane.ipc_channel.send(TmuxPaneIpcChannelData::Data(data));
+        }
+      }
+      Some(TmuxPending::Cursor(pane_id)) => {
+        if let Some(pane) = self.panes.get_mut(&pane_id) {
+          pane.live = true;
+          let cursor = block.lines.first().and_then(|line| {
+            let (x, y) = line.split_once(' ')?;
+            Some((x.parse::<u32>().ok()?, y.parse::<u32>().ok()?))
+          });
+          if let Some((x, y)) = cursor.filter(|_| !error) {
+            let data = format!("\x1b[{};{}H", y + 1, x + 1).into_bytes();
+            let _ = pane.ipc_channel.send(TmuxPaneIpcChannelData::Data(data));
+          }
+        }
+      }
+      Some(TmuxPending::Ignore) | None => {}
+    }
+  }
+
+  fn push_output(&self, pane_id: &[u8], value: &[u8], outputs: &mut HashMap<TmuxPaneId, Vec<u8>>) {
+    let pane_id = String::from_utf8_lossy(pane_id);
+    if self
+      .panes
+      .get(pane_id.as_ref())
+      .is_some_and(|pane| pane.live)
+    {
+      unescape_into(outputs.entry(pane_id.to_string()).or_default(), value);
+    }
+  }
+
+  fn send_outputs(&self, outputs: HashMap<TmuxPaneId, Vec<u8>>) {
+    for (pane_id, data) in outputs {
+      if let Some(pane) = self.panes.get(&pane_id)
+        && let Err(err) = pane.ipc_channel.send(TmuxPaneIpcChannelData::Data(data))
+      {
+        log::error!("tmux pane {} send output failed: {}", pane_id, err);
+      }
+    }
+  }
+
+  /// Records the window of the opened panes and closes the ones no longer in it.
+  fn update_window_panes(&mut self, window_id: &str, panes: &[TmuxPaneLayout]) {
+    let pane_ids = panes
+      .iter()
+      .map(|pane| pane.pane_id.as_str())
+      .collect::<HashSet<_>>();
+    for (pane_id, pane) in self.panes.iter_mut() {
+      if pane_ids.contains(pane_id.as_str()) {
+        pane.window_id = Some(window_id.to_string());
+      }
+    }
+    self.close_window_panes(window_id, &pane_ids);
+  }
+
+  fn close_window_panes(&mut self, window_id: &str, keep: &HashSet<&str>) {
+    self.panes.retain(|pane_id, pane| {
+      if pane.window_id.as_deref() != Some(window_id) || keep.contains(pane_id.as_str()) {
+        return true;
+      }
+      let _ = pane.ipc_channel.send(TmuxPaneIpcChannelData::Close);
+      false
+    });
+  }
+}
+
+fn split_once(data: &[u8], separator: u8) -> Option<(&[u8], &[u8])> {
+  let pos = data.iter().position(|byte| *byte == separator)?;
+  Some((&data[..pos], &data[pos + 1..]))
+}
+
+/// Undoes the octal escaping tmux applies to control characters and backslashes.
+fn unescape_into(buf: &mut Vec<u8>, value: &[u8]) {
+  let mut index = 0;
+  while index < value.len() {
+    let octal = value
+      .get(index + 1..index + 4)
+      .filter(|_| value[index] == b'\\')
+      .filter(|digits| digits.iter().all(|digit| (b'0'..=b'7').contains(digit)));
+    match octal {
+      Some(digits) => {
+        let byte = digits
+          .iter()
+          .fold(0u32, |byte, digit| byte * 8 + (digit - b'0') as u32);
+        buf.push(byte as u8);
+        index += 4;
+      }
+      None => {
+        buf.push(value[index]);
+        index += 1;
+      }
+    }
+  }
+}
+
+/// Parses a window layout such as `b25d,80x24,0,0{40x24,0,0,1,39x24,41,0,2}` into its panes.
+pub fn parse_layout(layout: &str) -> Vec<TmuxPaneLayout> {
+  // 开头是布局的校验和
+  let layout = layout.split_once(',').map_or(layout, |(_, layout)| layout);
+
+  let mut parser = LayoutParser {
+    input: layout.as_bytes(),
+    pos: 0,
+  };
+  let mut panes = Vec::new();
+  if parser.cell(&mut panes).is_none() {
+    log::warn!("tmux layout parse failed: {}", layout);
+  }
+  panes
+}
+
+struct LayoutParser<'a> {
+  input: &'a [u8],
+  pos: usize,
+}
+
+impl LayoutParser<'_> {
+  fn next(&mut self) -> Option<u8> {
+    let byte = self.input.get(self.pos).copied()?;
+    self.pos += 1;
+    Some(byte)
+  }
+
+  fn expect(&mut self, expected: u8) -> Option<()> {
+    (self.next()? == expected).then_some(())
+  }
+
+  fn number(&mut self) -> Option<u32> {
+    let start = self.pos;
+    while self.input.get(self.pos).is_some_and(u8::is_ascii_digit) {
+      self.pos += 1;
+    }
+    std::str::from_utf8(&self.input[start..self.pos])
+      .ok()?
+      .parse()
+      .ok()
+  }
+
+  /// A cell is `WxH,X,Y` followed by a pane id or nested cells in `{}` or `[]`.
+  fn cell(&mut self, panes: &mut Vec<TmuxPaneLayout>) -> Option<()> {
+    let width = self.number()?;
+    self.expect(b'x')?;
+    let height = self.number()?;
+    self.expect(b',')?;
+    let x = self.number()?;
+    self.expect(b',')?;
+    let y = self.number()?;
+
+    match self.next()? {
+      b',' => {
+        let pane_id = format!("%{}", self.number()?);
+        panes.push(TmuxPaneLayout {
+          pane_id,
+          width,
+          height,
+          x,
+          y,
+        });
+      }
+      open @ (b'{' | b'[') => {
+        let close = if open == b'{' { b'}' } else { b']' };
+        loop {
+          self.cell(panes)?;
+          match self.next()? {
+            b',' => continue,
+            byte if byte == close => break,
+            _ => return None,
+          }
+        }
+      }
+      _ => return None,
+    }
+
+    Some(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::sync::{Arc, Mutex};
+
+  use super::*;
+
+  /// Messages received by a pane channel, `None` stands for Close.
+  type Received = Arc<Mutex<Vec<Option<Vec<u8>>>>>;
+
+  fn pane_channel() -> (Channel<TmuxPaneIpcChannelData>, Received) {
+    let received = Received::default();
+    let sink = received.clone();
+    let ipc_channel = Channel::new(move |body| {
+      sink.lock().unwrap().push(match body {
+        InvokeResponseBody::Raw(data) => Some(data),
+        InvokeResponseBody::Json(_) => None,
+      });
+      Ok(())
+    });
+    (ipc_channel, received)
+  }
+
+  /// A control mode parser past the reply of the command tmux was started with.
+  fn started() -> TmuxControl {
+    let mut tmux = TmuxControl::default();
+    let feed = tmux.feed(b"%begin 1700000000 1 0\r\n%end 1700000000 1 0\r\n");
+    assert!(matches!(feed.events.as_slice(), [TmuxEvent::Start]));
+    tmux
+  }
+
+  /// Opens the pane and replays its capture and cursor, after which its output is forwarded.
+  fn live_pane(tmux: &mut TmuxControl, pane_id: &str) -> Received {
+    let (ipc_channel, received) = pane_channel();
+    tmux.open_pane(pane_id.to_string(), ipc_channel);
+    tmux.push_pending(vec![
+      TmuxPending::Capture(pane_id.to_string()),
+      TmuxPending::Cursor(pane_id.to_string()),
+    ]);
+    tmux
+      .feed(b"%begin 1 2 1\r\n$ ls\r\nfile\r\n%end 1 2 1\r\n%begin 1 3 1\r\n4 2\r\n%end 1 3 1\r\n");
+    received
+  }
+
+  #[test]
+  fn detect_finds_the_control_mode_start() {
+    assert_eq!(detect(b"$ tmux -CC\r\n\x1bP1000p%begin"), Some((12, 19)));
+    assert_eq!(detect(b"\x1bP1000"), None);
+  }
+
+  #[test]
+  fn blocks_reply_in_the_order_commands_are_sent() {
+    let mut tmux = started();
+    let (ok_sender, mut ok_receiver) = oneshot::channel();
+    let (error_sender, mut error_receiver) = oneshot::channel();
+    tmux.push_pending(vec![
+      TmuxPending::Reply(ok_sender),
+      TmuxPending::Ignore,
+      TmuxPending::Reply(error_sender),
+    ]);
+
+    let feed = tmux.feed(
+      b"%begin 1 10 1\r\n@1 %1\r\n@2 %3\r\n%end 1 10 1\r\n\
+        %begin 1 11 1\r\n%end 1 11 1\r\n\
+        %begin 1 12 1\r\nunknown command: nope\r\n%error 1 12 1\r\n",
+    );
+    assert!(feed.events.is_empty());
+    assert!(feed.exit.is_none());
+    assert_eq!(
+      ok_receiver.try_recv().unwrap(),
+      Ok(vec!["@1 %1".to_string(), "@2 %3".to_string()])
+    );
+    assert_eq!(
+      error_receiver.try_recv().unwrap(),
+      Err("unknown command: nope".to_string())
+    );
+  }
+
+  #[test]
+  fn notifications_inside_a_block_are_reply_lines() {
+    let mut tmux = started();
+    let (sender, mut receiver) = oneshot::channel();
+    tmux.push_pending(vec![TmuxPending::Reply(sender)]);
+
+    let feed = tmux.feed(b"%begin 1 5 1\r\n%window-add @9\r\n%end 1 5 1\r\n");
+    assert!(feed.events.is_empty());
+    assert_eq!(
+      receiver.try_recv().unwrap(),
+      Ok(vec!["%window-add @9".to_string()])
+    );
+  }
+
+  #[test]
+  fn opened_pane_receives_its_capture_cursor_and_output() {
+    let mut tmux = started();
+    // 打开之前的输出已包含在捕获的屏幕中
+    tmux.feed(b"%output %1 before\r\n");
+    let received = live_pane(&mut tmux, "%1");
+
+    tmux.feed(b"%output %1 a\\033[1mb\\134c\\015\\012\r\n%output %2 other\r\n");
+    tmux.feed(b"%extended-output %1 1200 : late\\040\\\\x\r\n");
+
+    assert_eq!(
+      *received.lock().unwrap(),
+      [
+        Some(b"$ ls\r\nfile".to_vec()),
+        Some(b"\x1b[3;5H".to_vec()),
+        Some(b"a\x1b[1mb\\c\r\n".to_vec()),
+        Some(b"late \\\\x".to_vec()),
+      ]
+    );
+  }
+
+  #[test]
+  fn output_of_one_feed_is_sent_as_one_message() {
+    let mut tmux = started();
+    let received = live_pane(&mut tmux, "%1");
+    received.lock().unwrap().clear();
+
+    tmux.feed(b"%output %1 one\\015\\012\r\n%output %1 two\r\n");
+    assert_eq!(*received.lock().unwrap(), [Some(b"one\r\ntwo".to_vec())]);
+  }
+
+  #[test]
+  fn window_notifications_become_events() {
+    let mut tmux = started();
+
+    let feed = tmux.feed(
+      b"%window-add @1\r\n%window-renamed @1 build logs\r\n%window-pane-changed @1 %4\r\n\
+        %session-changed $0 main\r\n%sessions-changed\r\n%window-close @1\r\n",
+    );
+    assert!(matches!(
+      feed.events.as_slice(),
+      [
+        TmuxEvent::WindowAdd { window_id },
+        TmuxEvent::WindowRenamed { name, .. },
+        TmuxEvent::WindowPaneChanged { pane_id, .. },
+        TmuxEvent::SessionChanged { session_id, .. },
+        TmuxEvent::WindowClose { .. },
+      ] if window_id == "@1" && name == "build logs" && pane_id == "%4" && session_id == "$0"
+    ));
+  }
+
+  #[test]
+  fn layout_change_closes_panes_gone_from_the_window() {
+    let mut tmux = started();
+    let first = live_pane(&mut tmux, "%1");
+    let second = live_pane(&mut tmux, "%2");
+
+    tmux.feed(b"%layout-change @1 b25d,80x24,0,0{40x24,0,0,1,39x24,41,0,2} b25d,80x24,0,0 *\r\n");
+    let feed = tmux.feed(b"%layout-change @1 a1b2,80x24,0,0,1 a1b2,80x24,0,0,1 *\r\n");
+    assert!(matches!(
+      feed.events.as_slice(),
+      [TmuxEvent::LayoutChange { window_id, panes }] if window_id == "@1" && panes.len() == 1
+    ));
+    assert_ne!(first.lock().unwrap().last(), Some(&None));
+    assert_eq!(second.lock().unwrap().last(), Some(&None));
+  }
+
+  #[test]
+  fn exit_returns_the_output_after_tmux() {
+    let mut tmux = started();
+    let received = live_pane(&mut tmux, "%1");
+
+    let feed = tmux.feed(b"%window-add @2\r\n%exit detached\r\n\x1b\\$ echo done\r\n");
+    assert!(matches!(
+      feed.events.as_slice(),
+      [
+        TmuxEvent::WindowAdd { .. },
+        TmuxEvent::Exit { reason: Some(reason) },
+      ] if reason == "detached"
+    ));
+    assert_eq!(feed.exit.as_deref(), Some(&b"$ echo done\r\n"[..]));
+    assert_eq!(received.lock().unwrap().last(), Some(&None));
+
+    let mut tmux = started();
+    let feed = tmux.feed(b"%exit\n");
+    assert!(matches!(
+      feed.events.as_slice(),
+      [TmuxEvent::Exit { reason: None }]
+    ));
+    assert_eq!(feed.exit.as_deref(), Some(&b""[..]));
+  }
+
+  #[test]
+  fn lines_split_across_reads() {
+    let mut tmux = TmuxControl::default();
+    let data = b"%begin 1 1 0\r\n%end 1 1 0\r\n%window-add @3\r\n%exit\r\n\x1b\\$ ";
+
+    let mut events = Vec::new();
+    let mut exit = None;
+    for byte in data {
+      let feed = tmux.feed(&[*byte]);
+      events.extend(feed.events);
+      if feed.exit.is_some() {
+        exit = feed.exit;
+        break;
+      }
+    }
+    assert!(matches!(
+      events.as_slice(),
+      [
+        TmuxEvent::Start,
+        TmuxEvent::WindowAdd { window_id },
+        TmuxEvent::Exit { reason: None },
+      ] if window_id == "@3"
+    ));
+    // 退出行之后的数据属于下一次读取
+    assert_eq!(exit.as_deref(), Some(&b""[..]));
+  }
+
+  #[test]
+  fn escapes_split_across_reads_are_unescaped() {
+    let mut tmux = started();
+    let received = live_pane(&mut tmux, "%1");
+    received.lock().unwrap().clear();
+
+    tmux.feed(b"%output %1 x\\0");
+    tmux.feed(b"33[0m\\");
+    tmux.feed(b"015\r");
+    tmux.feed(b"\n");
+    assert_eq!(*received.lock().unwrap(), [Some(b"x\x1b[0m\r".to_vec())]);
+  }
+
+  #[test]
+  fn parse_layout_reads_nested_cells() {
+    let panes = parse_layout("5e39,159x40,0,0{79x40,0,0,0,79x40,80,0[79x20,80,0,1,79x19,80,21,2]}");
+    assert_eq!(
+      panes
+        .iter()
+        .map(|pane| (
+          pane.pane_id.as_str(),
+          pane.width,
+          pane.height,
+          pane.x,
+          pane.y
+        ))
+        .collect::<Vec<_>>(),
+      [
+        ("%0", 79, 40, 0, 0),
+        ("%1", 79, 20, 80, 0),
+        ("%2", 79, 19, 80, 21),
+      ]
+    );
+    assert!(parse_layout("5e39,159x40,0,0{79x40").is_empty());
+  }
+}
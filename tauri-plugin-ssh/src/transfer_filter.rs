@@ -0,0 +1,248 @@
+use regex::Regex;
+
+use crate::error::SSHResult;
+
+pub const GITIGNORE: &str = ".gitignore";
+
+struct FilterRule {
+  regex: Regex,
+  /// `!pattern` re-includes a path excluded by an earlier rule
+  negated: bool,
+  /// `pattern/` only matches directories
+  dir_only: bool,
+}
+
+/// Exclude rules of a directory transfer with the `.gitignore` syntax, the last
+/// matching rule wins.
+#[derive(Default)]
+pub struct TransferFilter {
+  rules: Vec<FilterRule>,
+}
+
+impl TransferFilter {
+  pub fn new(excludes: &[String]) -> SSHResult<Self> {
+    let mut transfer_filter = Self::default();
+    for pattern in excludes {
+      transfer_filter.add_pattern(pattern, "")?;
+    }
+
+    Ok(transfer_filter)
+  }
+
+  /// Adds the rules of a `.gitignore` found in `base`, relative to the transfer root.
+  pub fn add_gitignore(&mut self, content: &str, base: &str) -> SSHResult<()> {
+    for line in content.lines() {
+      let line = line.trim_end();
+      if line.is_empty() || line.starts_with('#') {
+        continue;
+      }
+      self.add_pattern(line, base)?;
+    }
+
+    Ok(())
+  }
+
+  fn add_pattern(&mut self, pattern: &str, base: &str) -> SSHResult<()> {
+    let pattern = pattern.trim();
+    let (negated, pattern) = match pattern.strip_prefix('!') {
+      Some(pattern) => (true, pattern),
+      None => (false, pattern),
+    };
+    let (dir_only, pattern) = match pattern.strip_suffix('/') {
+      Some(pattern) => (true, pattern),
+      None => (false, pattern),
+    };
+    if pattern.is_empty() {
+      return Ok(());
+    }
+
+    // 不含 / 的模式匹配任意层级的文件名，否则相对于所在目录
+    let anchored = pattern.contains('/');
+    let pattern = pattern.trim_start_matches('/');
+
+    let mut regex = String::from("^");
+    if !base.is_empty() {
+      regex.push_str(&regex::escape(base));
+      regex.push('/');
+    }
+    if !anchored {
+      regex.push_str("(?:.*/)?");
+    }
+    regex.push_str(&glob_to_regex(pattern));
+    regex.push('$');
+
+    self.rules.push(FilterRule {
+      regex: Regex::new(&regex)?,
+      negated,
+      dir_only,
+    });
+
+    Ok(())
+  }
+
+  /// `path` is relative to the transfer root and separated by `/`.
+  pub fn is_excluded(&self, path: &str, is_dir: bool) -> bool {
+    self
+      .rules
+      .iter()
+      .rev()
+      .find(|rule| (is_dir || !rule.dir_only) && rule.regex.is_match(path))
+      .is_some_and(|rule| !rule.negated)
+  }
+}
+
+fn glob_to_regex(pattern: &str) -> String {
+  let mut regex = String::new();
+  let mut chars = pattern.chars().peekable();
+  while let Some(char) = chars.next() {
+    match char {
+      '*' if chars.peek() == Some(&'*') => {
+        chars.next();
+        if chars.peek() == Some(&'/') {
+          chars.next();
+          regex.push_str("(?:.*/)?");
+        } else {
+          regex.push_str(".*");
+        }
+      }
+      '*' => regex.push_str("[^/]*"),
+      '?' => regex.push_str("[^/]"),
+      '[' => {
+        let mut raw = String::from("[");
+        let mut class = String::from("[");
+        if chars.peek() == Some(&'!') {
+          chars.next();
+          raw.push('!');
+          class.push('^');
+        }
+        let mut closed = false;
+        let mut empty = true;
+        for char in chars.by_ref() {
+          // 开头的 ] 是普通字符
+          if char == ']' && !empty {
+            closed = true;
+            break;
+          }
+          empty = false;
+          raw.push(char);
+          // & 和 ~ 连用在 regex 的字符类中有特殊含义
+          if matches!(char, '\\' | '[' | ']' | '&' | '~') {
+            class.push('\\');
+          }
+          class.push(char);
+        }
+        if closed {
+          class.push(']');
+          regex.push_str(&class);
+        } else {
+          // 没有闭合的 [ 按普通字符匹配
+          regex.push_str(&regex::escape(&raw));
+        }
+      }
+      '\\' => {
+        if let Some(char) = chars.next() {
+          regex.push_str(&regex::escape(&char.to_string()));
+        }
+      }
+      char => regex.push_str(&regex::escape(&char.to_string())),
+    }
+  }
+
+  regex
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn matches(pattern: &str, path: &str) -> bool {
+    Regex::new(&format!("^{}$", glob_to_regex(pattern)))
+      .unwrap()
+      .is_match(path)
+  }
+
+  #[test]
+  fn glob_wildcards_stay_in_one_segment() {
+    assert!(matches("*.log", "debug.log"));
+    assert!(!matches("*.log", "logs/debug.log"));
+    assert!(matches("file?.txt", "file1.txt"));
+    assert!(!matches("file?.txt", "file/.txt"));
+  }
+
+  #[test]
+  fn glob_double_star_crosses_segments() {
+    assert!(matches("**/build", "build"));
+    assert!(matches("**/build", "a/b/build"));
+    assert!(matches("a/**/b", "a/b"));
+    assert!(matches("a/**/b", "a/x/y/b"));
+    assert!(matches("logs/**", "logs/a/b.log"));
+  }
+
+  #[test]
+  fn glob_character_classes() {
+    assert!(matches("[abc].txt", "b.txt"));
+    assert!(!matches("[abc].txt", "d.txt"));
+    assert!(matches("[!abc].txt", "d.txt"));
+    assert!(!matches("[!abc].txt", "a.txt"));
+    assert!(matches("[a-c]", "b"));
+    assert!(matches("[]]", "]"));
+    assert!(matches("[a^]", "^"));
+    assert!(matches("[&&]", "&"));
+  }
+
+  #[test]
+  fn glob_literals_are_escaped() {
+    assert!(matches("a.b", "a.b"));
+    assert!(!matches("a.b", "axb"));
+    assert!(matches("\\*", "*"));
+    assert!(!matches("\\*", "a"));
+    assert!(matches("[ab", "[ab"));
+    assert!(matches("[!ab", "[!ab"));
+  }
+
+  #[test]
+  fn unanchored_patterns_match_at_any_depth() {
+    let filter = TransferFilter::new(&["node_modules".to_string(), "*.tmp".to_string()]).unwrap();
+    assert!(filter.is_excluded("node_modules", true));
+    assert!(filter.is_excluded("a/b/node_modules", true));
+    assert!(filter.is_excluded("a/x.tmp", false));
+    assert!(!filter.is_excluded("a/x.txt", false));
+  }
+
+  #[test]
+  fn anchored_patterns_match_from_the_root() {
+    let filter = TransferFilter::new(&["/dist".to_string(), "docs/*.md".to_string()]).unwrap();
+    assert!(filter.is_excluded("dist", true));
+    assert!(!filter.is_excluded("a/dist", true));
+    assert!(filter.is_excluded("docs/a.md", false));
+    assert!(!filter.is_excluded("a/docs/a.md", false));
+  }
+
+  #[test]
+  fn dir_only_patterns_skip_files() {
+    let filter = TransferFilter::new(&["cache/".to_string()]).unwrap();
+    assert!(filter.is_excluded("cache", true));
+    assert!(!filter.is_excluded("cache", false));
+  }
+
+  #[test]
+  fn last_matching_rule_wins() {
+    let filter = TransferFilter::new(&["*.log".to_string(), "!keep.log".to_string()]).unwrap();
+    assert!(filter.is_excluded("a.log", false));
+    assert!(!filter.is_excluded("keep.log", false));
+    assert!(!filter.is_excluded("a/keep.log", false));
+  }
+
+  #[test]
+  fn gitignore_rules_are_relative_to_their_directory() {
+    let mut filter = TransferFilter::default();
+    filter
+      .add_gitignore("# comment\n\n/out\n*.o\n!main.o\n", "src")
+      .unwrap();
+    assert!(filter.is_excluded("src/out", true));
+    assert!(!filter.is_excluded("out", true));
+    assert!(filter.is_excluded("src/lib/a.o", false));
+    assert!(!filter.is_excluded("a.o", false));
+    assert!(!filter.is_excluded("src/main.o", false));
+  }
+}
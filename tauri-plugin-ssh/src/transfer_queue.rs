@@ -0,0 +1,675 @@
+use std::{
+  collections::HashMap,
+  sync::atomic::Ordering,
+  time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+use tauri::ipc::Channel;
+use tauri_plugin_fs::SafeFilePath;
+
+use crate::{
+  checksum::SftpVerifyOpts,
+  commands::{session::SSHSessionId, sftp::SSHSftpId},
+  error::{SSHError, SSHResult},
+  ssh_manager::TransferControl,
+};
+
+/// Transfers running at the same time on one session unless configured otherwise.
+pub const DEFAULT_QUEUE_CONCURRENCY: usize = 3;
+
+const MAX_QUEUE_CONCURRENCY: usize = 16;
+
+/// Minimum interval between two progress events of the same transfer.
+const EMIT_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Interval the transfer speed is sampled at.
+const SPEED_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TransferKind {
+  Upload,
+  Download,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TransferStatus {
+  Queued,
+  Running,
+  Paused,
+  Completed,
+  Failed,
+  Cancelled,
+}
+
+impl TransferStatus {
+  pub fn is_finished(self) -> bool {
+    matches!(
+      self,
+      TransferStatus::Completed | TransferStatus::Failed | TransferStatus::Cancelled
+    )
+  }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferQueueItem {
+  pub id: String,
+  pub ssh_session_id: SSHSessionId,
+  pub ssh_sftp_id: SSHSftpId,
+  /// Host the transfer history is recorded for
+  pub host_id: Option<String>,
+  pub kind: TransferKind,
+  pub local_filename: String,
+  pub remote_filename: String,
+  pub status: TransferStatus,
+  pub progress: u64,
+  pub total: u64,
+  /// Bytes per second, 0 while the transfer isn't running
+  pub speed: u64,
+  /// Seconds left at the current speed
+  pub eta: Option<u64>,
+  pub error: Option<String>,
+  pub attempts: u32,
+  pub created_at: u64,
+  /// Set every time the transfer is started
+  pub started_at: Option<u64>,
+  pub finished_at: Option<u64>,
+  /// Opened through the fs plugin like the direct transfers, `None` for items read back from an
+  /// event
+  #[serde(skip)]
+  pub local_path: Option<SafeFilePath>,
+  /// Continues from the partial target, set when the item is retried
+  #[serde(skip)]
+  pub resume: bool,
+  #[serde(skip)]
+  pub verify: Option<SftpVerifyOpts>,
+  #[serde(skip)]
+  sample: Option<(Instant, u64)>,
+  #[serde(skip)]
+  emitted_at: Option<Instant>,
+}
+
+impl TransferQueueItem {
+  #[allow(clippy::too_many_arguments)]
+  pub fn new(
+    id: String,
+    ssh_session_id: SSHSessionId,
+    ssh_sftp_id: SSHSftpId,
+    host_id: Option<String>,
+    kind: TransferKind,
+    local_path: SafeFilePath,
+    remote_filename: String,
+    resume: bool,
+    verify: Option<SftpVerifyOpts>,
+  ) -> Self {
+    Self {
+      id,
+      ssh_session_id,
+      ssh_sftp_id,
+      host_id,
+      kind,
+      local_filename: local_path.to_string(),
+      remote_filename,
+      status: TransferStatus::Queued,
+      progress: 0,
+      total: 0,
+      speed: 0,
+      eta: None,
+      error: None,
+      attempts: 0,
+      created_at: now_millis(),
+      started_at: None,
+      finished_at: None,
+      local_path: Some(local_path),
+      resume,
+      verify,
+      sample: None,
+      emitted_at: None,
+    }
+  }
+
+  fn update_speed(&mut self, now: Instant) {
+    let Some((sampled_at, sampled_progress)) = self.sample else {
+      self.sample = Some((now, self.progress));
+      return;
+    };
+
+    let elapsed = now.duration_since(sampled_at);
+    if elapsed < SPEED_INTERVAL {
+      return;
+    }
+
+    let bytes = self.progress.saturating_sub(sampled_progress) as f64;
+    let speed = bytes / elapsed.as_secs_f64();
+    // 指数平滑，避免速度与剩余时间跳动
+    self.speed = if self.speed == 0 {
+      speed as u64
+    } else {
+      (self.speed as f64 * 0.7 + speed * 0.3) as u64
+    };
+    self.eta = (self.speed > 0).then(|| self.total.saturating_sub(self.progress) / self.speed);
+    self.sample = Some((now, self.progress));
+  }
+
+  fn reset_stats(&mut self) {
+    self.speed = 0;
+    self.eta = None;
+    self.sample = None;
+  }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type")]
+pub enum TransferQueueEvent {
+  Update {
+    item: Box<TransferQueueItem>,
+  },
+  Remove {
+    ids: Vec<String>,
+  },
+  /// Ids of all items in the new queue order
+  Reorder {
+    ids: Vec<String>,
+  },
+}
+
+fn now_millis() -> u64 {
+  SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .map(|duration| duration.as_millis() as u64)
+    .unwrap_or(0)
+}
+
+/// Transfers of all sessions in queue order. Lives in the backend so it outlives window reloads,
+/// the window subscribes again and receives the current items.
+#[derive(Default)]
+pub struct TransferQueue {
+  items: Vec<TransferQueueItem>,
+  controls: HashMap<String, TransferControl>,
+  concurrency: HashMap<SSHSessionId, usize>,
+  ipc_channel: Option<Channel<TransferQueueEvent>>,
+}
+
+impl TransferQueue {
+  fn emit(&mut self, event: TransferQueueEvent) {
+    let Some(ipc_channel) = &self.ipc_channel else {
+      return;
+    };
+
+    if let Err(err) = ipc_channel.send(event) {
+      log::error!("transfer queue emit error {:?}", err);
+      self.ipc_channel = None;
+    }
+  }
+
+  fn emit_item(&mut self, index: usize) {
+    self.items[index].emitted_at = Some(Instant::now());
+    let item = self.items[index].clone();
+    self.emit(TransferQueueEvent::Update {
+      item: Box::new(item),
+    });
+  }
+
+  fn emit_order(&mut self) {
+    let ids = self.items.iter().map(|item| item.id.clone()).collect();
+    self.emit(TransferQueueEvent::Reorder { ids });
+  }
+
+  fn position(&self, id: &str) -> SSHResult<usize> {
+    self
+      .items
+      .iter()
+      .position(|item| item.id == id)
+      .ok_or(SSHError::NotFoundTransfer)
+  }
+
+  pub fn subscribe(&mut self, ipc_channel: Channel<TransferQueueEvent>) -> Vec<TransferQueueItem> {
+    self.ipc_channel = Some(ipc_channel);
+    self.items.clone()
+  }
+
+  pub fn list(&self) -> Vec<TransferQueueItem> {
+    self.items.clone()
+  }
+
+  /// Appends the item, or puts it before every other item with `priority`.
+  pub fn enqueue(&mut self, item: TransferQueueItem, priority: bool) {
+    let index = if priority { 0 } else { self.items.len() };
+    self.items.insert(index, item);
+    self.emit_item(index);
+    if priority {
+      self.emit_order();
+    }
+  }
+
+  pub fn set_concurrency(&mut self, ssh_session_id: SSHSessionId, concurrency: usize) {
+    self
+      .concurrency
+      .insert(ssh_session_id, concurrency.clamp(1, MAX_QUEUE_CONCURRENCY));
+  }
+
+  /// Marks the queued items that fit in the concurrency of their session as running, returns
+  /// them with the control of each transfer.
+  pub fn start_runnable(&mut self) -> Vec<(TransferQueueItem, TransferControl)> {
+    let mut active: HashMap<SSHSessionId, usize> = HashMap::new();
+    for item in &self.items {
+      // 暂停的传输仍然占用名额，恢复时不会超过并发数
+      if self.controls.contains_key(&item.id) {
+        *active.entry(item.ssh_session_id).or_default() += 1;
+      }
+    }
+
+    let mut runnable = Vec::new();
+    for index in 0..self.items.len() {
+      if self.items[index].status != TransferStatus::Queued {
+        continue;
+      }
+
+      let ssh_session_id = self.items[index].ssh_session_id;
+      let limit = self
+        .concurrency
+        .get(&ssh_session_id)
+        .copied()
+        .unwrap_or(DEFAULT_QUEUE_CONCURRENCY);
+      let count = active.entry(ssh_session_id).or_default();
+      if *count >= limit {
+        continue;
+      }
+      *count += 1;
+
+      let control = TransferControl::new();
+      let item = &mut self.items[index];
+      item.status = TransferStatus::Running;
+      item.attempts += 1;
+      item.started_at = Some(now_millis());
+      item.reset_stats();
+      self.controls.insert(item.id.clone(), control.clone());
+      runnable.push((item.clone(), control));
+      self.emit_item(index);
+    }
+
+    runnable
+  }
+
+  pub fn progress(&mut self, id: &str, progress: u64, total: u64) -> SSHResult<()> {
+    let index = self.position(id)?;
+    let now = Instant::now();
+    let item = &mut self.items[index];
+    item.progress = progress;
+    item.total = total;
+    item.update_speed(now);
+
+    let throttled = item
+      .emitted_at
+      .is_some_and(|emitted_at| now.duration_since(emitted_at) < EMIT_INTERVAL);
+    if !throttled || progress == total {
+      self.emit_item(index);
+    }
+
+    Ok(())
+  }
+
+  /// Sets the final status of a transfer that stopped running, returns the finished item.
+  pub fn finish(&mut self, id: &str, result: SSHResult<()>) -> SSHResult<TransferQueueItem> {
+    self.controls.remove(id);
+    let index = self.position(id)?;
+    let item = &mut self.items[index];
+    match result {
+      Ok(()) => {
+        item.status = TransferStatus::Completed;
+        item.progress = item.total;
+      }
+      Err(SSHError::TransferCancelled) => item.status = TransferStatus::Cancelled,
+      Err(err) => {
+        log::error!("transfer {} failed {:?}", id, err);
+        item.status = TransferStatus::Failed;
+        item.error = Some(err.to_string());
+      }
+    }
+    item.reset_stats();
+    item.finished_at = Some(now_millis());
+    self.emit_item(index);
+
+    Ok(self.items[index].clone())
+  }
+
+  pub fn cancel(&mut self, id: &str) -> SSHResult<()> {
+    let index = self.position(id)?;
+    if self.items[index].status.is_finished() {
+      return Ok(());
+    }
+
+    // 运行中的传输在停止后由 finish 更新状态
+    if let Some(control) = self.controls.get(id) {
+      control.cancel.store(true, Ordering::Relaxed);
+      control.pause.store(false, Ordering::Relaxed);
+      return Ok(());
+    }
+
+    let item = &mut self.items[index];
+    item.status = TransferStatus::Cancelled;
+    item.finished_at = Some(now_millis());
+    self.emit_item(index);
+
+    Ok(())
+  }
+
+  pub fn pause(&mut self, id: &str) -> SSHResult<()> {
+    let index = self.position(id)?;
+    if !matches!(
+      self.items[index].status,
+      TransferStatus::Queued | TransferStatus::Running
+    ) {
+      return Ok(());
+    }
+
+    if let Some(control) = self.controls.get(id) {
+      control.pause.store(true, Ordering::Relaxed);
+    }
+    let item = &mut self.items[index];
+    item.status = TransferStatus::Paused;
+    item.reset_stats();
+    self.emit_item(index);
+
+    Ok(())
+  }
+
+  pub fn resume(&mut self, id: &str) -> SSHResult<()> {
+    let index = self.position(id)?;
+    if self.items[index].status != TransferStatus::Paused {
+      return Ok(());
+    }
+
+    // 尚未开始的传输回到队列中等待调度
+    self.items[index].status = match self.controls.get(id) {
+      Some(control) => {
+        control.pause.store(false, Ordering::Relaxed);
+        TransferStatus::Running
+      }
+      None => TransferStatus::Queued,
+    };
+    self.emit_item(index);
+
+    Ok(())
+  }
+
+  /// Queues a failed or cancelled item again, it continues from the partial target.
+  pub fn retry(&mut self, id: &str) -> SSHResult<()> {
+    let index = self.position(id)?;
+    let item = &mut self.items[index];
+    if !matches!(
+      item.status,
+      TransferStatus::Failed | TransferStatus::Cancelled
+    ) {
+      return Ok(());
+    }
+
+    item.status = TransferStatus::Queued;
+    item.resume = true;
+    item.error = None;
+    item.finished_at = None;
+    item.reset_stats();
+    self.emit_item(index);
+
+    Ok(())
+  }
+
+  /// Moves the item to `index` in the queue, items before it are started first.
+  pub fn move_to(&mut self, id: &str, index: usize) -> SSHResult<()> {
+    let from = self.position(id)?;
+    let item = self.items.remove(from);
+    let index = index.min(self.items.len());
+    self.items.insert(index, item);
+    self.emit_order();
+
+    Ok(())
+  }
+
+  /// Removes a completed, failed or cancelled item, running ones have to be cancelled first.
+  pub fn remove(&mut self, id: &str) -> SSHResult<()> {
+    let index = self.position(id)?;
+    if !self.items[index].status.is_finished() {
+      return Ok(());
+    }
+
+    self.items.remove(index);
+    self.emit(TransferQueueEvent::Remove {
+      ids: vec![id.to_string()],
+    });
+
+    Ok(())
+  }
+
+  /// Removes the completed, failed and cancelled items.
+  pub fn clear(&mut self) -> Vec<String> {
+    let ids: Vec<String> = self
+      .items
+      .iter()
+      .filter(|item| item.status.is_finished())
+      .map(|item| item.id.clone())
+      .collect();
+    self.items.retain(|item| !item.status.is_finished());
+    self.emit(TransferQueueEvent::Remove { ids: ids.clone() });
+    ids
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use uuid::Uuid;
+
+  use super::*;
+
+  fn session(id: u128) -> SSHSessionId {
+    SSHSessionId::from(Uuid::from_u128(id))
+  }
+
+  fn item(id: &str, ssh_session_id: SSHSessionId) -> TransferQueueItem {
+    TransferQueueItem::new(
+      id.to_string(),
+      ssh_session_id,
+      SSHSftpId::from(Uuid::nil()),
+      None,
+      TransferKind::Upload,
+      "/tmp/local".parse().unwrap(),
+      "/tmp/remote".to_string(),
+      false,
+      None,
+    )
+  }
+
+  fn queue(ids: &[&str]) -> TransferQueue {
+    let mut transfer_queue = TransferQueue::default();
+    for id in ids {
+      transfer_queue.enqueue(item(id, session(1)), false);
+    }
+    transfer_queue
+  }
+
+  fn ids(transfer_queue: &TransferQueue) -> Vec<&str> {
+    transfer_queue
+      .items
+      .iter()
+      .map(|item| item.id.as_str())
+      .collect()
+  }
+
+  fn status(transfer_queue: &TransferQueue, id: &str) -> TransferStatus {
+    transfer_queue.items[transfer_queue.position(id).unwrap()].status
+  }
+
+  fn started(runnable: &[(TransferQueueItem, TransferControl)]) -> Vec<&str> {
+    runnable.iter().map(|(item, _)| item.id.as_str()).collect()
+  }
+
+  #[test]
+  fn enqueue_appends_and_priority_goes_first() {
+    let mut transfer_queue = queue(&["a", "b"]);
+    transfer_queue.enqueue(item("c", session(1)), true);
+    transfer_queue.enqueue(item("d", session(1)), false);
+
+    assert_eq!(ids(&transfer_queue), ["c", "a", "b", "d"]);
+    assert_eq!(status(&transfer_queue, "d"), TransferStatus::Queued);
+  }
+
+  #[test]
+  fn start_runnable_respects_the_session_concurrency() {
+    let mut transfer_queue = queue(&["a", "b", "c", "d"]);
+    transfer_queue.enqueue(item("e", session(2)), false);
+
+    let runnable = transfer_queue.start_runnable();
+    assert_eq!(started(&runnable), ["a", "b", "c", "e"]);
+    assert_eq!(status(&transfer_queue, "a"), TransferStatus::Running);
+    assert_eq!(transfer_queue.items[0].attempts, 1);
+    assert!(transfer_queue.start_runnable().is_empty());
+
+    transfer_queue.finish("b", Ok(())).unwrap();
+    assert_eq!(status(&transfer_queue, "b"), TransferStatus::Completed);
+    assert_eq!(started(&transfer_queue.start_runnable()), ["d"]);
+  }
+
+  #[test]
+  fn start_runnable_uses_the_configured_concurrency() {
+    let mut transfer_queue = queue(&["a", "b", "c"]);
+    transfer_queue.set_concurrency(session(1), 1);
+    assert_eq!(started(&transfer_queue.start_runnable()), ["a"]);
+
+    // 并发数至少为 1
+    transfer_queue.set_concurrency(session(1), 0);
+    assert!(transfer_queue.start_runnable().is_empty());
+
+    transfer_queue.set_concurrency(session(1), 2);
+    assert_eq!(started(&transfer_queue.start_runnable()), ["b"]);
+  }
+
+  #[test]
+  fn pause_and_resume_a_queued_transfer() {
+    let mut transfer_queue = queue(&["a", "b"]);
+    transfer_queue.pause("a").unwrap();
+    assert_eq!(status(&transfer_queue, "a"), TransferStatus::Paused);
+    assert_eq!(started(&transfer_queue.start_runnable()), ["b"]);
+
+    transfer_queue.resume("a").unwrap();
+    assert_eq!(status(&transfer_queue, "a"), TransferStatus::Queued);
+    assert_eq!(started(&transfer_queue.start_runnable()), ["a"]);
+  }
+
+  #[test]
+  fn pause_and_resume_a_running_transfer() {
+    let mut transfer_queue = queue(&["a", "b"]);
+    transfer_queue.set_concurrency(session(1), 1);
+    let runnable = transfer_queue.start_runnable();
+    let (_, control) = &runnable[0];
+
+    transfer_queue.pause("a").unwrap();
+    assert_eq!(status(&transfer_queue, "a"), TransferStatus::Paused);
+    assert!(control.pause.load(Ordering::Relaxed));
+    // 暂停的传输仍然占用名额
+    assert!(transfer_queue.start_runnable().is_empty());
+
+    transfer_queue.resume("a").unwrap();
+    assert_eq!(status(&transfer_queue, "a"), TransferStatus::Running);
+    assert!(!control.pause.load(Ordering::Relaxed));
+  }
+
+  #[test]
+  fn cancel_a_queued_transfer() {
+    let mut transfer_queue = queue(&["a"]);
+    transfer_queue.cancel("a").unwrap();
+
+    assert_eq!(status(&transfer_queue, "a"), TransferStatus::Cancelled);
+    assert!(transfer_queue.items[0].finished_at.is_some());
+    assert!(transfer_queue.start_runnable().is_empty());
+  }
+
+  #[test]
+  fn cancel_a_running_transfer_once_it_stops() {
+    let mut transfer_queue = queue(&["a"]);
+    let runnable = transfer_queue.start_runnable();
+    let (_, control) = &runnable[0];
+    transfer_queue.pause("a").unwrap();
+
+    transfer_queue.cancel("a").unwrap();
+    assert!(control.cancel.load(Ordering::Relaxed));
+    assert!(!control.pause.load(Ordering::Relaxed));
+    assert_eq!(status(&transfer_queue, "a"), TransferStatus::Paused);
+
+    let item = transfer_queue
+      .finish("a", Err(SSHError::TransferCancelled))
+      .unwrap();
+    assert_eq!(item.status, TransferStatus::Cancelled);
+    assert!(item.error.is_none());
+  }
+
+  #[test]
+  fn retry_queues_a_failed_transfer_with_resume() {
+    let mut transfer_queue = queue(&["a"]);
+    transfer_queue.start_runnable();
+    let item = transfer_queue
+      .finish("a", Err(SSHError::new("connection lost")))
+      .unwrap();
+    assert_eq!(item.status, TransferStatus::Failed);
+    assert_eq!(item.error.as_deref(), Some("connection lost"));
+    assert!(!item.resume);
+
+    transfer_queue.retry("a").unwrap();
+    let item = &transfer_queue.items[0];
+    assert_eq!(item.status, TransferStatus::Queued);
+    assert!(item.resume);
+    assert!(item.error.is_none());
+    assert!(item.finished_at.is_none());
+
+    let runnable = transfer_queue.start_runnable();
+    assert!(runnable[0].0.resume);
+    assert_eq!(runnable[0].0.attempts, 2);
+  }
+
+  #[test]
+  fn retry_ignores_completed_and_queued_transfers() {
+    let mut transfer_queue = queue(&["a", "b"]);
+    transfer_queue.set_concurrency(session(1), 1);
+    transfer_queue.start_runnable();
+    transfer_queue.finish("a", Ok(())).unwrap();
+
+    transfer_queue.retry("a").unwrap();
+    transfer_queue.retry("b").unwrap();
+    assert_eq!(status(&transfer_queue, "a"), TransferStatus::Completed);
+    assert_eq!(status(&transfer_queue, "b"), TransferStatus::Queued);
+    assert!(!transfer_queue.items[1].resume);
+  }
+
+  #[test]
+  fn move_to_reorders_the_queue() {
+    let mut transfer_queue = queue(&["a", "b", "c"]);
+    transfer_queue.move_to("c", 0).unwrap();
+    assert_eq!(ids(&transfer_queue), ["c", "a", "b"]);
+
+    transfer_queue.move_to("c", 10).unwrap();
+    assert_eq!(ids(&transfer_queue), ["a", "b", "c"]);
+
+    transfer_queue.set_concurrency(session(1), 1);
+    transfer_queue.move_to("b", 0).unwrap();
+    assert_eq!(started(&transfer_queue.start_runnable()), ["b"]);
+    assert!(matches!(
+      transfer_queue.move_to("x", 0),
+      Err(SSHError::NotFoundTransfer)
+    ));
+  }
+
+  #[test]
+  fn clear_removes_only_finished_transfers() {
+    let mut transfer_queue = queue(&["a", "b", "c", "d"]);
+    transfer_queue.set_concurrency(session(1), 2);
+    transfer_queue.start_runnable();
+    transfer_queue.finish("a", Ok(())).unwrap();
+    transfer_queue.cancel("c").unwrap();
+
+    assert_eq!(transfer_queue.clear(), ["a", "c"]);
+    assert_eq!(ids(&transfer_queue), ["b", "d"]);
+
+    // 运行中的传输需要先取消
+    transfer_queue.remove("b").unwrap();
+    assert_eq!(ids(&transfer_queue), ["b", "d"]);
+  }
+}
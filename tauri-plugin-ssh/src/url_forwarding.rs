@@ -0,0 +1,130 @@
+use std::collections::HashSet;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use terminal_core::utils::strip_ansi;
+
+use crate::{commands::port_forwarding::SSHPortForwardingId, error::SSHResult};
+
+/// Longest output line scanned, the rest of a longer line is ignored
+const LINE_SIZE: usize = 4096;
+
+const LOCAL_URL_PATTERN: &str =
+  r#"(https?)://(localhost|127\.0\.0\.1|0\.0\.0\.0|\[::1?\]):(\d{1,5})(/[^\s"'<>`]*)?"#;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum UrlForwardingMode {
+  /// The url is reported and the webview asks before forwarding it
+  Prompt,
+  /// A local forwarding is opened as soon as the url is printed
+  Auto,
+}
+
+/// A url on the loopback address of the remote host, printed e.g. by a dev server.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalUrl {
+  pub url: String,
+  pub scheme: String,
+  pub host: String,
+  pub port: u16,
+  pub path: String,
+}
+
+impl LocalUrl {
+  /// Address the remote host connects to, wildcard addresses are reached on loopback.
+  pub fn remote_address(&self) -> String {
+    match self.host.as_str() {
+      "0.0.0.0" => "127.0.0.1".to_string(),
+      "[::]" | "[::1]" => "::1".to_string(),
+      host => host.to_string(),
+    }
+  }
+
+  /// The url reached through the local forwarding.
+  pub fn local_url(&self, local_port: u16) -> String {
+    format!("{}://localhost:{}{}", self.scheme, local_port, self.path)
+  }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ForwardedUrl {
+  pub ssh_port_forwarding_id: SSHPortForwardingId,
+  pub url: String,
+  pub local_url: String,
+  pub local_port: u16,
+  pub remote_port: u16,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type")]
+pub enum UrlForwardingEvent {
+  Detected(LocalUrl),
+  Forwarded(ForwardedUrl),
+  /// The forwarding of a detected url couldn't be opened
+  Failed {
+    url: String,
+    message: String,
+  },
+}
+
+/// Finds loopback urls in complete output lines, each port is reported once per shell.
+pub struct LocalUrlDetector {
+  regex: Regex,
+  line: Vec<u8>,
+  ports: HashSet<u16>,
+}
+
+impl LocalUrlDetector {
+  pub fn new() -> SSHResult<Self> {
+    Ok(Self {
+      regex: Regex::new(LOCAL_URL_PATTERN)?,
+      line: Vec::new(),
+      ports: HashSet::new(),
+    })
+  }
+
+  pub fn feed(&mut self, data: &[u8]) -> Vec<LocalUrl> {
+    let mut urls = Vec::new();
+    for chunk in data.split_inclusive(|byte| *byte == b'\n') {
+      let size = chunk.len().min(LINE_SIZE.saturating_sub(self.line.len()));
+      self.line.extend_from_slice(&chunk[..size]);
+
+      if chunk.ends_with(b"\n") {
+        let line = strip_ansi(&std::mem::take(&mut self.line));
+        self.scan(&line, &mut urls);
+      }
+    }
+
+    urls
+  }
+
+  fn scan(&mut self, line: &str, urls: &mut Vec<LocalUrl>) {
+    for captures in self.regex.captures_iter(line) {
+      let Some(port) = captures[3].parse::<u16>().ok().filter(|port| *port != 0) else {
+        continue;
+      };
+      if !self.ports.insert(port) {
+        continue;
+      }
+
+      // 句末的标点不属于 url
+      let path = captures
+        .get(4)
+        .map(|path| {
+          path
+            .as_str()
+            .trim_end_matches(['.', ',', ';', ':', ')', ']'])
+        })
+        .unwrap_or_default();
+      urls.push(LocalUrl {
+        url: format!("{}://{}:{}{}", &captures[1], &captures[2], port, path),
+        scheme: captures[1].to_string(),
+        host: captures[2].to_string(),
+        port,
+        path: path.to_string(),
+      });
+    }
+  }
+}
@@ -0,0 +1,1075 @@
+use std::{
+  collections::VecDeque,
+  io::{self, SeekFrom},
+  path::{Path, PathBuf},
+  sync::{Arc, atomic::Ordering},
+  time::Duration,
+};
+
+use russh::{ChannelWriteHalf, client};
+use serde::Serialize;
+use tauri::ipc::Channel;
+use tokio::{
+  fs,
+  io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt, BufWriter},
+  sync::{Mutex as AsyncMutex, mpsc},
+  time::{sleep, timeout},
+};
+
+use crate::{
+  commands::sftp::SFTPProgressPayload,
+  error::{SSHError, SSHResult},
+  ssh_manager::TransferControl,
+};
+
+const ZPAD: u8 = b'*';
+const ZDLE: u8 = 0x18;
+const ZBIN: u8 = b'A';
+const ZHEX: u8 = b'B';
+const ZBIN32: u8 = b'C';
+
+const ZRQINIT: u8 = 0;
+const ZRINIT: u8 = 1;
+const ZSINIT: u8 = 2;
+const ZACK: u8 = 3;
+const ZFILE: u8 = 4;
+const ZSKIP: u8 = 5;
+const ZNAK: u8 = 6;
+const ZABORT: u8 = 7;
+const ZFIN: u8 = 8;
+const ZRPOS: u8 = 9;
+const ZDATA: u8 = 10;
+const ZEOF: u8 = 11;
+const ZFERR: u8 = 12;
+const ZCRC: u8 = 13;
+const ZCHALLENGE: u8 = 14;
+const ZCAN: u8 = 16;
+const ZCOMMAND: u8 = 18;
+
+/// Subpacket ends, the frame continues only after ZCRCG and ZCRCQ
+const ZCRCE: u8 = b'h';
+const ZCRCG: u8 = b'i';
+const ZCRCQ: u8 = b'j';
+const ZCRCW: u8 = b'k';
+const ZRUB0: u8 = b'l';
+const ZRUB1: u8 = b'm';
+
+const XON: u8 = 0x11;
+const XOFF: u8 = 0x13;
+
+/// ZRINIT flags, full duplex, overlapped io and 32 bit crc
+const CANFDX: u8 = 0x01;
+const CANOVIO: u8 = 0x02;
+const CANFC32: u8 = 0x20;
+/// ZFILE conversion flag, binary transfer
+const ZCBIN: u8 = 1;
+
+/// Start of the hex ZRQINIT header sent by `sz`
+const ZRQINIT_START: &[u8] = b"**\x18B00";
+/// Start of the hex ZRINIT header sent by `rz`
+const ZRINIT_START: &[u8] = b"**\x18B01";
+/// Same as lrzsz, ten CAN followed by ten backspaces to erase them
+const ABORT_SEQUENCE: &[u8] = &[
+  0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x08, 0x08, 0x08, 0x08, 0x08, 0x08,
+  0x08, 0x08, 0x08, 0x08,
+];
+
+const SUBPACKET_SIZE: usize = 1024;
+/// Longest subpacket accepted from the remote
+const MAX_SUBPACKET_SIZE: usize = 16 * 1024;
+/// Encoded data is written to the channel in chunks of this size
+const WRITE_SIZE: usize = 32 * 1024;
+const PROGRESS_INTERVAL: u64 = 64 * 1024;
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+/// Give up when the remote sends nothing for this long
+const IDLE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Direction of a transfer seen from the local side.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum ZmodemDirection {
+  /// The remote runs `rz` and waits for local files
+  Upload,
+  /// The remote runs `sz` and offers files
+  Download,
+}
+
+/// Finds the header `rz` or `sz` sends when it starts.
+pub fn detect(data: &[u8]) -> Option<(usize, ZmodemDirection)> {
+  data
+    .windows(ZRQINIT_START.len())
+    .position(|window| window == ZRQINIT_START || window == ZRINIT_START)
+    .map(|pos| {
+      let direction = if data[pos + ZRQINIT_START.len() - 1] == b'0' {
+        ZmodemDirection::Download
+      } else {
+        ZmodemDirection::Upload
+      };
+      (pos, direction)
+    })
+}
+
+pub struct ZmodemFile {
+  pub name: String,
+  file: fs::File,
+  size: u64,
+  mtime: u64,
+}
+
+impl ZmodemFile {
+  pub async fn new(name: String, file: fs::File) -> SSHResult<Self> {
+    let metadata = file.metadata().await?;
+    // 协议中的偏移量只有 32 位
+    if metadata.len() > u32::MAX as u64 {
+      return Err(SSHError::ZmodemError(format!(
+        "{} is larger than 4 GiB",
+        name
+      )));
+    }
+    let mtime = metadata
+      .modified()
+      .ok()
+      .and_then(|modified| modified.duration_since(std::time::UNIX_EPOCH).ok())
+      .map(|duration| duration.as_secs())
+      .unwrap_or_default();
+
+    Ok(Self {
+      name,
+      file,
+      size: metadata.len(),
+      mtime,
+    })
+  }
+}
+
+pub enum ZmodemJob {
+  Upload(Vec<ZmodemFile>),
+  /// Received files are saved in the directory
+  Download(PathBuf),
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Header {
+  frame_type: u8,
+  data: [u8; 4],
+}
+
+impl Header {
+  fn new(frame_type: u8, data: [u8; 4]) -> Self {
+    Self { frame_type, data }
+  }
+
+  fn with_position(frame_type: u8, position: u64) -> Self {
+    Self::new(frame_type, (position as u32).to_le_bytes())
+  }
+
+  fn position(&self) -> u64 {
+    u32::from_le_bytes(self.data) as u64
+  }
+
+  fn bytes(&self) -> [u8; 5] {
+    let [p0, p1, p2, p3] = self.data;
+    [self.frame_type, p0, p1, p2, p3]
+  }
+
+  fn encode_hex(&self) -> Vec<u8> {
+    let bytes = self.bytes();
+    let crc = crc16_update(0, &bytes);
+
+    let mut buf = vec![ZPAD, ZPAD, ZDLE, ZHEX];
+    for byte in bytes.iter().chain(&crc.to_be_bytes()) {
+      buf.extend_from_slice(format!("{:02x}", byte).as_bytes());
+    }
+    buf.extend_from_slice(&[b'\r', 0x8a]);
+    if self.frame_type != ZFIN && self.frame_type != ZACK {
+      buf.push(XON);
+    }
+    buf
+  }
+
+  fn encode_binary(&self, crc32: bool) -> Vec<u8> {
+    let bytes = self.bytes();
+
+    let mut buf = vec![ZPAD, ZDLE, if crc32 { ZBIN32 } else { ZBIN }];
+    escape_into(&mut buf, &bytes);
+    if crc32 {
+      escape_into(&mut buf, &(!crc32_update(!0, &bytes)).to_le_bytes());
+    } else {
+      escape_into(&mut buf, &crc16_update(0, &bytes).to_be_bytes());
+    }
+    buf
+  }
+}
+
+enum Escaped {
+  Byte(u8),
+  FrameEnd(u8),
+}
+
+/// Runs a ZMODEM transfer over the data the shell channel receives.
+///
+/// Only the receiving side of the shell output is taken over, the transfer
+/// writes to the channel directly and hands the unused input back when done.
+pub struct Zmodem {
+  shell_channel: Arc<AsyncMutex<ChannelWriteHalf<client::Msg>>>,
+  receiver: mpsc::Receiver<Vec<u8>>,
+  buffer: VecDeque<u8>,
+  on_progress: Channel<SFTPProgressPayload>,
+  control: TransferControl,
+  progress: u64,
+  reported: u64,
+  total: u64,
+  /// Subpackets received after the last header carry a 32 bit crc
+  receive_crc32: bool,
+  /// The remote accepts subpackets with a 32 bit crc
+  send_crc32: bool,
+}
+
+impl Zmodem {
+  pub fn new(
+    shell_channel: Arc<AsyncMutex<ChannelWriteHalf<client::Msg>>>,
+    receiver: mpsc::Receiver<Vec<u8>>,
+    on_progress: Channel<SFTPProgressPayload>,
+    control: TransferControl,
+  ) -> Self {
+    Self {
+      shell_channel,
+      receiver,
+      buffer: VecDeque::new(),
+      on_progress,
+      control,
+      progress: 0,
+      reported: 0,
+      total: 0,
+      receive_crc32: false,
+      send_crc32: false,
+    }
+  }
+
+  /// Runs the transfer and returns the names of the transferred files.
+  ///
+  /// The remote `rz` or `sz` is aborted when the transfer fails.
+  pub async fn run(&mut self, job: ZmodemJob) -> SSHResult<Vec<String>> {
+    let result = match job {
+      ZmodemJob::Upload(files) => self.send_files(files).await,
+      ZmodemJob::Download(dir) => self.receive_files(&dir).await,
+    };
+
+    if result.is_err() {
+      self.buffer.clear();
+      abort(&self.shell_channel, &mut self.receiver).await?;
+    }
+
+    result
+  }
+
+  /// Returns the receiver and the input read ahead but not used by the protocol.
+  pub fn into_parts(self) -> (mpsc::Receiver<Vec<u8>>, Vec<u8>) {
+    (self.receiver, self.buffer.into())
+  }
+
+  async fn send_files(&mut self, mut files: Vec<ZmodemFile>) -> SSHResult<Vec<String>> {
+    self.total = files.iter().map(|file| file.size).sum();
+    let window = self.read_zrinit().await?;
+
+    let mut filenames = Vec::with_capacity(files.len());
+    let mut bytes_left = self.total;
+    let count = files.len();
+    for (index, file) in files.iter_mut().enumerate() {
+      log::info!("zmodem send {} {} bytes", file.name, file.size);
+      if self
+        .send_file(file, count - index, bytes_left, window)
+        .await?
+      {
+        filenames.push(file.name.clone());
+      }
+      bytes_left -= file.size;
+    }
+
+    self.write(&Header::new(ZFIN, [0; 4]).encode_hex()).await?;
+    loop {
+      if self.read_header().await?.frame_type == ZFIN {
+        break;
+      }
+    }
+    self.write(b"OO").await?;
+
+    Ok(filenames)
+  }
+
+  /// Waits for the receiver to be ready and returns its buffer size, 0 means unlimited.
+  async fn read_zrinit(&mut self) -> SSHResult<usize> {
+    loop {
+      let header = self.read_header().await?;
+      match header.frame_type {
+        ZRINIT => {
+          self.send_crc32 = header.data[3] & CANFC32 != 0;
+          return Ok(u16::from_le_bytes([header.data[0], header.data[1]]) as usize);
+        }
+        ZCHALLENGE => {
+          self
+            .write(&Header::new(ZACK, header.data).encode_hex())
+            .await?
+        }
+        ZCOMMAND => {
+          return Err(SSHError::ZmodemError(
+            "Remote command is not supported".into(),
+          ));
+        }
+        ZABORT | ZCAN | ZFERR | ZFIN => return Err(SSHError::TransferCancelled),
+        _ => {}
+      }
+    }
+  }
+
+  /// Returns false when the receiver skips the file.
+  async fn send_file(
+    &mut self,
+    file: &mut ZmodemFile,
+    files_left: usize,
+    bytes_left: u64,
+    window: usize,
+  ) -> SSHResult<bool> {
+    let start = self.progress;
+    let mut info = file.name.as_bytes().to_vec();
+    info.push(0);
+    info.extend_from_slice(
+      format!(
+        "{} {:o} 0 0 {} {}",
+        file.size, file.mtime, files_left, bytes_left
+      )
+      .as_bytes(),
+    );
+    info.push(0);
+
+    'file: loop {
+      let mut buf = Header::new(ZFILE, [0, 0, 0, ZCBIN]).encode_binary(self.send_crc32);
+      buf.extend(encode_subpacket(&info, ZCRCW, self.send_crc32));
+      self.write(&buf).await?;
+
+      loop {
+        let header = self.read_header().await?;
+        match header.frame_type {
+          ZRPOS => {
+            self
+              .send_data(file, header.position(), start, window)
+              .await?;
+            return Ok(true);
+          }
+          ZSKIP => {
+            self.report_progress(start + file.size - self.progress)?;
+            return Ok(false);
+          }
+          ZCRC => {
+            let crc = file_crc32(&mut file.file).await?;
+            self
+              .write(&Header::new(ZCRC, crc.to_le_bytes()).encode_hex())
+              .await?;
+          }
+          ZNAK => continue 'file,
+          ZABORT | ZCAN | ZFERR | ZFIN => return Err(SSHError::TransferCancelled),
+          _ => {}
+        }
+      }
+    }
+  }
+
+  async fn send_data(
+    &mut self,
+    file: &mut ZmodemFile,
+    mut position: u64,
+    start: u64,
+    window: usize,
+  ) -> SSHResult<()> {
+    let mut chunk = vec![0; SUBPACKET_SIZE];
+
+    'data: loop {
+      file.file.seek(SeekFrom::Start(position)).await?;
+      self.progress = start + position;
+      let mut buf = Header::with_position(ZDATA, position).encode_binary(self.send_crc32);
+      let mut unacked = 0;
+
+      loop {
+        self.check_control().await?;
+
+        let size = file.file.read(&mut chunk).await?;
+        unacked += size;
+        let end = if size == 0 {
+          ZCRCE
+        } else if window > 0 && unacked + SUBPACKET_SIZE > window {
+          ZCRCW
+        } else {
+          ZCRCG
+        };
+        buf.extend(encode_subpacket(&chunk[..size], end, self.send_crc32));
+        position += size as u64;
+        self.report_progress(size as u64)?;
+
+        if buf.len() >= WRITE_SIZE || end != ZCRCG {
+          self.write(&buf).await?;
+          buf.clear();
+        }
+
+        match end {
+          ZCRCE => break,
+          // 接收方缓冲区有限，等待确认后重新发送 ZDATA
+          ZCRCW => loop {
+            let header = self.read_header().await?;
+            match header.frame_type {
+              ZACK => {
+                buf = Header::with_position(ZDATA, position).encode_binary(self.send_crc32);
+                unacked = 0;
+                break;
+              }
+              ZRPOS => {
+                position = header.position();
+                continue 'data;
+              }
+              ZSKIP => return Ok(()),
+              ZABORT | ZCAN | ZFERR | ZFIN => return Err(SSHError::TransferCancelled),
+              _ => {}
+            }
+          },
+          _ => {}
+        }
+      }
+
+      self
+        .write(&Header::with_position(ZEOF, position).encode_binary(self.send_crc32))
+        .await?;
+
+      loop {
+        let header = self.read_header().await?;
+        match header.frame_type {
+          ZRINIT | ZSKIP => return Ok(()),
+          ZRPOS => {
+            position = header.position();
+            continue 'data;
+          }
+          ZABORT | ZCAN | ZFERR | ZFIN => return Err(SSHError::TransferCancelled),
+          _ => {}
+        }
+      }
+    }
+  }
+
+  async fn receive_files(&mut self, dir: &Path) -> SSHResult<Vec<String>> {
+    let mut filenames = Vec::new();
+
+    loop {
+      let header = self.read_header().await?;
+      match header.frame_type {
+        ZRQINIT | ZEOF => self.send_zrinit().await?,
+        ZSINIT => {
+          self.read_subpacket().await?;
+          self
+            .write(&Header::with_position(ZACK, 0).encode_hex())
+            .await?;
+        }
+        ZFILE => {
+          let Some((info, _)) = self.read_subpacket().await? else {
+            self.write(&Header::new(ZNAK, [0; 4]).encode_hex()).await?;
+            continue;
+          };
+          let (name, size) = parse_file_info(&info)?;
+          let (filename, file) = create_unique_file(dir, &name).await?;
+          log::info!("zmodem receive {} as {:?} {} bytes", name, filename, size);
+
+          self.total = self.progress + size;
+          self.receive_file(file).await?;
+          filenames.push(filename.to_string_lossy().to_string());
+          self.send_zrinit().await?;
+        }
+        ZFIN => {
+          self.write(&Header::new(ZFIN, [0; 4]).encode_hex()).await?;
+          self.read_over_and_out().await;
+          return Ok(filenames);
+        }
+        ZCOMMAND => {
+          return Err(SSHError::ZmodemError(
+            "Remote command is not supported".into(),
+          ));
+        }
+        ZABORT | ZCAN | ZFERR => return Err(SSHError::TransferCancelled),
+        _ => {}
+      }
+    }
+  }
+
+  async fn receive_file(&mut self, file: fs::File) -> SSHResult<()> {
+    let mut writer = BufWriter::new(file);
+    let mut position = 0;
+    self
+      .write(&Header::with_position(ZRPOS, position).encode_hex())
+      .await?;
+
+    loop {
+      let header = self.read_header().await?;
+      match header.frame_type {
+        ZDATA if header.position() == position => loop {
+          let Some((data, end)) = self.read_subpacket().await? else {
+            // crc 错误时从当前位置重新请求
+            self
+              .write(&Header::with_position(ZRPOS, position).encode_hex())
+              .await?;
+            break;
+          };
+          writer.write_all(&data).await?;
+          position += data.len() as u64;
+          self.report_progress(data.len() as u64)?;
+
+          match end {
+            ZCRCG => {}
+            ZCRCQ => {
+              self
+                .write(&Header::with_position(ZACK, position).encode_hex())
+                .await?
+            }
+            ZCRCW => {
+              self
+                .write(&Header::with_position(ZACK, position).encode_hex())
+                .await?;
+              break;
+            }
+            _ => break,
+          }
+        },
+        ZDATA | ZNAK => {
+          self
+            .write(&Header::with_position(ZRPOS, position).encode_hex())
+            .await?
+        }
+        // 对端未收到 ZRPOS 时会重发 ZFILE
+        ZFILE => {
+          self.read_subpacket().await?;
+          self
+            .write(&Header::with_position(ZRPOS, position).encode_hex())
+            .await?;
+        }
+        ZEOF if header.position() == position => {
+          writer.flush().await?;
+          return Ok(());
+        }
+        ZFIN | ZABORT | ZCAN | ZFERR => return Err(SSHError::TransferCancelled),
+        _ => {}
+      }
+    }
+  }
+
+  async fn send_zrinit(&mut self) -> SSHResult<()> {
+    let header = Header::new(ZRINIT, [0, 0, 0, CANFDX | CANOVIO | CANFC32]);
+    self.write(&header.encode_hex()).await
+  }
+
+  /// Reads the "OO" the sender ends the session with, anything else goes back to the terminal.
+  async fn read_over_and_out(&mut self) {
+    for _ in 0..2 {
+      match timeout(Duration::from_secs(1), self.read_byte()).await {
+        Ok(Ok(b'O')) => {}
+        Ok(Ok(byte)) => {
+          self.buffer.push_front(byte);
+          return;
+        }
+        _ => return,
+      }
+    }
+  }
+
+  fn report_progress(&mut self, size: u64) -> SSHResult<()> {
+    self.progress += size;
+    if self.progress >= self.reported + PROGRESS_INTERVAL || self.progress >= self.total {
+      self.reported = self.progress;
+      self.on_progress.send(SFTPProgressPayload {
+        progress: self.progress,
+        total: self.total,
+      })?;
+    }
+
+    Ok(())
+  }
+
+  async fn check_control(&self) -> SSHResult<()> {
+    while self.control.pause.load(Ordering::Relaxed) && !self.control.cancel.load(Ordering::Relaxed)
+    {
+      sleep(POLL_INTERVAL).await;
+    }
+
+    if self.control.cancel.load(Ordering::Relaxed) {
+      return Err(SSHError::TransferCancelled);
+    }
+
+    Ok(())
+  }
+
+  async fn write(&self, data: &[u8]) -> SSHResult<()> {
+    self.shell_channel.lock().await.data(data).await?;
+
+    Ok(())
+  }
+
+  async fn read_byte(&mut self) -> SSHResult<u8> {
+    let mut idle = Duration::ZERO;
+    loop {
+      if let Some(byte) = self.buffer.pop_front() {
+        return Ok(byte);
+      }
+
+      self.check_control().await?;
+      match timeout(POLL_INTERVAL, self.receiver.recv()).await {
+        Ok(Some(data)) => {
+          self.buffer.extend(data);
+          idle = Duration::ZERO;
+        }
+        Ok(None) => return Err(SSHError::NotFoundShell),
+        Err(_) => {
+          idle += POLL_INTERVAL;
+          if idle >= IDLE_TIMEOUT {
+            return Err(SSHError::ZmodemError("Remote stopped responding".into()));
+          }
+        }
+      }
+    }
+  }
+
+  /// Reads a byte of a binary header or subpacket, undoing the ZDLE escaping.
+  async fn read_escaped(&mut self) -> SSHResult<Escaped> {
+    loop {
+      match self.read_byte().await? {
+        XON | XOFF | 0x91 | 0x93 => continue,
+        ZDLE => break,
+        byte => return Ok(Escaped::Byte(byte)),
+      }
+    }
+
+    // 连续 5 个 CAN 表示对端取消传输
+    let mut cancels = 1;
+    loop {
+      match self.read_byte().await? {
+        ZDLE => {
+          cancels += 1;
+          if cancels >= 5 {
+            return Err(SSHError::TransferCancelled);
+          }
+        }
+        XON | XOFF | 0x91 | 0x93 => continue,
+        byte @ (ZCRCE | ZCRCG | ZCRCQ | ZCRCW) => return Ok(Escaped::FrameEnd(byte)),
+        ZRUB0 => return Ok(Escaped::Byte(0x7f)),
+        ZRUB1 => return Ok(Escaped::Byte(0xff)),
+        byte if byte & 0x60 == 0x40 => return Ok(Escaped::Byte(byte ^ 0x40)),
+        byte => {
+          return Err(SSHError::ZmodemError(format!(
+            "Invalid escape 0x{:02x}",
+            byte
+          )));
+        }
+      }
+    }
+  }
+
+  async fn read_escaped_bytes<const N: usize>(&mut self) -> SSHResult<Option<[u8; N]>> {
+    let mut bytes = [0; N];
+    for byte in bytes.iter_mut() {
+      match self.read_escaped().await? {
+        Escaped::Byte(value) => *byte = value,
+        Escaped::FrameEnd(_) => return Ok(None),
+      }
+    }
+
+    Ok(Some(bytes))
+  }
+
+  /// Skips everything until a header with a valid crc.
+  async fn read_header(&mut self) -> SSHResult<Header> {
+    loop {
+      if self.read_byte().await? != ZPAD {
+        continue;
+      }
+      let mut byte = self.read_byte().await?;
+      while byte == ZPAD {
+        byte = self.read_byte().await?;
+      }
+      if byte != ZDLE {
+        continue;
+      }
+
+      let header = match self.read_byte().await? {
+        ZHEX => self.read_hex_header().await?,
+        ZBIN => self.read_binary_header(false).await?,
+        ZBIN32 => self.read_binary_header(true).await?,
+        _ => None,
+      };
+      if let Some(header) = header {
+        return Ok(header);
+      }
+    }
+  }
+
+  async fn read_hex_header(&mut self) -> SSHResult<Option<Header>> {
+    let mut bytes = [0; 7];
+    for byte in bytes.iter_mut() {
+      let mut value = 0;
+      for _ in 0..2 {
+        let Some(digit) = ((self.read_byte().await? & 0x7f) as char).to_digit(16) else {
+          return Ok(None);
+        };
+        value = (value << 4) | digit as u8;
+      }
+      *byte = value;
+    }
+
+    if crc16_update(0, &bytes) != 0 {
+      log::warn!("zmodem hex header crc error");
+      return Ok(None);
+    }
+
+    // 丢弃已收到的行尾，避免结束后输出到终端
+    while let Some(&byte) = self.buffer.front()
+      && matches!(byte, b'\r' | b'\n' | 0x8a | 0x8d | XON)
+    {
+      self.buffer.pop_front();
+    }
+
+    self.receive_crc32 = false;
+    Ok(Some(Header::new(
+      bytes[0],
+      [bytes[1], bytes[2], bytes[3], bytes[4]],
+    )))
+  }
+
+  async fn read_binary_header(&mut self, crc32: bool) -> SSHResult<Option<Header>> {
+    let Some(bytes) = self.read_escaped_bytes::<5>().await? else {
+      return Ok(None);
+    };
+
+    let valid = if crc32 {
+      self
+        .read_escaped_bytes::<4>()
+        .await?
+        .is_some_and(|crc| !crc32_update(!0, &bytes) == u32::from_le_bytes(crc))
+    } else {
+      self
+        .read_escaped_bytes::<2>()
+        .await?
+        .is_some_and(|crc| crc16_update(0, &bytes) == u16::from_be_bytes(crc))
+    };
+    if !valid {
+      log::warn!("zmodem binary header crc error");
+      return Ok(None);
+    }
+
+    self.receive_crc32 = crc32;
+    Ok(Some(Header::new(
+      bytes[0],
+      [bytes[1], bytes[2], bytes[3], bytes[4]],
+    )))
+  }
+
+  /// Reads a data subpacket and its frame end, None when the crc doesn't match.
+  async fn read_subpacket(&mut self) -> SSHResult<Option<(Vec<u8>, u8)>> {
+    let mut data = Vec::with_capacity(SUBPACKET_SIZE);
+    let end = loop {
+      match self.read_escaped().await? {
+        Escaped::Byte(byte) => data.push(byte),
+        Escaped::FrameEnd(end) => break end,
+      }
+      if data.len() > MAX_SUBPACKET_SIZE {
+        log::warn!("zmodem subpacket too long");
+        return Ok(None);
+      }
+    };
+
+    let valid = if self.receive_crc32 {
+      self.read_escaped_bytes::<4>().await?.is_some_and(|crc| {
+        !crc32_update(crc32_update(!0, &data), &[end]) == u32::from_le_bytes(crc)
+      })
+    } else {
+      self
+        .read_escaped_bytes::<2>()
+        .await?
+        .is_some_and(|crc| crc16_update(crc16_update(0, &data), &[end]) == u16::from_be_bytes(crc))
+    };
+    if !valid {
+      log::warn!("zmodem subpacket crc error");
+      return Ok(None);
+    }
+
+    Ok(Some((data, end)))
+  }
+}
+
+/// Aborts the remote `rz` or `sz` and drops what it sent before exiting.
+pub async fn abort(
+  shell_channel: &AsyncMutex<ChannelWriteHalf<client::Msg>>,
+  receiver: &mut mpsc::Receiver<Vec<u8>>,
+) -> SSHResult<()> {
+  shell_channel.lock().await.data(ABORT_SEQUENCE).await?;
+  // 对端退出前已发出的数据不应出现在终端中
+  while let Ok(Some(_)) = timeout(Duration::from_millis(500), receiver.recv()).await {}
+
+  Ok(())
+}
+
+/// Parses the ZFILE subpacket, the name followed by the size and other optional fields.
+fn parse_file_info(info: &[u8]) -> SSHResult<(String, u64)> {
+  let mut parts = info.splitn(2, |byte| *byte == 0);
+  let name = String::from_utf8_lossy(parts.next().unwrap_or_default());
+  // 只保留文件名，不允许写到目标目录之外
+  let name = Path::new(name.as_ref())
+    .file_name()
+    .map(|name| name.to_string_lossy().to_string())
+    .filter(|name| name != "..")
+    .ok_or_else(|| SSHError::ZmodemError(format!("Invalid file name {:?}", name)))?;
+
+  let size = parts
+    .next()
+    .map(|fields| String::from_utf8_lossy(fields).to_string())
+    .and_then(|fields| {
+      fields
+        .trim_end_matches('\0')
+        .split(' ')
+        .next()
+        .and_then(|size| size.parse().ok())
+    })
+    .unwrap_or_default();
+
+  Ok((name, size))
+}
+
+/// Creates the file a received one is saved to, never replacing a local file.
+///
+/// When the name is taken a number is added before the extension, like `name (1).txt`.
+async fn create_unique_file(dir: &Path, name: &str) -> SSHResult<(PathBuf, fs::File)> {
+  let mut index = 0;
+  loop {
+    let filename = dir.join(numbered_name(name, index));
+    match fs::OpenOptions::new()
+      .write(true)
+      .create_new(true)
+      .open(&filename)
+      .await
+    {
+      Ok(file) => return Ok((filename, file)),
+      Err(err) if err.kind() == io::ErrorKind::AlreadyExists => index += 1,
+      Err(err) => return Err(err.into()),
+    }
+  }
+}
+
+fn numbered_name(name: &str, index: usize) -> String {
+  if index == 0 {
+    return name.to_string();
+  }
+
+  // 以点开头的隐藏文件没有扩展名
+  match name.rfind('.').filter(|pos| *pos > 0) {
+    Some(pos) => format!("{} ({}){}", &name[..pos], index, &name[pos..]),
+    None => format!("{} ({})", name, index),
+  }
+}
+
+fn encode_subpacket(data: &[u8], end: u8, crc32: bool) -> Vec<u8> {
+  let mut buf = Vec::with_capacity(data.len() + data.len() / 8 + 16);
+  escape_into(&mut buf, data);
+  buf.extend_from_slice(&[ZDLE, end]);
+  if crc32 {
+    let crc = !crc32_update(crc32_update(!0, data), &[end]);
+    escape_into(&mut buf, &crc.to_le_bytes());
+  } else {
+    let crc = crc16_update(crc16_update(0, data), &[end]);
+    escape_into(&mut buf, &crc.to_be_bytes());
+  }
+  if end == ZCRCW {
+    buf.push(XON);
+  }
+  buf
+}
+
+/// Escapes ZDLE, flow control characters and carriage returns, which ssh servers
+/// and terminals in between may interpret.
+fn escape_into(buf: &mut Vec<u8>, data: &[u8]) {
+  for &byte in data {
+    match byte {
+      ZDLE | 0x10 | 0x90 | XON | 0x91 | XOFF | 0x93 | b'\r' | 0x8d => {
+        buf.extend_from_slice(&[ZDLE, byte ^ 0x40]);
+      }
+      _ => buf.push(byte),
+    }
+  }
+}
+
+async fn file_crc32(file: &mut fs::File) -> SSHResult<u32> {
+  file.seek(SeekFrom::Start(0)).await?;
+  let mut crc = !0;
+  let mut buffer = vec![0; 64 * 1024];
+  loop {
+    let size = file.read(&mut buffer).await?;
+    if size == 0 {
+      break;
+    }
+    crc = crc32_update(crc, &buffer[..size]);
+  }
+
+  Ok(!crc)
+}
+
+/// CRC-16/XMODEM, used by hex headers and binary headers without CANFC32.
+fn crc16_update(mut crc: u16, data: &[u8]) -> u16 {
+  for byte in data {
+    crc ^= (*byte as u16) << 8;
+    for _ in 0..8 {
+      crc = if crc & 0x8000 != 0 {
+        (crc << 1) ^ 0x1021
+      } else {
+        crc << 1
+      };
+    }
+  }
+  crc
+}
+
+/// CRC-32 without the final inversion, so it can be updated in parts.
+fn crc32_update(mut crc: u32, data: &[u8]) -> u32 {
+  for byte in data {
+    crc ^= *byte as u32;
+    for _ in 0..8 {
+      crc = if crc & 1 != 0 {
+        (crc >> 1) ^ 0xedb88320
+      } else {
+        crc >> 1
+      };
+    }
+  }
+  crc
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// Undoes `escape_into`, the way `read_escaped` does for data bytes.
+  fn unescape(data: &[u8]) -> Vec<u8> {
+    let mut bytes = data.iter();
+    let mut unescaped = Vec::new();
+    while let Some(&byte) = bytes.next() {
+      if byte == ZDLE {
+        let &escaped = bytes.next().unwrap();
+        unescaped.push(escaped ^ 0x40);
+      } else {
+        unescaped.push(byte);
+      }
+    }
+    unescaped
+  }
+
+  #[test]
+  fn crc16_matches_xmodem_check_value() {
+    assert_eq!(crc16_update(0, b"123456789"), 0x31c3);
+  }
+
+  #[test]
+  fn crc32_matches_check_value() {
+    assert_eq!(!crc32_update(!0, b"123456789"), 0xcbf43926);
+    // 分段计算与一次计算结果相同
+    assert_eq!(
+      crc32_update(crc32_update(!0, b"1234"), b"56789"),
+      crc32_update(!0, b"123456789")
+    );
+  }
+
+  #[test]
+  fn hex_header_matches_lrzsz() {
+    // lrzsz 的 rz 启动时发送的 ZRINIT
+    assert_eq!(
+      Header::new(ZRINIT, [0, 0, 0, CANFDX | CANOVIO | CANFC32]).encode_hex(),
+      b"**\x18B0100000023be50\r\x8a\x11"
+    );
+  }
+
+  #[test]
+  fn escapes_control_characters() {
+    let mut buf = Vec::new();
+    escape_into(&mut buf, &[ZDLE, XON, XOFF, b'\r', 0x10, 0x8d, b'a', 0x7f]);
+    assert_eq!(
+      buf,
+      [
+        ZDLE, 0x58, ZDLE, 0x51, ZDLE, 0x53, ZDLE, 0x4d, ZDLE, 0x50, ZDLE, 0xcd, b'a', 0x7f
+      ]
+    );
+  }
+
+  #[test]
+  fn escaped_data_round_trips() {
+    let data = (0..=255).collect::<Vec<u8>>();
+    let mut buf = Vec::new();
+    escape_into(&mut buf, &data);
+    assert!(
+      !buf
+        .iter()
+        .any(|&byte| matches!(byte, XON | XOFF | 0x91 | 0x93 | b'\r' | 0x8d))
+    );
+    assert_eq!(unescape(&buf), data);
+  }
+
+  #[test]
+  fn subpacket_ends_with_crc() {
+    let data = b"hello\r\x18world";
+
+    let buf = encode_subpacket(data, ZCRCG, false);
+    let end = buf
+      .windows(2)
+      .position(|window| window == [ZDLE, ZCRCG])
+      .unwrap();
+    assert_eq!(unescape(&buf[..end]), data);
+    let crc = unescape(&buf[end + 2..]);
+    assert_eq!(
+      u16::from_be_bytes([crc[0], crc[1]]),
+      crc16_update(crc16_update(0, data), &[ZCRCG])
+    );
+
+    let buf = encode_subpacket(data, ZCRCW, true);
+    assert_eq!(buf.last(), Some(&XON));
+    let end = buf
+      .windows(2)
+      .position(|window| window == [ZDLE, ZCRCW])
+      .unwrap();
+    let crc = unescape(&buf[end + 2..buf.len() - 1]);
+    assert_eq!(
+      u32::from_le_bytes([crc[0], crc[1], crc[2], crc[3]]),
+      !crc32_update(crc32_update(!0, data), &[ZCRCW])
+    );
+  }
+
+  #[test]
+  fn detects_transfer_direction() {
+    assert_eq!(
+      detect(b"$ sz file\r\n**\x18B00000000000000\r\x8a\x11"),
+      Some((11, ZmodemDirection::Download))
+    );
+    assert_eq!(
+      detect(b"rz waiting\r\n**\x18B0100000023be50\r\x8a\x11"),
+      Some((12, ZmodemDirection::Upload))
+    );
+    assert_eq!(detect(b"plain output"), None);
+  }
+
+  #[test]
+  fn file_info_keeps_only_the_name() {
+    assert_eq!(
+      parse_file_info(b"../../etc/passwd\x001234 14567 0 0 1 1234\x00").unwrap(),
+      ("passwd".to_string(), 1234)
+    );
+    assert_eq!(
+      parse_file_info(b"a.txt\x00").unwrap(),
+      ("a.txt".to_string(), 0)
+    );
+    assert!(parse_file_info(b"..\x00").is_err());
+  }
+
+  #[test]
+  fn numbers_taken_names() {
+    assert_eq!(numbered_name("a.txt", 0), "a.txt");
+    assert_eq!(numbered_name("a.txt", 2), "a (2).txt");
+    assert_eq!(numbered_name("archive.tar.gz", 1), "archive.tar (1).gz");
+    assert_eq!(numbered_name(".bashrc", 1), ".bashrc (1)");
+    assert_eq!(numbered_name("README", 1), "README (1)");
+  }
+}
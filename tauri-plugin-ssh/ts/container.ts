@@ -0,0 +1,118 @@
+import { Channel, invoke } from "@tauri-apps/api/core";
+import { v4 as uuidV4 } from "uuid";
+
+import type { SSHSession } from "./session";
+
+export type SSHContainerRuntime = "Docker" | "Podman";
+
+export type SSHContainer = {
+  id: string;
+  name: string;
+  image: string;
+  /**
+   * running、exited 等
+   */
+  state: string;
+  /**
+   * 如 Up 2 hours
+   */
+  status: string;
+  ports: string;
+  running: boolean;
+};
+
+export type SSHContainerList = {
+  runtime: SSHContainerRuntime;
+  containers: SSHContainer[];
+};
+
+export type SSHContainerAction = "Start" | "Stop" | "Restart";
+
+export type SSHContainerLogsOpenOpts = {
+  /**
+   * 先输出的行数，未设置时输出全部日志
+   */
+  tail?: number;
+  follow?: boolean;
+  timestamps?: boolean;
+};
+
+export type SSHContainerLogsIpcChannelEvent =
+  | ArrayBuffer
+  | { type: "Close"; exitStatus: number | null };
+
+/**
+ * 列出主机上的容器，未指定 runtime 时依次检测 docker、podman，
+ * 返回的 runtime 用于后续调用
+ */
+export function containerList(
+  session: SSHSession,
+  runtime?: SSHContainerRuntime,
+) {
+  return invoke<SSHContainerList>("plugin:ssh|container_list", {
+    sshSessionId: session.sshSessionId,
+    runtime,
+  });
+}
+
+export function containerAction(
+  session: SSHSession,
+  runtime: SSHContainerRuntime,
+  containerId: string,
+  action: SSHContainerAction,
+) {
+  return invoke<void>("plugin:ssh|container_action", {
+    sshSessionId: session.sshSessionId,
+    runtime,
+    containerId,
+    action,
+  });
+}
+
+export type SSHContainerLogsOpts = {
+  session: SSHSession;
+  runtime: SSHContainerRuntime;
+  containerId: string;
+  onData?: (data: Uint8Array) => unknown;
+  /**
+   * 日志命令退出或调用 close 后调用
+   */
+  onClose?: (exitStatus: number | null) => unknown;
+};
+
+/**
+ * 通过独立的 exec 通道读取容器日志，stdout 与 stderr 合并输出
+ */
+export class SSHContainerLogs {
+  sshContainerLogsId: string;
+
+  private opts: SSHContainerLogsOpts;
+
+  constructor(opts: SSHContainerLogsOpts) {
+    this.sshContainerLogsId = uuidV4();
+    this.opts = opts;
+  }
+
+  open(opts: SSHContainerLogsOpenOpts = {}): Promise<string> {
+    return invoke<string>("plugin:ssh|container_logs_open", {
+      sshSessionId: this.opts.session.sshSessionId,
+      sshContainerLogsId: this.sshContainerLogsId,
+      runtime: this.opts.runtime,
+      containerId: this.opts.containerId,
+      opts,
+      ipcChannel: new Channel<SSHContainerLogsIpcChannelEvent>((data) => {
+        if (data instanceof ArrayBuffer) {
+          this.opts.onData?.(new Uint8Array(data));
+        } else if (data.type === "Close") {
+          this.opts.onClose?.(data.exitStatus);
+        }
+      }),
+    });
+  }
+
+  close(): Promise<string> {
+    return invoke<string>("plugin:ssh|container_logs_close", {
+      sshContainerLogsId: this.sshContainerLogsId,
+    });
+  }
+}
@@ -1,4 +1,7 @@
+export * from "./container";
 export * from "./portForwarding";
 export * from "./session";
 export * from "./sftp";
 export * from "./shell";
+export * from "./tmux";
+export * from "./transferQueue";
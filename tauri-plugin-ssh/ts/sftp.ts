@@ -14,10 +14,33 @@ export type SSHSftpOnProgressOpts = {
   progress: number;
 };
 
+export type SSHSftpVerifyOpts = {
+  /**
+   * 校验值不一致时从头重新传输的次数，默认 0
+   */
+  retries?: number;
+};
+
 export type SSHSftpUploadFileOpts = {
   localFilename: string;
   remoteFilename: string;
   taskId?: string;
+  /**
+   * 从已传输的部分继续，末尾内容不一致时重新传输
+   */
+  resume?: boolean;
+  /**
+   * 同时进行的分块请求数，默认 16，高延迟链路可以调大
+   */
+  concurrency?: number;
+  /**
+   * 传输后比对本地与远端的 SHA-256，不一致时失败并返回 ChecksumMismatch
+   */
+  verify?: SSHSftpVerifyOpts;
+  /**
+   * 上传前检查远端可用空间，不足时失败并返回 InsufficientSpace
+   */
+  checkSpace?: boolean;
   onProgress?: (opts: SSHSftpOnProgressOpts) => unknown;
 };
 
@@ -25,14 +48,174 @@ export type SSHSftpDownloadFileOpts = {
   localFilename: string;
   remoteFilename: string;
   taskId?: string;
+  /**
+   * 从已传输的部分继续，末尾内容不一致时重新传输
+   */
+  resume?: boolean;
+  /**
+   * 同时进行的分块请求数，默认 16，高延迟链路可以调大
+   */
+  concurrency?: number;
+  /**
+   * 传输后比对本地与远端的 SHA-256，不一致时失败并返回 ChecksumMismatch
+   */
+  verify?: SSHSftpVerifyOpts;
   onProgress?: (opts: SSHSftpOnProgressOpts) => unknown;
 };
 
+export type SSHSftpDirTransferOpts = {
+  /**
+   * .gitignore 语法的排除规则，如 node_modules/、*.log
+   */
+  excludes?: string[];
+  /**
+   * 同时应用源目录中的 .gitignore
+   */
+  gitignore?: boolean;
+  /**
+   * 续传未完成的文件
+   */
+  resume?: boolean;
+  /**
+   * 每个文件同时进行的分块请求数
+   */
+  concurrency?: number;
+  /**
+   * 每个文件传输后比对 SHA-256
+   */
+  verify?: SSHSftpVerifyOpts;
+  /**
+   * 上传前检查远端可用空间，仅上传时生效
+   */
+  checkSpace?: boolean;
+};
+
+export type SSHSftpOnDirProgressOpts = {
+  files: number;
+  totalFiles: number;
+  total: number;
+  progress: number;
+  /**
+   * 正在传输的文件，相对于目录
+   */
+  current: string;
+};
+
+export type SSHSftpUploadDirOpts = SSHSftpDirTransferOpts & {
+  localDirname: string;
+  remoteDirname: string;
+  taskId?: string;
+  onProgress?: (opts: SSHSftpOnDirProgressOpts) => unknown;
+};
+
+export type SSHSftpDownloadDirOpts = SSHSftpDirTransferOpts & {
+  localDirname: string;
+  remoteDirname: string;
+  taskId?: string;
+  onProgress?: (opts: SSHSftpOnDirProgressOpts) => unknown;
+};
+
+export type SSHSftpRemoteTransferOpts = {
+  sourcePath: string;
+  /**
+   * 目标 sftp 通道，可以属于另一台主机
+   */
+  targetSshSftpId: string;
+  targetPath: string;
+  /**
+   * 传输目录时排除的路径，语法同 .gitignore
+   */
+  excludes?: string[];
+  gitignore?: boolean;
+  concurrency?: number;
+  taskId?: string;
+  onProgress?: (opts: SSHSftpOnDirProgressOpts) => unknown;
+};
+
+export type SSHSftpRemoveAllOpts = {
+  path: string;
+  taskId?: string;
+  /**
+   * files 与 totalFiles 为已删除与全部条目数
+   */
+  onProgress?: (opts: SSHSftpOnDirProgressOpts) => unknown;
+};
+
+export type SSHSftpCopyOpts = {
+  sourcePath: string;
+  /**
+   * 目标路径，已存在时报错
+   */
+  targetPath: string;
+  taskId?: string;
+  onProgress?: (opts: SSHSftpOnDirProgressOpts) => unknown;
+};
+
+export type SSHSftpStatvfs = {
+  totalBytes: number;
+  freeBytes: number;
+  /**
+   * 非 root 用户可用的字节数，不含保留块
+   */
+  availableBytes: number;
+  totalInodes: number;
+  freeInodes: number;
+  availableInodes: number;
+};
+
+export type SSHSftpFailure = {
+  path: string;
+  error: string;
+};
+
 export type SSHSftpRenameOpts = {
   oldPath: string;
   newPath: string;
 };
 
+export type SSHSftpChmodOpts = {
+  paths: string[];
+  /**
+   * 八进制如 755 或符号形式如 u+x,go-w
+   */
+  mode: string;
+  recursive?: boolean;
+};
+
+export type SSHSftpChownOpts = {
+  paths: string[];
+  /**
+   * 用户名或 uid，不填保持不变
+   */
+  owner?: string;
+  /**
+   * 组名或 gid，不填保持不变
+   */
+  group?: string;
+  recursive?: boolean;
+};
+
+export type SSHSftpSetTimesOpts = {
+  paths: string[];
+  /**
+   * 访问时间，单位秒，不填保持不变
+   */
+  atime?: number;
+  /**
+   * 修改时间，单位秒，不填保持不变
+   */
+  mtime?: number;
+  recursive?: boolean;
+};
+
+export type SSHSftpSetAttrResult = {
+  path: string;
+  /**
+   * 修改失败的原因，成功时为 null
+   */
+  error: string | null;
+};
+
 export enum SSHSftpFileType {
   Dir = "Dir",
   File = "File",
@@ -46,12 +229,35 @@ export type SSHSftpFile = {
   fileType: SSHSftpFileType;
   size: number;
   permissions: string;
+  /**
+   * 权限位，包含 setuid、setgid 与 sticky
+   */
+  mode: number;
   atime: number;
   mtime: number;
   uid?: number;
   user?: string;
   gid?: number;
   group?: string;
+  /**
+   * 符号链接中保存的目标路径
+   */
+  linkTarget?: string;
+  /**
+   * 符号链接指向的文件类型，链接失效时为空
+   */
+  targetType?: SSHSftpFileType;
+};
+
+export type SSHSftpLinkOpts = {
+  /**
+   * 链接指向的路径
+   */
+  target: string;
+  /**
+   * 新建链接的路径
+   */
+  linkPath: string;
 };
 
 export type SSHSftpIpcChannelEvent = {
@@ -100,6 +306,10 @@ export class SSHSftp {
     localFilename,
     remoteFilename,
     taskId,
+    resume,
+    concurrency,
+    verify,
+    checkSpace,
     onProgress,
   }: SSHSftpUploadFileOpts): Promise<string> {
     const progressChannel = new Channel<SSHSftpOnProgressOpts>();
@@ -113,6 +323,10 @@ export class SSHSftp {
       remoteFilename,
       onProgress: progressChannel,
       taskId: taskId ?? null,
+      resume: resume ?? false,
+      concurrency: concurrency ?? null,
+      verify: verify ?? null,
+      checkSpace: checkSpace ?? null,
     });
   }
 
@@ -120,6 +334,9 @@ export class SSHSftp {
     localFilename,
     remoteFilename,
     taskId,
+    resume,
+    concurrency,
+    verify,
     onProgress,
   }: SSHSftpDownloadFileOpts): Promise<string> {
     const progressChannel = new Channel<SSHSftpOnProgressOpts>();
@@ -133,6 +350,91 @@ export class SSHSftp {
       remoteFilename,
       onProgress: progressChannel,
       taskId: taskId ?? null,
+      resume: resume ?? false,
+      concurrency: concurrency ?? null,
+      verify: verify ?? null,
+    });
+  }
+
+  sftpUploadDir({
+    localDirname,
+    remoteDirname,
+    excludes,
+    gitignore,
+    resume,
+    concurrency,
+    verify,
+    checkSpace,
+    taskId,
+    onProgress,
+  }: SSHSftpUploadDirOpts): Promise<string> {
+    const progressChannel = new Channel<SSHSftpOnDirProgressOpts>();
+    progressChannel.onmessage = (data) => {
+      onProgress?.(data);
+    };
+
+    return invoke<string>("plugin:ssh|sftp_upload_dir", {
+      sshSftpId: this.sshSftpId,
+      localDirname,
+      remoteDirname,
+      opts: { excludes, gitignore, resume, concurrency, verify, checkSpace },
+      onProgress: progressChannel,
+      taskId: taskId ?? null,
+    });
+  }
+
+  sftpDownloadDir({
+    localDirname,
+    remoteDirname,
+    excludes,
+    gitignore,
+    resume,
+    concurrency,
+    verify,
+    taskId,
+    onProgress,
+  }: SSHSftpDownloadDirOpts): Promise<string> {
+    const progressChannel = new Channel<SSHSftpOnDirProgressOpts>();
+    progressChannel.onmessage = (data) => {
+      onProgress?.(data);
+    };
+
+    return invoke<string>("plugin:ssh|sftp_download_dir", {
+      sshSftpId: this.sshSftpId,
+      localDirname,
+      remoteDirname,
+      opts: { excludes, gitignore, resume, concurrency, verify },
+      onProgress: progressChannel,
+      taskId: taskId ?? null,
+    });
+  }
+
+  /**
+   * 从当前通道直接传输到另一个通道，不经过本地文件，支持目录
+   */
+  sftpRemoteTransfer({
+    sourcePath,
+    targetSshSftpId,
+    targetPath,
+    excludes,
+    gitignore,
+    concurrency,
+    taskId,
+    onProgress,
+  }: SSHSftpRemoteTransferOpts): Promise<string> {
+    const progressChannel = new Channel<SSHSftpOnDirProgressOpts>();
+    progressChannel.onmessage = (data) => {
+      onProgress?.(data);
+    };
+
+    return invoke<string>("plugin:ssh|sftp_remote_transfer", {
+      sourceSshSftpId: this.sshSftpId,
+      sourcePath,
+      targetSshSftpId,
+      targetPath,
+      opts: { excludes, gitignore, concurrency },
+      onProgress: progressChannel,
+      taskId: taskId ?? null,
     });
   }
 
@@ -157,6 +459,59 @@ export class SSHSftp {
     });
   }
 
+  /**
+   * 递归删除，返回删除失败的条目
+   */
+  sftpRemoveAll({ path, taskId, onProgress }: SSHSftpRemoveAllOpts) {
+    const progressChannel = new Channel<SSHSftpOnDirProgressOpts>();
+    progressChannel.onmessage = (data) => {
+      onProgress?.(data);
+    };
+
+    return invoke<SSHSftpFailure[]>("plugin:ssh|sftp_remove_all", {
+      sshSftpId: this.sshSftpId,
+      path,
+      onProgress: progressChannel,
+      taskId: taskId ?? null,
+    });
+  }
+
+  /**
+   * 在服务器上复制文件或目录，返回复制失败的条目
+   */
+  sftpCopy({ sourcePath, targetPath, taskId, onProgress }: SSHSftpCopyOpts) {
+    const progressChannel = new Channel<SSHSftpOnDirProgressOpts>();
+    progressChannel.onmessage = (data) => {
+      onProgress?.(data);
+    };
+
+    return invoke<SSHSftpFailure[]>("plugin:ssh|sftp_copy", {
+      sshSftpId: this.sshSftpId,
+      sourcePath,
+      targetPath,
+      onProgress: progressChannel,
+      taskId: taskId ?? null,
+    });
+  }
+
+  /**
+   * 移动文件或目录，跨文件系统时复制后删除源路径，返回失败的条目
+   */
+  sftpMove({ sourcePath, targetPath, taskId, onProgress }: SSHSftpCopyOpts) {
+    const progressChannel = new Channel<SSHSftpOnDirProgressOpts>();
+    progressChannel.onmessage = (data) => {
+      onProgress?.(data);
+    };
+
+    return invoke<SSHSftpFailure[]>("plugin:ssh|sftp_move", {
+      sshSftpId: this.sshSftpId,
+      sourcePath,
+      targetPath,
+      onProgress: progressChannel,
+      taskId: taskId ?? null,
+    });
+  }
+
   sftpRemoveFile(filename: string) {
     return invoke<string>("plugin:ssh|sftp_remove_file", {
       sshSftpId: this.sshSftpId,
@@ -172,6 +527,91 @@ export class SSHSftp {
     });
   }
 
+  sftpChmod({ paths, mode, recursive }: SSHSftpChmodOpts) {
+    return invoke<SSHSftpSetAttrResult[]>("plugin:ssh|sftp_chmod", {
+      sshSftpId: this.sshSftpId,
+      paths,
+      mode,
+      recursive: recursive ?? null,
+    });
+  }
+
+  sftpChown({ paths, owner, group, recursive }: SSHSftpChownOpts) {
+    return invoke<SSHSftpSetAttrResult[]>("plugin:ssh|sftp_chown", {
+      sshSftpId: this.sshSftpId,
+      paths,
+      owner: owner ?? null,
+      group: group ?? null,
+      recursive: recursive ?? null,
+    });
+  }
+
+  sftpSetTimes({ paths, atime, mtime, recursive }: SSHSftpSetTimesOpts) {
+    return invoke<SSHSftpSetAttrResult[]>("plugin:ssh|sftp_set_times", {
+      sshSftpId: this.sshSftpId,
+      paths,
+      atime: atime ?? null,
+      mtime: mtime ?? null,
+      recursive: recursive ?? null,
+    });
+  }
+
+  /**
+   * 获取路径的详细信息，符号链接返回其指向的文件
+   */
+  sftpStat(path: string) {
+    return invoke<SSHSftpFile>("plugin:ssh|sftp_stat", {
+      sshSftpId: this.sshSftpId,
+      path,
+    });
+  }
+
+  /**
+   * 获取路径本身的详细信息，符号链接同时返回目标路径与类型
+   */
+  sftpLstat(path: string) {
+    return invoke<SSHSftpFile>("plugin:ssh|sftp_lstat", {
+      sshSftpId: this.sshSftpId,
+      path,
+    });
+  }
+
+  sftpReadLink(path: string) {
+    return invoke<string>("plugin:ssh|sftp_read_link", {
+      sshSftpId: this.sshSftpId,
+      path,
+    });
+  }
+
+  sftpSymlink({ target, linkPath }: SSHSftpLinkOpts) {
+    return invoke<string>("plugin:ssh|sftp_symlink", {
+      sshSftpId: this.sshSftpId,
+      target,
+      linkPath,
+    });
+  }
+
+  /**
+   * 需要服务器支持 hardlink@openssh.com 扩展
+   */
+  sftpHardlink({ target, linkPath }: SSHSftpLinkOpts) {
+    return invoke<string>("plugin:ssh|sftp_hardlink", {
+      sshSftpId: this.sshSftpId,
+      target,
+      linkPath,
+    });
+  }
+
+  /**
+   * 获取路径所在文件系统的空间，需要服务器支持 statvfs@openssh.com
+   */
+  sftpStatvfs(path: string) {
+    return invoke<SSHSftpStatvfs>("plugin:ssh|sftp_statvfs", {
+      sshSftpId: this.sshSftpId,
+      path,
+    });
+  }
+
   sftpExists(path: string) {
     return invoke<boolean>("plugin:ssh|sftp_exists", {
       sshSftpId: this.sshSftpId,
@@ -2,13 +2,46 @@ import { Channel, invoke } from "@tauri-apps/api/core";
 import { Buffer } from "buffer";
 import { v4 as uuidV4 } from "uuid";
 
+import type { SSHContainerRuntime } from "./container";
 import type { SSHSession } from "./session";
+import type { SSHSftpOnProgressOpts } from "./sftp";
+import type { SSHTmuxEvent } from "./tmux";
 
 export type SSHShellOpts = {
   session: SSHSession;
+  /**
+   * 重新连接已存在的 shell 时传入其 id，随后调用 attach
+   */
+  sshShellId?: string;
+  /**
+   * 返回 Promise 时，在 Promise 完成后才确认数据已处理，用于后端流控
+   */
   onData?: (data: Uint8Array) => unknown;
   onEof?: () => unknown;
   onClose?: () => unknown;
+  /**
+   * 发送文本的触发器由后端直接处理，其余动作通过该回调交给界面处理
+   */
+  onTrigger?: (event: SSHShellTriggerEvent) => unknown;
+  /**
+   * 检测到 rz/sz 时调用，此后的输出暂停，直到调用 zmodemUpload、
+   * zmodemDownload 或 zmodemCancel
+   */
+  onZmodem?: (direction: SSHShellZmodemDirection) => unknown;
+  /**
+   * 远程运行 tmux -CC 时调用，此后的输出按窗口和面板分发，
+   * 通过 SSHTmuxPane 打开面板
+   */
+  onTmux?: (event: SSHTmuxEvent) => unknown;
+  /**
+   * shell 通过 OSC 133/633 标记命令边界、通过 OSC 7 报告工作目录时调用，
+   * 需要在 shell 中开启 shell integration
+   */
+  onShellIntegration?: (event: SSHShellIntegrationEvent) => unknown;
+  /**
+   * 打开 shell 时设置了 urlForwarding，检测到地址、转发完成或失败时调用
+   */
+  onUrlForwarding?: (event: SSHShellUrlForwardingEvent) => unknown;
 };
 
 export type SSHShellSize = {
@@ -18,15 +51,191 @@ export type SSHShellSize = {
   height: number;
 };
 
+export type SSHShellStartupScript = {
+  /**
+   * 按行发送的脚本，`#delay <ms>` 行表示暂停指定毫秒
+   */
+  script: string;
+  /**
+   * 匹配提示符的正则，匹配成功后发送下一行
+   */
+  promptPattern?: string;
+  /**
+   * 没有输出多少毫秒后认为 shell 已就绪
+   */
+  quiescence?: number;
+  /**
+   * 每行发送后等待的毫秒数
+   */
+  lineDelay?: number;
+};
+
+export type SSHShellTriggerAction =
+  | { type: "SendText"; text: string }
+  | { type: "Notify"; message?: string }
+  | { type: "Highlight"; color: string }
+  | { type: "PortForwarding"; portForwardingId: string };
+
+export type SSHShellTrigger = {
+  id: string;
+  /**
+   * 匹配终端渲染后的输出行的正则，自动换行的行会拼接为一行
+   */
+  pattern: string;
+  action: SSHShellTriggerAction;
+};
+
+export type SSHShellScreenSnapshot = {
+  rows: number;
+  cols: number;
+  cursorRow: number;
+  cursorCol: number;
+  alternateScreen: boolean;
+  lines: string[];
+};
+
+export type SSHShellScreenSearchOpts = {
+  query: string;
+  regex?: boolean;
+  caseSensitive?: boolean;
+};
+
+export type SSHShellScreenSearchMatch = {
+  /**
+   * 行首所在的行号，从最早的回滚行开始计数
+   */
+  row: number;
+  line: string;
+  /**
+   * 以字符计的匹配范围
+   */
+  start: number;
+  end: number;
+};
+
+export type SSHShellScreenExportFormat = "Text" | "Html";
+
+export type SSHShellTriggerEvent = {
+  ruleId: string;
+  action: SSHShellTriggerAction;
+  line: string;
+  matched: string;
+};
+
+export type SSHShellIntegrationEvent =
+  | { type: "CommandStarted"; command: string | null; cwd: string | null }
+  | {
+      type: "CommandFinished";
+      command: string | null;
+      cwd: string | null;
+      exitCode: number | null;
+      /**
+       * 命令执行的毫秒数
+       */
+      duration: number;
+    }
+  | { type: "CwdChanged"; cwd: string; host: string | null };
+
+/**
+ * Prompt 时上报检测到的地址，由用户确认后转发；Auto 时立即转发
+ */
+export type SSHShellUrlForwardingMode = "Prompt" | "Auto";
+
+/**
+ * 远程程序输出的本机地址，如 http://localhost:5173/
+ */
+export type SSHShellLocalUrl = {
+  url: string;
+  scheme: string;
+  host: string;
+  port: number;
+  path: string;
+};
+
+export type SSHShellForwardedUrl = {
+  sshPortForwardingId: string;
+  url: string;
+  /**
+   * 通过本地转发访问的地址，本地端口被占用时使用空闲端口
+   */
+  localUrl: string;
+  localPort: number;
+  remotePort: number;
+};
+
+export type SSHShellUrlForwardingEvent =
+  | ({ type: "Detected" } & SSHShellLocalUrl)
+  | ({ type: "Forwarded" } & SSHShellForwardedUrl)
+  | { type: "Failed"; url: string; message: string };
+
+/**
+ * Upload 表示远程运行了 rz 等待本地文件，Download 表示远程运行了 sz
+ */
+export type SSHShellZmodemDirection = "Upload" | "Download";
+
+export type SSHShellZmodemOpts = {
+  taskId?: string;
+  onProgress?: (opts: SSHSftpOnProgressOpts) => unknown;
+};
+
+export type SSHShellRequest =
+  | { type: "Shell" }
+  | { type: "Exec"; command: string }
+  | { type: "Subsystem"; name: string }
+  | {
+      type: "Container";
+      runtime: SSHContainerRuntime;
+      containerId: string;
+      /**
+       * 未设置时优先使用 bash，没有 bash 时使用 sh
+       */
+      shell?: string;
+    };
+
 export type SSHShellOpenOpts = {
   term?: string;
   envs?: Record<string, string>;
   size: SSHShellSize;
+  startupScript?: SSHShellStartupScript;
+  triggers?: SSHShellTrigger[];
+  /**
+   * 默认打开登录 shell，也可以通过 exec 运行指定命令或打开子系统
+   */
+  request?: SSHShellRequest;
+  /**
+   * 终端使用的旧编码，如 GBK、Big5、Shift_JIS，由后端与 UTF-8 互相转换
+   */
+  encoding?: string;
+  /**
+   * 检测输出中的 localhost 地址并转发到本地，未设置时不检测
+   */
+  urlForwarding?: SSHShellUrlForwardingMode;
 };
 
-export type SSHShellIpcChannelEventJson = {
-  type: "Eof" | "Close";
-};
+export type SSHShellIpcChannelEventJson =
+  | {
+      type: "Eof" | "Close";
+    }
+  | {
+      type: "Trigger";
+      event: SSHShellTriggerEvent;
+    }
+  | {
+      type: "Zmodem";
+      direction: SSHShellZmodemDirection;
+    }
+  | {
+      type: "Tmux";
+      event: SSHTmuxEvent;
+    }
+  | {
+      type: "ShellIntegration";
+      event: SSHShellIntegrationEvent;
+    }
+  | {
+      type: "UrlForwarding";
+      event: SSHShellUrlForwardingEvent;
+    };
 
 export type SSHShellIpcChannelEvent = ArrayBuffer | SSHShellIpcChannelEventJson;
 
@@ -37,30 +246,52 @@ export class SSHShell {
   private opts: SSHShellOpts;
 
   constructor(opts: SSHShellOpts) {
-    this.sshShellId = uuidV4();
+    this.sshShellId = opts.sshShellId ?? uuidV4();
     this.session = opts.session;
     this.opts = opts;
   }
 
-  open({ term, envs, size }: SSHShellOpenOpts): Promise<string> {
+  open({
+    term,
+    envs,
+    size,
+    startupScript,
+    triggers,
+    request,
+    encoding,
+    urlForwarding,
+  }: SSHShellOpenOpts): Promise<string> {
     return invoke<string>("plugin:ssh|shell_open", {
       sshSessionId: this.session.sshSessionId,
       sshShellId: this.sshShellId,
       term,
       envs,
       size,
-      ipcChannel: new Channel<SSHShellIpcChannelEvent>((data) => {
-        if (data instanceof ArrayBuffer) {
-          this.opts.onData?.(new Uint8Array(data));
-          return;
-        }
-
-        if (data.type === "Eof") {
-          this.opts.onEof?.();
-        } else if (data.type === "Close") {
-          this.opts.onClose?.();
-        }
-      }),
+      startupScript,
+      triggers,
+      request,
+      encoding,
+      urlForwarding,
+      ipcChannel: this.createIpcChannel(),
+    });
+  }
+
+  /**
+   * 重新连接到后台运行的 shell，会先回放最近的输出
+   */
+  attach(): Promise<string> {
+    return invoke<string>("plugin:ssh|shell_attach", {
+      sshShellId: this.sshShellId,
+      ipcChannel: this.createIpcChannel(),
+    });
+  }
+
+  /**
+   * 断开与 shell 的连接，shell 在后台继续运行
+   */
+  detach(): Promise<string> {
+    return invoke<string>("plugin:ssh|shell_detach", {
+      sshShellId: this.sshShellId,
     });
   }
 
@@ -79,10 +310,168 @@ export class SSHShell {
     });
   }
 
+  /**
+   * 通过 signal 请求向远程进程发送信号，如 INT、TERM、KILL、HUP、USR1
+   */
+  signal(signal: string): Promise<string> {
+    return invoke<string>("plugin:ssh|shell_signal", {
+      sshShellId: this.sshShellId,
+      signal,
+    });
+  }
+
+  /**
+   * 通过 break 请求发送 BREAK，用于服务器后面的串口控制台，length 单位为毫秒
+   */
+  sendBreak(length?: number): Promise<string> {
+    return invoke<string>("plugin:ssh|shell_break", {
+      sshShellId: this.sshShellId,
+      length,
+    });
+  }
+
+  ack(size: number): Promise<string | undefined> {
+    return invoke<string>("plugin:ssh|shell_ack", {
+      sshShellId: this.sshShellId,
+      size,
+    }).catch(() => undefined);
+  }
+
+  setTriggers(triggers: SSHShellTrigger[]): Promise<string> {
+    return invoke<string>("plugin:ssh|shell_set_triggers", {
+      sshShellId: this.sshShellId,
+      triggers,
+    });
+  }
+
+  /**
+   * 将本地文件发送给远程的 rz，返回已发送的文件名
+   */
+  zmodemUpload(
+    localFilenames: string[],
+    { taskId, onProgress }: SSHShellZmodemOpts = {},
+  ): Promise<string[]> {
+    return invoke<string[]>("plugin:ssh|shell_zmodem_upload", {
+      sshShellId: this.sshShellId,
+      localFilenames,
+      onProgress: createProgressChannel(onProgress),
+      taskId: taskId ?? null,
+    });
+  }
+
+  /**
+   * 将远程 sz 发送的文件保存到本地目录，返回本地文件路径
+   */
+  zmodemDownload(
+    localDirname: string,
+    { taskId, onProgress }: SSHShellZmodemOpts = {},
+  ): Promise<string[]> {
+    return invoke<string[]>("plugin:ssh|shell_zmodem_download", {
+      sshShellId: this.sshShellId,
+      localDirname,
+      onProgress: createProgressChannel(onProgress),
+      taskId: taskId ?? null,
+    });
+  }
+
+  /**
+   * 拒绝本次传输并中止远程的 rz/sz
+   */
+  zmodemCancel(): Promise<string> {
+    return invoke<string>("plugin:ssh|shell_zmodem_cancel", {
+      sshShellId: this.sshShellId,
+    });
+  }
+
+  /**
+   * 后端渲染的当前屏幕，未连接 webview 时也可获取
+   */
+  screen(): Promise<SSHShellScreenSnapshot> {
+    return invoke<SSHShellScreenSnapshot>("plugin:ssh|shell_screen", {
+      sshShellId: this.sshShellId,
+    });
+  }
+
+  /**
+   * 在回滚区和屏幕中搜索，最多返回 1000 个匹配
+   */
+  searchScreen(
+    opts: SSHShellScreenSearchOpts,
+  ): Promise<SSHShellScreenSearchMatch[]> {
+    return invoke<SSHShellScreenSearchMatch[]>(
+      "plugin:ssh|shell_screen_search",
+      {
+        sshShellId: this.sshShellId,
+        opts,
+      },
+    );
+  }
+
+  /**
+   * 导出回滚区和屏幕的内容，Html 保留颜色
+   */
+  exportScreen(format: SSHShellScreenExportFormat): Promise<string> {
+    return invoke<string>("plugin:ssh|shell_screen_export", {
+      sshShellId: this.sshShellId,
+      format,
+    });
+  }
+
+  /**
+   * 转发检测到的地址，已有相同的转发时直接复用
+   */
+  forwardUrl(localUrl: SSHShellLocalUrl): Promise<SSHShellForwardedUrl> {
+    return invoke<SSHShellForwardedUrl>(
+      "plugin:ssh|port_forwarding_url_open",
+      {
+        sshSessionId: this.session.sshSessionId,
+        localUrl,
+      },
+    );
+  }
+
   resize(size: SSHShellSize): Promise<string> {
     return invoke<string>("plugin:ssh|shell_resize", {
       sshShellId: this.sshShellId,
       size,
     });
   }
+
+  private createIpcChannel() {
+    return new Channel<SSHShellIpcChannelEvent>((data) => {
+      if (data instanceof ArrayBuffer) {
+        const bytes = new Uint8Array(data);
+        Promise.resolve(this.opts.onData?.(bytes)).finally(() => {
+          this.ack(bytes.byteLength);
+        });
+        return;
+      }
+
+      if (data.type === "Eof") {
+        this.opts.onEof?.();
+      } else if (data.type === "Close") {
+        this.opts.onClose?.();
+      } else if (data.type === "Trigger") {
+        this.opts.onTrigger?.(data.event);
+      } else if (data.type === "Zmodem") {
+        this.opts.onZmodem?.(data.direction);
+      } else if (data.type === "Tmux") {
+        this.opts.onTmux?.(data.event);
+      } else if (data.type === "ShellIntegration") {
+        this.opts.onShellIntegration?.(data.event);
+      } else if (data.type === "UrlForwarding") {
+        this.opts.onUrlForwarding?.(data.event);
+      }
+    });
+  }
+}
+
+function createProgressChannel(
+  onProgress?: (opts: SSHSftpOnProgressOpts) => unknown,
+) {
+  const progressChannel = new Channel<SSHSftpOnProgressOpts>();
+  progressChannel.onmessage = (data) => {
+    onProgress?.(data);
+  };
+  return progressChannel;
 }
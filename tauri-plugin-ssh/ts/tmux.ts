@@ -0,0 +1,141 @@
+import { Channel, invoke } from "@tauri-apps/api/core";
+import { Buffer } from "buffer";
+
+import type { SSHShell, SSHShellSize } from "./shell";
+
+export type SSHTmuxPaneLayout = {
+  paneId: string;
+  width: number;
+  height: number;
+  x: number;
+  y: number;
+};
+
+export type SSHTmuxEvent =
+  | { type: "Start" }
+  | { type: "Exit"; reason?: string }
+  | { type: "WindowAdd"; windowId: string }
+  | { type: "WindowClose"; windowId: string }
+  | { type: "WindowRenamed"; windowId: string; name: string }
+  | { type: "WindowPaneChanged"; windowId: string; paneId: string }
+  | { type: "LayoutChange"; windowId: string; panes: SSHTmuxPaneLayout[] }
+  | { type: "SessionChanged"; sessionId: string; name: string };
+
+export type SSHTmuxPaneInfo = {
+  windowId: string;
+  windowName: string;
+  windowActive: boolean;
+  paneId: string;
+  paneActive: boolean;
+  width: number;
+  height: number;
+  x: number;
+  y: number;
+};
+
+export type SSHTmuxPaneOpts = {
+  shell: SSHShell;
+  paneId: string;
+  onData?: (data: Uint8Array) => unknown;
+  /**
+   * 面板关闭或 tmux 退出时调用
+   */
+  onClose?: () => unknown;
+};
+
+export type SSHTmuxIpcChannelEvent = ArrayBuffer | { type: "Close" };
+
+/**
+ * 执行 tmux 命令，返回输出的行
+ */
+export function tmuxCommand(shell: SSHShell, command: string) {
+  return invoke<string[]>("plugin:ssh|tmux_command", {
+    sshShellId: shell.sshShellId,
+    command,
+  });
+}
+
+export function tmuxListPanes(shell: SSHShell) {
+  return invoke<SSHTmuxPaneInfo[]>("plugin:ssh|tmux_list_panes", {
+    sshShellId: shell.sshShellId,
+  });
+}
+
+export function tmuxWindowResize(
+  shell: SSHShell,
+  windowId: string,
+  size: SSHShellSize,
+) {
+  return invoke<string>("plugin:ssh|tmux_window_resize", {
+    sshShellId: shell.sshShellId,
+    windowId,
+    size,
+  });
+}
+
+/**
+ * 脱离 tmux，会话在服务器上继续运行
+ */
+export function tmuxDetach(shell: SSHShell) {
+  return invoke<string>("plugin:ssh|tmux_detach", {
+    sshShellId: shell.sshShellId,
+  });
+}
+
+/**
+ * tmux 面板对应的虚拟终端，打开时先回放历史和当前屏幕
+ */
+export class SSHTmuxPane {
+  paneId: string;
+
+  private shell: SSHShell;
+  private opts: SSHTmuxPaneOpts;
+
+  constructor(opts: SSHTmuxPaneOpts) {
+    this.paneId = opts.paneId;
+    this.shell = opts.shell;
+    this.opts = opts;
+  }
+
+  open(): Promise<string> {
+    return invoke<string>("plugin:ssh|tmux_pane_open", {
+      sshShellId: this.shell.sshShellId,
+      paneId: this.paneId,
+      ipcChannel: new Channel<SSHTmuxIpcChannelEvent>((data) => {
+        if (data instanceof ArrayBuffer) {
+          this.opts.onData?.(new Uint8Array(data));
+        } else if (data.type === "Close") {
+          this.opts.onClose?.();
+        }
+      }),
+    });
+  }
+
+  /**
+   * 停止接收面板输出，面板在 tmux 中继续运行
+   */
+  close(): Promise<string> {
+    return invoke<string>("plugin:ssh|tmux_pane_close", {
+      sshShellId: this.shell.sshShellId,
+      paneId: this.paneId,
+    });
+  }
+
+  send(data: string | Uint8Array): Promise<string> {
+    return invoke<string>("plugin:ssh|tmux_pane_send", {
+      sshShellId: this.shell.sshShellId,
+      paneId: this.paneId,
+      data: Array.from(
+        typeof data === "string" ? Buffer.from(data, "utf8") : data,
+      ),
+    });
+  }
+
+  resize(size: SSHShellSize): Promise<string> {
+    return invoke<string>("plugin:ssh|tmux_pane_resize", {
+      sshShellId: this.shell.sshShellId,
+      paneId: this.paneId,
+      size,
+    });
+  }
+}
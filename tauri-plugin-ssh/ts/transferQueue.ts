@@ -0,0 +1,172 @@
+import { Channel, invoke } from "@tauri-apps/api/core";
+
+import type { SSHSession } from "./session";
+import type { SSHSftp, SSHSftpVerifyOpts } from "./sftp";
+
+export type SSHTransferKind = "Upload" | "Download";
+
+export type SSHTransferStatus =
+  | "Queued"
+  | "Running"
+  | "Paused"
+  | "Completed"
+  | "Failed"
+  | "Cancelled";
+
+export type SSHTransferQueueItem = {
+  id: string;
+  sshSessionId: string;
+  sshSftpId: string;
+  /**
+   * 传输历史记录到的主机
+   */
+  hostId: string | null;
+  kind: SSHTransferKind;
+  localFilename: string;
+  remoteFilename: string;
+  status: SSHTransferStatus;
+  progress: number;
+  total: number;
+  /**
+   * 每秒字节数，未运行时为 0
+   */
+  speed: number;
+  /**
+   * 按当前速度估算的剩余秒数
+   */
+  eta: number | null;
+  error: string | null;
+  attempts: number;
+  createdAt: number;
+  /**
+   * 最近一次开始的时间
+   */
+  startedAt: number | null;
+  finishedAt: number | null;
+};
+
+export type SSHTransferQueueEvent =
+  | { type: "Update"; item: SSHTransferQueueItem }
+  | { type: "Remove"; ids: string[] }
+  | { type: "Reorder"; ids: string[] };
+
+export type SSHTransferQueueEnqueueOpts = {
+  sftp: SSHSftp;
+  kind: SSHTransferKind;
+  localFilename: string;
+  remoteFilename: string;
+  taskId?: string;
+  /**
+   * 结束后在后端写入该主机的传输历史
+   */
+  hostId?: string;
+  /**
+   * 排在其他等待中的传输之前
+   */
+  priority?: boolean;
+  /**
+   * 从已传输的部分继续
+   */
+  resume?: boolean;
+  /**
+   * 传输后比对 SHA-256
+   */
+  verify?: SSHSftpVerifyOpts;
+};
+
+export function isTransferFinished(status: SSHTransferStatus) {
+  return (
+    status === "Completed" || status === "Failed" || status === "Cancelled"
+  );
+}
+
+/**
+ * 订阅队列事件并返回当前的所有传输，队列保存在后端，
+ * 窗口重新加载后再次订阅即可恢复
+ */
+export function transferQueueSubscribe(
+  onEvent: (event: SSHTransferQueueEvent) => unknown,
+) {
+  return invoke<SSHTransferQueueItem[]>("plugin:ssh|transfer_queue_subscribe", {
+    ipcChannel: new Channel<SSHTransferQueueEvent>(onEvent),
+  });
+}
+
+export function transferQueueList() {
+  return invoke<SSHTransferQueueItem[]>("plugin:ssh|transfer_queue_list");
+}
+
+export function transferQueueEnqueue({
+  sftp,
+  kind,
+  localFilename,
+  remoteFilename,
+  taskId,
+  hostId,
+  priority,
+  resume,
+  verify,
+}: SSHTransferQueueEnqueueOpts) {
+  return invoke<string>("plugin:ssh|transfer_queue_enqueue", {
+    sshSftpId: sftp.sshSftpId,
+    kind,
+    localFilename,
+    remoteFilename,
+    opts: { priority, resume, verify },
+    taskId: taskId ?? null,
+    hostId: hostId ?? null,
+  });
+}
+
+export function transferQueueCancel(taskId: string) {
+  return invoke<void>("plugin:ssh|transfer_queue_cancel", { taskId });
+}
+
+export function transferQueuePause(taskId: string) {
+  return invoke<void>("plugin:ssh|transfer_queue_pause", { taskId });
+}
+
+export function transferQueueResume(taskId: string) {
+  return invoke<void>("plugin:ssh|transfer_queue_resume", { taskId });
+}
+
+/**
+ * 重新排队失败或取消的传输，从已传输的部分继续
+ */
+export function transferQueueRetry(taskId: string) {
+  return invoke<void>("plugin:ssh|transfer_queue_retry", { taskId });
+}
+
+/**
+ * 移动到队列中的指定位置，0 表示下一个开始
+ */
+export function transferQueueMove(taskId: string, index: number) {
+  return invoke<void>("plugin:ssh|transfer_queue_move", { taskId, index });
+}
+
+/**
+ * 移除一项已结束的传输，进行中的需要先取消
+ */
+export function transferQueueRemove(taskId: string) {
+  return invoke<void>("plugin:ssh|transfer_queue_remove", { taskId });
+}
+
+/**
+ * 移除已完成、失败和取消的传输
+ */
+export function transferQueueClear() {
+  return invoke<string[]>("plugin:ssh|transfer_queue_clear");
+}
+
+/**
+ * 设置会话同时进行的传输数，默认 3
+ */
+export function transferQueueSetConcurrency(
+  session: SSHSession,
+  concurrency: number,
+) {
+  return invoke<void>("plugin:ssh|transfer_queue_set_concurrency", {
+    sshSessionId: session.sshSessionId,
+    concurrency,
+  });
+}
@@ -0,0 +1,240 @@
+use std::{borrow::Cow, sync::Mutex};
+
+use encoding_rs::{CoderResult, Decoder, Encoder, EncoderResult, Encoding, UTF_8};
+
+use crate::error::{TerminalError, TerminalResult};
+
+struct TerminalInput {
+  encoder: Encoder,
+  /// Incomplete UTF-8 sequence at the end of the last input
+  carry: Vec<u8>,
+}
+
+/// Converts the terminal data between UTF-8, used by the webview, and a legacy
+/// encoding such as GBK, Big5, Shift_JIS or Latin-1.
+pub struct TerminalEncoding {
+  encoding: &'static Encoding,
+  decoder: Mutex<Decoder>,
+  input: Mutex<TerminalInput>,
+}
+
+impl TerminalEncoding {
+  /// Looks up the encoding by its label, returns None for UTF-8 which needs no conversion.
+  pub fn new(label: &str) -> TerminalResult<Option<Self>> {
+    let encoding = Encoding::for_label(label.trim().as_bytes())
+      .ok_or_else(|| TerminalError::new(format!("Unknown encoding {}", label)))?;
+    if encoding == UTF_8 {
+      return Ok(None);
+    }
+    // 控制序列按 ASCII 解析，UTF-16、ISO-2022-JP 等编码无法用于终端
+    if !encoding.is_ascii_compatible() {
+      return Err(TerminalError::new(format!(
+        "Encoding {} is not supported by terminals",
+        encoding.name()
+      )));
+    }
+
+    Ok(Some(Self {
+      encoding,
+      decoder: Mutex::new(encoding.new_decoder_without_bom_handling()),
+      input: Mutex::new(TerminalInput {
+        encoder: encoding.new_encoder(),
+        carry: Vec::new(),
+      }),
+    }))
+  }
+
+  /// Locale asked from the server so programs write in this encoding.
+  pub fn locale(&self) -> Option<&'static str> {
+    match self.encoding.name() {
+      "GBK" => Some("zh_CN.GBK"),
+      "gb18030" => Some("zh_CN.GB18030"),
+      "Big5" => Some("zh_TW.BIG5"),
+      "Shift_JIS" => Some("ja_JP.SJIS"),
+      "EUC-JP" => Some("ja_JP.eucJP"),
+      "EUC-KR" => Some("ko_KR.eucKR"),
+      "windows-1252" => Some("en_US.ISO-8859-1"),
+      _ => None,
+    }
+  }
+
+  /// Decodes the output to UTF-8, a sequence split across packets is completed by the next one.
+  pub fn decode(&self, data: &[u8]) -> TerminalResult<Vec<u8>> {
+    let mut decoder = self.decoder.lock()?;
+    let mut output = String::new();
+    let mut data = data;
+    loop {
+      output.reserve(
+        decoder
+          .max_utf8_buffer_length(data.len())
+          .unwrap_or(data.len() * 3 + 16),
+      );
+      let (result, read, _) = decoder.decode_to_string(data, &mut output, false);
+      data = &data[read..];
+      if let CoderResult::InputEmpty = result {
+        break;
+      }
+    }
+
+    Ok(output.into_bytes())
+  }
+
+  /// Encodes the input from UTF-8, characters missing in the encoding are sent as `?`.
+  pub fn encode(&self, data: &[u8]) -> TerminalResult<Vec<u8>> {
+    let mut input = self.input.lock()?;
+    let input = &mut *input;
+
+    let mut data = [input.carry.as_slice(), data].concat();
+    // 末尾不完整的 UTF-8 序列留到下次输入
+    let complete = match std::str::from_utf8(&data) {
+      Err(err) if err.error_len().is_none() => err.valid_up_to(),
+      _ => data.len(),
+    };
+    input.carry = data.split_off(complete);
+
+    let text = String::from_utf8_lossy(&data);
+    let mut text = text.as_ref();
+    let mut output = Vec::new();
+    loop {
+      output.reserve(
+        input
+          .encoder
+          .max_buffer_length_from_utf8_without_replacement(text.len())
+          .unwrap_or(text.len() * 4 + 16),
+      );
+      let (result, read) =
+        input
+          .encoder
+          .encode_from_utf8_to_vec_without_replacement(text, &mut output, false);
+      text = &text[read..];
+      match result {
+        EncoderResult::InputEmpty => break,
+        EncoderResult::OutputFull => {}
+        EncoderResult::Unmappable(_) => output.push(b'?'),
+      }
+    }
+
+    Ok(output)
+  }
+}
+
+/// Decodes the output when the terminal uses a legacy encoding.
+pub fn decode_output<'a>(
+  encoding: Option<&TerminalEncoding>,
+  data: &'a [u8],
+) -> TerminalResult<Cow<'a, [u8]>> {
+  match encoding {
+    Some(encoding) => Ok(Cow::Owned(encoding.decode(data)?)),
+    None => Ok(Cow::Borrowed(data)),
+  }
+}
+
+/// Encodes the input when the terminal uses a legacy encoding.
+pub fn encode_input<'a>(
+  encoding: Option<&TerminalEncoding>,
+  data: &'a [u8],
+) -> TerminalResult<Cow<'a, [u8]>> {
+  match encoding {
+    Some(encoding) => Ok(Cow::Owned(encoding.encode(data)?)),
+    None => Ok(Cow::Borrowed(data)),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn encoding(label: &str) -> TerminalEncoding {
+    TerminalEncoding::new(label).unwrap().unwrap()
+  }
+
+  /// Feeds the data one byte per call, as if every byte arrived in a read of its own.
+  fn byte_by_byte(data: &[u8], mut convert: impl FnMut(&[u8]) -> Vec<u8>) -> Vec<u8> {
+    data.iter().flat_map(|byte| convert(&[*byte])).collect()
+  }
+
+  #[test]
+  fn new_skips_utf8_and_rejects_unusable_encodings() {
+    assert!(TerminalEncoding::new("utf-8").unwrap().is_none());
+    assert!(TerminalEncoding::new(" UTF8 ").unwrap().is_none());
+    assert!(TerminalEncoding::new("no-such-encoding").is_err());
+    assert!(TerminalEncoding::new("utf-16le").is_err());
+    assert!(TerminalEncoding::new("iso-2022-jp").is_err());
+    assert_eq!(encoding("gbk").locale(), Some("zh_CN.GBK"));
+    assert_eq!(encoding("big5").locale(), Some("zh_TW.BIG5"));
+  }
+
+  #[test]
+  fn decode_gbk_split_across_reads() {
+    let gbk = encoding("gbk");
+    // "中文" 的 GBK 编码
+    let data = b"\x1b[31m\xd6\xd0\xce\xc4\x1b[0m\r\n";
+    let output = byte_by_byte(data, |data| gbk.decode(data).unwrap());
+    assert_eq!(output, "\x1b[31m中文\x1b[0m\r\n".as_bytes());
+
+    let output = [&data[..7], &data[7..]]
+      .iter()
+      .flat_map(|data| gbk.decode(data).unwrap())
+      .collect::<Vec<_>>();
+    assert_eq!(output, "\x1b[31m中文\x1b[0m\r\n".as_bytes());
+  }
+
+  #[test]
+  fn decode_big5_split_across_reads() {
+    let big5 = encoding("big5");
+    // "中文" 的 Big5 编码
+    let output = byte_by_byte(b"ls \xa4\xa4\xa4\xe5\n", |data| big5.decode(data).unwrap());
+    assert_eq!(output, "ls 中文\n".as_bytes());
+  }
+
+  #[test]
+  fn decode_shift_jis_split_across_reads() {
+    let shift_jis = encoding("shift_jis");
+    // "日本" 的 Shift_JIS 编码，半角片假名为单字节
+    let output = byte_by_byte(b"\x93\xfa\x96\x7b\xb1", |data| {
+      shift_jis.decode(data).unwrap()
+    });
+    assert_eq!(output, "日本ｱ".as_bytes());
+  }
+
+  #[test]
+  fn decode_keeps_the_incomplete_sequence_until_the_next_read() {
+    let gbk = encoding("gbk");
+    assert_eq!(gbk.decode(b"a\xd6").unwrap(), b"a");
+    assert_eq!(gbk.decode(b"\xd0b").unwrap(), "中b".as_bytes());
+  }
+
+  #[test]
+  fn encode_utf8_split_across_sends() {
+    let gbk = encoding("gbk");
+    let output = byte_by_byte("echo 中文\r".as_bytes(), |data| gbk.encode(data).unwrap());
+    assert_eq!(output, b"echo \xd6\xd0\xce\xc4\r");
+
+    let shift_jis = encoding("shift_jis");
+    let data = "日本".as_bytes();
+    assert_eq!(shift_jis.encode(&data[..2]).unwrap(), b"");
+    assert_eq!(shift_jis.encode(&data[2..4]).unwrap(), b"\x93\xfa");
+    assert_eq!(shift_jis.encode(&data[4..]).unwrap(), b"\x96\x7b");
+  }
+
+  #[test]
+  fn encode_replaces_unmappable_characters() {
+    let gbk = encoding("gbk");
+    assert_eq!(gbk.encode("a😀b".as_bytes()).unwrap(), b"a?b");
+
+    let latin1 = encoding("latin1");
+    assert_eq!(latin1.encode("é中".as_bytes()).unwrap(), b"\xe9?");
+  }
+
+  #[test]
+  fn utf8_terminals_pass_data_through() {
+    assert!(matches!(
+      decode_output(None, b"\xe4\xb8").unwrap(),
+      Cow::Borrowed(b"\xe4\xb8")
+    ));
+    assert!(matches!(
+      encode_input(None, b"\xe4\xb8").unwrap(),
+      Cow::Borrowed(b"\xe4\xb8")
+    ));
+  }
+}
@@ -0,0 +1,27 @@
+use std::sync::PoisonError;
+
+use thiserror::Error;
+
+pub type TerminalResult<T> = Result<T, TerminalError>;
+
+#[derive(Debug, Error)]
+pub enum TerminalError {
+  #[error(transparent)]
+  RegexError(#[from] regex::Error),
+  #[error("{0}")]
+  StdSyncPoisonError(String),
+  #[error("{0}")]
+  Error(String),
+}
+
+impl TerminalError {
+  pub fn new<T: ToString>(err: T) -> Self {
+    TerminalError::Error(err.to_string())
+  }
+}
+
+impl<T> From<PoisonError<T>> for TerminalError {
+  fn from(value: PoisonError<T>) -> Self {
+    TerminalError::StdSyncPoisonError(value.to_string())
+  }
+}
@@ -0,0 +1,12 @@
+//! Terminal output processing shared by the ssh and pty plugins: output flow control, the screen
+//! model, shell integration, legacy encodings and triggers.
+
+pub mod encoding;
+pub mod error;
+pub mod output_flow;
+pub mod screen;
+pub mod shell_integration;
+pub mod trigger;
+pub mod utils;
+
+pub use error::{TerminalError, TerminalResult};
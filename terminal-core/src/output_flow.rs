@@ -0,0 +1,216 @@
+use std::{collections::VecDeque, time::Duration};
+
+/// Pending output is flushed as one frame after this interval
+pub const FRAME_INTERVAL: Duration = Duration::from_millis(10);
+/// Pending output is flushed immediately once it reaches this size
+const FRAME_SIZE: usize = 32 * 1024;
+/// Reading pauses once this many bytes are sent but not acknowledged
+const HIGH_WATERMARK: usize = 1024 * 1024;
+/// Reading resumes once the unacknowledged bytes drop below this size
+const LOW_WATERMARK: usize = 256 * 1024;
+/// Recent output kept in memory and replayed when a channel attaches
+const SCROLLBACK_SIZE: usize = 1024 * 1024;
+
+/// Scrollback, frame coalescing and acknowledgement based flow control of a shell's output.
+///
+/// The plugins own the channel to the webview and the waiting, this only keeps the accounts.
+pub struct OutputFlow {
+  /// False while the shell runs detached from any webview
+  attached: bool,
+  scrollback: VecDeque<u8>,
+  scrollback_truncated: bool,
+  pending: Vec<u8>,
+  unacked: usize,
+  flush_scheduled: bool,
+}
+
+impl Default for OutputFlow {
+  fn default() -> Self {
+    Self {
+      attached: true,
+      scrollback: VecDeque::with_capacity(SCROLLBACK_SIZE),
+      scrollback_truncated: false,
+      pending: Vec::with_capacity(FRAME_SIZE),
+      unacked: 0,
+      flush_scheduled: false,
+    }
+  }
+}
+
+impl OutputFlow {
+  pub fn is_attached(&self) -> bool {
+    self.attached
+  }
+
+  /// Keeps the output in the scrollback and queues it for the webview, returns true when a full
+  /// frame is pending and should be flushed right away.
+  pub fn push(&mut self, data: &[u8]) -> bool {
+    self.push_scrollback(data);
+    // 未连接 webview 时输出只保留在回滚缓冲区中
+    if !self.attached {
+      return false;
+    }
+    self.pending.extend_from_slice(data);
+
+    self.pending.len() >= FRAME_SIZE
+  }
+
+  /// Returns true when the caller should flush after `FRAME_INTERVAL`, once per interval.
+  pub fn schedule_flush(&mut self) -> bool {
+    if !self.attached || self.flush_scheduled {
+      return false;
+    }
+    self.flush_scheduled = true;
+
+    true
+  }
+
+  /// Takes the pending output as one frame, unacknowledged until the webview has processed it.
+  pub fn take_frame(&mut self) -> Option<Vec<u8>> {
+    if self.pending.is_empty() {
+      return None;
+    }
+
+    let frame = std::mem::replace(&mut self.pending, Vec::with_capacity(FRAME_SIZE));
+    self.unacked += frame.len();
+    Some(frame)
+  }
+
+  /// Takes the frame of a scheduled flush, the next output schedules a new one.
+  pub fn take_scheduled_frame(&mut self) -> Option<Vec<u8>> {
+    self.flush_scheduled = false;
+    self.take_frame()
+  }
+
+  /// Attaches a webview, returns the scrollback it needs to replay before any new output.
+  pub fn attach(&mut self) -> Vec<u8> {
+    // 待发送的数据已在回滚缓冲区中，随回放一起发送
+    self.pending.clear();
+    let replay = self.replay();
+    self.unacked = replay.len();
+    self.attached = true;
+
+    replay
+  }
+
+  /// Detaches the webview, the output goes to the scrollback only.
+  pub fn detach(&mut self) {
+    self.attached = false;
+    self.pending.clear();
+    self.unacked = 0;
+  }
+
+  /// Counts `size` bytes as processed by the webview, returns true when writing may resume.
+  pub fn ack(&mut self, size: usize) -> bool {
+    self.unacked = self.unacked.saturating_sub(size);
+    self.unacked <= LOW_WATERMARK
+  }
+
+  /// False while the webview is too far behind, the reader should stop reading.
+  pub fn is_writable(&self) -> bool {
+    !self.attached || self.unacked <= HIGH_WATERMARK
+  }
+
+  fn push_scrollback(&mut self, data: &[u8]) {
+    let data = &data[data.len().saturating_sub(SCROLLBACK_SIZE)..];
+    let overflow = (self.scrollback.len() + data.len()).saturating_sub(SCROLLBACK_SIZE);
+    if overflow > 0 {
+      self.scrollback.drain(..overflow);
+      self.scrollback_truncated = true;
+    }
+    self.scrollback.extend(data);
+  }
+
+  fn replay(&self) -> Vec<u8> {
+    let (front, back) = self.scrollback.as_slices();
+    let mut replay = [front, back].concat();
+    // 缓冲区被截断过时从第一个换行后开始回放，避免回放半个转义序列
+    if self.scrollback_truncated
+      && let Some(pos) = replay.iter().position(|byte| *byte == b'\n')
+    {
+      replay.drain(..=pos);
+    }
+    replay
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn push_flushes_full_frames() {
+    let mut flow = OutputFlow::default();
+
+    assert!(!flow.push(b"$ ls\r\n"));
+    assert!(flow.schedule_flush());
+    assert!(!flow.schedule_flush());
+    assert_eq!(
+      flow.take_scheduled_frame().as_deref(),
+      Some(&b"$ ls\r\n"[..])
+    );
+    assert_eq!(flow.take_frame(), None);
+    assert!(flow.schedule_flush());
+
+    assert!(!flow.push(&[b'a'; FRAME_SIZE - 1]));
+    assert!(flow.push(b"b"));
+    assert_eq!(flow.take_frame().map(|frame| frame.len()), Some(FRAME_SIZE));
+  }
+
+  #[test]
+  fn unacked_output_pauses_writing_until_the_low_watermark() {
+    let mut flow = OutputFlow::default();
+    let frame = vec![b'a'; HIGH_WATERMARK + 1];
+
+    flow.push(&frame);
+    flow.take_frame();
+    assert!(!flow.is_writable());
+
+    // 低于高水位后仍等到低水位才唤醒读取
+    assert!(!flow.ack(HIGH_WATERMARK - LOW_WATERMARK));
+    assert!(flow.is_writable());
+    assert!(flow.ack(1));
+
+    flow.push(&frame);
+    flow.take_frame();
+    flow.detach();
+    assert!(flow.is_writable());
+  }
+
+  #[test]
+  fn detached_output_only_goes_to_the_scrollback() {
+    let mut flow = OutputFlow::default();
+    flow.push(b"one\r\n");
+    flow.take_frame();
+    flow.push(b"two\r\n");
+
+    flow.detach();
+    assert!(!flow.is_attached());
+    assert!(!flow.push(b"three\r\n"));
+    assert!(!flow.schedule_flush());
+    assert_eq!(flow.take_frame(), None);
+
+    assert_eq!(flow.attach(), b"one\r\ntwo\r\nthree\r\n");
+    assert!(flow.is_attached());
+    assert_eq!(flow.take_frame(), None);
+    // 回放的数据同样需要确认
+    assert_eq!(flow.unacked, 17);
+  }
+
+  #[test]
+  fn truncated_scrollback_replays_from_the_first_full_line() {
+    let mut flow = OutputFlow::default();
+    flow.push(b"\x1b[31mfirst line\r\n");
+    flow.push(&[b'a'; SCROLLBACK_SIZE - 4]);
+    flow.push(b"\r\nlast\r\n");
+
+    let replay = flow.attach();
+    assert_eq!(replay, b"last\r\n");
+
+    let mut flow = OutputFlow::default();
+    flow.push(&[b'b'; SCROLLBACK_SIZE + 10]);
+    assert_eq!(flow.scrollback.len(), SCROLLBACK_SIZE);
+    // 没有换行时只能整体回放
+    assert_eq!(flow.attach().len(), SCROLLBACK_SIZE);
+  }
+}
@@ -0,0 +1,553 @@
+use std::fmt::Write;
+
+use regex::RegexBuilder;
+use serde::{Deserialize, Serialize};
+use vt100::{Color, Parser, Screen};
+
+use crate::error::TerminalResult;
+
+/// Rows kept after they scroll off the screen
+const SCROLLBACK_LINES: usize = 10000;
+/// Matches returned by one search
+const SEARCH_LIMIT: usize = 1000;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScreenSnapshot {
+  pub rows: u16,
+  pub cols: u16,
+  pub cursor_row: u16,
+  pub cursor_col: u16,
+  pub alternate_screen: bool,
+  /// Visible rows, trailing spaces removed
+  pub lines: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScreenSearchOpts {
+  pub query: String,
+  #[serde(default)]
+  pub regex: bool,
+  #[serde(default)]
+  pub case_sensitive: bool,
+}
+
+/// A match in the scrollback and screen, offsets are in characters of the line.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScreenSearchMatch {
+  /// Index of the first row of the line, counted from the oldest scrollback row
+  pub row: usize,
+  pub line: String,
+  pub start: usize,
+  pub end: usize,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub enum ScreenExportFormat {
+  Text,
+  Html,
+}
+
+/// Rendered lines of a processed chunk, used to match triggers.
+#[derive(Debug, Default)]
+pub struct ScreenLines {
+  /// Lines ended by a newline, as they looked at that moment
+  pub finished: Vec<String>,
+  /// The line of the cursor after the chunk
+  pub current: String,
+}
+
+/// A logical line, rows wrapped by the terminal are joined.
+struct ScreenLine {
+  row: usize,
+  text: String,
+  html: String,
+}
+
+/// Headless terminal fed with the shell output, answers what is on screen
+/// and in the scrollback without asking the webview.
+pub struct TerminalScreen {
+  parser: Parser,
+}
+
+impl TerminalScreen {
+  pub fn new(rows: u32, cols: u32) -> Self {
+    Self {
+      parser: Parser::new(to_u16(rows), to_u16(cols), SCROLLBACK_LINES),
+    }
+  }
+
+  pub fn resize(&mut self, rows: u32, cols: u32) {
+    self
+      .parser
+      .screen_mut()
+      .set_size(to_u16(rows), to_u16(cols));
+  }
+
+  pub fn process(&mut self, data: &[u8]) {
+    self.parser.process(data);
+  }
+
+  /// Processes the output and renders the lines it finished.
+  pub fn process_lines(&mut self, data: &[u8]) -> ScreenLines {
+    let mut finished = Vec::new();
+    for chunk in data.split_inclusive(|byte| *byte == b'\n') {
+      match chunk.strip_suffix(b"\n") {
+        // 换行前读取光标所在行，此时行内的回车、退格等已生效
+        Some(line) => {
+          self.parser.process(line);
+          finished.push(self.cursor_line());
+          self.parser.process(b"\n");
+        }
+        None => self.parser.process(chunk),
+      }
+    }
+
+    ScreenLines {
+      finished,
+      current: self.cursor_line(),
+    }
+  }
+
+  pub fn snapshot(&self) -> ScreenSnapshot {
+    let screen = self.parser.screen();
+    let (rows, cols) = screen.size();
+    let (cursor_row, cursor_col) = screen.cursor_position();
+
+    ScreenSnapshot {
+      rows,
+      cols,
+      cursor_row,
+      cursor_col,
+      alternate_screen: screen.alternate_screen(),
+      lines: (0..rows).map(|row| row_text(screen, row)).collect(),
+    }
+  }
+
+  pub fn search(&mut self, opts: &ScreenSearchOpts) -> TerminalResult<Vec<ScreenSearchMatch>> {
+    let pattern = if opts.regex {
+      opts.query.clone()
+    } else {
+      regex::escape(&opts.query)
+    };
+    let regex = RegexBuilder::new(&pattern)
+      .case_insensitive(!opts.case_sensitive)
+      .build()?;
+
+    let mut matches = Vec::new();
+    for line in self.lines(false) {
+      for matched in regex.find_iter(&line.text) {
+        if matched.is_empty() {
+          continue;
+        }
+        matches.push(ScreenSearchMatch {
+          row: line.row,
+          start: line.text[..matched.start()].chars().count(),
+          end: line.text[..matched.end()].chars().count(),
+          line: line.text.clone(),
+        });
+        if matches.len() >= SEARCH_LIMIT {
+          return Ok(matches);
+        }
+      }
+    }
+
+    Ok(matches)
+  }
+
+  /// Exports the scrollback and the screen as a transcript.
+  pub fn export(&mut self, format: ScreenExportFormat) -> String {
+    match format {
+      ScreenExportFormat::Text => {
+        let lines = self.lines(false);
+        let mut text = lines
+          .into_iter()
+          .map(|line| line.text)
+          .collect::<Vec<_>>()
+          .join("\n");
+        text.push('\n');
+        text
+      }
+      ScreenExportFormat::Html => {
+        let mut html = String::from(
+          "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n</head>\n\
+           <body>\n<pre style=\"font-family: monospace\">",
+        );
+        for line in self.lines(true) {
+          html.push_str(&line.html);
+          html.push('\n');
+        }
+        html.push_str("</pre>\n</body>\n</html>\n");
+        html
+      }
+    }
+  }
+
+  /// The logical line of the cursor, including the rows it wrapped from.
+  fn cursor_line(&self) -> String {
+    let screen = self.parser.screen();
+    let (row, _) = screen.cursor_position();
+    let mut start = row;
+    while start > 0 && screen.row_wrapped(start - 1) {
+      start -= 1;
+    }
+
+    (start..=row).map(|row| row_text(screen, row)).collect()
+  }
+
+  /// Collects the logical lines of the scrollback and the screen, empty lines at the end are dropped.
+  ///
+  /// The parser only exposes the visible rows, so the scrollback is walked a screen at a time.
+  fn lines(&mut self, html: bool) -> Vec<ScreenLine> {
+    let screen = self.parser.screen_mut();
+    let (rows, _) = screen.size();
+    let rows = rows.max(1);
+    screen.set_scrollback(usize::MAX);
+    let scrollback = screen.scrollback();
+
+    let mut lines: Vec<ScreenLine> = Vec::new();
+    let mut wrapped = false;
+    let mut next_row = 0;
+    let mut offset = scrollback;
+    loop {
+      let screen = self.parser.screen_mut();
+      screen.set_scrollback(offset);
+      let screen = self.parser.screen();
+      let top = scrollback - offset;
+
+      for visible_row in 0..rows {
+        let row = top + visible_row as usize;
+        if row < next_row {
+          continue;
+        }
+        next_row = row + 1;
+
+        let text = row_text(screen, visible_row);
+        let row_html = if html {
+          row_html(screen, visible_row)
+        } else {
+          String::new()
+        };
+        match lines.last_mut() {
+          Some(line) if wrapped => {
+            line.text.push_str(&text);
+            line.html.push_str(&row_html);
+          }
+          _ => lines.push(ScreenLine {
+            row,
+            text,
+            html: row_html,
+          }),
+        }
+        wrapped = screen.row_wrapped(visible_row);
+      }
+
+      if offset == 0 {
+        break;
+      }
+      offset = offset.saturating_sub(rows as usize);
+    }
+    self.parser.screen_mut().set_scrollback(0);
+
+    while lines.last().is_some_and(|line| line.text.is_empty()) {
+      lines.pop();
+    }
+    lines
+  }
+}
+
+fn to_u16(value: u32) -> u16 {
+  value.clamp(1, u16::MAX as u32) as u16
+}
+
+fn row_text(screen: &Screen, row: u16) -> String {
+  let (_, cols) = screen.size();
+  let mut text = String::new();
+  for col in 0..cols {
+    let Some(cell) = screen.cell(row, col) else {
+      break;
+    };
+    if cell.is_wide_continuation() {
+      continue;
+    }
+    if cell.has_contents() {
+      text.push_str(cell.contents());
+    } else {
+      text.push(' ');
+    }
+  }
+
+  // 自动换行的行尾空格属于内容
+  if !screen.row_wrapped(row) {
+    text.truncate(text.trim_end().len());
+  }
+  text
+}
+
+fn row_html(screen: &Screen, row: u16) -> String {
+  let (_, cols) = screen.size();
+  let mut html = String::new();
+  let mut span: Option<String> = None;
+  let mut pending_spaces = 0;
+
+  for col in 0..cols {
+    let Some(cell) = screen.cell(row, col) else {
+      break;
+    };
+    if cell.is_wide_continuation() {
+      continue;
+    }
+
+    // 页面使用白底黑字，反色时默认颜色需要显式写出
+    let (fg, bg) = if cell.inverse() {
+      (
+        color_css(cell.bgcolor(), Some("#ffffff")),
+        color_css(cell.fgcolor(), Some("#000000")),
+      )
+    } else {
+      (
+        color_css(cell.fgcolor(), None),
+        color_css(cell.bgcolor(), None),
+      )
+    };
+    let mut style = String::new();
+    if let Some(color) = fg {
+      let _ = write!(style, "color:{};", color);
+    }
+    if let Some(color) = bg {
+      let _ = write!(style, "background-color:{};", color);
+    }
+    if cell.bold() {
+      style.push_str("font-weight:bold;");
+    }
+    if cell.italic() {
+      style.push_str("font-style:italic;");
+    }
+    if cell.underline() {
+      style.push_str("text-decoration:underline;");
+    }
+
+    // 行尾没有样式的空格不输出
+    if !cell.has_contents() && style.is_empty() {
+      pending_spaces += 1;
+      continue;
+    }
+
+    if span.as_deref() != Some(style.as_str()) {
+      if span.as_deref().is_some_and(|style| !style.is_empty()) {
+        html.push_str("</span>");
+      }
+      html.extend(std::iter::repeat_n(' ', pending_spaces));
+      pending_spaces = 0;
+      if !style.is_empty() {
+        let _ = write!(html, "<span style=\"{}\">", style);
+      }
+      span = Some(style);
+    } else {
+      html.extend(std::iter::repeat_n(' ', pending_spaces));
+      pending_spaces = 0;
+    }
+
+    if cell.has_contents() {
+      escape_html_into(&mut html, cell.contents());
+    } else {
+      html.push(' ');
+    }
+  }
+
+  if span.is_some_and(|style| !style.is_empty()) {
+    html.push_str("</span>");
+  }
+  html
+}
+
+fn color_css(color: Color, default: Option<&str>) -> Option<String> {
+  match color {
+    Color::Default => default.map(|default| default.to_string()),
+    Color::Idx(index) => {
+      let (r, g, b) = xterm_color(index);
+      Some(format!("#{:02x}{:02x}{:02x}", r, g, b))
+    }
+    Color::Rgb(r, g, b) => Some(format!("#{:02x}{:02x}{:02x}", r, g, b)),
+  }
+}
+
+/// The xterm 256 color palette.
+fn xterm_color(index: u8) -> (u8, u8, u8) {
+  const BASIC: [(u8, u8, u8); 16] = [
+    (0x00, 0x00, 0x00),
+    (0xcd, 0x00, 0x00),
+    (0x00, 0xcd, 0x00),
+    (0xcd, 0xcd, 0x00),
+    (0x00, 0x00, 0xee),
+    (0xcd, 0x00, 0xcd),
+    (0x00, 0xcd, 0xcd),
+    (0xe5, 0xe5, 0xe5),
+    (0x7f, 0x7f, 0x7f),
+    (0xff, 0x00, 0x00),
+    (0x00, 0xff, 0x00),
+    (0xff, 0xff, 0x00),
+    (0x5c, 0x5c, 0xff),
+    (0xff, 0x00, 0xff),
+    (0x00, 0xff, 0xff),
+    (0xff, 0xff, 0xff),
+  ];
+
+  match index {
+    0..=15 => BASIC[index as usize],
+    16..=231 => {
+      let index = index - 16;
+      let level = |value: u8| if value == 0 { 0 } else { 55 + value * 40 };
+      (level(index / 36), level(index / 6 % 6), level(index % 6))
+    }
+    _ => {
+      let gray = 8 + (index - 232) * 10;
+      (gray, gray, gray)
+    }
+  }
+}
+
+fn escape_html_into(html: &mut String, text: &str) {
+  for char in text.chars() {
+    match char {
+      '&' => html.push_str("&amp;"),
+      '<' => html.push_str("&lt;"),
+      '>' => html.push_str("&gt;"),
+      '"' => html.push_str("&quot;"),
+      char => html.push(char),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn screen(rows: u32, cols: u32, data: &str) -> TerminalScreen {
+    let mut screen = TerminalScreen::new(rows, cols);
+    screen.process(data.as_bytes());
+    screen
+  }
+
+  fn opts(query: &str, regex: bool, case_sensitive: bool) -> ScreenSearchOpts {
+    ScreenSearchOpts {
+      query: query.to_string(),
+      regex,
+      case_sensitive,
+    }
+  }
+
+  fn found(matches: &[ScreenSearchMatch]) -> Vec<(usize, &str, usize, usize)> {
+    matches
+      .iter()
+      .map(|matched| {
+        (
+          matched.row,
+          matched.line.as_str(),
+          matched.start,
+          matched.end,
+        )
+      })
+      .collect()
+  }
+
+  /// Ten numbered lines on a three row screen, most of them are in the scrollback.
+  fn numbered_lines() -> TerminalScreen {
+    let data = (0..10)
+      .map(|index| format!("line {} of 10\r\n", index))
+      .collect::<String>();
+    screen(3, 20, &data)
+  }
+
+  #[test]
+  fn plain_search_covers_the_scrollback() {
+    let mut screen = numbered_lines();
+
+    let matches = screen.search(&opts("LINE 1 ", false, false)).unwrap();
+    assert_eq!(found(&matches), [(1, "line 1 of 10", 0, 7)]);
+
+    let matches = screen.search(&opts("of 10", false, true)).unwrap();
+    assert_eq!(
+      matches
+        .iter()
+        .map(|matched| matched.row)
+        .collect::<Vec<_>>(),
+      (0..10).collect::<Vec<_>>()
+    );
+    assert!(
+      screen
+        .search(&opts("LINE", false, true))
+        .unwrap()
+        .is_empty()
+    );
+  }
+
+  #[test]
+  fn plain_search_matches_regex_characters_literally() {
+    let mut screen = screen(5, 40, "a.b axb\r\n[ok] (1+1)\r\n");
+
+    let matches = screen.search(&opts("a.b", false, false)).unwrap();
+    assert_eq!(found(&matches), [(0, "a.b axb", 0, 3)]);
+
+    let matches = screen.search(&opts("[ok] (1+1)", false, false)).unwrap();
+    assert_eq!(found(&matches), [(1, "[ok] (1+1)", 0, 10)]);
+  }
+
+  #[test]
+  fn regex_search_covers_the_scrollback() {
+    let mut screen = numbered_lines();
+
+    let matches = screen.search(&opts(r"^line [159] ", true, false)).unwrap();
+    assert_eq!(
+      found(&matches),
+      [
+        (1, "line 1 of 10", 0, 7),
+        (5, "line 5 of 10", 0, 7),
+        (9, "line 9 of 10", 0, 7),
+      ]
+    );
+
+    // 空匹配不返回
+    assert!(screen.search(&opts("x*", true, false)).unwrap().is_empty());
+    assert!(screen.search(&opts("(", true, false)).is_err());
+  }
+
+  #[test]
+  fn search_joins_wrapped_rows_and_counts_characters() {
+    let mut screen = screen(3, 10, "0123456789abc\r\n中文 error\r\n");
+
+    let matches = screen.search(&opts("9a", false, false)).unwrap();
+    assert_eq!(found(&matches), [(0, "0123456789abc", 9, 11)]);
+
+    let matches = screen.search(&opts("error", false, false)).unwrap();
+    assert_eq!(found(&matches), [(2, "中文 error", 3, 8)]);
+  }
+
+  #[test]
+  fn text_export_includes_the_scrollback_without_styles() {
+    let mut screen = screen(
+      3,
+      20,
+      "\x1b[31mred\x1b[0m   \r\none\r\n\r\ntwo\r\nthree\r\n\r\n",
+    );
+
+    assert_eq!(
+      screen.export(ScreenExportFormat::Text),
+      "red\none\n\ntwo\nthree\n"
+    );
+    // 导出后恢复到当前屏幕
+    assert_eq!(screen.snapshot().lines, ["three", "", ""]);
+  }
+
+  #[test]
+  fn html_export_escapes_and_styles_the_text() {
+    let mut screen = screen(3, 20, "\x1b[1;31m<b>&\x1b[0m ok\r\n");
+
+    let html = screen.export(ScreenExportFormat::Html);
+    assert!(html.contains(
+      "<span style=\"color:#cd0000;font-weight:bold;\">&lt;b&gt;&amp;</span> ok\n</pre>"
+    ));
+  }
+}
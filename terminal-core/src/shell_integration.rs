@@ -0,0 +1,408 @@
+use std::time::Instant;
+
+use serde::Serialize;
+
+use crate::utils::strip_ansi;
+
+/// Longest OSC sequence kept, longer ones are ignored
+const OSC_SIZE: usize = 8192;
+/// Longest echoed command line kept
+const INPUT_SIZE: usize = 8192;
+
+const ESC: u8 = 0x1b;
+const BEL: u8 = 0x07;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all_fields = "camelCase")]
+pub enum ShellIntegrationEvent {
+  CommandStarted {
+    command: Option<String>,
+    cwd: Option<String>,
+  },
+  /// Duration is in milliseconds, the exit code is missing when the shell doesn't report it
+  CommandFinished {
+    command: Option<String>,
+    cwd: Option<String>,
+    exit_code: Option<i32>,
+    duration: u64,
+  },
+  CwdChanged {
+    cwd: String,
+    host: Option<String>,
+  },
+}
+
+#[derive(Debug, Default, PartialEq)]
+enum Phase {
+  #[default]
+  Idle,
+  /// Between the end of the prompt and the command start, the output is the echoed input
+  Input,
+  Running,
+}
+
+struct RunningCommand {
+  command: Option<String>,
+  started_at: Instant,
+}
+
+/// Follows the prompt markers of OSC 133 (FinalTerm) and OSC 633 (VS Code) and the
+/// working directory reports of OSC 7 in the shell output.
+#[derive(Default)]
+pub struct ShellIntegration {
+  /// Body of an unfinished OSC sequence
+  osc: Option<Vec<u8>>,
+  /// The unfinished OSC sequence outgrew `OSC_SIZE` and is skipped up to its terminator
+  overflow: bool,
+  escape: bool,
+  phase: Phase,
+  input: Vec<u8>,
+  /// Command line reported by OSC 633;E, preferred over the echoed input
+  command: Option<String>,
+  running: Option<RunningCommand>,
+  cwd: Option<String>,
+}
+
+impl ShellIntegration {
+  pub fn feed(&mut self, data: &[u8]) -> Vec<ShellIntegrationEvent> {
+    let mut events = Vec::new();
+
+    for &byte in data {
+      if self.osc.is_some() {
+        if byte == BEL || (self.escape && byte == b'\\') {
+          self.escape = false;
+          let osc = self.osc.take();
+          if !std::mem::take(&mut self.overflow)
+            && let Some(osc) = osc
+          {
+            self.handle_osc(&osc, &mut events);
+          }
+          continue;
+        }
+
+        // ESC 后面不是 \ 时不是 ST，这个 ESC 仍是 OSC 的内容
+        if std::mem::take(&mut self.escape) {
+          self.push_osc(ESC);
+        }
+        if byte == ESC {
+          self.escape = true;
+        } else {
+          self.push_osc(byte);
+        }
+        continue;
+      }
+
+      if self.escape {
+        self.escape = false;
+        if byte == b']' {
+          self.osc = Some(Vec::new());
+        } else {
+          self.push_input(&[ESC, byte]);
+        }
+        continue;
+      }
+
+      if byte == ESC {
+        self.escape = true;
+      } else {
+        self.push_input(&[byte]);
+      }
+    }
+
+    events
+  }
+
+  fn push_osc(&mut self, byte: u8) {
+    if let Some(osc) = &mut self.osc {
+      if osc.len() < OSC_SIZE {
+        osc.push(byte);
+      } else {
+        self.overflow = true;
+      }
+    }
+  }
+
+  fn push_input(&mut self, data: &[u8]) {
+    if self.phase == Phase::Input && self.input.len() < INPUT_SIZE {
+      self.input.extend_from_slice(data);
+    }
+  }
+
+  fn handle_osc(&mut self, osc: &[u8], events: &mut Vec<ShellIntegrationEvent>) {
+    let osc = String::from_utf8_lossy(osc);
+    let mut parts = osc.splitn(3, ';');
+
+    match (parts.next(), parts.next()) {
+      (Some("133" | "633"), Some(mark)) => self.handle_mark(mark, parts.next(), events),
+      (Some("7"), Some(url)) => {
+        if let Some((host, cwd)) = parse_file_url(url) {
+          self.change_cwd(cwd, host, events);
+        }
+      }
+      _ => {}
+    }
+  }
+
+  fn handle_mark(
+    &mut self,
+    mark: &str,
+    args: Option<&str>,
+    events: &mut Vec<ShellIntegrationEvent>,
+  ) {
+    match mark {
+      // 提示符开始
+      "A" => {
+        self.phase = Phase::Idle;
+        self.input.clear();
+      }
+      // 提示符结束，之后是用户输入的命令
+      "B" => {
+        self.phase = Phase::Input;
+        self.input.clear();
+        self.command = None;
+      }
+      // 命令开始执行
+      "C" => {
+        let command = self.command.take().or_else(|| parse_input(&self.input));
+        self.phase = Phase::Running;
+        self.input.clear();
+        events.push(ShellIntegrationEvent::CommandStarted {
+          command: command.clone(),
+          cwd: self.cwd.clone(),
+        });
+        self.running = Some(RunningCommand {
+          command,
+          started_at: Instant::now(),
+        });
+      }
+      // 命令结束，没有执行命令时（如直接回车）也会收到，此时忽略
+      "D" => {
+        self.phase = Phase::Idle;
+        if let Some(running) = self.running.take() {
+          let exit_code = args
+            .and_then(|args| args.split(';').next())
+            .and_then(|code| code.trim().parse().ok());
+          events.push(ShellIntegrationEvent::CommandFinished {
+            command: running.command,
+            cwd: self.cwd.clone(),
+            exit_code,
+            duration: running.started_at.elapsed().as_millis() as u64,
+          });
+        }
+      }
+      // OSC 633 的命令行和属性
+      "E" => self.command = args.map(unescape_633).filter(|command| !command.is_empty()),
+      "P" => {
+        if let Some(cwd) = args.and_then(|args| args.strip_prefix("Cwd=")) {
+          self.change_cwd(unescape_633(cwd), None, events);
+        }
+      }
+      _ => {}
+    }
+  }
+
+  fn change_cwd(
+    &mut self,
+    cwd: String,
+    host: Option<String>,
+    events: &mut Vec<ShellIntegrationEvent>,
+  ) {
+    if self.cwd.as_ref() == Some(&cwd) {
+      return;
+    }
+
+    self.cwd = Some(cwd.clone());
+    events.push(ShellIntegrationEvent::CwdChanged { cwd, host });
+  }
+}
+
+/// Rebuilds the command line from the echoed input, applying backspaces.
+fn parse_input(input: &[u8]) -> Option<String> {
+  let mut command = String::new();
+  for char in strip_ansi(input).chars() {
+    match char {
+      '\x08' | '\x7f' => {
+        command.pop();
+      }
+      '\n' => command.push('\n'),
+      char if char.is_control() => {}
+      char => command.push(char),
+    }
+  }
+
+  let command = command.trim();
+  (!command.is_empty()).then(|| command.to_string())
+}
+
+/// OSC 633 escapes `;`, control characters and backslashes as `\xAB`.
+fn unescape_633(value: &str) -> String {
+  let bytes = value.as_bytes();
+  let mut output = Vec::with_capacity(bytes.len());
+  let mut index = 0;
+  while index < bytes.len() {
+    if bytes[index] == b'\\' {
+      if bytes.get(index + 1) == Some(&b'\\') {
+        output.push(b'\\');
+        index += 2;
+        continue;
+      }
+      if bytes.get(index + 1) == Some(&b'x')
+        && let Some(byte) = bytes
+          .get(index + 2..index + 4)
+          .and_then(|hex| std::str::from_utf8(hex).ok())
+          .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+      {
+        output.push(byte);
+        index += 4;
+        continue;
+      }
+    }
+    output.push(bytes[index]);
+    index += 1;
+  }
+
+  String::from_utf8_lossy(&output).to_string()
+}
+
+/// Parses `file://host/path` into the host and the percent decoded path.
+fn parse_file_url(url: &str) -> Option<(Option<String>, String)> {
+  let rest = url.strip_prefix("file://")?;
+  let (host, path) = match rest.find('/') {
+    Some(pos) => (&rest[..pos], &rest[pos..]),
+    None => return None,
+  };
+  let host = Some(host.to_string()).filter(|host| !host.is_empty());
+
+  Some((host, percent_decode(path)))
+}
+
+fn percent_decode(value: &str) -> String {
+  let bytes = value.as_bytes();
+  let mut output = Vec::with_capacity(bytes.len());
+  let mut index = 0;
+  while index < bytes.len() {
+    if bytes[index] == b'%'
+      && let Some(byte) = bytes
+        .get(index + 1..index + 3)
+        .and_then(|hex| std::str::from_utf8(hex).ok())
+        .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+    {
+      output.push(byte);
+      index += 3;
+      continue;
+    }
+    output.push(bytes[index]);
+    index += 1;
+  }
+
+  String::from_utf8_lossy(&output).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn feed_bytes(integration: &mut ShellIntegration, data: &[u8]) -> Vec<ShellIntegrationEvent> {
+    data
+      .iter()
+      .flat_map(|byte| integration.feed(&[*byte]))
+      .collect()
+  }
+
+  fn cwd(events: &[ShellIntegrationEvent]) -> Vec<(&str, Option<&str>)> {
+    events
+      .iter()
+      .filter_map(|event| match event {
+        ShellIntegrationEvent::CwdChanged { cwd, host } => Some((cwd.as_str(), host.as_deref())),
+        _ => None,
+      })
+      .collect()
+  }
+
+  fn started(events: &[ShellIntegrationEvent]) -> Vec<Option<&str>> {
+    events
+      .iter()
+      .filter_map(|event| match event {
+        ShellIntegrationEvent::CommandStarted { command, .. } => Some(command.as_deref()),
+        _ => None,
+      })
+      .collect()
+  }
+
+  #[test]
+  fn osc_ends_with_bel_or_st() {
+    let mut integration = ShellIntegration::default();
+
+    let events = integration.feed(b"\x1b]7;file://web-1/srv\x07$ ");
+    assert_eq!(cwd(&events), [("/srv", Some("web-1"))]);
+
+    let events = integration.feed(b"\x1b]7;file:///tmp\x1b\\$ ");
+    assert_eq!(cwd(&events), [("/tmp", None)]);
+  }
+
+  #[test]
+  fn osc_split_across_feeds() {
+    let mut integration = ShellIntegration::default();
+    let data =
+      b"\x1b]133;A\x07$ \x1b]133;B\x07ls -l\x08a\r\n\x1b]133;C\x07total 0\r\n\x1b]133;D;2\x1b\\";
+
+    let events = feed_bytes(&mut integration, data);
+    assert_eq!(started(&events), [Some("ls -a")]);
+    assert!(matches!(
+      events.last(),
+      Some(ShellIntegrationEvent::CommandFinished {
+        command: Some(command),
+        exit_code: Some(2),
+        ..
+      }) if command == "ls -a"
+    ));
+  }
+
+  #[test]
+  fn esc_inside_osc_is_kept() {
+    let mut integration = ShellIntegration::default();
+    let data = b"\x1b]633;B\x07\x1b]633;E;printf \x1b[1m\x1b\x1b\\\x1b]633;C\x07";
+
+    let events = integration.feed(data);
+    assert_eq!(started(&events), [Some("printf \x1b[1m\x1b")]);
+  }
+
+  #[test]
+  fn overflowed_osc_is_skipped_up_to_its_terminator() {
+    let mut integration = ShellIntegration::default();
+    let mut data = b"\x1b]133;B\x07ls".to_vec();
+    data.extend_from_slice(b"\x1b]7;file:///");
+    data.extend(std::iter::repeat_n(b'a', OSC_SIZE));
+    data.extend_from_slice(b"\x1b\\\x1b]133;C\x07");
+
+    let events = integration.feed(&data);
+    assert!(cwd(&events).is_empty());
+    assert_eq!(started(&events), [Some("ls")]);
+
+    let events = integration.feed(b"\x1b]7;file:///var/log\x07");
+    assert_eq!(cwd(&events), [("/var/log", None)]);
+  }
+
+  #[test]
+  fn osc_7_path_is_percent_decoded() {
+    let mut integration = ShellIntegration::default();
+
+    let events = integration.feed(b"\x1b]7;file://host/home/a%20b/%E4%B8%AD%zz\x07");
+    assert_eq!(cwd(&events), [("/home/a b/\u{4e2d}%zz", Some("host"))]);
+
+    // 目录没有变化时不重复通知
+    let events = integration.feed(b"\x1b]7;file://host/home/a b/\xe4\xb8\xad%zz\x07");
+    assert!(cwd(&events).is_empty());
+
+    assert!(integration.feed(b"\x1b]7;http://host/tmp\x07").is_empty());
+    assert!(integration.feed(b"\x1b]7;file://host\x07").is_empty());
+  }
+
+  #[test]
+  fn osc_633_cwd_is_unescaped() {
+    let mut integration = ShellIntegration::default();
+
+    let events = integration.feed(b"\x1b]633;P;Cwd=/tmp/a\\x3bb\\\\c\x07");
+    assert_eq!(cwd(&events), [("/tmp/a;b\\c", None)]);
+  }
+}
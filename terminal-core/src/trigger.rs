@@ -0,0 +1,122 @@
+use std::collections::HashSet;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::{error::TerminalResult, screen::ScreenLines};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all_fields = "camelCase")]
+pub enum TriggerAction {
+  /// Sent to the shell by the backend as soon as the pattern matches
+  SendText {
+    text: String,
+  },
+  Notify {
+    message: Option<String>,
+  },
+  Highlight {
+    color: String,
+  },
+  /// Id of a saved port forwarding, opened by the webview
+  PortForwarding {
+    port_forwarding_id: String,
+  },
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TriggerRule {
+  pub id: String,
+  pub pattern: String,
+  pub action: TriggerAction,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TriggerEvent {
+  pub rule_id: String,
+  pub action: TriggerAction,
+  pub line: String,
+  pub matched: String,
+}
+
+struct CompiledTriggerRule {
+  rule: TriggerRule,
+  regex: Regex,
+}
+
+/// Matches the lines rendered by the terminal screen against the trigger rules.
+#[derive(Default)]
+pub struct TriggerEngine {
+  rules: Vec<CompiledTriggerRule>,
+  line: String,
+  /// Rules already fired on the current unfinished line, e.g. a password prompt
+  fired: HashSet<usize>,
+}
+
+impl TriggerEngine {
+  pub fn new(rules: Vec<TriggerRule>) -> TerminalResult<Self> {
+    let mut trigger_engine = Self::default();
+    trigger_engine.set_rules(rules)?;
+
+    Ok(trigger_engine)
+  }
+
+  pub fn set_rules(&mut self, rules: Vec<TriggerRule>) -> TerminalResult<()> {
+    self.rules = rules
+      .into_iter()
+      .filter(|rule| !rule.pattern.is_empty())
+      .map(|rule| {
+        Ok(CompiledTriggerRule {
+          regex: Regex::new(&rule.pattern)?,
+          rule,
+        })
+      })
+      .collect::<TerminalResult<Vec<_>>>()?;
+    self.fired.clear();
+
+    Ok(())
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.rules.is_empty()
+  }
+
+  pub fn feed(&mut self, lines: ScreenLines) -> Vec<TriggerEvent> {
+    if self.rules.is_empty() {
+      return Vec::new();
+    }
+
+    let mut events = Vec::new();
+    for line in lines.finished {
+      self.line = line;
+      self.match_line(&mut events);
+      self.fired.clear();
+    }
+
+    // 未换行的内容也需要匹配，如 sudo 的密码提示
+    self.line = lines.current;
+    self.match_line(&mut events);
+
+    events
+  }
+
+  fn match_line(&mut self, events: &mut Vec<TriggerEvent>) {
+    for (index, compiled_rule) in self.rules.iter().enumerate() {
+      if self.fired.contains(&index) {
+        continue;
+      }
+
+      if let Some(matched) = compiled_rule.regex.find(&self.line) {
+        self.fired.insert(index);
+        events.push(TriggerEvent {
+          rule_id: compiled_rule.rule.id.clone(),
+          action: compiled_rule.rule.action.clone(),
+          line: self.line.clone(),
+          matched: matched.as_str().to_string(),
+        });
+      }
+    }
+  }
+}
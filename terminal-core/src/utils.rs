@@ -0,0 +1,38 @@
+/// Removes ANSI escape sequences (CSI, OSC and two byte escapes) from terminal output.
+pub fn strip_ansi(data: &[u8]) -> String {
+  let mut output = Vec::with_capacity(data.len());
+  let mut iter = data.iter().copied().peekable();
+
+  while let Some(byte) = iter.next() {
+    if byte != 0x1b {
+      output.push(byte);
+      continue;
+    }
+
+    match iter.next() {
+      // CSI: ESC [ params final
+      Some(b'[') => {
+        for byte in iter.by_ref() {
+          if (0x40..=0x7e).contains(&byte) {
+            break;
+          }
+        }
+      }
+      // OSC: ESC ] ... BEL | ESC \
+      Some(b']') => {
+        while let Some(byte) = iter.next() {
+          if byte == 0x07 {
+            break;
+          }
+          if byte == 0x1b && iter.peek() == Some(&b'\\') {
+            iter.next();
+            break;
+          }
+        }
+      }
+      _ => {}
+    }
+  }
+
+  String::from_utf8_lossy(&output).to_string()
+}